@@ -64,7 +64,8 @@ const ChatContent: Component = () => {
             context.type,
             context.id,
             MESSAGES_LIMIT,
-            cursor
+            cursor,
+            msgs[0]?.id
         );
 
         if (result.isErr()) {
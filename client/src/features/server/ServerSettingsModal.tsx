@@ -1,6 +1,6 @@
 import type { Component } from 'solid-js'
 import { createSignal, createMemo, For, Show } from 'solid-js'
-import { Search, Trash2, X, Plus, Copy, Users, FileText, RefreshCw, Upload, HardDrive } from 'lucide-solid'
+import { Search, Trash2, X, Plus, Copy, Users, FileText, RefreshCw, Upload, HardDrive, Mic } from 'lucide-solid'
 import { useToaster } from '../../components/Toaster'
 import { useConfirm } from '../../components/ConfirmDialog'
 import Button from '../../components/Button'
@@ -21,8 +21,11 @@ interface Invite {
     inviteId: number;
     code: string;
     availableRegistrations: number;
+    exhausted: boolean;
     roleId: number;
     createdAt: string;
+    expiresAt: string | null;
+    singleUse: boolean;
 }
 
 interface LogEntry {
@@ -305,6 +308,26 @@ const ServerSettingsModal: Component = () => {
                             </Card>
                         )}
                     </Show>
+                    <Show when={serverConfig()}>
+                        {(config) => (
+                            <Card title="Voice" icon={<Mic class="w-4 h-4" />}>
+                                <div class="grid grid-cols-1 md:grid-cols-2 gap-4">
+                                    <Input
+                                        label="Max Participants Per Voice Channel"
+                                        type="number"
+                                        value={String(config().maxVoipParticipants)}
+                                        onBlur={async (e) => {
+                                            const result = await serverActions.updateVoipLimits(parseInt(e.currentTarget.value));
+                                            if (result.isErr()) {
+                                                addToast(result.error, 'error');
+                                            }
+                                        }}
+                                        min="1"
+                                    />
+                                </div>
+                            </Card>
+                        )}
+                    </Show>
                 </div>
             ),
         },
@@ -495,11 +518,11 @@ const ServerSettingsModal: Component = () => {
                                                         </div>
                                                     </TableCell>
                                                     <TableCell align="center">
-                                                        <span class={`px-2 py-1 rounded text-xs font-medium ${invite.availableRegistrations > 0
+                                                        <span class={`px-2 py-1 rounded text-xs font-medium ${!invite.exhausted
                                                             ? 'bg-status-success/20 text-status-success'
                                                             : 'bg-status-danger/20 text-status-danger'
                                                             }`}>
-                                                            {invite.availableRegistrations}
+                                                            {invite.exhausted ? 'Exhausted' : invite.availableRegistrations}
                                                         </span>
                                                     </TableCell>
                                                     <TableCell align="center">
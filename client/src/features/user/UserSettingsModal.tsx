@@ -88,15 +88,15 @@ const UserSettingsModal: Component = () => {
         setSessions(result.value);
     };
 
-    const terminateSession = async (sessionToken: string) => {
-        const result = await authActions.terminateSession(sessionToken);
+    const terminateSession = async (sessionId: number) => {
+        const result = await authActions.terminateSession(sessionId);
 
         if (result.isErr()) {
             addToast(result.error, "error");
             return;
         }
 
-        setSessions(sessions().filter(session => session.sessionToken !== sessionToken));
+        setSessions(sessions().filter(session => session.sessionId !== sessionId));
     };
 
     const formatDate = (dateString: string) => {
@@ -447,7 +447,7 @@ const UserSettingsModal: Component = () => {
                                                 </TableCell>
                                                 <TableCell align="center">
                                                     <Button
-                                                        onClick={() => terminateSession(session.sessionToken)}
+                                                        onClick={() => terminateSession(session.sessionId)}
                                                         disabled={isCurrentSession(session.sessionToken)}
                                                         variant="ghost"
                                                         size="sm"
@@ -70,16 +70,50 @@ export interface Message {
   senderId: number;
   channelId: number | undefined;
   recipientId: number | undefined;
+  conversationId?: number;
   messageText: string | undefined;
   createdAt: string;
   modifiedAt: string | undefined;
   replyToMessageId: number | undefined;
+  forwardedFromMessageId?: number;
+  metadata?: Record<string, unknown>;
+  expiresAt?: string;
+}
+
+export interface GroupConversation {
+  conversationId: number;
+  name: string | null;
+  createdBy: number | null;
+  createdAt: string;
+  memberIds: number[];
+}
+
+export type DmRequestStatus = "Pending" | "Accepted" | "Declined";
+
+export interface DmRequest {
+  senderId: number;
+  recipientId: number;
+  status: DmRequestStatus;
+  createdAt: string;
+}
+
+export interface ReplyCount {
+  messageId: number;
+  replyCount: number;
 }
 
 export interface MessagesResponse {
   messages: Message[];
   files: File[];
   reactions: Reaction[];
+  replyCounts: ReplyCount[];
+}
+
+export interface Paginated<T> {
+  items: T;
+  nextCursor: string | undefined;
+  nextCursorId: number | undefined;
+  hasMore: boolean;
 }
 
 
@@ -92,6 +126,8 @@ export interface Group {
 export interface User {
   userId: number;
   username: string;
+  displayName: string | null;
+  lastSeenAt: string | null;
   createdAt: string;
   avatarFileId: number | undefined;
   roleId: number;
@@ -129,12 +165,48 @@ export interface VoipParticipant {
   createdAt: string;
 }
 
+export interface VoipCodecParams {
+  userId: number;
+  codec: string;
+  sampleRate: number;
+  channels: number;
+}
+
 export interface GroupRoleRights {
   groupId: number;
   roleId: number;
   rights: number;
 }
 
+export interface EffectiveGroupRights {
+  groupId: number;
+  rights: number;
+  canRead: boolean;
+  canWrite: boolean;
+  canManage: boolean;
+}
+
+export interface EffectiveChannelRights {
+  channelId: number;
+  groupId: number;
+  rights: number;
+  canRead: boolean;
+  canWrite: boolean;
+  canManage: boolean;
+}
+
+export interface EffectiveRights {
+  roleId: number;
+  groups: EffectiveGroupRights[];
+  channels: EffectiveChannelRights[];
+}
+
+export interface RightsMatrix {
+  roleIds: number[];
+  groupIds: number[];
+  rights: number[][];
+}
+
 export interface MessageWithFiles {
   message: Message;
   files: File[];
@@ -147,16 +219,29 @@ export interface Session {
   expiresAt: string;
 }
 
+export interface PendingRegistration {
+  registrationId: number;
+  username: string;
+  roleId: number;
+  inviteId: number | null;
+  clientIp: string | null;
+  createdAt: string;
+}
+
 export interface RegisterResponse {
-  user: User;
+  user: User | null;
+  pending_registration?: PendingRegistration;
 }
 
 export interface ServerConfig {
   id: number;
   serverName: string;
+  description: string;
   avatarFileId: number | undefined;
   maxFileSizeMb: number;
   maxFilesPerMessage: number;
+  maxVoipParticipants: number;
+  requireRegistrationApproval: boolean;
 }
 
 export type QualityPreset = "720p" | "1080p" | "1440p" | "4k";
@@ -59,7 +59,11 @@ const LoginPage: Component = () => {
             return;
         }
 
-        addToast("Account created successfully!", "success");
+        if (result.value.pending) {
+            addToast("Registration submitted, an admin must approve it before you can log in", "success");
+        } else {
+            addToast("Account created successfully!", "success");
+        }
         setActiveForm("login");
     };
 
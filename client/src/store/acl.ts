@@ -1,6 +1,6 @@
 import { createStore } from "solid-js/store";
 import { createRoot } from "solid-js";
-import type { GroupRoleRights } from "../model";
+import type { EffectiveRights, GroupRoleRights, RightsMatrix } from "../model";
 import type { Result } from "opencord-utils";
 import { ok, err } from "opencord-utils";
 import { request } from "../utils";
@@ -24,6 +24,9 @@ interface AclActions {
     grant: (right: GroupRoleRights) => Promise<Result<void, string>>;
     grantMany: (rights: GroupRoleRights[]) => Promise<Result<void, string>>;
     updateUserRole: (userId: number, roleId: number) => Promise<Result<void, string>>;
+    updateUserRoles: (assignments: { userId: number; roleId: number }[]) => Promise<Result<void, string>>;
+    fetchEffectiveRights: () => Promise<Result<EffectiveRights, string>>;
+    fetchRightsMatrix: () => Promise<Result<RightsMatrix, string>>;
 }
 
 export type AclStore = [AclState, AclActions];
@@ -144,6 +147,37 @@ function createAclStore(): AclStore {
             }
             return ok(undefined);
         },
+
+        async updateUserRoles(assignments) {
+            const result = await request("/acl/user-roles", {
+                method: "PUT",
+                body: { assignments },
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(undefined);
+        },
+
+        async fetchEffectiveRights() {
+            const result = await request<EffectiveRights>("/acl/me", {
+                method: "GET",
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(result.value);
+        },
+
+        async fetchRightsMatrix() {
+            const result = await request<RightsMatrix>("/acl/matrix", {
+                method: "GET",
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(result.value);
+        },
     };
 
     return [state, actions];
@@ -4,7 +4,7 @@ import type { Result } from "opencord-utils";
 import { ok, err } from "opencord-utils";
 import { request } from "../utils";
 import { setDomain } from "../lib/ServerConfig";
-import type { User, Session } from "../model";
+import type { User, Session, RegisterResponse } from "../model";
 import { useUser } from "./user";
 import { clearImageCache } from "../components/ImagePreview";
 
@@ -45,10 +45,10 @@ export interface AuthActions {
     getSession: () => AuthSession;
     clearLocal: () => void;
     login: (username: string, password: string, domain?: string) => Promise<Result<void, string>>;
-    register: (username: string, password: string, inviteCode: string, domain?: string) => Promise<Result<void, string>>;
+    register: (username: string, password: string, inviteCode: string, domain?: string) => Promise<Result<{ pending: boolean }, string>>;
     logout: () => Promise<Result<void, string>>;
     getSessions: () => Promise<Result<Session[], string>>;
-    terminateSession: (sessionToken: string) => Promise<Result<void, string>>;
+    terminateSession: (sessionId: number) => Promise<Result<void, string>>;
 }
 
 export type AuthStore = [AuthState, AuthActions];
@@ -122,7 +122,7 @@ function createAuthStore(): AuthStore {
                 setDomain(domain);
             }
 
-            const result = await request<{ user_id: number }>("/auth/register", {
+            const result = await request<RegisterResponse>("/auth/register", {
                 method: "POST",
                 body: {
                     username,
@@ -137,7 +137,7 @@ function createAuthStore(): AuthStore {
                 return err(result.error.reason || "Registration failed");
             }
 
-            return ok(undefined);
+            return ok({ pending: !!result.value.pending_registration });
         },
 
         async logout() {
@@ -174,10 +174,9 @@ function createAuthStore(): AuthStore {
             return ok(result.value);
         },
 
-        async terminateSession(sessionToken) {
-            const result = await request("/auth/logout", {
-                method: "POST",
-                body: { session_token: sessionToken },
+        async terminateSession(sessionId) {
+            const result = await request(`/auth/sessions/${sessionId}`, {
+                method: "DELETE",
             });
 
             if (result.isErr()) {
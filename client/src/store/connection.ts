@@ -8,10 +8,13 @@ import type {
     Role,
     User,
     VoipParticipant,
+    VoipCodecParams,
     GroupRoleRights,
     File,
     ServerConfig,
     Reaction,
+    GroupConversation,
+    DmRequest,
 } from "../model";
 import { getWsUrl } from "../lib/ServerConfig";
 
@@ -21,7 +24,28 @@ const PONG_TIMEOUT_MS = 2000;
 const MAX_MISSED_PONGS = 1;
 
 const CLOSE_CODE_NORMAL = 1000;
-const CLOSE_CODE_DISCONNECTED = 4002;
+const COMPRESSED_FRAME_FLAG = 0xc1;
+
+export type CloseReason =
+    | "disconnected"
+    | "loggedOut"
+    | "sessionRevoked"
+    | "passwordChanged"
+    | "banned"
+    | "userDeleted"
+    | "terminated"
+    | "sessionExpired";
+
+const CLOSE_REASONS: Record<number, CloseReason> = {
+    4002: "disconnected",
+    4003: "loggedOut",
+    4004: "sessionRevoked",
+    4005: "passwordChanged",
+    4006: "banned",
+    4007: "userDeleted",
+    4008: "terminated",
+    4009: "sessionExpired",
+};
 
 
 export type EventPayload =
@@ -42,50 +66,92 @@ export type EventPayload =
     | { type: "voipParticipantCreated"; user: VoipParticipant }
     | { type: "voipParticipantUpdated"; user: VoipParticipant }
     | { type: "voipParticipantDeleted"; userId: number }
+    | { type: "voipCodecUpdated"; codec: VoipCodecParams }
+    | {
+        type: "snapshot";
+        users: User[];
+        channels: Channel[];
+        groupRoleRights: GroupRoleRights[];
+        voipParticipants: VoipParticipant[];
+    }
     | {
         type: "messageCreated";
         messageId: number;
         senderId: number;
-        messageType: { type: "Channel"; channel_id: number } | { type: "Direct"; recipient_id: number };
+        messageType:
+            | { type: "Channel"; channel_id: number }
+            | { type: "Direct"; recipient_id: number }
+            | { type: "Group"; conversation_id: number };
         messageText: string | null;
         replyToMessageId: number | undefined;
+        forwardedFromMessageId: number | null;
+        metadata: Record<string, unknown> | null;
         timestamp: string;
+        expiresAt: string | null;
         files: File[];
+        muted: boolean;
     }
+    | { type: "groupConversationCreated"; conversation: GroupConversation }
+    | { type: "dmRequestCreated"; request: DmRequest }
+    | { type: "dmRequestUpdated"; request: DmRequest }
     | { type: "messageUpdated"; messageId: number; messageText: string }
     | { type: "messageDeleted"; messageId: number }
+    | { type: "messagesPurged"; channelId: number; messageIds: number[] }
     | {
         type: "reactionAdded";
         reaction: Reaction;
-        messageType: { type: "Channel"; channelId: number } | { type: "Direct"; recipientId: number };
+        messageType:
+            | { type: "Channel"; channelId: number }
+            | { type: "Direct"; recipientId: number }
+            | { type: "Group"; conversationId: number };
     }
     | {
         type: "reactionRemoved";
         messageId: number;
         userId: number;
         emoji: string;
-        messageType: { type: "Channel"; channelId: number } | { type: "Direct"; recipientId: number };
+        messageType:
+            | { type: "Channel"; channelId: number }
+            | { type: "Direct"; recipientId: number }
+            | { type: "Group"; conversationId: number };
     }
     | { type: "readStatusUpdated"; channelId: number | null; recipientId: number | null; hasNewMessage: boolean }
     | { type: "speakStatusUpdated"; userId: number; speaking: boolean };
 
 type ConnectionMessage =
-    | { type: "answer"; ok: boolean }
+    | { type: "answer"; ok: boolean; resumeToken: string | null }
     | { type: "ping"; timestamp: number }
     | { type: "pong"; timestamp: number }
-    | { type: "event"; payload: EventPayload };
+    | { type: "echo"; timestamp: number; payload: string }
+    | { type: "echoReply"; timestamp: number; payload: string; receivedAt: number; remoteAddress: string }
+    | { type: "event"; payload: EventPayload; cursor: number }
+    | { type: "congestion"; congested: boolean; rttMs: number }
+    | { type: "subscribePresence"; userIds: number[] }
+    | { type: "backfill"; sinceCursor: number }
+    | { type: "backfillComplete"; cursor: number; truncated: boolean };
 
 export type ConnectionError =
     | { type: "networkError" }
     | { type: "authFailed" };
 
+export interface EchoResult {
+    payload: string;
+    remoteAddress: string;
+    roundTripMs: number;
+    uplinkMs: number;
+}
+
 export interface ConnectionActions {
     connect: (token: string) => Promise<Result<void, ConnectionError>>;
     disconnect: () => void;
     onServerEvent: (callback: (event: EventPayload) => void) => () => void;
-    onConnectionClosed: (callback: () => void) => () => void;
+    onConnectionClosed: (callback: (reason: CloseReason) => void) => () => void;
     onConnectionLost: (callback: () => void) => () => void;
+    onCongestionChanged: (callback: (congested: boolean, rttMs: number) => void) => () => void;
     sendSpeakStatus: (userId: number, speaking: boolean) => void;
+    subscribePresence: (userIds: number[]) => void;
+    requestBackfill: (onComplete: (truncated: boolean) => void) => void;
+    echo: (payload: string) => Promise<EchoResult | null>;
 }
 
 
@@ -98,14 +164,19 @@ interface PendingPing {
 
 function createConnectionStore(): ConnectionActions {
     const serverEventCallbacks = new Set<(event: EventPayload) => void>();
-    const closedCallbacks = new Set<() => void>();
+    const closedCallbacks = new Set<(reason: CloseReason) => void>();
     const connectionLostCallbacks = new Set<() => void>();
+    const congestionCallbacks = new Set<(congested: boolean, rttMs: number) => void>();
 
     let socket: WebSocket | null = null;
     let pingIntervalId: number | null = null;
     let pendingPings: PendingPing[] = [];
     let missedPongs = 0;
     let connectResolve: ((result: Result<void, ConnectionError>) => void) | null = null;
+    let resumeToken: string | null = null;
+    let lastCursor = 0;
+    let backfillCallback: ((truncated: boolean) => void) | null = null;
+    const pendingEchoes = new Map<number, (result: EchoResult | null) => void>();
 
     function disconnect() {
         if (pingIntervalId !== null) {
@@ -115,6 +186,8 @@ function createConnectionStore(): ConnectionActions {
         pendingPings.forEach(p => clearTimeout(p.timeoutId));
         pendingPings = [];
         missedPongs = 0;
+        pendingEchoes.forEach(resolve => resolve(null));
+        pendingEchoes.clear();
         if (socket) {
             socket.onmessage = null;
             socket.onclose = null;
@@ -145,6 +218,22 @@ function createConnectionStore(): ConnectionActions {
         }, PING_INTERVAL_MS);
     }
 
+    let inboundQueue: Promise<void> = Promise.resolve();
+
+    async function inflateFrame(data: ArrayBuffer): Promise<ArrayBuffer> {
+        const stream = new Blob([new Uint8Array(data, 1)])
+            .stream()
+            .pipeThrough(new DecompressionStream("deflate"));
+        return new Response(stream).arrayBuffer();
+    }
+
+    function receiveFrame(data: ArrayBuffer) {
+        const compressed = new Uint8Array(data)[0] === COMPRESSED_FRAME_FLAG;
+        inboundQueue = inboundQueue
+            .then(async () => handleMessage(compressed ? await inflateFrame(data) : data))
+            .catch(() => {});
+    }
+
     function handleMessage(data: ArrayBuffer) {
         const message = decode(data) as ConnectionMessage;
 
@@ -154,6 +243,7 @@ function createConnectionStore(): ConnectionActions {
                     const resolve = connectResolve;
                     connectResolve = null;
                     if (message.ok) {
+                        resumeToken = message.resumeToken;
                         startPingPong();
                         resolve(ok(undefined));
                     } else {
@@ -179,9 +269,34 @@ function createConnectionStore(): ConnectionActions {
                 break;
             }
 
+            case "echoReply": {
+                const resolve = pendingEchoes.get(message.timestamp);
+                pendingEchoes.delete(message.timestamp);
+                resolve?.({
+                    payload: message.payload,
+                    remoteAddress: message.remoteAddress,
+                    roundTripMs: Date.now() - message.timestamp,
+                    uplinkMs: message.receivedAt - message.timestamp,
+                });
+                break;
+            }
+
+            case "congestion":
+                congestionCallbacks.forEach((cb) => cb(message.congested, message.rttMs));
+                break;
+
             case "event":
+                lastCursor = Math.max(lastCursor, message.cursor);
                 notifyServerEvent(message.payload);
                 break;
+
+            case "backfillComplete": {
+                lastCursor = message.cursor;
+                const callback = backfillCallback;
+                backfillCallback = null;
+                callback?.(message.truncated);
+                break;
+            }
         }
     }
 
@@ -194,8 +309,9 @@ function createConnectionStore(): ConnectionActions {
         disconnect();
         if (wasConnecting) return;
 
-        if (event.code === CLOSE_CODE_DISCONNECTED) {
-            notifyClosed();
+        const reason = CLOSE_REASONS[event.code];
+        if (reason) {
+            notifyClosed(reason);
         } else if (event.code !== CLOSE_CODE_NORMAL) {
             notifyConnectionLost();
         }
@@ -205,8 +321,8 @@ function createConnectionStore(): ConnectionActions {
         serverEventCallbacks.forEach((cb) => cb(event));
     }
 
-    function notifyClosed(): void {
-        closedCallbacks.forEach((cb) => cb());
+    function notifyClosed(reason: CloseReason): void {
+        closedCallbacks.forEach((cb) => cb(reason));
     }
 
     function notifyConnectionLost(): void {
@@ -222,10 +338,13 @@ function createConnectionStore(): ConnectionActions {
             disconnect();
 
             try {
-                const wsUrl = `${getWsUrl()}/ws?token=${encodeURIComponent(token)}`;
+                let wsUrl = `${getWsUrl()}/ws?token=${encodeURIComponent(token)}&compress=true`;
+                if (resumeToken) {
+                    wsUrl += `&resume=${encodeURIComponent(resumeToken)}`;
+                }
                 socket = new WebSocket(wsUrl);
                 socket.binaryType = "arraybuffer";
-                socket.onmessage = (event) => handleMessage(event.data);
+                socket.onmessage = (event) => receiveFrame(event.data);
                 socket.onclose = handleClose;
                 socket.onerror = () => {};
 
@@ -245,7 +364,7 @@ function createConnectionStore(): ConnectionActions {
             return () => serverEventCallbacks.delete(callback);
         },
 
-        onConnectionClosed(callback: () => void): () => void {
+        onConnectionClosed(callback: (reason: CloseReason) => void): () => void {
             closedCallbacks.add(callback);
             return () => closedCallbacks.delete(callback);
         },
@@ -255,6 +374,11 @@ function createConnectionStore(): ConnectionActions {
             return () => connectionLostCallbacks.delete(callback);
         },
 
+        onCongestionChanged(callback: (congested: boolean, rttMs: number) => void): () => void {
+            congestionCallbacks.add(callback);
+            return () => congestionCallbacks.delete(callback);
+        },
+
         sendSpeakStatus(userId: number, speaking: boolean): void {
             if (socket && socket.readyState === WebSocket.OPEN) {
                 socket.send(encode({
@@ -263,6 +387,31 @@ function createConnectionStore(): ConnectionActions {
                 }));
             }
         },
+
+        subscribePresence(userIds: number[]): void {
+            if (socket && socket.readyState === WebSocket.OPEN) {
+                socket.send(encode({ type: "subscribePresence", userIds }));
+            }
+        },
+
+        requestBackfill(onComplete: (truncated: boolean) => void): void {
+            if (!socket || socket.readyState !== WebSocket.OPEN) {
+                onComplete(true);
+                return;
+            }
+            backfillCallback = onComplete;
+            socket.send(encode({ type: "backfill", sinceCursor: lastCursor }));
+        },
+
+        echo(payload: string): Promise<EchoResult | null> {
+            if (!socket || socket.readyState !== WebSocket.OPEN) {
+                return Promise.resolve(null);
+            }
+            let timestamp = Date.now();
+            while (pendingEchoes.has(timestamp)) timestamp++;
+            socket.send(encode({ type: "echo", timestamp, payload }));
+            return new Promise((resolve) => pendingEchoes.set(timestamp, resolve));
+        },
     };
 
     return actions;
@@ -38,6 +38,8 @@ function createFileStore(): FileStore {
             cleanupFn = connection.onServerEvent((event) => {
                 if (event.type === "messageDeleted") {
                     actions.removeByMessageId(event.messageId);
+                } else if (event.type === "messagesPurged") {
+                    event.messageIds.forEach((messageId) => actions.removeByMessageId(messageId));
                 }
             });
 
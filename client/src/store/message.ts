@@ -1,6 +1,6 @@
 import { createStore } from "solid-js/store";
 import { createRoot } from "solid-js";
-import type { Message, File as StoredFile, MessagesResponse } from "../model";
+import type { Message, File as StoredFile, MessagesResponse, Paginated } from "../model";
 import type { Result } from "opencord-utils";
 import { ok, err } from "opencord-utils";
 import { request, upload } from "../utils";
@@ -32,8 +32,10 @@ interface MessageActions {
         contextType: "channel" | "dm",
         contextId: number,
         limit: number,
-        timestamp: string
+        timestamp: string,
+        beforeId?: number
     ) => Promise<Result<Message[], string>>;
+    fetchFeed: (limit: number, timestamp: string) => Promise<Result<Paginated<MessagesResponse>, string>>;
     fetchMessagesRange: (
         contextType: "channel" | "dm",
         contextId: number,
@@ -47,7 +49,8 @@ interface MessageActions {
         files?: File[],
         replyToMessageId?: number | undefined,
         onProgress?: (percent: number) => void,
-        signal?: AbortSignal
+        signal?: AbortSignal,
+        ttlSeconds?: number
     ) => Promise<Result<void, string>>;
     updateMessage: (messageId: number, text: string) => Promise<Result<void, string>>;
     delete: (messageId: number) => Promise<Result<void, string>>;
@@ -74,20 +77,25 @@ function createMessageStore(): MessageStore {
 
             cleanupFn = connection.onServerEvent((event) => {
                 if (event.type === "messageCreated") {
-                    const { messageId, senderId, messageType, messageText, timestamp, replyToMessageId, files } = event;
+                    const { messageId, senderId, messageType, messageText, timestamp, expiresAt, replyToMessageId, forwardedFromMessageId, metadata, files, muted } = event;
 
                     const channelId = messageType.type === "Channel" ? messageType.channel_id : undefined;
                     const recipientId = messageType.type === "Direct" ? messageType.recipient_id : undefined;
+                    const conversationId = messageType.type === "Group" ? messageType.conversation_id : undefined;
 
                     actions.add({
                         id: messageId,
                         senderId,
                         channelId,
                         recipientId,
+                        conversationId,
                         messageText: messageText ?? undefined,
                         createdAt: timestamp,
                         modifiedAt: undefined,
                         replyToMessageId,
+                        forwardedFromMessageId: forwardedFromMessageId ?? undefined,
+                        metadata: metadata ?? undefined,
+                        expiresAt: expiresAt ?? undefined,
                     });
 
                     for (const file of files) {
@@ -95,7 +103,7 @@ function createMessageStore(): MessageStore {
                     }
 
                     const currentUserId = authActions.getUser().userId;
-                    if (senderId !== currentUserId) {
+                    if (senderId !== currentUserId && !muted) {
                         if (messageType.type === "Channel" && !context.isCurrentContext("channel", messageType.channel_id)) {
                             notification.pushChannel(messageId, messageType.channel_id);
                         } else if (messageType.type === "Direct" && !context.isCurrentContext("dm", senderId)) {
@@ -118,6 +126,13 @@ function createMessageStore(): MessageStore {
                     fileActions.removeByMessageId(deletedId);
                     reactionActions.removeByMessageId(deletedId);
                     actions.remove(deletedId);
+                } else if (event.type === "messagesPurged") {
+                    for (const purgedId of event.messageIds) {
+                        actions.markRepliesDeleted(purgedId);
+                        fileActions.removeByMessageId(purgedId);
+                        reactionActions.removeByMessageId(purgedId);
+                        actions.remove(purgedId);
+                    }
                 }
             });
 
@@ -210,34 +225,57 @@ function createMessageStore(): MessageStore {
             );
         },
 
-        async fetchMessages(contextType, contextId, limit, timestamp) {
+        async fetchMessages(contextType, contextId, limit, timestamp, beforeId) {
             const endpoint =
                 contextType === "dm"
                     ? `/message/dm/${contextId}/messages`
                     : `/message/channel/${contextId}/messages`;
 
-            const result = await request<MessagesResponse>(endpoint, {
+            const result = await request<Paginated<MessagesResponse>>(endpoint, {
                 method: "GET",
-                query: { limit, timestamp },
+                query: { limit, timestamp, beforeId },
             });
 
             if (result.isErr()) {
                 return err(result.error.reason);
             }
 
-            for (const message of result.value.messages) {
+            const page = result.value.items;
+
+            for (const message of page.messages) {
                 actions.add(message);
             }
 
-            for (const file of result.value.files) {
+            for (const file of page.files) {
                 fileActions.add(file);
             }
 
-            for (const reaction of result.value.reactions) {
+            for (const reaction of page.reactions) {
                 reactionActions.add(reaction);
             }
 
-            return ok(result.value.messages);
+            return ok(page.messages);
+        },
+
+        async fetchFeed(limit, timestamp) {
+            const result = await request<Paginated<MessagesResponse>>("/message/feed", {
+                method: "GET",
+                query: { limit, timestamp },
+            });
+
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+
+            for (const file of result.value.items.files) {
+                fileActions.add(file);
+            }
+
+            for (const reaction of result.value.items.reactions) {
+                reactionActions.add(reaction);
+            }
+
+            return ok(result.value);
         },
 
         async fetchMessagesRange(contextType, contextId, fromMessageId, upToMessageId) {
@@ -270,7 +308,7 @@ function createMessageStore(): MessageStore {
             return ok(result.value.messages);
         },
 
-        async send(contextType, contextId, text, files = [], replyToMessageId = undefined, onProgress, signal) {
+        async send(contextType, contextId, text, files = [], replyToMessageId = undefined, onProgress, signal, ttlSeconds) {
             const endpoint =
                 contextType === "dm"
                     ? `/message/dm/${contextId}/messages`
@@ -283,6 +321,9 @@ function createMessageStore(): MessageStore {
             if (replyToMessageId) {
                 formData.append("replyToMessageId", String(replyToMessageId));
             }
+            if (ttlSeconds) {
+                formData.append("ttlSeconds", String(ttlSeconds));
+            }
             for (const file of files) {
                 formData.append("files", file);
             }
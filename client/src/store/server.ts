@@ -16,8 +16,10 @@ interface ServerActions {
     get: () => ServerConfig | null;
     set: (config: ServerConfig) => void;
     updateName: (name: string) => Promise<Result<void, string>>;
+    updateDescription: (description: string) => Promise<Result<void, string>>;
     updateAvatar: (file: File) => Promise<Result<void, string>>;
     updateFileLimits: (maxFileSizeMb: number, maxFilesPerMessage: number) => Promise<Result<void, string>>;
+    updateVoipLimits: (maxVoipParticipants: number) => Promise<Result<void, string>>;
 }
 
 export type ServerStore = [ServerState, ServerActions];
@@ -76,6 +78,17 @@ function createServerStore(): ServerStore {
             return ok(undefined);
         },
 
+        async updateDescription(description) {
+            const result = await request<ServerConfig>("/server/description", {
+                method: "PUT",
+                body: { description },
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(undefined);
+        },
+
         async updateAvatar(file: File) {
             const formData = new FormData();
             formData.append("file", file);
@@ -96,6 +109,17 @@ function createServerStore(): ServerStore {
             }
             return ok(undefined);
         },
+
+        async updateVoipLimits(maxVoipParticipants) {
+            const result = await request("/server/voip-limits", {
+                method: "PUT",
+                body: { maxVoipParticipants },
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(undefined);
+        },
     };
 
     return [state, actions];
@@ -20,6 +20,7 @@ interface UserActions {
     update: (user: User) => void;
     remove: (id: number) => void;
     updateUsername: (name: string) => Promise<Result<void, string>>;
+    updateDisplayName: (name: string | null) => Promise<Result<void, string>>;
     updateStatus: (userId: number, status: UserStatusType) => Promise<Result<void, string>>;
     updateAvatar: (file: File) => Promise<Result<void, string>>;
     delete: (userId: number) => Promise<Result<void, string>>;
@@ -103,6 +104,17 @@ function createUserStore(): UserStore {
             return ok(undefined);
         },
 
+        async updateDisplayName(name) {
+            const result = await request("/user/me/display-name", {
+                method: "PUT",
+                body: { displayName: name },
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(undefined);
+        },
+
         async updateStatus(userId, status) {
             const result = await request(`/user/${userId}/manual-status`, {
                 method: "PUT",
@@ -1,6 +1,6 @@
 import { createStore } from "solid-js/store";
 import { createRoot } from "solid-js";
-import type { VoipParticipant } from "../model";
+import type { VoipCodecParams, VoipParticipant } from "../model";
 import type { Result } from "opencord-utils";
 import { ok, err } from "opencord-utils";
 import { request } from "../utils";
@@ -17,6 +17,7 @@ interface JoinVoipResponse {
 
 interface VoipState {
     voipState: VoipParticipant[];
+    codecs: VoipCodecParams[];
     currentCallType: CallType | null;
 }
 
@@ -26,6 +27,7 @@ interface VoipActions {
     list: () => VoipParticipant[];
     findByChannel: (channelId: number) => VoipParticipant[];
     findById: (userId: number) => VoipParticipant | undefined;
+    findCodec: (userId: number) => VoipCodecParams | undefined;
     replaceAll: (participants: VoipParticipant[]) => void;
     add: (participant: VoipParticipant) => void;
     update: (participant: VoipParticipant) => void;
@@ -40,6 +42,8 @@ interface VoipActions {
     publishScreen: (publish: boolean) => Promise<Result<void, string>>;
     publishCamera: (publish: boolean) => Promise<Result<void, string>>;
     kick: (targetUserId: number) => Promise<Result<void, string>>;
+    clearChannel: (channelId: number) => Promise<Result<void, string>>;
+    advertiseCodec: (codec: string, sampleRate: number, channels: number) => Promise<Result<void, string>>;
 }
 
 export type VoipStore = [VoipState, VoipActions];
@@ -47,6 +51,7 @@ export type VoipStore = [VoipState, VoipActions];
 function createVoipStore(): VoipStore {
     const [state, setState] = createStore<VoipState>({
         voipState: [],
+        codecs: [],
         currentCallType: null,
     });
 
@@ -68,6 +73,14 @@ function createVoipStore(): VoipStore {
             }
             actions.replaceAll(result.value);
 
+            const codecsResult = await request<VoipCodecParams[]>("/voip/codecs", {
+                method: "GET",
+            });
+            if (codecsResult.isErr()) {
+                return err(codecsResult.error.reason);
+            }
+            setState("codecs", codecsResult.value);
+
             cleanupFn = connection.onServerEvent(async (event) => {
                 const myChannelId = actions.findById(authActions.getUser().userId)?.channelId;
 
@@ -89,6 +102,10 @@ function createVoipStore(): VoipStore {
                         setState("currentCallType", null);
                     }
                     actions.remove(event.userId as number);
+                    setState("codecs", (codecs) => codecs.filter((c) => c.userId !== event.userId));
+                } else if (event.type === "voipCodecUpdated") {
+                    const codec = event.codec as VoipCodecParams;
+                    setState("codecs", (codecs) => [...codecs.filter((c) => c.userId !== codec.userId), codec]);
                 } else if (event.type === "speakStatusUpdated") {
                     livekit.setSpeakingState(event.userId, event.speaking);
                 }
@@ -103,6 +120,7 @@ function createVoipStore(): VoipStore {
                 cleanupFn = null;
             }
             setState("voipState", []);
+            setState("codecs", []);
             setState("currentCallType", null);
         },
 
@@ -118,6 +136,10 @@ function createVoipStore(): VoipStore {
             return state.voipState.find((p) => p.userId === userId);
         },
 
+        findCodec(userId) {
+            return state.codecs.find((c) => c.userId === userId);
+        },
+
         replaceAll(participants) {
             setState("voipState", participants);
         },
@@ -278,6 +300,27 @@ function createVoipStore(): VoipStore {
             }
             return ok(undefined);
         },
+
+        async clearChannel(channelId) {
+            const result = await request(`/voip/channel/${channelId}/clear`, {
+                method: "POST",
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(undefined);
+        },
+
+        async advertiseCodec(codec, sampleRate, channels) {
+            const result = await request("/voip/codec", {
+                method: "PUT",
+                body: { codec, sampleRate, channels },
+            });
+            if (result.isErr()) {
+                return err(result.error.reason);
+            }
+            return ok(undefined);
+        },
     };
 
     return [state, actions];
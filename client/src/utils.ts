@@ -74,7 +74,8 @@ export function cn(...inputs: ClassValue[]) {
 }
 
 type ErrorResponse = {
-  code: string;
+  code: string | number;
+  errorCode?: string;
   reason: string;
 };
 
@@ -0,0 +1,13 @@
+CREATE TABLE sync_cursors (
+    cursor_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT REFERENCES channels(channel_id) ON DELETE CASCADE,
+    recipient_id BIGINT REFERENCES users(user_id) ON DELETE CASCADE,
+    last_message_id BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    CHECK ( (channel_id IS NOT NULL AND recipient_id IS NULL) OR (channel_id IS NULL AND recipient_id IS NOT NULL) )
+);
+
+CREATE UNIQUE INDEX idx_sync_cursors_channel ON sync_cursors(user_id, channel_id) WHERE channel_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_sync_cursors_recipient ON sync_cursors(user_id, recipient_id) WHERE recipient_id IS NOT NULL;
@@ -0,0 +1,10 @@
+-- Custom emojis table - server-wide emojis usable as :name: reactions
+CREATE TABLE custom_emojis (
+    emoji_id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(30) NOT NULL UNIQUE,
+    avatar_file_id BIGINT NOT NULL,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(avatar_file_id) REFERENCES avatar_files(file_id) ON DELETE CASCADE,
+    FOREIGN KEY(created_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
@@ -0,0 +1,12 @@
+-- Blocked users table - pairs of users where the blocker refuses DMs and calls
+CREATE TABLE blocked_users (
+    blocker_id BIGINT NOT NULL,
+    blocked_id BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (blocker_id, blocked_id),
+    FOREIGN KEY(blocker_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(blocked_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    CHECK (blocker_id <> blocked_id)
+);
+
+CREATE INDEX idx_blocked_users_blocked ON blocked_users(blocked_id);
@@ -0,0 +1,10 @@
+-- Message mentions table - users mentioned with @username in channel messages
+CREATE TABLE message_mentions (
+    message_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    PRIMARY KEY (message_id, user_id),
+    FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE,
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_message_mentions_user ON message_mentions(user_id);
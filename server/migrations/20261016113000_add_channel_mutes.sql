@@ -0,0 +1,10 @@
+-- Channel mutes table - per-user notification mute for a channel
+CREATE TABLE channel_mutes (
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    suppress BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, channel_id),
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(channel_id) REFERENCES channels(channel_id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- Webhooks table - outbound HTTP endpoints notified of new channel messages
+CREATE TABLE webhooks (
+    webhook_id BIGSERIAL PRIMARY KEY,
+    channel_id BIGINT NOT NULL,
+    target_url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(channel_id) REFERENCES channels(channel_id) ON DELETE CASCADE,
+    FOREIGN KEY(created_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_webhooks_channel_id ON webhooks(channel_id);
@@ -0,0 +1,11 @@
+-- Webhook tokens table - inbound tokens that post to a channel as a bot user
+CREATE TABLE webhook_tokens (
+    token TEXT PRIMARY KEY,
+    channel_id BIGINT NOT NULL,
+    bot_user_id BIGINT NOT NULL,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(channel_id) REFERENCES channels(channel_id) ON DELETE CASCADE,
+    FOREIGN KEY(bot_user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(created_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
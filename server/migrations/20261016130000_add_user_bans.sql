@@ -0,0 +1,10 @@
+-- User bans table - blocks login until banned_until (NULL = permanent)
+CREATE TABLE user_bans (
+    user_id BIGINT PRIMARY KEY,
+    banned_by BIGINT,
+    reason TEXT,
+    banned_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(banned_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE server_config ADD COLUMN description VARCHAR(500) NOT NULL DEFAULT '';
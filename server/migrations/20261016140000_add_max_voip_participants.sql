@@ -0,0 +1 @@
+ALTER TABLE server_config ADD COLUMN max_voip_participants INTEGER NOT NULL DEFAULT 25;
@@ -0,0 +1,5 @@
+CREATE TABLE channel_retention (
+    channel_id BIGINT PRIMARY KEY REFERENCES channels(channel_id) ON DELETE CASCADE,
+    retention_days INTEGER NOT NULL CHECK (retention_days > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,3 @@
+ALTER TABLE messages ADD COLUMN metadata JSONB;
+
+CREATE INDEX idx_messages_metadata ON messages USING GIN (metadata);
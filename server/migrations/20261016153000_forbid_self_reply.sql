@@ -0,0 +1,2 @@
+ALTER TABLE messages
+    ADD CONSTRAINT messages_no_self_reply CHECK (reply_to_message_id IS NULL OR reply_to_message_id <> id);
@@ -0,0 +1,11 @@
+-- Usernames are unique regardless of case. Accounts that only differ by case
+-- keep the oldest as is and append the user id to the others.
+UPDATE users
+SET username = LEFT(username, 254 - LENGTH(user_id::TEXT)) || '_' || user_id
+WHERE EXISTS (
+    SELECT 1 FROM users earlier
+    WHERE LOWER(earlier.username) = LOWER(users.username)
+      AND earlier.user_id < users.user_id
+);
+
+CREATE UNIQUE INDEX unique_username_lower ON users (LOWER(username));
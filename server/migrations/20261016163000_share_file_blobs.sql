@@ -0,0 +1,21 @@
+-- Files with identical content share one stored blob; blob_id is the storage key
+ALTER TABLE files ADD COLUMN blob_id BIGINT;
+UPDATE files SET blob_id = file_id;
+ALTER TABLE files ALTER COLUMN blob_id SET NOT NULL;
+
+CREATE INDEX idx_files_blob ON files(blob_id);
+CREATE INDEX idx_files_hash ON files(file_hash, file_size);
+
+-- A file without an existing blob stores its own content under its file_id
+CREATE OR REPLACE FUNCTION set_file_blob_id_func()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.blob_id := COALESCE(NEW.blob_id, NEW.file_id);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_file_blob_id
+BEFORE INSERT ON files
+FOR EACH ROW
+EXECUTE FUNCTION set_file_blob_id_func();
@@ -0,0 +1,2 @@
+-- Optional display name shown instead of the username
+ALTER TABLE users ADD COLUMN display_name VARCHAR(64) DEFAULT NULL;
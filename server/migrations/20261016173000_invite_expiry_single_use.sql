@@ -0,0 +1,16 @@
+-- Invites can expire and can be restricted to one registration per username or client IP
+ALTER TABLE invites
+    ADD COLUMN expires_at TIMESTAMPTZ DEFAULT NULL,
+    ADD COLUMN single_use BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Invite registrations table - records which username and client IP redeemed an invite
+CREATE TABLE invite_registrations (
+    invite_id BIGINT NOT NULL,
+    username VARCHAR(255) NOT NULL,
+    client_ip VARCHAR(255) DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (invite_id) REFERENCES invites(invite_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_invite_registrations_invite_username ON invite_registrations(invite_id, LOWER(username));
+CREATE INDEX idx_invite_registrations_invite_ip ON invite_registrations(invite_id, client_ip);
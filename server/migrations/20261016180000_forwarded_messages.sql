@@ -0,0 +1,8 @@
+-- Forwarded messages keep a reference to the message they were forwarded from
+ALTER TABLE messages ADD COLUMN forwarded_from_message_id BIGINT DEFAULT NULL;
+
+ALTER TABLE messages
+ADD CONSTRAINT fk_forwarded_from_message
+FOREIGN KEY (forwarded_from_message_id)
+REFERENCES messages(id)
+ON DELETE SET NULL;
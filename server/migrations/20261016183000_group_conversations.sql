@@ -0,0 +1,23 @@
+-- Group conversations are direct messages between more than two users
+CREATE TABLE group_conversations (
+    conversation_id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(64) DEFAULT NULL,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE group_conversation_members (
+    conversation_id BIGINT NOT NULL REFERENCES group_conversations(conversation_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (conversation_id, user_id)
+);
+
+CREATE INDEX idx_group_conversation_members_user ON group_conversation_members(user_id);
+
+ALTER TABLE messages ADD COLUMN conversation_id BIGINT REFERENCES group_conversations(conversation_id) ON DELETE CASCADE;
+
+ALTER TABLE messages DROP CONSTRAINT messages_check;
+ALTER TABLE messages ADD CONSTRAINT messages_check CHECK (num_nonnulls(channel_id, recipient_id, conversation_id) = 1);
+
+CREATE INDEX idx_messages_conversation ON messages(conversation_id);
@@ -0,0 +1,2 @@
+-- Time the user's last connection closed, shown while they are offline
+ALTER TABLE users ADD COLUMN last_seen_at TIMESTAMPTZ DEFAULT NULL;
@@ -0,0 +1,15 @@
+-- Optional manual approval of invite registrations
+ALTER TABLE server_config
+    ADD COLUMN require_registration_approval BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE pending_registrations (
+    registration_id BIGSERIAL PRIMARY KEY,
+    username VARCHAR(255) NOT NULL,
+    password_hash TEXT NOT NULL,
+    role_id BIGINT NOT NULL REFERENCES roles(role_id) ON DELETE CASCADE,
+    invite_id BIGINT REFERENCES invites(invite_id) ON DELETE SET NULL,
+    client_ip VARCHAR(255) DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_pending_registrations_username ON pending_registrations(LOWER(username));
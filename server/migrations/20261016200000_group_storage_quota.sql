@@ -0,0 +1,3 @@
+-- Optional per-group cap on the total size of files posted in its channels
+ALTER TABLE groups
+    ADD COLUMN storage_quota_bytes BIGINT CHECK (storage_quota_bytes >= 0);
@@ -0,0 +1,15 @@
+-- Message requests: first DMs from users the recipient has never written to
+CREATE TYPE dm_request_status AS ENUM ('Pending', 'Accepted', 'Declined');
+
+CREATE TABLE dm_requests (
+    sender_id BIGINT NOT NULL,
+    recipient_id BIGINT NOT NULL,
+    status dm_request_status NOT NULL DEFAULT 'Pending',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (sender_id, recipient_id),
+    FOREIGN KEY(sender_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(recipient_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    CHECK (sender_id <> recipient_id)
+);
+
+CREATE INDEX idx_dm_requests_recipient ON dm_requests(recipient_id, status);
@@ -0,0 +1,7 @@
+-- Codec parameters advertised by VoIP publishers, relayed as-is to subscribers
+CREATE TABLE voip_codec_params (
+    user_id BIGINT PRIMARY KEY REFERENCES voip_participants(user_id) ON DELETE CASCADE,
+    codec VARCHAR(32) NOT NULL,
+    sample_rate INTEGER NOT NULL CHECK (sample_rate > 0),
+    channels SMALLINT NOT NULL CHECK (channels BETWEEN 1 AND 2)
+);
@@ -0,0 +1,4 @@
+-- Ephemeral messages are hidden once expires_at passes and purged shortly after
+ALTER TABLE messages ADD COLUMN expires_at TIMESTAMPTZ;
+
+CREATE INDEX idx_messages_expires_at ON messages(expires_at) WHERE expires_at IS NOT NULL;
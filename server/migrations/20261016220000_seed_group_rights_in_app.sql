@@ -0,0 +1,3 @@
+-- Group role rights are seeded by the server, which knows the configured member default
+DROP TRIGGER IF EXISTS add_group_role_rights ON groups;
+DROP FUNCTION IF EXISTS add_group_role_rights_func();
@@ -32,6 +32,8 @@ CREATE TYPE user_status_type AS ENUM ('Online', 'Away', 'DoNotDisturb', 'Offline
 CREATE TABLE users (
     user_id BIGSERIAL PRIMARY KEY,
     username VARCHAR(255) NOT NULL UNIQUE,
+    display_name VARCHAR(64) DEFAULT NULL,
+    last_seen_at TIMESTAMPTZ DEFAULT NULL,
     created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
     avatar_file_id BIGINT DEFAULT NULL,
     role_id BIGINT NOT NULL DEFAULT 3,
@@ -43,6 +45,9 @@ CREATE TABLE users (
     FOREIGN KEY(avatar_file_id) REFERENCES avatar_files(file_id) ON DELETE SET NULL
 );
 
+-- Usernames are unique regardless of case
+CREATE UNIQUE INDEX unique_username_lower ON users (LOWER(username));
+
 -- Authentication table - stores password hashes and TOTP secrets
 CREATE TABLE auth (
     user_id BIGINT PRIMARY KEY,
@@ -57,12 +62,39 @@ CREATE TABLE invites (
     available_registrations INTEGER NOT NULL DEFAULT 1,
     role_id BIGINT NOT NULL DEFAULT 3,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ DEFAULT NULL,
+    single_use BOOLEAN NOT NULL DEFAULT FALSE,
     FOREIGN KEY (role_id) REFERENCES roles(role_id)
 );
 
 -- Create index on invite code for faster lookups
 CREATE INDEX idx_invites_code ON invites(code);
 
+-- Invite registrations table - records which username and client IP redeemed an invite
+CREATE TABLE invite_registrations (
+    invite_id BIGINT NOT NULL,
+    username VARCHAR(255) NOT NULL,
+    client_ip VARCHAR(255) DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (invite_id) REFERENCES invites(invite_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_invite_registrations_invite_username ON invite_registrations(invite_id, LOWER(username));
+CREATE INDEX idx_invite_registrations_invite_ip ON invite_registrations(invite_id, client_ip);
+
+-- Registrations waiting for an admin when the server requires approval
+CREATE TABLE pending_registrations (
+    registration_id BIGSERIAL PRIMARY KEY,
+    username VARCHAR(255) NOT NULL,
+    password_hash TEXT NOT NULL,
+    role_id BIGINT NOT NULL REFERENCES roles(role_id) ON DELETE CASCADE,
+    invite_id BIGINT REFERENCES invites(invite_id) ON DELETE SET NULL,
+    client_ip VARCHAR(255) DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_pending_registrations_username ON pending_registrations(LOWER(username));
+
 -- ============================================
 -- Communication Structure Tables
 -- ============================================
@@ -70,7 +102,8 @@ CREATE INDEX idx_invites_code ON invites(code);
 -- Groups table - logical groupings for channels
 CREATE TABLE groups (
     group_id BIGSERIAL PRIMARY KEY,
-    group_name VARCHAR(255) NOT NULL
+    group_name VARCHAR(255) NOT NULL,
+    storage_quota_bytes BIGINT CHECK (storage_quota_bytes >= 0)
 );
 
 -- Create enum type for channel types
@@ -89,18 +122,40 @@ CREATE TABLE channels (
 -- ============================================
 -- Messaging Tables
 -- ============================================
--- Messages table - stores all messages (channel and direct)
+-- Group conversations - direct messages between more than two users
+CREATE TABLE group_conversations (
+    conversation_id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(64) DEFAULT NULL,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE group_conversation_members (
+    conversation_id BIGINT NOT NULL REFERENCES group_conversations(conversation_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (conversation_id, user_id)
+);
+
+CREATE INDEX idx_group_conversation_members_user ON group_conversation_members(user_id);
+
+-- Messages table - stores all messages (channel, direct and group)
 CREATE TABLE messages (
     id BIGSERIAL PRIMARY KEY,
     sender_id BIGINT NOT NULL,
     channel_id BIGINT REFERENCES channels(channel_id) ON DELETE CASCADE,
     recipient_id BIGINT REFERENCES users(user_id) ON DELETE CASCADE,
+    conversation_id BIGINT REFERENCES group_conversations(conversation_id) ON DELETE CASCADE,
     message_text TEXT,
     created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
     modified_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
     reply_to_message_id BIGINT,
+    forwarded_from_message_id BIGINT DEFAULT NULL,
+    metadata JSONB,
+    expires_at TIMESTAMPTZ,
     FOREIGN KEY(sender_id) REFERENCES users(user_id) ON DELETE CASCADE,
-    CHECK ( (channel_id IS NOT NULL AND recipient_id IS NULL) OR (channel_id IS NULL AND recipient_id IS NOT NULL) )
+    CHECK ( num_nonnulls(channel_id, recipient_id, conversation_id) = 1 ),
+    CONSTRAINT messages_no_self_reply CHECK (reply_to_message_id IS NULL OR reply_to_message_id <> id)
 );
 
 -- Self-referencing foreign key for reply chains
@@ -112,6 +167,13 @@ FOREIGN KEY (reply_to_message_id)
 REFERENCES messages(id) 
 ON DELETE SET NULL;
 
+-- Forwarded messages keep a reference to the message they were forwarded from
+ALTER TABLE messages
+ADD CONSTRAINT fk_forwarded_from_message
+FOREIGN KEY (forwarded_from_message_id)
+REFERENCES messages(id)
+ON DELETE SET NULL;
+
 -- Files table - stores all file attachments
 CREATE TABLE files (
     file_id BIGSERIAL PRIMARY KEY,
@@ -120,6 +182,7 @@ CREATE TABLE files (
     file_name VARCHAR(255) NOT NULL,
     file_size BIGINT NOT NULL,
     file_hash VARCHAR(255) NOT NULL,
+    blob_id BIGINT NOT NULL,
     metadata JSONB NOT NULL DEFAULT '{"type":"file","mime":"application/octet-stream"}',
     created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
     FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE
@@ -140,12 +203,45 @@ CREATE INDEX idx_messages_sender_modified ON messages(sender_id, modified_at);
 CREATE INDEX idx_messages_reply_to ON messages(reply_to_message_id);
 CREATE INDEX idx_messages_channel ON messages(channel_id);
 CREATE INDEX idx_messages_recipient ON messages(recipient_id);
+CREATE INDEX idx_messages_conversation ON messages(conversation_id);
+CREATE INDEX idx_messages_metadata ON messages USING GIN (metadata);
+CREATE INDEX idx_messages_expires_at ON messages(expires_at) WHERE expires_at IS NOT NULL;
 
 CREATE INDEX idx_files_message ON files(message_id);
 CREATE INDEX idx_files_uuid ON files(file_uuid);
+CREATE INDEX idx_files_blob ON files(blob_id);
+CREATE INDEX idx_files_hash ON files(file_hash, file_size);
 
 CREATE INDEX idx_reactions_message ON reactions(message_id);
 
+-- Idempotency keys - maps client retry keys to the message they created
+CREATE TABLE message_idempotency_keys (
+    user_id BIGINT NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    message_id BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, idempotency_key),
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_message_idempotency_keys_created ON message_idempotency_keys(created_at);
+
+-- Sync cursors - last message each user has synced per conversation
+CREATE TABLE sync_cursors (
+    cursor_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT REFERENCES channels(channel_id) ON DELETE CASCADE,
+    recipient_id BIGINT REFERENCES users(user_id) ON DELETE CASCADE,
+    last_message_id BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    CHECK ( (channel_id IS NOT NULL AND recipient_id IS NULL) OR (channel_id IS NULL AND recipient_id IS NOT NULL) )
+);
+
+CREATE UNIQUE INDEX idx_sync_cursors_channel ON sync_cursors(user_id, channel_id) WHERE channel_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_sync_cursors_recipient ON sync_cursors(user_id, recipient_id) WHERE recipient_id IS NOT NULL;
+
 -- ============================================
 -- Permission System Tables
 -- ============================================
@@ -201,6 +297,14 @@ CREATE TYPE media_type AS ENUM ('camera', 'microphone', 'screen_share', 'screen_
 CREATE INDEX idx_voip_participants_channel ON voip_participants(channel_id);
 CREATE INDEX idx_voip_participants_recipient ON voip_participants(recipient_id);
 
+-- Codec parameters advertised by VoIP publishers, relayed as-is to subscribers
+CREATE TABLE voip_codec_params (
+    user_id BIGINT PRIMARY KEY REFERENCES voip_participants(user_id) ON DELETE CASCADE,
+    codec VARCHAR(32) NOT NULL,
+    sample_rate INTEGER NOT NULL CHECK (sample_rate > 0),
+    channels SMALLINT NOT NULL CHECK (channels BETWEEN 1 AND 2)
+);
+
 
 -- ============================================
 -- Configuration Tables
@@ -210,34 +314,124 @@ CREATE INDEX idx_voip_participants_recipient ON voip_participants(recipient_id);
 CREATE TABLE server_config (
     id BIGSERIAL PRIMARY KEY,
     server_name VARCHAR(100) NOT NULL DEFAULT 'Opencord',
+    description VARCHAR(500) NOT NULL DEFAULT '',
     avatar_file_id BIGINT REFERENCES avatar_files(file_id) ON DELETE SET NULL,
     max_file_size_mb INTEGER NOT NULL DEFAULT 20,
-    max_files_per_message INTEGER NOT NULL DEFAULT 5
+    max_files_per_message INTEGER NOT NULL DEFAULT 5,
+    max_voip_participants INTEGER NOT NULL DEFAULT 25,
+    require_registration_approval BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+-- Custom emojis table - server-wide emojis usable as :name: reactions
+CREATE TABLE custom_emojis (
+    emoji_id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(30) NOT NULL UNIQUE,
+    avatar_file_id BIGINT NOT NULL,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(avatar_file_id) REFERENCES avatar_files(file_id) ON DELETE CASCADE,
+    FOREIGN KEY(created_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+-- Blocked users table - pairs of users where the blocker refuses DMs and calls
+CREATE TABLE blocked_users (
+    blocker_id BIGINT NOT NULL,
+    blocked_id BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (blocker_id, blocked_id),
+    FOREIGN KEY(blocker_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(blocked_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    CHECK (blocker_id <> blocked_id)
+);
+
+CREATE INDEX idx_blocked_users_blocked ON blocked_users(blocked_id);
+
+-- Message requests table - first DMs from users the recipient has never written to
+CREATE TYPE dm_request_status AS ENUM ('Pending', 'Accepted', 'Declined');
+
+CREATE TABLE dm_requests (
+    sender_id BIGINT NOT NULL,
+    recipient_id BIGINT NOT NULL,
+    status dm_request_status NOT NULL DEFAULT 'Pending',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (sender_id, recipient_id),
+    FOREIGN KEY(sender_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(recipient_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    CHECK (sender_id <> recipient_id)
+);
+
+CREATE INDEX idx_dm_requests_recipient ON dm_requests(recipient_id, status);
+
+-- Message mentions table - users mentioned with @username in channel messages
+CREATE TABLE message_mentions (
+    message_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    PRIMARY KEY (message_id, user_id),
+    FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE,
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_message_mentions_user ON message_mentions(user_id);
+
+-- Channel mutes table - per-user notification mute for a channel
+CREATE TABLE channel_mutes (
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    suppress BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, channel_id),
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(channel_id) REFERENCES channels(channel_id) ON DELETE CASCADE
+);
+
+-- Webhooks table - outbound HTTP endpoints notified of new channel messages
+CREATE TABLE webhooks (
+    webhook_id BIGSERIAL PRIMARY KEY,
+    channel_id BIGINT NOT NULL,
+    target_url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(channel_id) REFERENCES channels(channel_id) ON DELETE CASCADE,
+    FOREIGN KEY(created_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_webhooks_channel_id ON webhooks(channel_id);
+
+-- Webhook tokens table - inbound tokens that post to a channel as a bot user
+CREATE TABLE webhook_tokens (
+    token TEXT PRIMARY KEY,
+    channel_id BIGINT NOT NULL,
+    bot_user_id BIGINT NOT NULL,
+    created_by BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(channel_id) REFERENCES channels(channel_id) ON DELETE CASCADE,
+    FOREIGN KEY(bot_user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(created_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+-- User bans table - blocks login until banned_until (NULL = permanent)
+CREATE TABLE user_bans (
+    user_id BIGINT PRIMARY KEY,
+    banned_by BIGINT,
+    reason TEXT,
+    banned_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY(banned_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+-- Channel retention - opt-in auto-purge of channel messages older than retention_days
+CREATE TABLE channel_retention (
+    channel_id BIGINT PRIMARY KEY REFERENCES channels(channel_id) ON DELETE CASCADE,
+    retention_days INTEGER NOT NULL CHECK (retention_days > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
 
 -- ============================================
 -- Triggers for Permission Management
 -- ============================================
 
--- Function to add default role rights when a new group is created
-CREATE OR REPLACE FUNCTION add_group_role_rights_func()
-RETURNS TRIGGER AS $$
-BEGIN
-    INSERT INTO group_role_rights (group_id, role_id, rights)
-    SELECT
-        NEW.group_id,
-        role_id,
-        CASE WHEN role_id IN (1, 2) THEN 8 ELSE 0 END
-    FROM roles;
-    RETURN NEW;
-END;
-$$ LANGUAGE plpgsql;
-
-CREATE TRIGGER add_group_role_rights
-AFTER INSERT ON groups
-FOR EACH ROW
-EXECUTE FUNCTION add_group_role_rights_func();
-
 -- Function to add group rights for all existing groups when a new role is created
 CREATE OR REPLACE FUNCTION add_role_group_rights_func()
 RETURNS TRIGGER AS $$
@@ -257,6 +451,20 @@ AFTER INSERT ON roles
 FOR EACH ROW
 EXECUTE FUNCTION add_role_group_rights_func();
 
+-- A file without an existing blob stores its own content under its file_id
+CREATE OR REPLACE FUNCTION set_file_blob_id_func()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.blob_id := COALESCE(NEW.blob_id, NEW.file_id);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_file_blob_id
+BEFORE INSERT ON files
+FOR EACH ROW
+EXECUTE FUNCTION set_file_blob_id_func();
+
 -- ============================================
 -- Initial Data
 -- ============================================
@@ -12,22 +12,81 @@ use crate::error::{ApiError, DatabaseError};
 use crate::group::{Group, GroupRoleRights};
 use crate::livekit::{LiveKitService, room_name_for_channel};
 use crate::managers::{
-    DefaultNotifierManager, FileError, FileManager, LocalFileManager, LogManager, NotifierManager,
-    TextLogManager,
+    DefaultNotifierManager, EncryptedFileManager, FileError, FileManager, LocalFileManager,
+    LogManager, NotifierManager, TextLogManager,
 };
 use crate::message::{File, FileMetadata};
 use crate::middleware::{AuthorizeService, authorize};
 use crate::model::EventPayload;
 use crate::role::{ADMIN_ROLE_ID, OWNER_ROLE_ID};
-use crate::transport::{ControlRoutingPolicy, ServerMessage};
+use crate::transport::{ControlRoutingPolicy, QueryPayload, ServerMessage};
 use crate::user::User;
 use crate::voip::VoipParticipant;
 
 use axum::Json;
 use axum::extract::{Extension, Path, State};
 use axum::middleware::from_fn_with_state;
+use std::collections::HashSet;
+use tokio::sync::oneshot;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// RIGHTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Levels stored in `group_role_rights.rights`. Service checks, SQL filters and
+/// event routing policies all read their thresholds from these constants.
+pub const ACKNOWLEDGE_RIGHTS: i64 = 1;
+pub const READ_RIGHTS: i64 = 2;
+pub const WRITE_RIGHTS: i64 = 4;
+pub const ACL_RIGHTS: i64 = 8;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MODELS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// What a rights level allows, using the same thresholds the services check:
+/// `can_read` covers reading history and joining voice, `can_write` sending
+/// messages and publishing media, `can_manage` channel and ACL management.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveGroupRights {
+    pub group_id: i64,
+    pub rights: i64,
+    pub can_read: bool,
+    pub can_write: bool,
+    pub can_manage: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveChannelRights {
+    pub channel_id: i64,
+    pub group_id: i64,
+    pub rights: i64,
+    pub can_read: bool,
+    pub can_write: bool,
+    pub can_manage: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveRights {
+    pub role_id: i64,
+    pub groups: Vec<EffectiveGroupRights>,
+    pub channels: Vec<EffectiveChannelRights>,
+}
+
+/// Rights of every role in every group. `rights[i][j]` belongs to
+/// `role_ids[i]` in `group_ids[j]`; pairs without an entry are 0.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RightsMatrix {
+    pub role_ids: Vec<i64>,
+    pub group_ids: Vec<i64>,
+    pub rights: Vec<Vec<i64>>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ERROR
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -40,18 +99,25 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 
     #[error("File manager error")]
     FileManagerError(#[from] FileError),
+
+    #[error("Realtime server unavailable")]
+    RealtimeUnavailable,
 }
 
 impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -60,6 +126,10 @@ impl From<DomainError> for ApiError {
                 tracing::error!("File manager error: {}", file_err);
                 ApiError::InternalServerError("File system error".to_string())
             }
+            DomainError::RealtimeUnavailable => {
+                tracing::error!("Realtime server unavailable");
+                ApiError::InternalServerError("Internal server error".to_string())
+            }
         }
     }
 }
@@ -106,6 +176,8 @@ pub trait AclTransaction: Send + Sync {
         group_id: i64,
     ) -> Result<Vec<File>, DatabaseError>;
 
+    async fn find_unreferenced_blobs(&mut self, files: &[File]) -> Result<Vec<i64>, DatabaseError>;
+
     async fn delete_voip_participant_by_user(
         &mut self,
         user_id: i64,
@@ -166,6 +238,22 @@ pub trait AclRepository: Send + Sync + Clone {
         &self,
         role_id: i64,
     ) -> Result<Vec<GroupRoleRights>, DatabaseError>;
+
+    async fn find_effective_group_rights(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<EffectiveGroupRights>, DatabaseError>;
+
+    async fn find_effective_channel_rights(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<EffectiveChannelRights>, DatabaseError>;
+
+    async fn find_all_role_ids(&self) -> Result<Vec<i64>, DatabaseError>;
+
+    async fn find_all_group_ids(&self) -> Result<Vec<i64>, DatabaseError>;
+
+    async fn find_all_group_role_rights(&self) -> Result<Vec<GroupRoleRights>, DatabaseError>;
 }
 
 pub struct PgAclTransaction {
@@ -279,7 +367,7 @@ impl AclTransaction for PgAclTransaction {
                AND m.channel_id = c.channel_id
                AND u.role_id = $1
                AND c.group_id = $2
-               RETURNING files.file_id, files.file_uuid, files.message_id, files.file_name, files.metadata as "metadata: sqlx::types::Json<FileMetadata>", files.file_size, files.file_hash, files.created_at"#,
+               RETURNING files.file_id, files.file_uuid, files.message_id, files.file_name, files.metadata as "metadata: sqlx::types::Json<FileMetadata>", files.file_size, files.file_hash, files.blob_id, files.created_at"#,
             role_id,
             group_id
         )
@@ -301,7 +389,7 @@ impl AclTransaction for PgAclTransaction {
                AND m.channel_id = c.channel_id
                AND m.sender_id = $1
                AND c.group_id = $2
-               RETURNING files.file_id, files.file_uuid, files.message_id, files.file_name, files.metadata as "metadata: sqlx::types::Json<FileMetadata>", files.file_size, files.file_hash, files.created_at"#,
+               RETURNING files.file_id, files.file_uuid, files.message_id, files.file_name, files.metadata as "metadata: sqlx::types::Json<FileMetadata>", files.file_size, files.file_hash, files.blob_id, files.created_at"#,
             user_id,
             group_id
         )
@@ -310,6 +398,19 @@ impl AclTransaction for PgAclTransaction {
         Ok(files)
     }
 
+    async fn find_unreferenced_blobs(&mut self, files: &[File]) -> Result<Vec<i64>, DatabaseError> {
+        let blob_ids: Vec<i64> = files.iter().map(|file| file.blob_id).collect();
+        let blobs = sqlx::query_scalar!(
+            r#"SELECT DISTINCT blob AS "blob!"
+               FROM UNNEST($1::bigint[]) AS blob
+               WHERE NOT EXISTS (SELECT 1 FROM files WHERE files.blob_id = blob)"#,
+            &blob_ids
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+        Ok(blobs)
+    }
+
     async fn delete_voip_participant_by_user(
         &mut self,
         user_id: i64,
@@ -386,6 +487,8 @@ impl AclTransaction for PgAclTransaction {
                RETURNING
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -434,9 +537,10 @@ impl AclRepository for Postgre {
             INNER JOIN groups g ON g.group_id = grr.group_id
             INNER JOIN group_role_rights user_grr ON user_grr.group_id = grr.group_id
             INNER JOIN users u ON u.role_id = user_grr.role_id
-            WHERE u.user_id = $1 AND user_grr.rights >= 1
+            WHERE u.user_id = $1 AND user_grr.rights >= $2
             ORDER BY grr.group_id, grr.role_id"#,
-            user_id
+            user_id,
+            ACKNOWLEDGE_RIGHTS
         )
         .fetch_all(&self.pool)
         .await?;
@@ -535,6 +639,87 @@ impl AclRepository for Postgre {
         .await?;
         Ok(results)
     }
+
+    async fn find_all_role_ids(&self) -> Result<Vec<i64>, DatabaseError> {
+        let results = sqlx::query_scalar!("SELECT role_id FROM roles ORDER BY role_id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(results)
+    }
+
+    async fn find_all_group_ids(&self) -> Result<Vec<i64>, DatabaseError> {
+        let results = sqlx::query_scalar!("SELECT group_id FROM groups ORDER BY group_id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(results)
+    }
+
+    async fn find_all_group_role_rights(&self) -> Result<Vec<GroupRoleRights>, DatabaseError> {
+        let results = sqlx::query_as!(
+            GroupRoleRights,
+            r#"SELECT group_id, role_id, rights
+               FROM group_role_rights"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(results)
+    }
+
+    async fn find_effective_group_rights(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<EffectiveGroupRights>, DatabaseError> {
+        let results = sqlx::query_as!(
+            EffectiveGroupRights,
+            r#"SELECT
+                grr.group_id,
+                grr.rights,
+                grr.rights >= $3 AS "can_read!",
+                grr.rights >= $4 AS "can_write!",
+                grr.rights >= $5 AS "can_manage!"
+            FROM group_role_rights grr
+            INNER JOIN users u ON u.role_id = grr.role_id
+            WHERE u.user_id = $1 AND grr.rights >= $2
+            ORDER BY grr.group_id"#,
+            user_id,
+            ACKNOWLEDGE_RIGHTS,
+            READ_RIGHTS,
+            WRITE_RIGHTS,
+            ACL_RIGHTS
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(results)
+    }
+
+    async fn find_effective_channel_rights(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<EffectiveChannelRights>, DatabaseError> {
+        let results = sqlx::query_as!(
+            EffectiveChannelRights,
+            r#"SELECT
+                c.channel_id,
+                c.group_id,
+                grr.rights,
+                grr.rights >= $3 AS "can_read!",
+                grr.rights >= $4 AS "can_write!",
+                grr.rights >= $5 AS "can_manage!"
+            FROM channels c
+            INNER JOIN group_role_rights grr ON grr.group_id = c.group_id
+            INNER JOIN users u ON u.role_id = grr.role_id
+            WHERE u.user_id = $1 AND grr.rights >= $2
+            ORDER BY c.channel_id"#,
+            user_id,
+            ACKNOWLEDGE_RIGHTS,
+            READ_RIGHTS,
+            WRITE_RIGHTS,
+            ACL_RIGHTS
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(results)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -602,7 +787,7 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
         new_rights: i64,
         previous_rights: i64,
     ) -> Result<(), DomainError> {
-        if new_rights < 0 || new_rights > 8 {
+        if new_rights < 0 || new_rights > ACL_RIGHTS {
             return Err(DomainError::BadRequest(format!(
                 "Invalid rights value: {} (must be 0-8)",
                 new_rights
@@ -619,14 +804,14 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
             return Ok(());
         }
 
-        let acl_changing = (previous_rights >= 8) != (new_rights >= 8);
+        let acl_changing = (previous_rights >= ACL_RIGHTS) != (new_rights >= ACL_RIGHTS);
         if acl_changing {
             return Err(DomainError::PermissionDenied(
                 "Only owner or admin can grant/remove ACL rights".to_string(),
             ));
         }
 
-        if assigner_rights < 8 {
+        if assigner_rights < ACL_RIGHTS {
             return Err(DomainError::PermissionDenied(
                 "Insufficient permissions to update ACL".to_string(),
             ));
@@ -644,6 +829,94 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
         Ok(rights)
     }
 
+    pub async fn get_effective_rights(&self, user_id: i64) -> Result<EffectiveRights, DomainError> {
+        let role_id = self
+            .repository
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!("User {} not found", user_id)))?;
+
+        let groups = self.repository.find_effective_group_rights(user_id).await?;
+        let channels = self
+            .repository
+            .find_effective_channel_rights(user_id)
+            .await?;
+
+        Ok(EffectiveRights {
+            role_id,
+            groups,
+            channels,
+        })
+    }
+
+    pub async fn get_rights_matrix(&self, user_id: i64) -> Result<RightsMatrix, DomainError> {
+        let role_id = self
+            .repository
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only owner or admin can view the rights matrix".to_string(),
+            ));
+        }
+
+        let role_ids = self.repository.find_all_role_ids().await?;
+        let group_ids = self.repository.find_all_group_ids().await?;
+        let entries = self.repository.find_all_group_role_rights().await?;
+
+        let rights = role_ids
+            .iter()
+            .map(|&role_id| {
+                group_ids
+                    .iter()
+                    .map(|&group_id| {
+                        entries
+                            .iter()
+                            .find(|e| e.role_id == role_id && e.group_id == group_id)
+                            .map(|e| e.rights)
+                            .unwrap_or(0)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(RightsMatrix {
+            role_ids,
+            group_ids,
+            rights,
+        })
+    }
+
+    pub async fn resolve_routing_recipients(
+        &self,
+        user_id: i64,
+        policy: ControlRoutingPolicy,
+    ) -> Result<Vec<i64>, DomainError> {
+        let role_id = self
+            .repository
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only owner or admin can inspect event routing".to_string(),
+            ));
+        }
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.notifier
+            .notify(ServerMessage::Query(QueryPayload::Recipients(
+                policy, reply_tx,
+            )))
+            .await
+            .map_err(|_| DomainError::RealtimeUnavailable)?;
+
+        reply_rx.await.map_err(|_| DomainError::RealtimeUnavailable)
+    }
+
     pub async fn set_group_role_rights(
         &self,
         acls: Vec<GroupRoleRights>,
@@ -686,10 +959,10 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
                 .await
                 .map_err(|e| match e {
                     DatabaseError::ForeignKeyViolation { column, .. } if column == "group_id" => {
-                        DomainError::BadRequest(format!("Group {} not found", acl.group_id))
+                        DomainError::NotFound(format!("Group {} not found", acl.group_id))
                     }
                     DatabaseError::ForeignKeyViolation { column, .. } if column == "role_id" => {
-                        DomainError::BadRequest(format!("Role {} not found", acl.role_id))
+                        DomainError::NotFound(format!("Role {} not found", acl.role_id))
                     }
                     other => DomainError::InternalError(other),
                 })?
@@ -708,13 +981,13 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
                     event,
                     ControlRoutingPolicy::GroupRights {
                         group_id: acl.group_id,
-                        minimun_rights: 1,
+                        minimun_rights: ACKNOWLEDGE_RIGHTS,
                     },
                 ))
                 .await;
 
-            let had_publish = previous_rights > 2;
-            let has_publish = acl.rights > 2;
+            let had_publish = previous_rights > READ_RIGHTS;
+            let has_publish = acl.rights > READ_RIGHTS;
             if had_publish != has_publish && acl.rights > 0 {
                 let participants = tx
                     .find_voip_participants_by_role(acl.role_id, acl.group_id)
@@ -736,17 +1009,18 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
                     .delete_voip_participants_by_role(acl.role_id, acl.group_id)
                     .await?;
                 let deleted_files = tx.delete_files_by_role(acl.role_id, acl.group_id).await?;
+                let deleted_blobs = tx.find_unreferenced_blobs(&deleted_files).await?;
                 let deleted_messages = tx
                     .delete_messages_by_role(acl.role_id, acl.group_id)
                     .await?;
 
-                for file in &deleted_files {
-                    self.file_manager.delete_file(file.file_id)?;
+                for blob_id in deleted_blobs {
+                    self.file_manager.delete_file(blob_id)?;
                 }
 
                 let routing = ControlRoutingPolicy::GroupRights {
                     group_id: acl.group_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 };
 
                 for participant in deleted_participants {
@@ -883,11 +1157,130 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
             .repository
             .find_user_role(target_user_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 target_user_id
             )))?;
 
+        Self::check_role_assignment(requester_role, target_role, new_role_id)?;
+
+        let mut tx = self.repository.begin().await?;
+
+        let updated_user = self
+            .apply_user_role(&mut tx, target_user_id, target_role, new_role_id)
+            .await?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self.notifier.notify(ServerMessage::InvalidateAcl).await;
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                EventPayload::UserUpdated {
+                    user: updated_user.clone(),
+                },
+                ControlRoutingPolicy::Broadcast,
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "User role updated: user_id={}, session_id={}, target_user_id={}, new_role_id={}",
+                    requester_user_id, session_id, target_user_id, new_role_id
+                ),
+                "acl".to_string(),
+            )
+            .await;
+
+        Ok(updated_user)
+    }
+
+    pub async fn set_user_roles(
+        &self,
+        assignments: Vec<(i64, i64)>,
+        requester_user_id: i64,
+        session_id: i64,
+    ) -> Result<Vec<User>, DomainError> {
+        let requester_role = self
+            .repository
+            .find_user_role(requester_user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        let mut seen = HashSet::new();
+        let mut targets = Vec::with_capacity(assignments.len());
+        for (target_user_id, new_role_id) in assignments {
+            if !seen.insert(target_user_id) {
+                return Err(DomainError::BadRequest(format!(
+                    "User {} is assigned more than once",
+                    target_user_id
+                )));
+            }
+
+            let target_role = self
+                .repository
+                .find_user_role(target_user_id)
+                .await?
+                .ok_or(DomainError::NotFound(format!(
+                    "User {} not found",
+                    target_user_id
+                )))?;
+
+            Self::check_role_assignment(requester_role, target_role, new_role_id)?;
+            targets.push((target_user_id, target_role, new_role_id));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let mut updated_users = Vec::with_capacity(targets.len());
+        for &(target_user_id, target_role, new_role_id) in &targets {
+            let updated_user = self
+                .apply_user_role(&mut tx, target_user_id, target_role, new_role_id)
+                .await?;
+            updated_users.push(updated_user);
+        }
+
+        self.repository.commit(tx).await?;
+
+        let _ = self.notifier.notify(ServerMessage::InvalidateAcl).await;
+
+        for user in &updated_users {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    EventPayload::UserUpdated { user: user.clone() },
+                    ControlRoutingPolicy::Broadcast,
+                ))
+                .await;
+        }
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "User roles updated in bulk: user_id={}, session_id={}, assignments={:?}",
+                    requester_user_id,
+                    session_id,
+                    targets
+                        .iter()
+                        .map(|&(target_user_id, _, new_role_id)| (target_user_id, new_role_id))
+                        .collect::<Vec<_>>()
+                ),
+                "acl".to_string(),
+            )
+            .await;
+
+        Ok(updated_users)
+    }
+
+    fn check_role_assignment(
+        requester_role: i64,
+        target_role: i64,
+        new_role_id: i64,
+    ) -> Result<(), DomainError> {
         if requester_role > ADMIN_ROLE_ID {
             return Err(DomainError::PermissionDenied(
                 "Insufficient permissions to update user role".to_string(),
@@ -914,12 +1307,20 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
             ));
         }
 
-        let mut tx = self.repository.begin().await?;
+        Ok(())
+    }
 
+    async fn apply_user_role(
+        &self,
+        tx: &mut R::Transaction,
+        target_user_id: i64,
+        target_role: i64,
+        new_role_id: i64,
+    ) -> Result<User, DomainError> {
         let updated_user =
             tx.set_user_role(target_user_id, new_role_id)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "User {} not found",
                     target_user_id
                 )))?;
@@ -931,7 +1332,7 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
             let new = new_rights.iter().find(|r| r.group_id == old.group_id);
             let new_right = new.map(|r| r.rights).unwrap_or(0);
 
-            let had_publish = old.rights > 2;
+            let had_publish = old.rights > READ_RIGHTS;
             let has_publish = new_right > 2;
             if had_publish != has_publish && new_right > 0 {
                 if let Some(participant) = tx
@@ -956,17 +1357,18 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
                 let deleted_files = tx
                     .delete_files_by_user(target_user_id, old.group_id)
                     .await?;
+                let deleted_blobs = tx.find_unreferenced_blobs(&deleted_files).await?;
                 let deleted_messages = tx
                     .delete_messages_by_user(target_user_id, old.group_id)
                     .await?;
 
-                for file in &deleted_files {
-                    self.file_manager.delete_file(file.file_id)?;
+                for blob_id in deleted_blobs {
+                    self.file_manager.delete_file(blob_id)?;
                 }
 
                 let routing = ControlRoutingPolicy::GroupRights {
                     group_id: old.group_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 };
 
                 if let Some(participant) = deleted_participant {
@@ -1062,31 +1464,6 @@ impl<R: AclRepository, N: NotifierManager, G: LogManager, F: FileManager> AclSer
             }
         }
 
-        self.repository.commit(tx).await?;
-
-        let _ = self.notifier.notify(ServerMessage::InvalidateAcl).await;
-
-        let _ = self
-            .notifier
-            .notify(ServerMessage::Control(
-                EventPayload::UserUpdated {
-                    user: updated_user.clone(),
-                },
-                ControlRoutingPolicy::Broadcast,
-            ))
-            .await;
-
-        let _ = self
-            .logger
-            .log_entry(
-                format!(
-                    "User role updated: user_id={}, session_id={}, target_user_id={}, new_role_id={}",
-                    requester_user_id, session_id, target_user_id, new_role_id
-                ),
-                "acl".to_string(),
-            )
-            .await;
-
         Ok(updated_user)
     }
 }
@@ -1109,18 +1486,40 @@ pub struct UpdateUserRoleRequest {
     pub role_id: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleAssignment {
+    pub user_id: i64,
+    pub role_id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateUserRolesRequest {
+    pub assignments: Vec<RoleAssignment>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ROUTES
 // ═══════════════════════════════════════════════════════════════════════════════
 
 pub fn acl_routes(
-    acl_service: AclService<Postgre, DefaultNotifierManager, TextLogManager, LocalFileManager>,
+    acl_service: AclService<
+        Postgre,
+        DefaultNotifierManager,
+        TextLogManager,
+        EncryptedFileManager<LocalFileManager>,
+    >,
     authorize_service: AuthorizeService<Postgre>,
 ) -> OpenApiRouter<Postgre> {
     OpenApiRouter::new()
         .routes(routes!(get_all_group_role_rights_handler))
+        .routes(routes!(get_effective_rights_handler))
+        .routes(routes!(get_rights_matrix_handler))
         .routes(routes!(set_group_role_rights_handler))
         .routes(routes!(update_user_role_handler))
+        .routes(routes!(update_user_roles_handler))
+        .routes(routes!(resolve_routing_recipients_handler))
         .layer(from_fn_with_state(authorize_service, authorize))
         .with_state(acl_service)
 }
@@ -1144,7 +1543,12 @@ pub fn acl_routes(
 )]
 async fn get_all_group_role_rights_handler(
     State(service): State<
-        AclService<Postgre, DefaultNotifierManager, TextLogManager, LocalFileManager>,
+        AclService<
+            Postgre,
+            DefaultNotifierManager,
+            TextLogManager,
+            EncryptedFileManager<LocalFileManager>,
+        >,
     >,
     Extension(session): Extension<Session>,
 ) -> Result<Json<Vec<GroupRoleRights>>, ApiError> {
@@ -1156,6 +1560,66 @@ async fn get_all_group_role_rights_handler(
     Ok(Json(rights))
 }
 
+#[utoipa::path(
+    get,
+    tag = "acl",
+    path = "/matrix",
+    responses(
+        (status = 200, description = "Rights of every role in every group", body = RightsMatrix),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_rights_matrix_handler(
+    State(service): State<
+        AclService<
+            Postgre,
+            DefaultNotifierManager,
+            TextLogManager,
+            EncryptedFileManager<LocalFileManager>,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+) -> Result<Json<RightsMatrix>, ApiError> {
+    let matrix = service
+        .get_rights_matrix(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(matrix))
+}
+
+#[utoipa::path(
+    get,
+    tag = "acl",
+    path = "/me",
+    responses(
+        (status = 200, description = "Effective rights of the current user per group and channel", body = EffectiveRights),
+        (status = 404, description = "User not found", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn get_effective_rights_handler(
+    State(service): State<
+        AclService<
+            Postgre,
+            DefaultNotifierManager,
+            TextLogManager,
+            EncryptedFileManager<LocalFileManager>,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+) -> Result<Json<EffectiveRights>, ApiError> {
+    let rights = service
+        .get_effective_rights(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(rights))
+}
+
 #[utoipa::path(
     put,
     tag = "acl",
@@ -1174,7 +1638,12 @@ async fn get_all_group_role_rights_handler(
 )]
 async fn set_group_role_rights_handler(
     State(service): State<
-        AclService<Postgre, DefaultNotifierManager, TextLogManager, LocalFileManager>,
+        AclService<
+            Postgre,
+            DefaultNotifierManager,
+            TextLogManager,
+            EncryptedFileManager<LocalFileManager>,
+        >,
     >,
     Extension(session): Extension<Session>,
     Json(payload): Json<Vec<GroupRoleRights>>,
@@ -1205,7 +1674,12 @@ async fn set_group_role_rights_handler(
 )]
 async fn update_user_role_handler(
     State(service): State<
-        AclService<Postgre, DefaultNotifierManager, TextLogManager, LocalFileManager>,
+        AclService<
+            Postgre,
+            DefaultNotifierManager,
+            TextLogManager,
+            EncryptedFileManager<LocalFileManager>,
+        >,
     >,
     Extension(session): Extension<Session>,
     Path(target_user_id): Path<i64>,
@@ -1222,3 +1696,225 @@ async fn update_user_role_handler(
         .map_err(ApiError::from)?;
     Ok(Json(updated_user))
 }
+
+#[utoipa::path(
+    put,
+    tag = "acl",
+    path = "/user-roles",
+    request_body = UpdateUserRolesRequest,
+    responses(
+        (status = 200, description = "All user roles updated successfully", body = Vec<User>),
+        (status = 400, description = "Duplicate user in assignments", body = ApiError),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 404, description = "User not found", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn update_user_roles_handler(
+    State(service): State<
+        AclService<
+            Postgre,
+            DefaultNotifierManager,
+            TextLogManager,
+            EncryptedFileManager<LocalFileManager>,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<UpdateUserRolesRequest>,
+) -> Result<Json<Vec<User>>, ApiError> {
+    let assignments = payload
+        .assignments
+        .into_iter()
+        .map(|a| (a.user_id, a.role_id))
+        .collect();
+
+    let updated_users = service
+        .set_user_roles(assignments, session.user_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(updated_users))
+}
+
+#[utoipa::path(
+    post,
+    tag = "acl",
+    path = "/routing/recipients",
+    request_body = ControlRoutingPolicy,
+    responses(
+        (status = 200, description = "Connected user ids that would receive an event under the policy", body = Vec<i64>),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn resolve_routing_recipients_handler(
+    State(service): State<
+        AclService<
+            Postgre,
+            DefaultNotifierManager,
+            TextLogManager,
+            EncryptedFileManager<LocalFileManager>,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+    Json(policy): Json<ControlRoutingPolicy>,
+) -> Result<Json<Vec<i64>>, ApiError> {
+    let user_ids = service
+        .resolve_routing_recipients(session.user_id, policy)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(user_ids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::role::DEFAULT_ROLE_ID;
+    use sqlx::PgPool;
+    use tokio::sync::mpsc;
+
+    type TestAclService =
+        AclService<Postgre, DefaultNotifierManager, TextLogManager, LocalFileManager>;
+
+    fn service(pool: PgPool) -> (TestAclService, mpsc::Receiver<ServerMessage>) {
+        let (sender, receiver) = mpsc::channel(16);
+        let service = AclService::new(
+            Postgre { pool },
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-acl-test.log")),
+            LocalFileManager::new(std::env::temp_dir().join("opencord-acl-test-files")),
+            LiveKitService::new("localhost:7880", "key", "secret"),
+        );
+        (service, receiver)
+    }
+
+    async fn create_user(pool: &PgPool, username: &str, role_id: i64) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO users (username, role_id) VALUES ($1, $2) RETURNING user_id",
+            username,
+            role_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn create_role(pool: &PgPool, role_name: &str) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO roles (role_name) VALUES ($1) RETURNING role_id",
+            role_name
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn role_of(pool: &PgPool, user_id: i64) -> i64 {
+        sqlx::query_scalar!("SELECT role_id FROM users WHERE user_id = $1", user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn bulk_role_assignment_is_rejected_as_a_whole(pool: PgPool) {
+        let admin = create_user(&pool, "admin", ADMIN_ROLE_ID).await;
+        let other_admin = create_user(&pool, "other-admin", ADMIN_ROLE_ID).await;
+        let member = create_user(&pool, "member", DEFAULT_ROLE_ID).await;
+        let moderator = create_role(&pool, "Moderator").await;
+        let (service, mut receiver) = service(pool.clone());
+
+        let result = service
+            .set_user_roles(
+                vec![(member, moderator), (other_admin, DEFAULT_ROLE_ID)],
+                admin,
+                0,
+            )
+            .await;
+
+        assert!(matches!(result, Err(DomainError::PermissionDenied(_))));
+        assert_eq!(role_of(&pool, member).await, DEFAULT_ROLE_ID);
+        assert_eq!(role_of(&pool, other_admin).await, ADMIN_ROLE_ID);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[sqlx::test]
+    async fn bulk_role_assignment_emits_an_update_per_user(pool: PgPool) {
+        let owner = create_user(&pool, "owner", OWNER_ROLE_ID).await;
+        let alice = create_user(&pool, "alice", DEFAULT_ROLE_ID).await;
+        let bob = create_user(&pool, "bob", DEFAULT_ROLE_ID).await;
+        let moderator = create_role(&pool, "Moderator").await;
+        let (service, mut receiver) = service(pool.clone());
+
+        let updated = service
+            .set_user_roles(vec![(alice, moderator), (bob, ADMIN_ROLE_ID)], owner, 0)
+            .await
+            .unwrap();
+
+        assert_eq!(updated.len(), 2);
+        assert_eq!(role_of(&pool, alice).await, moderator);
+        assert_eq!(role_of(&pool, bob).await, ADMIN_ROLE_ID);
+
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(ServerMessage::InvalidateAcl)
+        ));
+        let mut updates = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                ServerMessage::Control(EventPayload::UserUpdated { user }, _) => {
+                    updates.push((user.user_id, user.role_id))
+                }
+                _ => panic!("unexpected event"),
+            }
+        }
+        assert_eq!(updates, vec![(alice, moderator), (bob, ADMIN_ROLE_ID)]);
+    }
+
+    async fn create_group(pool: &PgPool, group_name: &str) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO groups (group_name) VALUES ($1) RETURNING group_id",
+            group_name
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn rights_matrix_fills_unset_pairs_with_zero(pool: PgPool) {
+        let admin = create_user(&pool, "admin", ADMIN_ROLE_ID).await;
+        let member = create_user(&pool, "member", DEFAULT_ROLE_ID).await;
+        let lobby = create_group(&pool, "lobby").await;
+        let staff = create_group(&pool, "staff").await;
+        sqlx::query!(
+            "INSERT INTO group_role_rights (group_id, role_id, rights) VALUES ($1, $2, $3)",
+            lobby,
+            DEFAULT_ROLE_ID,
+            READ_RIGHTS
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (service, _receiver) = service(pool);
+
+        let matrix = service.get_rights_matrix(admin).await.unwrap();
+
+        assert_eq!(
+            matrix.role_ids,
+            vec![OWNER_ROLE_ID, ADMIN_ROLE_ID, DEFAULT_ROLE_ID]
+        );
+        assert_eq!(matrix.group_ids, vec![lobby, staff]);
+        assert_eq!(
+            matrix.rights,
+            vec![vec![0, 0], vec![0, 0], vec![READ_RIGHTS, 0]]
+        );
+        assert!(matches!(
+            service.get_rights_matrix(member).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+}
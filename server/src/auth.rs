@@ -10,20 +10,20 @@ use crate::db::Postgre;
 use crate::error::{ApiError, DatabaseError};
 use crate::managers::{
     DefaultLockoutManager, DefaultNotifierManager, DefaultPasswordValidator, LockoutManager,
-    LogManager, NotifierManager, PasswordValidator, TextLogManager,
+    LogManager, NotifierManager, PasswordValidator, TextLogManager, UsernameValidator,
 };
-use crate::middleware::{AuthorizeService, authorize};
+use crate::middleware::{AuthorizeService, ClientAddr, authorize};
 use crate::model::EventPayload;
 use crate::role::{ADMIN_ROLE_ID, OWNER_ROLE_ID};
 use crate::user::{User, UserStatusType};
-use crate::transport::{ControlRoutingPolicy, ServerMessage};
+use crate::transport::{CloseReason, ControlRoutingPolicy, ServerMessage};
 
 use bcrypt::{DEFAULT_COST, hash, verify};
 use time::Duration;
 use uuid::Uuid;
 
 use axum::Json;
-use axum::extract::{Extension, State};
+use axum::extract::{Extension, Path, State};
 use axum::middleware::from_fn_with_state;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
@@ -56,9 +56,46 @@ pub struct Invite {
     pub invite_id: i64,
     pub code: String,
     pub available_registrations: i32,
+    pub exhausted: bool,
     pub role_id: i64,
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    pub single_use: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRegistration {
+    pub registration_id: i64,
+    pub username: String,
+    #[serde(skip)]
+    pub password_hash: String,
+    pub role_id: i64,
+    pub invite_id: Option<i64>,
+    pub client_ip: Option<String>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Outcome of `register_user`: an active account, or a request waiting for an
+/// admin when the server requires registration approval.
+pub enum Registration {
+    Created(User),
+    Pending(PendingRegistration),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBan {
+    pub user_id: i64,
+    pub banned_by: Option<i64>,
+    pub reason: Option<String>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub banned_until: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -73,6 +110,12 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 
@@ -87,7 +130,9 @@ impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
+            DomainError::RateLimited(msg) => ApiError::RateLimited(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -140,6 +185,12 @@ pub trait AuthTransaction: Send + Sync {
         user_id: i64,
     ) -> Result<Option<Session>, DatabaseError>;
 
+    async fn remove_user_session_by_id(
+        &mut self,
+        session_id: i64,
+        user_id: i64,
+    ) -> Result<Option<Session>, DatabaseError>;
+
     async fn invalidate_sessions(
         &mut self,
         user_id: i64,
@@ -150,15 +201,56 @@ pub trait AuthTransaction: Send + Sync {
         code: &str,
         available_registrations: i32,
         role_id: i64,
+        expires_at: Option<OffsetDateTime>,
+        single_use: bool,
     ) -> Result<Invite, DatabaseError>;
 
-    async fn update_invite_registrations(
+    async fn consume_invite_registration(
         &mut self,
         invite_id: i64,
-        available_registrations: i32,
     ) -> Result<Option<Invite>, DatabaseError>;
 
+    async fn has_invite_registration(
+        &mut self,
+        invite_id: i64,
+        username: &str,
+        client_ip: Option<&str>,
+    ) -> Result<bool, DatabaseError>;
+
+    async fn create_invite_registration(
+        &mut self,
+        invite_id: i64,
+        username: &str,
+        client_ip: Option<&str>,
+    ) -> Result<(), DatabaseError>;
+
     async fn delete_invite(&mut self, invite_id: i64) -> Result<Option<Invite>, DatabaseError>;
+
+    async fn release_invite_registration(&mut self, invite_id: i64) -> Result<(), DatabaseError>;
+
+    async fn upsert_ban(
+        &mut self,
+        user_id: i64,
+        banned_by: i64,
+        reason: Option<String>,
+        banned_until: Option<OffsetDateTime>,
+    ) -> Result<UserBan, DatabaseError>;
+
+    async fn delete_ban(&mut self, user_id: i64) -> Result<Option<UserBan>, DatabaseError>;
+
+    async fn create_pending_registration(
+        &mut self,
+        username: &str,
+        password_hash: &str,
+        role_id: i64,
+        invite_id: i64,
+        client_ip: Option<&str>,
+    ) -> Result<PendingRegistration, DatabaseError>;
+
+    async fn delete_pending_registration(
+        &mut self,
+        registration_id: i64,
+    ) -> Result<Option<PendingRegistration>, DatabaseError>;
 }
 
 pub trait AuthRepository: Send + Sync + Clone {
@@ -183,6 +275,12 @@ pub trait AuthRepository: Send + Sync + Clone {
     async fn find_invite(&self, code: &str) -> Result<Option<Invite>, DatabaseError>;
 
     async fn find_sessions(&self, user_id: i64) -> Result<Vec<Session>, DatabaseError>;
+
+    async fn find_active_ban(&self, user_id: i64) -> Result<Option<UserBan>, DatabaseError>;
+
+    async fn find_registration_approval_required(&self) -> Result<bool, DatabaseError>;
+
+    async fn find_pending_registrations(&self) -> Result<Vec<PendingRegistration>, DatabaseError>;
 }
 
 pub struct PgAuthTransaction {
@@ -249,6 +347,8 @@ impl AuthTransaction for PgAuthTransaction {
                RETURNING
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -310,6 +410,30 @@ impl AuthTransaction for PgAuthTransaction {
         Ok(result)
     }
 
+    async fn remove_user_session_by_id(
+        &mut self,
+        session_id: i64,
+        user_id: i64,
+    ) -> Result<Option<Session>, DatabaseError> {
+        let result = sqlx::query_as!(
+            Session,
+            r#"DELETE FROM sessions
+               WHERE session_id = $1 AND user_id = $2
+               RETURNING
+                   session_id,
+                   session_token,
+                   user_id,
+                   created_at,
+                   expires_at"#,
+            session_id,
+            user_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
     async fn invalidate_sessions(&mut self, user_id: i64) -> Result<Vec<String>, DatabaseError> {
         let tokens = sqlx::query_scalar!(
             r#"DELETE FROM sessions WHERE user_id = $1 RETURNING session_token"#,
@@ -326,20 +450,27 @@ impl AuthTransaction for PgAuthTransaction {
         code: &str,
         available_registrations: i32,
         role_id: i64,
+        expires_at: Option<OffsetDateTime>,
+        single_use: bool,
     ) -> Result<Invite, DatabaseError> {
         let result = sqlx::query_as!(
             Invite,
-            r#"INSERT INTO invites (code, available_registrations, role_id)
-               VALUES ($1, $2, $3)
+            r#"INSERT INTO invites (code, available_registrations, role_id, expires_at, single_use)
+               VALUES ($1, $2, $3, $4, $5)
                RETURNING
                    invite_id,
                    code,
                    available_registrations,
+                   available_registrations <= 0 as "exhausted!",
                    role_id,
-                   created_at"#,
+                   created_at,
+                   expires_at,
+                   single_use"#,
             code,
             available_registrations,
-            role_id
+            role_id,
+            expires_at,
+            single_use
         )
         .fetch_one(&mut *self.transaction)
         .await?;
@@ -347,23 +478,26 @@ impl AuthTransaction for PgAuthTransaction {
         Ok(result)
     }
 
-    async fn update_invite_registrations(
+    async fn consume_invite_registration(
         &mut self,
         invite_id: i64,
-        available_registrations: i32,
     ) -> Result<Option<Invite>, DatabaseError> {
         let result = sqlx::query_as!(
             Invite,
             r#"UPDATE invites
-               SET available_registrations = $1
-               WHERE invite_id = $2
+               SET available_registrations = available_registrations - 1
+               WHERE invite_id = $1
+                 AND available_registrations > 0
+                 AND (expires_at IS NULL OR expires_at > NOW())
                RETURNING
                    invite_id,
                    code,
                    available_registrations,
+                   available_registrations <= 0 as "exhausted!",
                    role_id,
-                   created_at"#,
-            available_registrations,
+                   created_at,
+                   expires_at,
+                   single_use"#,
             invite_id
         )
         .fetch_optional(&mut *self.transaction)
@@ -372,6 +506,58 @@ impl AuthTransaction for PgAuthTransaction {
         Ok(result)
     }
 
+    async fn has_invite_registration(
+        &mut self,
+        invite_id: i64,
+        username: &str,
+        client_ip: Option<&str>,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM invite_registrations
+                   WHERE invite_id = $1
+                     AND (LOWER(username) = LOWER($2) OR client_ip = $3)
+               ) as "exists!""#,
+            invite_id,
+            username,
+            client_ip
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn create_invite_registration(
+        &mut self,
+        invite_id: i64,
+        username: &str,
+        client_ip: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"INSERT INTO invite_registrations (invite_id, username, client_ip)
+               VALUES ($1, $2, $3)"#,
+            invite_id,
+            username,
+            client_ip
+        )
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release_invite_registration(&mut self, invite_id: i64) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "UPDATE invites SET available_registrations = available_registrations + 1 WHERE invite_id = $1",
+            invite_id
+        )
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
     async fn delete_invite(&mut self, invite_id: i64) -> Result<Option<Invite>, DatabaseError> {
         let result = sqlx::query_as!(
             Invite,
@@ -381,8 +567,11 @@ impl AuthTransaction for PgAuthTransaction {
                    invite_id,
                    code,
                    available_registrations,
+                   available_registrations <= 0 as "exhausted!",
                    role_id,
-                   created_at"#,
+                   created_at,
+                   expires_at,
+                   single_use"#,
             invite_id
         )
         .fetch_optional(&mut *self.transaction)
@@ -390,6 +579,90 @@ impl AuthTransaction for PgAuthTransaction {
 
         Ok(result)
     }
+
+    async fn upsert_ban(
+        &mut self,
+        user_id: i64,
+        banned_by: i64,
+        reason: Option<String>,
+        banned_until: Option<OffsetDateTime>,
+    ) -> Result<UserBan, DatabaseError> {
+        let result = sqlx::query_as!(
+            UserBan,
+            r#"INSERT INTO user_bans (user_id, banned_by, reason, banned_until)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (user_id) DO UPDATE SET
+                   banned_by = EXCLUDED.banned_by,
+                   reason = EXCLUDED.reason,
+                   banned_until = EXCLUDED.banned_until,
+                   created_at = CURRENT_TIMESTAMP
+               RETURNING user_id, banned_by, reason, banned_until, created_at"#,
+            user_id,
+            banned_by,
+            reason,
+            banned_until
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_ban(&mut self, user_id: i64) -> Result<Option<UserBan>, DatabaseError> {
+        let result = sqlx::query_as!(
+            UserBan,
+            r#"DELETE FROM user_bans
+               WHERE user_id = $1
+               RETURNING user_id, banned_by, reason, banned_until, created_at"#,
+            user_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn create_pending_registration(
+        &mut self,
+        username: &str,
+        password_hash: &str,
+        role_id: i64,
+        invite_id: i64,
+        client_ip: Option<&str>,
+    ) -> Result<PendingRegistration, DatabaseError> {
+        let result = sqlx::query_as!(
+            PendingRegistration,
+            r#"INSERT INTO pending_registrations (username, password_hash, role_id, invite_id, client_ip)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING registration_id, username, password_hash, role_id, invite_id, client_ip, created_at"#,
+            username,
+            password_hash,
+            role_id,
+            invite_id,
+            client_ip
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_pending_registration(
+        &mut self,
+        registration_id: i64,
+    ) -> Result<Option<PendingRegistration>, DatabaseError> {
+        let result = sqlx::query_as!(
+            PendingRegistration,
+            r#"DELETE FROM pending_registrations
+               WHERE registration_id = $1
+               RETURNING registration_id, username, password_hash, role_id, invite_id, client_ip, created_at"#,
+            registration_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
 }
 
 impl AuthRepository for Postgre {
@@ -451,6 +724,8 @@ impl AuthRepository for Postgre {
             r#"SELECT
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -458,7 +733,7 @@ impl AuthRepository for Postgre {
                    server_deafen,
                    server_mute
                FROM users
-               WHERE username = $1"#,
+               WHERE LOWER(username) = LOWER($1)"#,
             username
         )
         .fetch_optional(&self.pool)
@@ -474,8 +749,11 @@ impl AuthRepository for Postgre {
                    invite_id,
                    code,
                    available_registrations,
+                   available_registrations <= 0 as "exhausted!",
                    role_id,
-                   created_at
+                   created_at,
+                   expires_at,
+                   single_use
                FROM invites
                ORDER BY created_at DESC"#
         )
@@ -491,6 +769,8 @@ impl AuthRepository for Postgre {
             r#"SELECT
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -514,8 +794,11 @@ impl AuthRepository for Postgre {
                    invite_id,
                    code,
                    available_registrations,
+                   available_registrations <= 0 as "exhausted!",
                    role_id,
-                   created_at
+                   created_at,
+                   expires_at,
+                   single_use
                FROM invites
                WHERE code = $1"#,
             code
@@ -545,6 +828,44 @@ impl AuthRepository for Postgre {
 
         Ok(result)
     }
+
+    async fn find_active_ban(&self, user_id: i64) -> Result<Option<UserBan>, DatabaseError> {
+        let result = sqlx::query_as!(
+            UserBan,
+            r#"SELECT user_id, banned_by, reason, banned_until, created_at
+               FROM user_bans
+               WHERE user_id = $1
+               AND (banned_until IS NULL OR banned_until > NOW())"#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_registration_approval_required(&self) -> Result<bool, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            "SELECT require_registration_approval FROM server_config WHERE id = 1"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    async fn find_pending_registrations(&self) -> Result<Vec<PendingRegistration>, DatabaseError> {
+        let result = sqlx::query_as!(
+            PendingRegistration,
+            r#"SELECT registration_id, username, password_hash, role_id, invite_id, client_ip, created_at
+               FROM pending_registrations
+               ORDER BY created_at"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -564,6 +885,13 @@ pub struct AuthService<
     password_validator: P,
     notifier: N,
     logger: G,
+    bcrypt_cost: u32,
+    username_validator: UsernameValidator,
+    delete_exhausted_invites: bool,
+}
+
+fn hash_cost(password_hash: &str) -> Option<u32> {
+    password_hash.split('$').nth(2)?.parse().ok()
 }
 
 impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierManager, G: LogManager>
@@ -582,21 +910,48 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
             password_validator,
             notifier,
             logger,
+            bcrypt_cost: DEFAULT_COST,
+            username_validator: UsernameValidator::default(),
+            delete_exhausted_invites: false,
         }
     }
 
+    pub fn with_bcrypt_cost(mut self, bcrypt_cost: u32) -> Self {
+        self.bcrypt_cost = bcrypt_cost;
+        self
+    }
+
+    pub fn with_username_validator(mut self, username_validator: UsernameValidator) -> Self {
+        self.username_validator = username_validator;
+        self
+    }
+
+    /// Deletes an invite in the registration that uses its last slot instead of
+    /// keeping it listed as exhausted.
+    pub fn with_delete_exhausted_invites(mut self, delete_exhausted_invites: bool) -> Self {
+        self.delete_exhausted_invites = delete_exhausted_invites;
+        self
+    }
+
     pub async fn register_user(
         &mut self,
         username: &str,
         password: &str,
         invite_code: &str,
-    ) -> Result<User, DomainError> {
+        client_ip: Option<&str>,
+    ) -> Result<Registration, DomainError> {
+        let username = self
+            .username_validator
+            .normalize(username)
+            .map_err(|e| DomainError::BadRequest(format!("Username validation failed: {}", e)))?;
+        let username = username.as_str();
+
         self.password_validator
             .validate_password(password)
             .map_err(|e| DomainError::BadRequest(format!("Password validation failed: {}", e)))?;
 
         let password_hash =
-            hash(password, DEFAULT_COST).map_err(|_| DomainError::PasswordHashingError)?;
+            hash(password, self.bcrypt_cost).map_err(|_| DomainError::PasswordHashingError)?;
 
         let mut tx = self.repository.begin().await?;
 
@@ -606,12 +961,89 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
             .await?
             .ok_or(DomainError::BadRequest("Invalid invite code".to_string()))?;
 
-        if invite.available_registrations <= 0 {
+        if invite
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+        {
             return Err(DomainError::BadRequest(
-                "Invite code has no remaining registrations".to_string(),
+                "Invite code has expired".to_string(),
+            ));
+        }
+
+        if invite.single_use
+            && tx
+                .has_invite_registration(invite.invite_id, username, client_ip)
+                .await?
+        {
+            return Err(DomainError::BadRequest(
+                "Invite code has already been used from this account or address".to_string(),
             ));
         }
 
+        let consumed = tx
+            .consume_invite_registration(invite.invite_id)
+            .await?
+            .ok_or(DomainError::BadRequest(
+                "Invite code has no remaining registrations".to_string(),
+            ))?;
+        let delete_invite = self.delete_exhausted_invites && consumed.exhausted;
+
+        if self
+            .repository
+            .find_registration_approval_required()
+            .await?
+        {
+            if self
+                .repository
+                .find_user_by_username(username)
+                .await?
+                .is_some()
+            {
+                return Err(DomainError::BadRequest(format!(
+                    "Username {} already exists",
+                    username
+                )));
+            }
+
+            let registration = tx
+                .create_pending_registration(
+                    username,
+                    &password_hash,
+                    invite.role_id,
+                    invite.invite_id,
+                    client_ip,
+                )
+                .await
+                .map_err(|e| match e {
+                    DatabaseError::UniqueConstraintViolation { .. } => DomainError::BadRequest(
+                        format!("Username {} is already awaiting approval", username),
+                    ),
+                    e => DomainError::InternalError(e),
+                })?;
+
+            tx.create_invite_registration(invite.invite_id, username, client_ip)
+                .await?;
+
+            if delete_invite {
+                tx.delete_invite(invite.invite_id).await?;
+            }
+
+            self.repository.commit(tx).await?;
+
+            let _ = self
+                .logger
+                .log_entry(
+                    format!(
+                        "Registration pending approval: registration_id={}",
+                        registration.registration_id
+                    ),
+                    "auth".to_string(),
+                )
+                .await;
+
+            return Ok(Registration::Pending(registration));
+        }
+
         let user = tx
             .create_user_with_role(username, invite.role_id)
             .await
@@ -624,9 +1056,13 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
 
         tx.create_auth(user.user_id, &password_hash).await?;
 
-        tx.update_invite_registrations(invite.invite_id, invite.available_registrations - 1)
+        tx.create_invite_registration(invite.invite_id, username, client_ip)
             .await?;
 
+        if delete_invite {
+            tx.delete_invite(invite.invite_id).await?;
+        }
+
         self.repository.commit(tx).await?;
 
         let event = EventPayload::UserCreated { user: user.clone() };
@@ -649,45 +1085,208 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
             )
             .await;
 
-        Ok(user)
+        Ok(Registration::Created(user))
     }
 
-    pub async fn login(&mut self, username: &str, password: &str) -> Result<Session, DomainError> {
-        if let Some(remaining_seconds) = self.lockout_manager.is_locked_out(username) {
-            return Err(DomainError::BadRequest(format!(
-                "Account locked for {} seconds",
-                remaining_seconds
-            )));
-        }
-
-        let mut tx = self.repository.begin().await?;
-
+    pub async fn get_pending_registrations(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<PendingRegistration>, DomainError> {
         let user = self
             .repository
-            .find_user_by_username(username)
+            .find_user(user_id)
             .await?
-            .ok_or_else(|| {
-                self.lockout_manager.record_failed_attempt(username);
-                DomainError::BadRequest("Invalid credentials".to_string())
-            })?;
+            .ok_or(DomainError::NotFound(format!("User {} not found", user_id)))?;
 
-        let auth = self
+        if user.role_id != OWNER_ROLE_ID && user.role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to view registrations".to_string(),
+            ));
+        }
+
+        let registrations = self.repository.find_pending_registrations().await?;
+
+        Ok(registrations)
+    }
+
+    pub async fn approve_registration(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        registration_id: i64,
+    ) -> Result<User, DomainError> {
+        let user = self
             .repository
-            .find_auth(user.user_id)
+            .find_user(user_id)
             .await?
-            .ok_or(DomainError::BadRequest("Invalid credentials".to_string()))?;
+            .ok_or(DomainError::NotFound(format!("User {} not found", user_id)))?;
 
-        if !verify(password, &auth.password_hash)
-            .map_err(|_| DomainError::PasswordVerificationError)?
-        {
-            self.lockout_manager.record_failed_attempt(username);
-            return Err(DomainError::BadRequest("Invalid credentials".to_string()));
+        if user.role_id != OWNER_ROLE_ID && user.role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to approve registrations".to_string(),
+            ));
         }
 
-        self.lockout_manager.record_successful_login(username);
-
-        let session_token = Uuid::new_v4().to_string();
-        let expires_at = OffsetDateTime::now_utc() + Duration::days(30);
+        let mut tx = self.repository.begin().await?;
+
+        let registration = tx
+            .delete_pending_registration(registration_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Registration {} not found",
+                registration_id
+            )))?;
+
+        let created = tx
+            .create_user_with_role(&registration.username, registration.role_id)
+            .await
+            .map_err(|e| match e {
+                DatabaseError::UniqueConstraintViolation { .. } => DomainError::BadRequest(
+                    format!("Username {} already exists", registration.username),
+                ),
+                e => DomainError::InternalError(e),
+            })?;
+
+        tx.create_auth(created.user_id, &registration.password_hash)
+            .await?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::UserCreated {
+            user: created.clone(),
+        };
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Broadcast,
+            ))
+            .await;
+
+        let _ = self.notifier.notify(ServerMessage::InvalidateUsers).await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Registration approved: user_id={}, session_id={}, registration_id={}, new_user_id={}",
+                    user_id, session_id, registration_id, created.user_id
+                ),
+                "auth".to_string(),
+            )
+            .await;
+
+        Ok(created)
+    }
+
+    pub async fn deny_registration(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        registration_id: i64,
+    ) -> Result<(), DomainError> {
+        let user = self
+            .repository
+            .find_user(user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!("User {} not found", user_id)))?;
+
+        if user.role_id != OWNER_ROLE_ID && user.role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to deny registrations".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let registration = tx
+            .delete_pending_registration(registration_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Registration {} not found",
+                registration_id
+            )))?;
+
+        if let Some(invite_id) = registration.invite_id {
+            tx.release_invite_registration(invite_id).await?;
+        }
+
+        self.repository.commit(tx).await?;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Registration denied: user_id={}, session_id={}, registration_id={}",
+                    user_id, session_id, registration_id
+                ),
+                "auth".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<Session, DomainError> {
+        if let Some(remaining_seconds) = self.lockout_manager.is_locked_out(username) {
+            return Err(DomainError::RateLimited(format!(
+                "Account locked for {} seconds",
+                remaining_seconds
+            )));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let user = self
+            .repository
+            .find_user_by_username(username)
+            .await?
+            .ok_or_else(|| {
+                self.lockout_manager.record_failed_attempt(username);
+                DomainError::BadRequest("Invalid credentials".to_string())
+            })?;
+
+        let auth = self
+            .repository
+            .find_auth(user.user_id)
+            .await?
+            .ok_or(DomainError::BadRequest("Invalid credentials".to_string()))?;
+
+        if !verify(password, &auth.password_hash)
+            .map_err(|_| DomainError::PasswordVerificationError)?
+        {
+            self.lockout_manager.record_failed_attempt(username);
+            return Err(DomainError::BadRequest("Invalid credentials".to_string()));
+        }
+
+        self.lockout_manager.record_successful_login(username);
+
+        if self.repository.find_active_ban(user.user_id).await?.is_some() {
+            return Err(DomainError::PermissionDenied(
+                "Account is banned".to_string(),
+            ));
+        }
+
+        if hash_cost(&auth.password_hash).is_some_and(|cost| cost < self.bcrypt_cost) {
+            let password_hash =
+                hash(password, self.bcrypt_cost).map_err(|_| DomainError::PasswordHashingError)?;
+            tx.update_password(auth.user_id, &password_hash).await?;
+
+            let _ = self
+                .logger
+                .log_entry(
+                    format!(
+                        "Password rehashed: user_id={}, cost={}",
+                        auth.user_id, self.bcrypt_cost
+                    ),
+                    "auth".to_string(),
+                )
+                .await;
+        }
+
+        let session_token = Uuid::new_v4().to_string();
+        let expires_at = OffsetDateTime::now_utc() + Duration::days(30);
 
         let session = tx
             .create_session(&session_token, auth.user_id, expires_at)
@@ -726,7 +1325,7 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
             .repository
             .find_auth(user_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -740,11 +1339,11 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
         }
 
         let new_password_hash =
-            hash(new_password, DEFAULT_COST).map_err(|_| DomainError::PasswordHashingError)?;
+            hash(new_password, self.bcrypt_cost).map_err(|_| DomainError::PasswordHashingError)?;
 
         tx.update_password(user_id, &new_password_hash)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -756,7 +1355,10 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
         let _ = self
             .notifier
             .notify(ServerMessage::Command(
-                crate::transport::CommandPayload::DisconnectUser(user_id),
+                crate::transport::CommandPayload::DisconnectUser(
+                    user_id,
+                    CloseReason::PasswordChanged,
+                ),
             ))
             .await;
 
@@ -780,7 +1382,7 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
         let session = tx
             .remove_user_session(session_token, user_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Session {} not found",
                 session_token
             )))?;
@@ -788,7 +1390,11 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
         let _ = self
             .notifier
             .notify(ServerMessage::Command(
-                crate::transport::CommandPayload::Disconnect(user_id, session_token.to_string()),
+                crate::transport::CommandPayload::Disconnect(
+                    user_id,
+                    session_token.to_string(),
+                    CloseReason::LoggedOut,
+                ),
             ))
             .await;
 
@@ -808,6 +1414,49 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
         Ok(())
     }
 
+    pub async fn revoke_session(
+        &self,
+        user_id: i64,
+        current_session_id: i64,
+        session_id: i64,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let session = tx
+            .remove_user_session_by_id(session_id, user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Session {} not found",
+                session_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Command(
+                crate::transport::CommandPayload::Disconnect(
+                    user_id,
+                    session.session_token,
+                    CloseReason::SessionRevoked,
+                ),
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Session revoked: user_id={}, session_id={}, revoked_session_id={}",
+                    user_id, current_session_id, session_id
+                ),
+                "auth".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+
     pub async fn get_user_sessions(&mut self, user_id: i64) -> Result<Vec<Session>, DomainError> {
         let sessions = self.repository.find_sessions(user_id).await?;
         Ok(sessions)
@@ -820,14 +1469,22 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
         code: &str,
         available_registrations: i32,
         role_id: i64,
+        expires_in_minutes: Option<i64>,
+        single_use: bool,
     ) -> Result<Invite, DomainError> {
+        if expires_in_minutes.is_some_and(|m| m <= 0) {
+            return Err(DomainError::BadRequest(
+                "Invite expiry must be positive".to_string(),
+            ));
+        }
+
         let mut tx = self.repository.begin().await?;
 
         let user = self
             .repository
             .find_user(user_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -844,15 +1501,24 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
             ));
         }
 
+        let expires_at =
+            expires_in_minutes.map(|m| OffsetDateTime::now_utc() + Duration::minutes(m));
+
         let invite = tx
-            .create_invite(code, available_registrations, role_id)
+            .create_invite(
+                code,
+                available_registrations,
+                role_id,
+                expires_at,
+                single_use,
+            )
             .await
             .map_err(|e| match &e {
                 DatabaseError::UniqueConstraintViolation { .. } => {
                     DomainError::BadRequest("Invite code already exists".to_string())
                 }
                 DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
-                    "role_id" => DomainError::BadRequest(format!("Role {} not found", role_id)),
+                    "role_id" => DomainError::NotFound(format!("Role {} not found", role_id)),
                     _ => DomainError::InternalError(e),
                 },
                 _ => DomainError::InternalError(e),
@@ -886,7 +1552,7 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
             .repository
             .find_user(user_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -899,7 +1565,7 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
 
         tx.delete_invite(invite_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Invite {} not found",
                 invite_id
             )))?;
@@ -925,7 +1591,7 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
             .repository
             .find_user(user_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -940,6 +1606,143 @@ impl<R: AuthRepository, L: LockoutManager, P: PasswordValidator, N: NotifierMana
 
         Ok(invites)
     }
+
+    pub async fn ban_user(
+        &mut self,
+        user_id: i64,
+        session_id: i64,
+        target_user_id: i64,
+        duration_minutes: Option<i64>,
+        reason: Option<String>,
+    ) -> Result<UserBan, DomainError> {
+        if user_id == target_user_id {
+            return Err(DomainError::BadRequest("You can't ban yourself".to_string()));
+        }
+
+        if duration_minutes.is_some_and(|m| m <= 0) {
+            return Err(DomainError::BadRequest(
+                "Ban duration must be positive".to_string(),
+            ));
+        }
+
+        let user = self
+            .repository
+            .find_user(user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "User {} not found",
+                user_id
+            )))?;
+
+        if user.role_id != OWNER_ROLE_ID && user.role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to ban users".to_string(),
+            ));
+        }
+
+        let target = self
+            .repository
+            .find_user(target_user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "User {} not found",
+                target_user_id
+            )))?;
+
+        if target.role_id == OWNER_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "You can't ban the owner".to_string(),
+            ));
+        }
+
+        if target.role_id == ADMIN_ROLE_ID && user.role_id != OWNER_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only the owner can ban an admin".to_string(),
+            ));
+        }
+
+        let banned_until =
+            duration_minutes.map(|m| OffsetDateTime::now_utc() + Duration::minutes(m));
+
+        let mut tx = self.repository.begin().await?;
+
+        let ban = tx
+            .upsert_ban(target_user_id, user_id, reason, banned_until)
+            .await?;
+
+        let invalidated_count = tx.invalidate_sessions(target_user_id).await?.len();
+
+        self.repository.commit(tx).await?;
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Command(
+                crate::transport::CommandPayload::DisconnectUser(
+                    target_user_id,
+                    CloseReason::Banned,
+                ),
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "User banned: user_id={}, session_id={}, target_user_id={}, duration_minutes={:?}, sessions_invalidated={}",
+                    user_id, session_id, target_user_id, duration_minutes, invalidated_count
+                ),
+                "auth".to_string(),
+            )
+            .await;
+
+        Ok(ban)
+    }
+
+    pub async fn unban_user(
+        &mut self,
+        user_id: i64,
+        session_id: i64,
+        target_user_id: i64,
+    ) -> Result<(), DomainError> {
+        let user = self
+            .repository
+            .find_user(user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "User {} not found",
+                user_id
+            )))?;
+
+        if user.role_id != OWNER_ROLE_ID && user.role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to unban users".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        tx.delete_ban(target_user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "User {} is not banned",
+                target_user_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "User unbanned: user_id={}, session_id={}, target_user_id={}",
+                    user_id, session_id, target_user_id
+                ),
+                "auth".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -967,7 +1770,9 @@ pub struct ChangePasswordRequest {
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RegisterResponse {
-    pub user: User,
+    pub user: Option<User>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_registration: Option<PendingRegistration>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -988,6 +1793,10 @@ pub struct CreateInviteRequest {
     pub code: String,
     pub available_registrations: i32,
     pub role_id: i64,
+    #[serde(default)]
+    pub expires_in_minutes: Option<i64>,
+    #[serde(default)]
+    pub single_use: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -995,6 +1804,12 @@ pub struct DeleteInviteRequest {
     pub invite_id: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BanUserRequest {
+    pub duration_minutes: Option<i64>,
+    pub reason: Option<String>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ROUTES
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -1017,9 +1832,14 @@ pub fn auth_routes(
         .routes(routes!(change_password_handler))
         .routes(routes!(logout_handler))
         .routes(routes!(get_sessions_handler))
+        .routes(routes!(revoke_session_handler))
         .routes(routes!(create_invite_handler))
         .routes(routes!(delete_invite_handler))
         .routes(routes!(get_invites_handler))
+        .routes(routes!(ban_user_handler, unban_user_handler))
+        .routes(routes!(get_pending_registrations_handler))
+        .routes(routes!(approve_registration_handler))
+        .routes(routes!(deny_registration_handler))
         .layer(from_fn_with_state(authorize_service, authorize));
 
     public_routes
@@ -1053,14 +1873,30 @@ async fn register_handler(
             TextLogManager,
         >,
     >,
+    ClientAddr(client_addr): ClientAddr,
     Json(payload): Json<RegisterRequest>,
 ) -> Result<Json<RegisterResponse>, ApiError> {
-    let user = service
-        .register_user(&payload.username, &payload.password, &payload.invite_code)
+    let client_ip = client_addr.to_string();
+    let registration = service
+        .register_user(
+            &payload.username,
+            &payload.password,
+            &payload.invite_code,
+            Some(&client_ip),
+        )
         .await
         .map_err(ApiError::from)?;
 
-    let response = RegisterResponse { user };
+    let response = match registration {
+        Registration::Created(user) => RegisterResponse {
+            user: Some(user),
+            pending_registration: None,
+        },
+        Registration::Pending(registration) => RegisterResponse {
+            user: None,
+            pending_registration: Some(registration),
+        },
+    };
 
     Ok(Json(response))
 }
@@ -1206,9 +2042,42 @@ async fn get_sessions_handler(
 }
 
 #[utoipa::path(
-    post,
+    delete,
     tag = "auth",
-    path = "/invites",
+    path = "/sessions/{session_id}",
+    description = "Revoke one of the current user's sessions",
+    params(("session_id" = i64, Path, description = "Session ID")),
+    responses(
+        (status = 204, description = "Revoked"),
+        (status = 404, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn revoke_session_handler(
+    State(service): State<
+        AuthService<
+            Postgre,
+            DefaultLockoutManager,
+            DefaultPasswordValidator,
+            DefaultNotifierManager,
+            TextLogManager,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+    Path(session_id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .revoke_session(session.user_id, session.session_id, session_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    tag = "auth",
+    path = "/invites",
     description = "Create invite",
     request_body = CreateInviteRequest,
     responses(
@@ -1238,6 +2107,8 @@ async fn create_invite_handler(
             &payload.code,
             payload.available_registrations,
             payload.role_id,
+            payload.expires_in_minutes,
+            payload.single_use,
         )
         .await
         .map_err(ApiError::from)?;
@@ -1307,3 +2178,568 @@ async fn get_invites_handler(
         .map_err(ApiError::from)?;
     Ok(Json(invites))
 }
+
+#[utoipa::path(
+    post,
+    tag = "auth",
+    path = "/users/{user_id}/ban",
+    description = "Ban a user and revoke all of their sessions",
+    params(("user_id" = i64, Path, description = "User ID")),
+    request_body = BanUserRequest,
+    responses(
+        (status = 200, body = UserBan),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn ban_user_handler(
+    State(mut service): State<
+        AuthService<
+            Postgre,
+            DefaultLockoutManager,
+            DefaultPasswordValidator,
+            DefaultNotifierManager,
+            TextLogManager,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+    Path(target_user_id): Path<i64>,
+    Json(payload): Json<BanUserRequest>,
+) -> Result<Json<UserBan>, ApiError> {
+    let ban = service
+        .ban_user(
+            session.user_id,
+            session.session_id,
+            target_user_id,
+            payload.duration_minutes,
+            payload.reason,
+        )
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(ban))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "auth",
+    path = "/users/{user_id}/ban",
+    description = "Lift a user ban",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 204, description = "Unbanned"),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn unban_user_handler(
+    State(mut service): State<
+        AuthService<
+            Postgre,
+            DefaultLockoutManager,
+            DefaultPasswordValidator,
+            DefaultNotifierManager,
+            TextLogManager,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+    Path(target_user_id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .unban_user(session.user_id, session.session_id, target_user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "auth",
+    path = "/registrations",
+    description = "Get registrations waiting for approval",
+    responses(
+        (status = 200, body = Vec<PendingRegistration>),
+        (status = 403, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_pending_registrations_handler(
+    State(service): State<
+        AuthService<
+            Postgre,
+            DefaultLockoutManager,
+            DefaultPasswordValidator,
+            DefaultNotifierManager,
+            TextLogManager,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<PendingRegistration>>, ApiError> {
+    let registrations = service
+        .get_pending_registrations(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(registrations))
+}
+
+#[utoipa::path(
+    post,
+    tag = "auth",
+    path = "/registrations/{registration_id}/approve",
+    description = "Approve a pending registration and create the account",
+    params(("registration_id" = i64, Path, description = "Registration ID")),
+    responses(
+        (status = 200, body = User),
+        (status = 403, body = ApiError),
+        (status = 404, body = ApiError),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn approve_registration_handler(
+    State(service): State<
+        AuthService<
+            Postgre,
+            DefaultLockoutManager,
+            DefaultPasswordValidator,
+            DefaultNotifierManager,
+            TextLogManager,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+    Path(registration_id): Path<i64>,
+) -> Result<Json<User>, ApiError> {
+    let user = service
+        .approve_registration(session.user_id, session.session_id, registration_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(user))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "auth",
+    path = "/registrations/{registration_id}",
+    description = "Deny a pending registration",
+    params(("registration_id" = i64, Path, description = "Registration ID")),
+    responses(
+        (status = 204, description = "Denied"),
+        (status = 403, body = ApiError),
+        (status = 404, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn deny_registration_handler(
+    State(service): State<
+        AuthService<
+            Postgre,
+            DefaultLockoutManager,
+            DefaultPasswordValidator,
+            DefaultNotifierManager,
+            TextLogManager,
+        >,
+    >,
+    Extension(session): Extension<Session>,
+    Path(registration_id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .deny_registration(session.user_id, session.session_id, registration_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::role::DEFAULT_ROLE_ID;
+    use sqlx::PgPool;
+    use tokio::sync::mpsc;
+
+    const INVITE_CODE: &str = "TEST_INVITE";
+    const PASSWORD: &str = "Correct-Horse-Battery-42";
+
+    type TestAuthService = AuthService<
+        Postgre,
+        DefaultLockoutManager,
+        DefaultPasswordValidator,
+        DefaultNotifierManager,
+        TextLogManager,
+    >;
+
+    async fn service(pool: PgPool) -> TestAuthService {
+        let repository = Postgre { pool };
+        let mut tx = repository.begin().await.unwrap();
+        tx.create_invite(INVITE_CODE, 10, DEFAULT_ROLE_ID, None, false)
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+
+        let (sender, _) = mpsc::channel(16);
+        AuthService::new(
+            repository,
+            DefaultLockoutManager::default(),
+            DefaultPasswordValidator::default(),
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-auth-test.log")),
+        )
+        .with_bcrypt_cost(4)
+    }
+
+    async fn register(service: &mut TestAuthService, username: &str) -> Result<User, DomainError> {
+        match service
+            .register_user(username, PASSWORD, INVITE_CODE, None)
+            .await?
+        {
+            Registration::Created(user) => Ok(user),
+            Registration::Pending(_) => panic!("registration approval is off"),
+        }
+    }
+
+    #[sqlx::test]
+    async fn login_ignores_username_case(pool: PgPool) {
+        let mut service = service(pool).await;
+        let user = register(&mut service, "Alice").await.unwrap();
+
+        let session = service.login("aLICE", PASSWORD).await.unwrap();
+
+        assert_eq!(session.user_id, user.user_id);
+    }
+
+    #[sqlx::test]
+    async fn usernames_differing_only_in_case_are_taken(pool: PgPool) {
+        let mut service = service(pool).await;
+        register(&mut service, "Alice").await.unwrap();
+
+        let duplicate = register(&mut service, "ALICE").await;
+
+        assert!(matches!(duplicate, Err(DomainError::BadRequest(_))));
+    }
+
+    async fn require_approval(pool: &PgPool) {
+        sqlx::query!("UPDATE server_config SET require_registration_approval = TRUE WHERE id = 1")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn register_pending(
+        service: &mut TestAuthService,
+        username: &str,
+    ) -> PendingRegistration {
+        match service
+            .register_user(username, PASSWORD, INVITE_CODE, None)
+            .await
+            .unwrap()
+        {
+            Registration::Pending(registration) => registration,
+            Registration::Created(_) => panic!("registration approval is on"),
+        }
+    }
+
+    async fn remaining_uses(service: &TestAuthService) -> i32 {
+        service
+            .repository
+            .find_invite(INVITE_CODE)
+            .await
+            .unwrap()
+            .unwrap()
+            .available_registrations
+    }
+
+    #[sqlx::test]
+    async fn denying_a_registration_returns_the_invite_use(pool: PgPool) {
+        let mut service = service(pool.clone()).await;
+        let owner = staff(&service, "Owner", OWNER_ROLE_ID).await;
+        require_approval(&pool).await;
+
+        let registration = register_pending(&mut service, "Alice").await;
+        assert_eq!(remaining_uses(&service).await, 9);
+
+        service
+            .deny_registration(owner.user_id, 0, registration.registration_id)
+            .await
+            .unwrap();
+
+        assert_eq!(remaining_uses(&service).await, 10);
+    }
+
+    #[sqlx::test]
+    async fn denying_twice_returns_the_invite_use_once(pool: PgPool) {
+        let mut service = service(pool.clone()).await;
+        let owner = staff(&service, "Owner", OWNER_ROLE_ID).await;
+        require_approval(&pool).await;
+        let registration = register_pending(&mut service, "Alice").await;
+
+        service
+            .deny_registration(owner.user_id, 0, registration.registration_id)
+            .await
+            .unwrap();
+        let second = service
+            .deny_registration(owner.user_id, 0, registration.registration_id)
+            .await;
+
+        assert!(matches!(second, Err(DomainError::NotFound(_))));
+        assert_eq!(remaining_uses(&service).await, 10);
+    }
+
+    #[sqlx::test]
+    async fn approving_a_registration_keeps_the_invite_use(pool: PgPool) {
+        let mut service = service(pool.clone()).await;
+        let owner = staff(&service, "Owner", OWNER_ROLE_ID).await;
+        require_approval(&pool).await;
+        let registration = register_pending(&mut service, "Alice").await;
+
+        let user = service
+            .approve_registration(owner.user_id, 0, registration.registration_id)
+            .await
+            .unwrap();
+
+        assert_eq!(user.username, registration.username);
+        assert_eq!(remaining_uses(&service).await, 9);
+        assert!(matches!(
+            service
+                .deny_registration(owner.user_id, 0, registration.registration_id)
+                .await,
+            Err(DomainError::NotFound(_))
+        ));
+        assert_eq!(remaining_uses(&service).await, 9);
+    }
+
+    async fn staff(service: &TestAuthService, username: &str, role_id: i64) -> User {
+        let mut tx = service.repository.begin().await.unwrap();
+        let user = tx.create_user_with_role(username, role_id).await.unwrap();
+        service.repository.commit(tx).await.unwrap();
+        user
+    }
+
+    #[sqlx::test]
+    async fn banning_revokes_sessions_and_blocks_login(pool: PgPool) {
+        let mut service = service(pool.clone()).await;
+        let admin = staff(&service, "Admin", ADMIN_ROLE_ID).await;
+        let target = register(&mut service, "Mallory").await.unwrap();
+        service.login("Mallory", PASSWORD).await.unwrap();
+        service.login("Mallory", PASSWORD).await.unwrap();
+
+        service
+            .ban_user(admin.user_id, 0, target.user_id, None, None)
+            .await
+            .unwrap();
+
+        let active = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM sessions WHERE user_id = $1 AND expires_at > NOW()"#,
+            target.user_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(active, 0);
+        assert!(matches!(
+            service.login("Mallory", PASSWORD).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn bans_respect_the_role_hierarchy(pool: PgPool) {
+        let mut service = service(pool).await;
+        let owner = staff(&service, "Owner", OWNER_ROLE_ID).await;
+        let admin = staff(&service, "Admin", ADMIN_ROLE_ID).await;
+        let other_admin = staff(&service, "Other", ADMIN_ROLE_ID).await;
+        let member = register(&mut service, "Member").await.unwrap();
+
+        for (actor, target) in [
+            (admin.user_id, owner.user_id),
+            (admin.user_id, other_admin.user_id),
+            (member.user_id, admin.user_id),
+        ] {
+            assert!(matches!(
+                service.ban_user(actor, 0, target, None, None).await,
+                Err(DomainError::PermissionDenied(_))
+            ));
+        }
+        assert!(
+            service
+                .ban_user(owner.user_id, 0, other_admin.user_id, Some(60), None)
+                .await
+                .is_ok()
+        );
+    }
+
+    async fn stored_hash(pool: &PgPool, user_id: i64) -> String {
+        sqlx::query_scalar!("SELECT password_hash FROM auth WHERE user_id = $1", user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn hash_cost_is_read_from_the_bcrypt_prefix() {
+        let password_hash = hash("password", 4).unwrap();
+
+        assert_eq!(hash_cost(&password_hash), Some(4));
+        assert_eq!(hash_cost("not a hash"), None);
+    }
+
+    #[sqlx::test]
+    async fn login_upgrades_a_hash_below_the_configured_cost(pool: PgPool) {
+        let mut service = service(pool.clone()).await;
+        let user = register(&mut service, "Alice").await.unwrap();
+        assert_eq!(hash_cost(&stored_hash(&pool, user.user_id).await), Some(4));
+        let mut service = service.with_bcrypt_cost(5);
+
+        service.login("Alice", PASSWORD).await.unwrap();
+
+        assert_eq!(hash_cost(&stored_hash(&pool, user.user_id).await), Some(5));
+        assert!(service.login("Alice", PASSWORD).await.is_ok());
+    }
+
+    async fn invite(
+        service: &TestAuthService,
+        code: &str,
+        available_registrations: i32,
+        expires_at: Option<OffsetDateTime>,
+        single_use: bool,
+    ) {
+        let mut tx = service.repository.begin().await.unwrap();
+        tx.create_invite(
+            code,
+            available_registrations,
+            DEFAULT_ROLE_ID,
+            expires_at,
+            single_use,
+        )
+        .await
+        .unwrap();
+        service.repository.commit(tx).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn expired_invite_is_rejected(pool: PgPool) {
+        let mut service = service(pool).await;
+        let expired = OffsetDateTime::now_utc() - Duration::minutes(1);
+        invite(&service, "EXPIRED", 5, Some(expired), false).await;
+
+        let result = service
+            .register_user("Alice", PASSWORD, "EXPIRED", None)
+            .await;
+
+        assert!(matches!(result, Err(DomainError::BadRequest(_))));
+        let remaining = sqlx::query_scalar!(
+            "SELECT available_registrations FROM invites WHERE code = 'EXPIRED'"
+        )
+        .fetch_one(&service.repository.pool)
+        .await
+        .unwrap();
+        assert_eq!(remaining, 5);
+    }
+
+    #[sqlx::test]
+    async fn used_up_invite_is_rejected(pool: PgPool) {
+        let mut service = service(pool).await;
+        invite(&service, "ONCE", 1, None, false).await;
+
+        service
+            .register_user("Alice", PASSWORD, "ONCE", None)
+            .await
+            .unwrap();
+        let result = service.register_user("Bob", PASSWORD, "ONCE", None).await;
+
+        assert!(matches!(result, Err(DomainError::BadRequest(_))));
+        let users = sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE LOWER(username) = 'bob'")
+            .fetch_one(&service.repository.pool)
+            .await
+            .unwrap();
+        assert_eq!(users, Some(0));
+    }
+
+    #[sqlx::test]
+    async fn single_use_invite_rejects_a_second_registration_from_the_same_address(pool: PgPool) {
+        let mut service = service(pool).await;
+        invite(&service, "PERSONAL", 5, None, true).await;
+
+        service
+            .register_user("Alice", PASSWORD, "PERSONAL", Some("203.0.113.7"))
+            .await
+            .unwrap();
+        let result = service
+            .register_user("Bob", PASSWORD, "PERSONAL", Some("203.0.113.7"))
+            .await;
+
+        assert!(matches!(result, Err(DomainError::BadRequest(_))));
+    }
+
+    #[sqlx::test]
+    async fn exhausted_invite_is_kept_and_marked_by_default(pool: PgPool) {
+        let mut service = service(pool).await;
+        invite(&service, "TWICE", 2, None, false).await;
+
+        service
+            .register_user("Alice", PASSWORD, "TWICE", None)
+            .await
+            .unwrap();
+        let invite = service
+            .repository
+            .find_invite("TWICE")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!invite.exhausted);
+
+        service
+            .register_user("Bob", PASSWORD, "TWICE", None)
+            .await
+            .unwrap();
+        let invite = service
+            .repository
+            .find_invite("TWICE")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(invite.available_registrations, 0);
+        assert!(invite.exhausted);
+        assert!(matches!(
+            service
+                .register_user("Carol", PASSWORD, "TWICE", None)
+                .await,
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn exhausted_invite_is_deleted_when_configured(pool: PgPool) {
+        let mut service = service(pool).await.with_delete_exhausted_invites(true);
+        invite(&service, "TWICE", 2, None, false).await;
+
+        service
+            .register_user("Alice", PASSWORD, "TWICE", None)
+            .await
+            .unwrap();
+        assert!(
+            service
+                .repository
+                .find_invite("TWICE")
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let registration = service
+            .register_user("Bob", PASSWORD, "TWICE", None)
+            .await
+            .unwrap();
+        assert!(matches!(registration, Registration::Created(_)));
+        assert!(
+            service
+                .repository
+                .find_invite("TWICE")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
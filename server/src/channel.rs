@@ -4,16 +4,20 @@
 
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
 use utoipa::ToSchema;
 
+use crate::acl::{ACKNOWLEDGE_RIGHTS, ACL_RIGHTS, READ_RIGHTS};
 use crate::auth::Session;
 use crate::db::Postgre;
 use crate::error::{ApiError, DatabaseError};
+use crate::livekit::{LiveKitService, room_name_for_channel};
 use crate::managers::{DefaultNotifierManager, LogManager, NotifierManager, TextLogManager};
 use crate::middleware::{AuthorizeService, authorize};
 use crate::model::EventPayload;
 use crate::role::{ADMIN_ROLE_ID, OWNER_ROLE_ID};
 use crate::transport::{ControlRoutingPolicy, ServerMessage};
+use crate::voip::VoipParticipant;
 
 use axum::Json;
 use axum::extract::{Extension, Path, State};
@@ -52,6 +56,25 @@ pub struct Channel {
     pub channel_type: ChannelType,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelMute {
+    pub user_id: i64,
+    pub channel_id: i64,
+    pub suppress: bool,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelRetention {
+    pub channel_id: i64,
+    pub retention_days: i32,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ERROR
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -64,6 +87,9 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 }
@@ -72,7 +98,8 @@ impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -108,6 +135,45 @@ pub trait ChannelTransaction: Send + Sync {
     async fn delete(&mut self, channel_id: i64) -> Result<Option<Channel>, DatabaseError>;
 
     async fn find_user_role(&mut self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_group_id(&mut self, channel_id: i64) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_role_group_rights(
+        &mut self,
+        role_id: i64,
+        group_id: i64,
+    ) -> Result<Option<i64>, DatabaseError>;
+
+    async fn delete_unauthorized_voip_participants(
+        &mut self,
+        channel_id: i64,
+        group_id: i64,
+        minimum_rights: i64,
+    ) -> Result<Vec<VoipParticipant>, DatabaseError>;
+
+    async fn upsert_mute(
+        &mut self,
+        user_id: i64,
+        channel_id: i64,
+        suppress: bool,
+    ) -> Result<ChannelMute, DatabaseError>;
+
+    async fn delete_mute(
+        &mut self,
+        user_id: i64,
+        channel_id: i64,
+    ) -> Result<Option<ChannelMute>, DatabaseError>;
+
+    async fn upsert_retention(
+        &mut self,
+        channel_id: i64,
+        retention_days: i32,
+    ) -> Result<ChannelRetention, DatabaseError>;
+
+    async fn delete_retention(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<Option<ChannelRetention>, DatabaseError>;
 }
 
 pub trait ChannelRepository: Send + Sync + Clone {
@@ -120,6 +186,19 @@ pub trait ChannelRepository: Send + Sync + Clone {
     async fn rollback(&self, transaction: Self::Transaction) -> Result<(), DatabaseError>;
 
     async fn list_by_user_role(&self, user_id: i64) -> Result<Vec<Channel>, DatabaseError>;
+
+    async fn list_mutes(&self, user_id: i64) -> Result<Vec<ChannelMute>, DatabaseError>;
+
+    async fn find_user_channel_rights(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+    ) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_retention(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<ChannelRetention>, DatabaseError>;
 }
 
 pub struct PgChannelTransaction {
@@ -218,6 +297,135 @@ impl ChannelTransaction for PgChannelTransaction {
             .await?;
         Ok(result)
     }
+
+    async fn find_group_id(&mut self, channel_id: i64) -> Result<Option<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            "SELECT group_id FROM channels WHERE channel_id = $1 FOR UPDATE",
+            channel_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_role_group_rights(
+        &mut self,
+        role_id: i64,
+        group_id: i64,
+    ) -> Result<Option<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            "SELECT rights FROM group_role_rights WHERE role_id = $1 AND group_id = $2",
+            role_id,
+            group_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+        Ok(result)
+    }
+
+    async fn delete_unauthorized_voip_participants(
+        &mut self,
+        channel_id: i64,
+        group_id: i64,
+        minimum_rights: i64,
+    ) -> Result<Vec<VoipParticipant>, DatabaseError> {
+        let deleted = sqlx::query_as!(
+            VoipParticipant,
+            r#"DELETE FROM voip_participants
+            USING users u
+            WHERE voip_participants.user_id = u.user_id
+            AND voip_participants.channel_id = $1
+            AND COALESCE(
+                (SELECT grr.rights FROM group_role_rights grr
+                WHERE grr.group_id = $2 AND grr.role_id = u.role_id),
+                0
+            ) < $3
+            RETURNING voip_participants.*"#,
+            channel_id,
+            group_id,
+            minimum_rights
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(deleted)
+    }
+
+    async fn upsert_mute(
+        &mut self,
+        user_id: i64,
+        channel_id: i64,
+        suppress: bool,
+    ) -> Result<ChannelMute, DatabaseError> {
+        let mute = sqlx::query_as!(
+            ChannelMute,
+            r#"INSERT INTO channel_mutes (user_id, channel_id, suppress)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, channel_id) DO UPDATE SET suppress = EXCLUDED.suppress
+            RETURNING user_id, channel_id, suppress, created_at"#,
+            user_id,
+            channel_id,
+            suppress
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+        Ok(mute)
+    }
+
+    async fn delete_mute(
+        &mut self,
+        user_id: i64,
+        channel_id: i64,
+    ) -> Result<Option<ChannelMute>, DatabaseError> {
+        let mute = sqlx::query_as!(
+            ChannelMute,
+            r#"DELETE FROM channel_mutes
+            WHERE user_id = $1 AND channel_id = $2
+            RETURNING user_id, channel_id, suppress, created_at"#,
+            user_id,
+            channel_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+        Ok(mute)
+    }
+
+    async fn upsert_retention(
+        &mut self,
+        channel_id: i64,
+        retention_days: i32,
+    ) -> Result<ChannelRetention, DatabaseError> {
+        let retention = sqlx::query_as!(
+            ChannelRetention,
+            r#"INSERT INTO channel_retention (channel_id, retention_days)
+            VALUES ($1, $2)
+            ON CONFLICT (channel_id) DO UPDATE SET
+                retention_days = EXCLUDED.retention_days,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING channel_id, retention_days, updated_at"#,
+            channel_id,
+            retention_days
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+        Ok(retention)
+    }
+
+    async fn delete_retention(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<Option<ChannelRetention>, DatabaseError> {
+        let retention = sqlx::query_as!(
+            ChannelRetention,
+            r#"DELETE FROM channel_retention
+            WHERE channel_id = $1
+            RETURNING channel_id, retention_days, updated_at"#,
+            channel_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+        Ok(retention)
+    }
 }
 
 impl ChannelRepository for Postgre {
@@ -249,7 +457,22 @@ impl ChannelRepository for Postgre {
             FROM channels c
             INNER JOIN group_role_rights grr ON c.group_id = grr.group_id
             INNER JOIN users u ON u.role_id = grr.role_id
-            WHERE u.user_id = $1 AND grr.rights >= 1"#,
+            WHERE u.user_id = $1 AND grr.rights >= $2"#,
+            user_id,
+            ACKNOWLEDGE_RIGHTS
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn list_mutes(&self, user_id: i64) -> Result<Vec<ChannelMute>, DatabaseError> {
+        let results = sqlx::query_as!(
+            ChannelMute,
+            r#"SELECT user_id, channel_id, suppress, created_at
+            FROM channel_mutes
+            WHERE user_id = $1"#,
             user_id
         )
         .fetch_all(&self.pool)
@@ -257,6 +480,43 @@ impl ChannelRepository for Postgre {
 
         Ok(results)
     }
+
+    async fn find_user_channel_rights(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+    ) -> Result<Option<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT grr.rights
+            FROM group_role_rights grr
+            INNER JOIN users u ON u.role_id = grr.role_id
+            INNER JOIN channels c ON c.group_id = grr.group_id
+            WHERE c.channel_id = $1 AND u.user_id = $2"#,
+            channel_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_retention(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<ChannelRetention>, DatabaseError> {
+        let result = sqlx::query_as!(
+            ChannelRetention,
+            r#"SELECT channel_id, retention_days, updated_at
+            FROM channel_retention
+            WHERE channel_id = $1"#,
+            channel_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -268,14 +528,16 @@ pub struct ChannelService<R: ChannelRepository, N: NotifierManager, G: LogManage
     repository: R,
     notifier: N,
     logger: G,
+    livekit: LiveKitService,
 }
 
 impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R, N, G> {
-    pub fn new(repository: R, notifier: N, logger: G) -> Self {
+    pub fn new(repository: R, notifier: N, logger: G, livekit: LiveKitService) -> Self {
         Self {
             repository,
             notifier,
             logger,
+            livekit,
         }
     }
 
@@ -318,7 +580,7 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
                     format!("Channel name '{}' is already taken", trimmed_name),
                 ),
                 DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
-                    "group_id" => DomainError::BadRequest(format!("Group {} not found", group_id)),
+                    "group_id" => DomainError::NotFound(format!("Group {} not found", group_id)),
                     _ => DomainError::InternalError(e),
                 },
                 _ => DomainError::InternalError(e),
@@ -336,7 +598,7 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
                 event,
                 ControlRoutingPolicy::GroupRights {
                     group_id: channel.group_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 },
             ))
             .await;
@@ -356,6 +618,105 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
         Ok(channels)
     }
 
+    pub async fn list_channel_mutes(&self, user_id: i64) -> Result<Vec<ChannelMute>, DomainError> {
+        let mutes = self.repository.list_mutes(user_id).await?;
+        Ok(mutes)
+    }
+
+    pub async fn mute_channel(
+        &self,
+        channel_id: i64,
+        suppress: bool,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<ChannelMute, DomainError> {
+        let rights = self
+            .repository
+            .find_user_channel_rights(channel_id, user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied(
+                "No access to channel".to_string(),
+            ))?;
+
+        if rights < READ_RIGHTS {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to mute channel".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+        let mute = tx.upsert_mute(user_id, channel_id, suppress).await?;
+        self.repository.commit(tx).await?;
+
+        let _ = self.notifier.notify(ServerMessage::InvalidateMutes).await;
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                EventPayload::ChannelMuteUpdated {
+                    channel_id,
+                    mute: Some(mute.clone()),
+                },
+                ControlRoutingPolicy::User { user_id },
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Channel muted: user_id={}, session_id={}, channel_id={}, suppress={}",
+                    user_id, session_id, channel_id, suppress
+                ),
+                "channel".to_string(),
+            )
+            .await;
+
+        Ok(mute)
+    }
+
+    pub async fn unmute_channel(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.repository.begin().await?;
+        tx.delete_mute(user_id, channel_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Channel {} is not muted",
+                channel_id
+            )))?;
+        self.repository.commit(tx).await?;
+
+        let _ = self.notifier.notify(ServerMessage::InvalidateMutes).await;
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                EventPayload::ChannelMuteUpdated {
+                    channel_id,
+                    mute: None,
+                },
+                ControlRoutingPolicy::User { user_id },
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Channel unmuted: user_id={}, session_id={}, channel_id={}",
+                    user_id, session_id, channel_id
+                ),
+                "channel".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+
     pub async fn update_channel_name(
         &self,
         channel_id: i64,
@@ -395,7 +756,7 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
                 ),
                 other => DomainError::InternalError(other),
             })?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Channel {} not found",
                 channel_id
             )))?;
@@ -412,7 +773,7 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
                 event,
                 ControlRoutingPolicy::ChannelRights {
                     channel_id: updated_channel.channel_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 },
             ))
             .await;
@@ -431,11 +792,11 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
         Ok(())
     }
 
-    pub async fn update_channel_group(
+    pub async fn move_channel(
         &self,
+        user_id: i64,
         channel_id: i64,
         new_group_id: i64,
-        user_id: i64,
         session_id: i64,
     ) -> Result<(), DomainError> {
         let mut tx = self.repository.begin().await?;
@@ -451,48 +812,227 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
             ));
         }
 
+        let old_group_id = tx
+            .find_group_id(channel_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Channel {} not found",
+                channel_id
+            )))?;
+
+        let new_group_rights = tx
+            .find_role_group_rights(role_id, new_group_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Group {} not found",
+                new_group_id
+            )))?;
+        let old_group_rights = tx
+            .find_role_group_rights(role_id, old_group_id)
+            .await?
+            .unwrap_or(0);
+
+        if old_group_rights < ACL_RIGHTS || new_group_rights < ACL_RIGHTS {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions on source or target group".to_string(),
+            ));
+        }
+
         let updated_channel = tx
             .update_group(channel_id, new_group_id)
             .await
             .map_err(|e| match &e {
                 DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
                     "group_id" => {
-                        DomainError::BadRequest(format!("Group {} not found", new_group_id))
+                        DomainError::NotFound(format!("Group {} not found", new_group_id))
                     }
                     _ => DomainError::InternalError(e),
                 },
                 _ => DomainError::InternalError(e),
             })?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Channel {} not found",
                 channel_id
             )))?;
 
+        let evicted = tx
+            .delete_unauthorized_voip_participants(channel_id, new_group_id, 2)
+            .await?;
+
         self.repository.commit(tx).await?;
 
-        let event = EventPayload::ChannelUpdated {
-            channel: updated_channel,
-        };
+        for participant in &evicted {
+            let room = room_name_for_channel(channel_id);
+            let _ = self
+                .livekit
+                .remove_participant(&room, &participant.user_id.to_string())
+                .await;
+
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    EventPayload::VoipParticipantDeleted {
+                        user_id: participant.user_id,
+                    },
+                    ControlRoutingPolicy::GroupRights {
+                        group_id: old_group_id,
+                        minimun_rights: ACKNOWLEDGE_RIGHTS,
+                    },
+                ))
+                .await;
+        }
 
-        let _ = self
-            .notifier
-            .notify(ServerMessage::Control(
-                event,
-                ControlRoutingPolicy::ChannelRights {
-                    channel_id,
-                    minimun_rights: 1,
-                },
-            ))
-            .await;
+        if !evicted.is_empty() {
+            let _ = self.notifier.notify(ServerMessage::InvalidateVoip).await;
+        }
+
+        let _ = self.notifier.notify(ServerMessage::InvalidateAcl).await;
+
+        let mut group_ids = vec![old_group_id];
+        if new_group_id != old_group_id {
+            group_ids.push(new_group_id);
+        }
+
+        for group_id in group_ids {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    EventPayload::ChannelUpdated {
+                        channel: updated_channel.clone(),
+                    },
+                    ControlRoutingPolicy::GroupRights {
+                        group_id,
+                        minimun_rights: ACKNOWLEDGE_RIGHTS,
+                    },
+                ))
+                .await;
+        }
 
         let _ = self.logger.log_entry(
-            format!("Channel group updated: user_id={}, session_id={}, channel_id={}, new_group_id={}", user_id, session_id, channel_id, new_group_id),
+            format!("Channel moved: user_id={}, session_id={}, channel_id={}, old_group_id={}, new_group_id={}, evicted={}", user_id, session_id, channel_id, old_group_id, new_group_id, evicted.len()),
             "channel".to_string(),
         ).await;
 
         Ok(())
     }
 
+    pub async fn get_channel_retention(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+    ) -> Result<Option<ChannelRetention>, DomainError> {
+        let rights = self
+            .repository
+            .find_user_channel_rights(channel_id, user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied(
+                "No access to channel".to_string(),
+            ))?;
+
+        if rights < READ_RIGHTS {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to read channel".to_string(),
+            ));
+        }
+
+        let retention = self.repository.find_retention(channel_id).await?;
+        Ok(retention)
+    }
+
+    pub async fn set_channel_retention(
+        &self,
+        channel_id: i64,
+        retention_days: i32,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<ChannelRetention, DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let role_id = tx
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to update channel".to_string(),
+            ));
+        }
+
+        if retention_days < 1 {
+            return Err(DomainError::BadRequest(
+                "Retention must be at least 1 day".to_string(),
+            ));
+        }
+
+        let retention = tx
+            .upsert_retention(channel_id, retention_days)
+            .await
+            .map_err(|e| match &e {
+                DatabaseError::ForeignKeyViolation { .. } => {
+                    DomainError::NotFound(format!("Channel {} not found", channel_id))
+                }
+                _ => DomainError::InternalError(e),
+            })?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Channel retention set: user_id={}, session_id={}, channel_id={}, retention_days={}",
+                    user_id, session_id, channel_id, retention_days
+                ),
+                "channel".to_string(),
+            )
+            .await;
+
+        Ok(retention)
+    }
+
+    pub async fn clear_channel_retention(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let role_id = tx
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to update channel".to_string(),
+            ));
+        }
+
+        tx.delete_retention(channel_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Channel {} has no retention policy",
+                channel_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Channel retention cleared: user_id={}, session_id={}, channel_id={}",
+                    user_id, session_id, channel_id
+                ),
+                "channel".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+
     pub async fn delete_channel(
         &self,
         channel_id: i64,
@@ -515,7 +1055,7 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
         let deleted = tx
             .delete(channel_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Channel {} not found",
                 channel_id
             )))?;
@@ -530,7 +1070,7 @@ impl<R: ChannelRepository, N: NotifierManager, G: LogManager> ChannelService<R,
                 event,
                 ControlRoutingPolicy::GroupRights {
                     group_id: deleted.group_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 },
             ))
             .await;
@@ -581,6 +1121,19 @@ pub struct UpdateChannelGroupRequest {
     pub group_id: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MuteChannelRequest {
+    #[serde(default)]
+    pub suppress: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateChannelRetentionRequest {
+    pub retention_days: i32,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ROUTES
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -595,6 +1148,13 @@ pub fn channel_routes(
         .routes(routes!(delete_channel_handler))
         .routes(routes!(update_channel_name_handler))
         .routes(routes!(update_channel_group_handler))
+        .routes(routes!(list_channel_mutes_handler))
+        .routes(routes!(mute_channel_handler, unmute_channel_handler))
+        .routes(routes!(
+            get_channel_retention_handler,
+            set_channel_retention_handler,
+            clear_channel_retention_handler
+        ))
         .layer(from_fn_with_state(authorize_service, authorize))
         .with_state(channel_service)
 }
@@ -745,7 +1305,7 @@ async fn update_channel_name_handler(
     ),
     request_body = UpdateChannelGroupRequest,
     responses(
-        (status = 204, description = "Channel group updated successfully"),
+        (status = 204, description = "Channel moved to the group successfully"),
         (status = 403, description = "Permission denied", body = ApiError),
         (status = 404, description = "Channel not found", body = ApiError),
         (status = 422, description = "Invalid input", body = ApiError),
@@ -762,7 +1322,187 @@ async fn update_channel_group_handler(
     Json(payload): Json<UpdateChannelGroupRequest>,
 ) -> Result<(), ApiError> {
     service
-        .update_channel_group(id, payload.group_id, session.user_id, session.session_id)
+        .move_channel(session.user_id, id, payload.group_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "channel",
+    path = "/mutes",
+    responses(
+        (status = 200, description = "Successfully retrieved channel mutes", body = Vec<ChannelMute>),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn list_channel_mutes_handler(
+    State(service): State<ChannelService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<ChannelMute>>, ApiError> {
+    let mutes = service
+        .list_channel_mutes(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(mutes))
+}
+
+#[utoipa::path(
+    put,
+    tag = "channel",
+    path = "/{id}/mute",
+    params(
+        ("id", Path, description = "The ID of the channel to mute"),
+    ),
+    request_body = MuteChannelRequest,
+    responses(
+        (status = 200, description = "Channel muted successfully", body = ChannelMute),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn mute_channel_handler(
+    State(service): State<ChannelService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i64>,
+    Json(payload): Json<MuteChannelRequest>,
+) -> Result<Json<ChannelMute>, ApiError> {
+    let mute = service
+        .mute_channel(id, payload.suppress, session.user_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(mute))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "channel",
+    path = "/{id}/mute",
+    params(
+        ("id", Path, description = "The ID of the channel to unmute"),
+    ),
+    responses(
+        (status = 204, description = "Channel unmuted successfully"),
+        (status = 404, description = "Channel not muted", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn unmute_channel_handler(
+    State(service): State<ChannelService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .unmute_channel(id, session.user_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "channel",
+    path = "/{id}/retention",
+    params(
+        ("id", Path, description = "The ID of the channel"),
+    ),
+    responses(
+        (status = 200, description = "Channel retention policy, null when messages are kept forever", body = Option<ChannelRetention>),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn get_channel_retention_handler(
+    State(service): State<ChannelService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i64>,
+) -> Result<Json<Option<ChannelRetention>>, ApiError> {
+    let retention = service
+        .get_channel_retention(id, session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(retention))
+}
+
+#[utoipa::path(
+    put,
+    tag = "channel",
+    path = "/{id}/retention",
+    params(
+        ("id", Path, description = "The ID of the channel"),
+    ),
+    request_body = UpdateChannelRetentionRequest,
+    responses(
+        (status = 200, description = "Channel retention updated successfully", body = ChannelRetention),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 404, description = "Channel not found", body = ApiError),
+        (status = 422, description = "Invalid input", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn set_channel_retention_handler(
+    State(service): State<ChannelService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdateChannelRetentionRequest>,
+) -> Result<Json<ChannelRetention>, ApiError> {
+    let retention = service
+        .set_channel_retention(
+            id,
+            payload.retention_days,
+            session.user_id,
+            session.session_id,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(retention))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "channel",
+    path = "/{id}/retention",
+    params(
+        ("id", Path, description = "The ID of the channel"),
+    ),
+    responses(
+        (status = 204, description = "Channel retention cleared successfully"),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 404, description = "Channel has no retention policy", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn clear_channel_retention_handler(
+    State(service): State<ChannelService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .clear_channel_retention(id, session.user_id, session.session_id)
         .await
         .map_err(ApiError::from)?;
 
@@ -8,22 +8,52 @@ use serde_json::json;
 use std::{error::Error, fmt};
 use utoipa::ToSchema;
 
+#[derive(Debug, Serialize, ToSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    ValidationFailed,
+    PermissionDenied,
+    NotFound,
+    RateLimited,
+    InternalError,
+}
+
 #[derive(Debug, ToSchema, Clone, PartialEq, Eq)]
 pub enum ApiError {
     InternalServerError(String),
     UnprocessableEntity(String),
+    PermissionDenied(String),
+    NotFound(String),
+    RateLimited(String),
+}
+
+impl ApiError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::InternalServerError(_) => ErrorCode::InternalError,
+            ApiError::UnprocessableEntity(_) => ErrorCode::ValidationFailed,
+            ApiError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::RateLimited(_) => ErrorCode::RateLimited,
+        }
+    }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        let code = self.code();
         let (status, message) = match self {
             ApiError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
-            ApiError::UnprocessableEntity(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
+            ApiError::UnprocessableEntity(msg)
+            | ApiError::PermissionDenied(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::RateLimited(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
         };
 
         let body = Json(json!({
             "reason": message,
-            "code": 1
+            "code": 1,
+            "errorCode": code
         }));
 
         (status, body).into_response()
@@ -183,3 +213,63 @@ impl DatabaseError {
         error.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    async fn body(error: ApiError) -> (StatusCode, Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn error_bodies_keep_the_numeric_code_and_add_an_error_code() {
+        let cases = [
+            (
+                ApiError::InternalServerError("boom".to_string()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+            ),
+            (
+                ApiError::UnprocessableEntity("boom".to_string()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "VALIDATION_FAILED",
+            ),
+            (
+                ApiError::PermissionDenied("boom".to_string()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "PERMISSION_DENIED",
+            ),
+            (
+                ApiError::NotFound("boom".to_string()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "NOT_FOUND",
+            ),
+            (
+                ApiError::RateLimited("boom".to_string()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "RATE_LIMITED",
+            ),
+        ];
+
+        for (error, expected_status, expected_code) in cases {
+            let (status, body) = body(error).await;
+
+            assert_eq!(status, expected_status);
+            assert_eq!(
+                body,
+                json!({
+                    "reason": "boom",
+                    "code": 1,
+                    "errorCode": expected_code
+                })
+            );
+        }
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 use utoipa::ToSchema;
 
+use crate::acl::{ACKNOWLEDGE_RIGHTS, ACL_RIGHTS};
 use crate::auth::Session;
 use crate::db::Postgre;
 use crate::error::{ApiError, DatabaseError};
@@ -32,6 +33,16 @@ pub struct Group {
     pub group_name: String,
 }
 
+/// Total size of the files posted in a group's channels, against its optional
+/// quota. Deleted messages and files free their bytes immediately.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupStorage {
+    pub group_id: i64,
+    pub used_bytes: i64,
+    pub quota_bytes: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GroupRoleRights {
@@ -52,6 +63,9 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 }
@@ -60,7 +74,8 @@ impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -76,12 +91,24 @@ impl From<DomainError> for ApiError {
 pub trait GroupTransaction: Send + Sync {
     async fn create(&mut self, name: &str) -> Result<Group, DatabaseError>;
 
+    async fn seed_role_rights(
+        &mut self,
+        group_id: i64,
+        member_rights: i64,
+    ) -> Result<(), DatabaseError>;
+
     async fn update_name(
         &mut self,
         group_id: i64,
         name: &str,
     ) -> Result<Option<Group>, DatabaseError>;
 
+    async fn update_storage_quota(
+        &mut self,
+        group_id: i64,
+        quota_bytes: Option<i64>,
+    ) -> Result<Option<Group>, DatabaseError>;
+
     async fn delete(&mut self, group_id: i64) -> Result<Option<Group>, DatabaseError>;
 }
 
@@ -97,6 +124,8 @@ pub trait GroupRepository: Send + Sync + Clone {
     async fn list_by_user_role(&self, user_id: i64) -> Result<Vec<Group>, DatabaseError>;
 
     async fn find_user_role(&self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_storage(&self, group_id: i64) -> Result<Option<GroupStorage>, DatabaseError>;
 }
 
 pub struct PgGroupTransaction {
@@ -118,6 +147,27 @@ impl GroupTransaction for PgGroupTransaction {
         })
     }
 
+    async fn seed_role_rights(
+        &mut self,
+        group_id: i64,
+        member_rights: i64,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"INSERT INTO group_role_rights (group_id, role_id, rights)
+               SELECT $1, role_id, CASE WHEN role_id IN ($2, $3) THEN $4::BIGINT ELSE $5::BIGINT END
+               FROM roles"#,
+            group_id,
+            OWNER_ROLE_ID,
+            ADMIN_ROLE_ID,
+            ACL_RIGHTS,
+            member_rights
+        )
+        .execute(&mut *self.transaction)
+        .await?;
+
+        Ok(())
+    }
+
     async fn update_name(
         &mut self,
         group_id: i64,
@@ -135,6 +185,25 @@ impl GroupTransaction for PgGroupTransaction {
         Ok(group)
     }
 
+    async fn update_storage_quota(
+        &mut self,
+        group_id: i64,
+        quota_bytes: Option<i64>,
+    ) -> Result<Option<Group>, DatabaseError> {
+        let group = sqlx::query_as!(
+            Group,
+            r#"UPDATE groups SET storage_quota_bytes = $1
+               WHERE group_id = $2
+               RETURNING group_id, group_name"#,
+            quota_bytes,
+            group_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(group)
+    }
+
     async fn delete(&mut self, group_id: i64) -> Result<Option<Group>, DatabaseError> {
         let result = sqlx::query_as!(
             Group,
@@ -174,8 +243,9 @@ impl GroupRepository for Postgre {
             FROM groups g
             INNER JOIN group_role_rights grr ON g.group_id = grr.group_id
             INNER JOIN users u ON u.role_id = grr.role_id
-            WHERE u.user_id = $1 AND grr.rights >= 1"#,
-            user_id
+            WHERE u.user_id = $1 AND grr.rights >= $2"#,
+            user_id,
+            ACKNOWLEDGE_RIGHTS
         )
         .fetch_all(&self.pool)
         .await?;
@@ -189,17 +259,43 @@ impl GroupRepository for Postgre {
             .await?;
         Ok(result)
     }
+
+    async fn find_storage(&self, group_id: i64) -> Result<Option<GroupStorage>, DatabaseError> {
+        let storage = sqlx::query_as!(
+            GroupStorage,
+            r#"SELECT
+                g.group_id,
+                COALESCE((
+                    SELECT SUM(f.file_size)
+                    FROM files f
+                    INNER JOIN messages m ON m.id = f.message_id
+                    INNER JOIN channels c ON c.channel_id = m.channel_id
+                    WHERE c.group_id = g.group_id
+                ), 0)::BIGINT AS "used_bytes!",
+                g.storage_quota_bytes AS quota_bytes
+            FROM groups g
+            WHERE g.group_id = $1"#,
+            group_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(storage)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SERVICE
 // ═══════════════════════════════════════════════════════════════════════════════
 
+pub const DEFAULT_MEMBER_GROUP_RIGHTS: i64 = 0;
+
 #[derive(Clone)]
 pub struct GroupService<R: GroupRepository, N: NotifierManager, G: LogManager> {
     repository: R,
     notifier: N,
     logger: G,
+    default_member_rights: i64,
 }
 
 impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G> {
@@ -208,9 +304,15 @@ impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G
             repository,
             notifier,
             logger,
+            default_member_rights: DEFAULT_MEMBER_GROUP_RIGHTS,
         }
     }
 
+    pub fn with_default_member_rights(mut self, rights: i64) -> Self {
+        self.default_member_rights = rights;
+        self
+    }
+
     pub async fn create_group(
         &self,
         name: String,
@@ -248,6 +350,9 @@ impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G
             other => DomainError::InternalError(other),
         })?;
 
+        tx.seed_role_rights(group.group_id, self.default_member_rights)
+            .await?;
+
         self.repository.commit(tx).await?;
 
         let _ = self.notifier.notify(ServerMessage::InvalidateAcl).await;
@@ -261,7 +366,7 @@ impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G
                 event,
                 ControlRoutingPolicy::GroupRights {
                     group_id: group.group_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 },
             ))
             .await;
@@ -326,7 +431,7 @@ impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G
                 ),
                 other => DomainError::InternalError(other),
             })?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Group {} not found",
                 group_id
             )))?;
@@ -342,7 +447,7 @@ impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G
                 event,
                 ControlRoutingPolicy::GroupRights {
                     group_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 },
             ))
             .await;
@@ -384,7 +489,7 @@ impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G
         let deleted = tx
             .delete(group_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Group {} not found",
                 group_id
             )))?;
@@ -415,6 +520,82 @@ impl<R: GroupRepository, N: NotifierManager, G: LogManager> GroupService<R, N, G
 
         Ok(Some(deleted))
     }
+
+    pub async fn get_group_storage(
+        &self,
+        group_id: i64,
+        user_id: i64,
+    ) -> Result<GroupStorage, DomainError> {
+        let role_id = self
+            .repository
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to view group storage".to_string(),
+            ));
+        }
+
+        self.repository
+            .find_storage(group_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Group {} not found",
+                group_id
+            )))
+    }
+
+    pub async fn update_group_storage_quota(
+        &self,
+        group_id: i64,
+        quota_bytes: Option<i64>,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<GroupStorage, DomainError> {
+        let role_id = self
+            .repository
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to update group storage quota".to_string(),
+            ));
+        }
+
+        if quota_bytes.is_some_and(|quota| quota < 0) {
+            return Err(DomainError::BadRequest(
+                "Storage quota cannot be negative".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        tx.update_storage_quota(group_id, quota_bytes)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Group {} not found",
+                group_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Group storage quota updated: user_id={}, session_id={}, group_id={}, quota_bytes={:?}",
+                    user_id, session_id, group_id, quota_bytes
+                ),
+                "group".to_string(),
+            )
+            .await;
+
+        self.get_group_storage(group_id, user_id).await
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -438,6 +619,12 @@ pub struct UpdateGroupRequest {
     pub name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateGroupStorageQuotaRequest {
+    pub quota_bytes: Option<i64>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ROUTES
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -451,6 +638,8 @@ pub fn group_routes(
         .routes(routes!(create_group_handler))
         .routes(routes!(delete_group_handler))
         .routes(routes!(update_group_name_handler))
+        .routes(routes!(get_group_storage_handler))
+        .routes(routes!(update_group_storage_quota_handler))
         .layer(from_fn_with_state(authorize_service, authorize))
         .with_state(group_service)
 }
@@ -586,3 +775,115 @@ async fn update_group_name_handler(
 
     Ok(())
 }
+
+#[utoipa::path(
+    get,
+    tag = "group",
+    path = "/{id}/storage",
+    params(
+        ("id", Path, description = "The ID of the group"),
+    ),
+    responses(
+        (status = 200, description = "Successfully retrieved group storage usage", body = GroupStorage),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 404, description = "Group not found", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn get_group_storage_handler(
+    State(service): State<GroupService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i64>,
+) -> Result<Json<GroupStorage>, ApiError> {
+    let storage = service
+        .get_group_storage(id, session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(storage))
+}
+
+#[utoipa::path(
+    put,
+    tag = "group",
+    path = "/{id}/storage",
+    params(
+        ("id", Path, description = "The ID of the group"),
+    ),
+    request_body = UpdateGroupStorageQuotaRequest,
+    responses(
+        (status = 200, description = "Group storage quota updated", body = GroupStorage),
+        (status = 400, description = "Invalid quota", body = ApiError),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 404, description = "Group not found", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+async fn update_group_storage_quota_handler(
+    State(service): State<GroupService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdateGroupStorageQuotaRequest>,
+) -> Result<Json<GroupStorage>, ApiError> {
+    let storage = service
+        .update_group_storage_quota(id, payload.quota_bytes, session.user_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(storage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acl::READ_RIGHTS;
+    use crate::role::DEFAULT_ROLE_ID;
+    use sqlx::PgPool;
+    use tokio::sync::mpsc;
+
+    #[sqlx::test]
+    async fn new_groups_are_seeded_with_the_configured_rights(pool: PgPool) {
+        let owner_id = sqlx::query_scalar!(
+            "INSERT INTO users (username, role_id) VALUES ('owner', $1) RETURNING user_id",
+            OWNER_ROLE_ID
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let (sender, _receiver) = mpsc::channel(16);
+        let service = GroupService::new(
+            Postgre { pool: pool.clone() },
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-group-test.log")),
+        )
+        .with_default_member_rights(READ_RIGHTS);
+
+        let group = service
+            .create_group("General".to_string(), owner_id, 0)
+            .await
+            .unwrap();
+
+        let rights = sqlx::query!(
+            "SELECT role_id, rights FROM group_role_rights WHERE group_id = $1 ORDER BY role_id",
+            group.group_id
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let rights: Vec<(i64, i64)> = rights.into_iter().map(|r| (r.role_id, r.rights)).collect();
+        assert_eq!(
+            rights,
+            vec![
+                (OWNER_ROLE_ID, ACL_RIGHTS),
+                (ADMIN_ROLE_ID, ACL_RIGHTS),
+                (DEFAULT_ROLE_ID, READ_RIGHTS),
+            ]
+        );
+    }
+}
@@ -6,7 +6,7 @@ use livekit_api::access_token::{AccessToken, AccessTokenError, TokenVerifier, Vi
 use livekit_api::services::room::{RoomClient, UpdateParticipantOptions};
 use livekit_api::services::ServiceError;
 use livekit_api::webhooks::{WebhookError, WebhookReceiver};
-use livekit_protocol::ParticipantPermission;
+use livekit_protocol::{ParticipantPermission, TrackSource};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -33,6 +33,15 @@ pub enum LiveKitError {
 // SERVICE
 // ═══════════════════════════════════════════════════════════════════════════════
 
+fn source_grant(source: TrackSource) -> &'static str {
+    match source {
+        TrackSource::Camera => "camera",
+        TrackSource::ScreenShare => "screen_share",
+        TrackSource::ScreenShareAudio => "screen_share_audio",
+        _ => "microphone",
+    }
+}
+
 #[derive(Clone)]
 pub struct LiveKitService {
     client: Arc<RoomClient>,
@@ -40,6 +49,8 @@ pub struct LiveKitService {
     api_key: String,
     api_secret: String,
     pub ws_url: String,
+    camera_enabled: bool,
+    screen_share_enabled: bool,
 }
 
 impl LiveKitService {
@@ -54,7 +65,41 @@ impl LiveKitService {
             api_key: api_key.to_string(),
             api_secret: api_secret.to_string(),
             ws_url: format!("wss://{}", url),
+            camera_enabled: true,
+            screen_share_enabled: true,
+        }
+    }
+
+    /// Restricts what participants may publish; microphone audio is always allowed.
+    pub fn with_media_sources(mut self, camera_enabled: bool, screen_share_enabled: bool) -> Self {
+        self.camera_enabled = camera_enabled;
+        self.screen_share_enabled = screen_share_enabled;
+        self
+    }
+
+    pub fn camera_enabled(&self) -> bool {
+        self.camera_enabled
+    }
+
+    pub fn screen_share_enabled(&self) -> bool {
+        self.screen_share_enabled
+    }
+
+    /// An empty list leaves every source allowed.
+    fn publish_sources(&self) -> Vec<TrackSource> {
+        if self.camera_enabled && self.screen_share_enabled {
+            return vec![];
+        }
+
+        let mut sources = vec![TrackSource::Microphone];
+        if self.camera_enabled {
+            sources.push(TrackSource::Camera);
         }
+        if self.screen_share_enabled {
+            sources.push(TrackSource::ScreenShare);
+            sources.push(TrackSource::ScreenShareAudio);
+        }
+        sources
     }
 
     pub fn create_join_token(
@@ -71,6 +116,11 @@ impl LiveKitService {
                 room: room.to_string(),
                 can_publish,
                 can_subscribe: true,
+                can_publish_sources: self
+                    .publish_sources()
+                    .into_iter()
+                    .map(|source| source_grant(source).to_string())
+                    .collect(),
                 ..Default::default()
             })
             .to_jwt()?;
@@ -99,6 +149,11 @@ impl LiveKitService {
                         can_publish,
                         can_subscribe,
                         can_publish_data: true,
+                        can_publish_sources: self
+                            .publish_sources()
+                            .into_iter()
+                            .map(|source| source as i32)
+                            .collect(),
                         ..Default::default()
                     }),
                     ..Default::default()
@@ -279,3 +334,44 @@ async fn webhook_handler(
 
     Ok(Json(WebhookResponse { processed: true }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn publish_sources(livekit: &LiveKitService) -> Vec<String> {
+        let token = livekit.create_join_token(1, "room", true).unwrap();
+        TokenVerifier::with_api_key("key", "secret")
+            .verify(&token)
+            .unwrap()
+            .video
+            .can_publish_sources
+    }
+
+    #[test]
+    fn join_token_leaves_sources_unrestricted_by_default() {
+        let livekit = LiveKitService::new("localhost:7880", "key", "secret");
+
+        assert!(publish_sources(&livekit).is_empty());
+    }
+
+    #[test]
+    fn join_token_drops_disabled_camera_but_keeps_microphone() {
+        let livekit =
+            LiveKitService::new("localhost:7880", "key", "secret").with_media_sources(false, true);
+
+        let sources = publish_sources(&livekit);
+
+        assert!(sources.contains(&"microphone".to_string()));
+        assert!(sources.contains(&"screen_share".to_string()));
+        assert!(!sources.contains(&"camera".to_string()));
+    }
+
+    #[test]
+    fn join_token_drops_disabled_screen_share() {
+        let livekit =
+            LiveKitService::new("localhost:7880", "key", "secret").with_media_sources(true, false);
+
+        assert_eq!(publish_sources(&livekit), vec!["microphone", "camera"]);
+    }
+}
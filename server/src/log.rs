@@ -41,7 +41,7 @@ impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
             DomainError::LogError(e) => {
                 tracing::error!("Log error: {}", e);
                 ApiError::InternalServerError("Internal server error".to_string())
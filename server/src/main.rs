@@ -8,15 +8,19 @@ mod livekit;
 mod log;
 mod managers;
 mod message;
+mod metrics;
 mod middleware;
 mod model;
 mod realtime_server;
 mod role;
 mod server;
 mod subscriber_session;
+mod sync;
 mod user;
 mod voip;
+mod tls;
 mod transport;
+mod webhook;
 
 use acl::{AclService, acl_routes};
 use auth::{AuthService, auth_routes};
@@ -26,29 +30,47 @@ use group::{GroupService, group_routes};
 use http::Method;
 use log::{LogService, log_routes};
 use managers::{
-    DefaultLockoutManager, DefaultNotifierManager, DefaultPasswordValidator, LocalFileManager,
-    TextLogManager,
+    DefaultLockoutManager, DefaultNotifierManager, DefaultPasswordValidator, DefaultRateLimiter,
+    DefaultScanManager, DmRateLimiter, EncryptedFileManager, FileLayout, LocalFileManager,
+    LogRedaction, LogRotation, RedisNotifierManager, TextLogManager, UsernameRequirements,
+    UsernameValidator,
 };
 use message::{MessageService, message_routes};
-use middleware::AuthorizeService;
+use metrics::{MetricsService, TransportMetrics, metrics_routes};
+use middleware::{AuthorizeService, TrustedProxies};
 use role::{RoleService, role_routes};
 use server::{ServerService, server_routes};
+use sync::{SyncService, sync_routes};
 use user::{UserService, user_routes};
 use livekit::{LiveKitService, livekit_webhook_routes};
 use voip::{VoipService, voip_routes};
-use subscriber_session::SessionService;
-use realtime_server::{RealtimeServer, WebSocketState, websocket_handler};
-use transport::ServerMessage;
+use subscriber_session::{
+    ConnectionLimiter, DEFAULT_COMPRESSION_MIN_BYTES, DEFAULT_CONNECTIONS_PER_MINUTE,
+    DEFAULT_MAX_CONNECTIONS, KeepAliveConfig, SessionService,
+};
+use realtime_server::{
+    DEFAULT_BACKFILL_RESYNC_THRESHOLD, DEFAULT_BACKLOG_CAPACITY, DEFAULT_CACHE_WARMUP_ATTEMPTS,
+    DEFAULT_OVERFLOW_GRACE, RealtimeServer, WebSocketState,
+    websocket_handler,
+};
+use tls::CertificateReloader;
+use transport::{OverflowPolicy, ServerMessage};
+use webhook::{WebhookDispatcher, WebhookEvent, WebhookService, webhook_routes};
 
+use axum::Extension;
 use axum::extract::DefaultBodyLimit;
 use axum::routing::get;
 use axum_server::tls_rustls::RustlsConfig;
-use std::net::SocketAddr;
+use base64::Engine;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer};
 use tower_http::services::{ServeDir, ServeFile};
-use utoipa::OpenApi;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 use utoipa_axum::router::OpenApiRouter;
 
 use utoipa_swagger_ui::SwaggerUi;
@@ -63,9 +85,10 @@ pub const VOIP_TAG: &str = "voip";
 pub const FILE_TAG: &str = "file";
 pub const LOG_TAG: &str = "log";
 pub const SERVER_TAG: &str = "server";
+pub const WEBHOOK_TAG: &str = "webhook";
 
 #[derive(OpenApi)]
-#[openapi(tags(
+#[openapi(modifiers(&SecurityAddon), tags(
     (name = CHANNEL_TAG, description = "Channel API endpoints"),
     (name = AUTH_TAG, description = "Auth API endpoints"),
     (name = ROLE_TAG, description = "Role API endpoints"),
@@ -75,9 +98,27 @@ pub const SERVER_TAG: &str = "server";
     (name = VOIP_TAG, description = "VoIP API endpoints"),
     (name = FILE_TAG, description = "File API endpoints"),
     (name = LOG_TAG, description = "Log API endpoints"),
-    (name = SERVER_TAG, description = "Server configuration API endpoints")
+    (name = SERVER_TAG, description = "Server configuration API endpoints"),
+    (name = WEBHOOK_TAG, description = "Webhook API endpoints")
 ))]
 struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(middleware::API_KEY_HEADER))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), sqlx::Error> {
     rustls::crypto::aws_lc_rs::default_provider()
@@ -93,28 +134,197 @@ async fn main() -> Result<(), sqlx::Error> {
     let db = sqlx::PgPool::connect(&db_url).await.unwrap();
 
     let postgre = Postgre { pool: db.clone() };
-    let log_manager = TextLogManager::default();
+    let env_list = |name: &str| -> HashSet<String> {
+        std::env::var(name)
+            .map(|v| {
+                v.split(',')
+                    .map(|field| field.trim().to_string())
+                    .filter(|field| !field.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let log_manager = TextLogManager::default()
+        .with_rotation(LogRotation {
+            max_bytes: std::env::var("LOG_MAX_SIZE_MB")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|&mb| mb > 0)
+                .map(|mb| mb * 1024 * 1024),
+            daily: std::env::var("LOG_ROTATE_DAILY")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            retained_files: std::env::var("LOG_RETAINED_FILES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+        })
+        .with_redaction(LogRedaction {
+            fields: env_list("LOG_REDACT_FIELDS"),
+            categories: env_list("LOG_REDACT_CATEGORIES"),
+            hash_key: std::env::var("LOG_REDACT_HASH_KEY").ok(),
+        });
+
+    let trusted_proxies = TrustedProxies::new(env_list("TRUSTED_PROXIES").iter().map(|addr| {
+        addr.parse::<IpAddr>().unwrap_or_else(|e| {
+            eprintln!("Invalid TRUSTED_PROXIES entry '{}': {}", addr, e);
+            std::process::exit(1);
+        })
+    }));
 
     let (observer_tx, observer_rx): (mpsc::Sender<ServerMessage>, mpsc::Receiver<ServerMessage>) =
         mpsc::channel(1000);
 
-    let realtime_server = RealtimeServer::new(
+    let mut realtime_server = RealtimeServer::new(
         postgre.clone(),
         log_manager.clone(),
         observer_rx,
         observer_tx.clone(),
+    )
+    .with_overflow_policy(
+        match std::env::var("SUBSCRIBER_OVERFLOW_POLICY").as_deref() {
+            Ok("drop") => OverflowPolicy::Drop,
+            _ => OverflowPolicy::Disconnect,
+        },
+        std::env::var("SUBSCRIBER_OVERFLOW_GRACE_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_OVERFLOW_GRACE),
+    )
+    .with_backlog_capacity(
+        std::env::var("EVENT_BACKLOG_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_BACKLOG_CAPACITY),
+    )
+    .with_backfill_resync_threshold(
+        std::env::var("BACKFILL_RESYNC_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_BACKFILL_RESYNC_THRESHOLD),
+    )
+    .with_voip_idle_timeout(
+        std::env::var("VOIP_IDLE_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs),
+        std::env::var("PRIVATE_VOIP_IDLE_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs),
     );
+    realtime_server
+        .warm_up(
+            std::env::var("CACHE_WARMUP_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_CACHE_WARMUP_ATTEMPTS),
+        )
+        .await;
 
-    let file_manager = LocalFileManager::new("server/files");
+    let file_encryption_key = std::env::var("FILE_ENCRYPTION_KEY").ok().map(|key| {
+        base64::engine::general_purpose::STANDARD
+            .decode(key)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .expect("FILE_ENCRYPTION_KEY must be 32 bytes encoded as base64")
+    });
+    let file_layout = match std::env::var("FILE_LAYOUT").as_deref() {
+        Ok("sharded") => FileLayout::Sharded,
+        _ => FileLayout::Flat,
+    };
+    let local_file_manager = LocalFileManager::new("server/files").with_layout(file_layout);
+    let moved_files = local_file_manager
+        .migrate_to_sharded()
+        .expect("Failed to migrate files into the sharded layout");
+    if moved_files > 0 {
+        println!("Moved {} files into the sharded layout", moved_files);
+    }
+    let file_manager = EncryptedFileManager::new(local_file_manager, file_encryption_key);
     let avatar_manager = LocalFileManager::new("server/avatars");
-    let notifier_manager = DefaultNotifierManager::new(observer_tx.clone());
+    let (webhook_tx, webhook_rx): (mpsc::Sender<WebhookEvent>, mpsc::Receiver<WebhookEvent>) =
+        mpsc::channel(1000);
+    let webhook_dispatcher = WebhookDispatcher::new(postgre.clone());
+    let notifier_manager =
+        DefaultNotifierManager::new(observer_tx.clone()).with_webhooks(webhook_tx);
+    let notifier_manager = match std::env::var("REDIS_URL") {
+        Ok(redis_url) => {
+            let channel =
+                std::env::var("REDIS_CHANNEL").unwrap_or_else(|_| "opencord:events".to_string());
+            let relay = RedisNotifierManager::connect(&redis_url, &channel)
+                .await
+                .expect("Failed to connect to REDIS_URL");
+            tokio::spawn(relay.clone().run(observer_tx.clone()));
+            notifier_manager.with_relay(relay)
+        }
+        Err(_) => notifier_manager,
+    };
     let lockout_manager = DefaultLockoutManager::default();
     let password_validator = DefaultPasswordValidator::default();
+    let default_username_requirements = UsernameRequirements::default();
+    let username_validator = UsernameValidator::new(UsernameRequirements {
+        max_length: std::env::var("MAX_USERNAME_LENGTH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_username_requirements.max_length),
+        allowed_special_chars: std::env::var("USERNAME_SPECIAL_CHARS")
+            .unwrap_or(default_username_requirements.allowed_special_chars.clone()),
+        ..default_username_requirements
+    });
+
+    let transport_metrics = TransportMetrics::default();
+    let metrics_service = MetricsService::new(
+        observer_tx.clone(),
+        transport_metrics.clone(),
+        lockout_manager.clone(),
+        notifier_manager.clone(),
+    );
 
     let session_service = SessionService::new(postgre.clone(), log_manager.clone());
+    let default_keepalive = KeepAliveConfig::default();
+    let keepalive = KeepAliveConfig::new(
+        std::env::var("PING_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(default_keepalive.ping_interval),
+        std::env::var("PONG_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(default_keepalive.pong_timeout),
+        std::env::var("MAX_MISSED_PONGS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_keepalive.max_missed_pongs),
+    );
     let ws_state = WebSocketState {
         session_service,
         observer_tx,
+        keepalive,
+        metrics: transport_metrics,
+        connection_limiter: ConnectionLimiter::new(
+            std::env::var("MAX_CONNECTIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_CONNECTIONS),
+        ),
+        connection_rate_limiter: DefaultRateLimiter::per_minute(
+            std::env::var("WS_CONNECTIONS_PER_MINUTE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_CONNECTIONS_PER_MINUTE),
+        ),
+        compression_min_bytes: Some(
+            std::env::var("WS_COMPRESSION_MIN_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_COMPRESSION_MIN_BYTES),
+        )
+        .filter(|&min_bytes| min_bytes > 0),
     };
 
     let auth_service = AuthService::new(
@@ -123,23 +333,110 @@ async fn main() -> Result<(), sqlx::Error> {
         password_validator,
         notifier_manager.clone(),
         log_manager.clone(),
+    )
+    .with_bcrypt_cost(
+        std::env::var("BCRYPT_COST")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(bcrypt::DEFAULT_COST),
+    )
+    .with_username_validator(username_validator.clone())
+    .with_delete_exhausted_invites(
+        std::env::var("DELETE_EXHAUSTED_INVITES")
+            .map(|v| v == "true")
+            .unwrap_or(false),
     );
     let authorize_service = AuthorizeService::new(postgre.clone());
+    let livekit_service = LiveKitService::new(
+        &std::env::var("LIVEKIT_URL").expect("LIVEKIT_URL not set"),
+        &std::env::var("LIVEKIT_API_KEY").expect("LIVEKIT_API_KEY not set"),
+        &std::env::var("LIVEKIT_API_SECRET").expect("LIVEKIT_API_SECRET not set"),
+    )
+    .with_media_sources(
+        std::env::var("VOIP_CAMERA_ENABLED")
+            .map(|v| v != "false")
+            .unwrap_or(true),
+        std::env::var("VOIP_SCREEN_SHARE_ENABLED")
+            .map(|v| v != "false")
+            .unwrap_or(true),
+    );
     let channel_service = ChannelService::new(
         postgre.clone(),
         notifier_manager.clone(),
         log_manager.clone(),
+        livekit_service.clone(),
     );
+    let max_message_length = std::env::var("MAX_MESSAGE_LENGTH")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(message::DEFAULT_MAX_MESSAGE_LENGTH);
+    let scan_manager = match std::env::var("FILE_SCAN_COMMAND") {
+        Ok(command) => DefaultScanManager::new().with_command(&command),
+        Err(_) => DefaultScanManager::new(),
+    };
+    let scan_manager = match std::env::var("FILE_SCAN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&secs| secs > 0)
+    {
+        Some(secs) => scan_manager.with_timeout(Duration::from_secs(secs)),
+        None => scan_manager,
+    };
     let message_service = MessageService::new(
         postgre.clone(),
         file_manager.clone(),
+        scan_manager,
         notifier_manager.clone(),
         log_manager.clone(),
-    );
-    let livekit_service = LiveKitService::new(
-        &std::env::var("LIVEKIT_URL").expect("LIVEKIT_URL not set"),
-        &std::env::var("LIVEKIT_API_KEY").expect("LIVEKIT_API_KEY not set"),
-        &std::env::var("LIVEKIT_API_SECRET").expect("LIVEKIT_API_SECRET not set"),
+        max_message_length,
+    )
+    .with_dm_rate_limiter(DmRateLimiter::new(
+        std::env::var("DM_RATE_LIMIT_MESSAGES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30),
+        std::env::var("DM_RATE_LIMIT_RECIPIENTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5),
+        60,
+    ))
+    .with_reaction_limits(
+        std::env::var("MAX_REACTION_EMOJIS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(message::DEFAULT_MAX_REACTION_EMOJIS),
+        std::env::var("MAX_REACTIONS_PER_USER")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(message::DEFAULT_MAX_REACTIONS_PER_USER),
+    )
+    .with_max_emoji_bytes(
+        std::env::var("MAX_REACTION_EMOJI_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(message::DEFAULT_MAX_EMOJI_BYTES),
+    )
+    .with_multipart_limits(
+        std::env::var("MAX_MULTIPART_PARTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(message::DEFAULT_MAX_MULTIPART_PARTS),
+        std::env::var("MAX_MULTIPART_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(message::DEFAULT_MAX_MULTIPART_BYTES),
+    )
+    .with_max_reply_depth(
+        std::env::var("MAX_REPLY_DEPTH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&depth| depth > 0),
+    )
+    .with_dm_requests(
+        std::env::var("DM_MESSAGE_REQUESTS")
+            .map(|v| v == "true")
+            .unwrap_or(false),
     );
     let acl_service = AclService::new(
         postgre.clone(),
@@ -157,13 +454,21 @@ async fn main() -> Result<(), sqlx::Error> {
         postgre.clone(),
         notifier_manager.clone(),
         log_manager.clone(),
+    )
+    .with_default_member_rights(
+        std::env::var("DEFAULT_GROUP_RIGHTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|rights| (0..=8).contains(rights))
+            .unwrap_or(group::DEFAULT_MEMBER_GROUP_RIGHTS),
     );
     let user_service = UserService::new(
         postgre.clone(),
         avatar_manager.clone(),
         notifier_manager.clone(),
         log_manager.clone(),
-    );
+    )
+    .with_username_validator(username_validator);
     let voip_service = VoipService::new(
         postgre.clone(),
         notifier_manager.clone(),
@@ -171,6 +476,8 @@ async fn main() -> Result<(), sqlx::Error> {
         livekit_service.clone(),
     );
     let log_service = LogService::new(log_manager.clone(), postgre.clone());
+    let sync_service = SyncService::new(postgre.clone(), notifier_manager.clone());
+    let webhook_service = WebhookService::new(postgre.clone(), log_manager.clone());
     let server_service = ServerService::new(
         postgre.clone(),
         avatar_manager.clone(),
@@ -193,6 +500,10 @@ async fn main() -> Result<(), sqlx::Error> {
         let _ = realtime_server.run().await;
     });
 
+    tokio::spawn(webhook_dispatcher.run(webhook_rx));
+
+    tokio::spawn(message_service.clone().run_retention_purge());
+
     let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
         .nest(
             "/auth",
@@ -224,10 +535,18 @@ async fn main() -> Result<(), sqlx::Error> {
             voip_routes(voip_service.clone(), authorize_service.clone()),
         )
         .nest("/log", log_routes(log_service, authorize_service.clone()))
+        .nest(
+            "/sync",
+            sync_routes(sync_service, authorize_service.clone()),
+        )
         .nest(
             "/server",
             server_routes(server_service, authorize_service.clone()),
         )
+        .nest(
+            "/webhook",
+            webhook_routes(webhook_service, authorize_service.clone()),
+        )
         .layer(DefaultBodyLimit::max(512 * 1024 * 1024))
         .layer(cors)
         .with_state(postgre)
@@ -237,7 +556,23 @@ async fn main() -> Result<(), sqlx::Error> {
     let router = router
         .merge(SwaggerUi::new("/swagger-ui").url("/apidoc/openapi.json", api))
         .nest("/livekit", livekit_routes)
-        .route("/ws", get(websocket_handler::<TextLogManager>).with_state(ws_state));
+        .route("/ws", get(websocket_handler::<TextLogManager>).with_state(ws_state))
+        .layer(Extension(trusted_proxies));
+
+    let router = match std::env::var("METRICS_ADDR") {
+        Ok(metrics_addr) => {
+            let metrics_addr: SocketAddr = metrics_addr.parse().expect("Invalid METRICS_ADDR");
+            let listener = tokio::net::TcpListener::bind(metrics_addr)
+                .await
+                .expect("Failed to bind METRICS_ADDR");
+            println!("Metrics available at http://{}/metrics", metrics_addr);
+            tokio::spawn(async move {
+                let _ = axum::serve(listener, metrics_routes(metrics_service)).await;
+            });
+            router
+        }
+        Err(_) => router.merge(metrics_routes(metrics_service)),
+    };
 
     let serve_client = std::env::var("SERVE_CLIENT")
         .map(|v| v == "true")
@@ -257,17 +592,72 @@ async fn main() -> Result<(), sqlx::Error> {
     let tls_config = RustlsConfig::from_pem_file(&cert_path, &key_path)
         .await
         .expect("Failed to load TLS certificates");
+    let certificate_reloader =
+        CertificateReloader::new(tls_config.clone(), cert_path.clone(), key_path.clone())
+            .with_poll_interval(
+                std::env::var("TLS_RELOAD_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&secs| secs > 0)
+                    .map(Duration::from_secs),
+            );
+    tokio::spawn(certificate_reloader.run());
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
+    let bind_addrs: Vec<SocketAddr> = match std::env::var("BIND_ADDRS") {
+        Ok(addrs) => addrs
+            .split(',')
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .map(|addr| {
+                addr.parse().unwrap_or_else(|e| {
+                    eprintln!("Invalid BIND_ADDRS entry '{}': {}", addr, e);
+                    std::process::exit(1);
+                })
+            })
+            .collect(),
+        Err(_) => vec![SocketAddr::from(([0, 0, 0, 0], 3000))],
+    };
+    if bind_addrs.is_empty() {
+        eprintln!("BIND_ADDRS must contain at least one address");
+        std::process::exit(1);
+    }
 
-    println!("HTTPS server listening on https://0.0.0.0:3000");
-    println!("WebSocket endpoint at wss://0.0.0.0:3000/ws");
-    println!("Swagger UI available at https://0.0.0.0:3000/swagger-ui");
+    let listeners: Vec<(SocketAddr, std::net::TcpListener)> = bind_addrs
+        .into_iter()
+        .map(|addr| {
+            let listener = std::net::TcpListener::bind(addr)
+                .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+                .unwrap_or_else(|e| {
+                    eprintln!("Failed to bind {}: {}", addr, e);
+                    std::process::exit(1);
+                });
+            (addr, listener)
+        })
+        .collect();
 
-    axum_server::bind_rustls(addr, tls_config)
-        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
-        .await
-        .unwrap();
+    for (addr, _) in &listeners {
+        println!("HTTPS server listening on https://{}", addr);
+        println!("WebSocket endpoint at wss://{}/ws", addr);
+        println!("Swagger UI available at https://{}/swagger-ui", addr);
+    }
+
+    let servers = listeners.into_iter().map(|(addr, listener)| {
+        let service = router
+            .clone()
+            .into_make_service_with_connect_info::<SocketAddr>();
+        let tls_config = tls_config.clone();
+        async move {
+            axum_server::from_tcp_rustls(listener, tls_config)
+                .serve(service)
+                .await
+                .map_err(|e| (addr, e))
+        }
+    });
+
+    if let Err((addr, e)) = futures_util::future::try_join_all(servers).await {
+        eprintln!("Server on {} stopped: {}", addr, e);
+        std::process::exit(1);
+    }
 
     Ok(())
 }
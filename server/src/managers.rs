@@ -1,3 +1,5 @@
+use aes_gcm::aead::{Aead, AeadCore, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -7,6 +9,7 @@ pub enum FileError {
     Io(io::Error),
     NotFound(i64),
     TransactionFailed(String),
+    Encryption(String),
 }
 
 impl From<io::Error> for FileError {
@@ -21,6 +24,7 @@ impl std::fmt::Display for FileError {
             FileError::Io(err) => write!(f, "IO error: {}", err),
             FileError::NotFound(id) => write!(f, "File not found: {}", id),
             FileError::TransactionFailed(msg) => write!(f, "Transaction failed: {}", msg),
+            FileError::Encryption(msg) => write!(f, "Encryption error: {}", msg),
         }
     }
 }
@@ -47,25 +51,93 @@ pub trait FileManager: Send + Sync {
     fn delete_file(&self, id: i64) -> Result<(), FileError>;
 }
 
+/// `Flat` keeps every file directly under the base directory. `Sharded` spreads
+/// them over two levels of subdirectories (`ab/cd/<id>`) derived from the id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileLayout {
+    Flat,
+    Sharded,
+}
+
+fn layout_file_path(base_directory: &Path, layout: FileLayout, id: i64) -> PathBuf {
+    match layout {
+        FileLayout::Flat => base_directory.join(id.to_string()),
+        FileLayout::Sharded => base_directory
+            .join(format!("{:02x}", id & 0xff))
+            .join(format!("{:02x}", (id >> 8) & 0xff))
+            .join(id.to_string()),
+    }
+}
+
 #[derive(Clone)]
 pub struct LocalFileManager {
     pub base_directory: PathBuf,
+    layout: FileLayout,
 }
 
 impl LocalFileManager {
     pub fn new<P: AsRef<Path>>(base_directory: P) -> Self {
         Self {
             base_directory: base_directory.as_ref().to_path_buf(),
+            layout: FileLayout::Flat,
         }
     }
 
+    pub fn with_layout(mut self, layout: FileLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
     fn get_file_path(&self, id: i64) -> PathBuf {
-        self.base_directory.join(id.to_string())
+        layout_file_path(&self.base_directory, self.layout, id)
+    }
+
+    /// Falls back to the flat path so files that have not been migrated yet
+    /// stay readable and deletable under the sharded layout.
+    fn find_file_path(&self, id: i64) -> Option<PathBuf> {
+        let path = self.get_file_path(id);
+        if path.exists() {
+            return Some(path);
+        }
+        let flat = layout_file_path(&self.base_directory, FileLayout::Flat, id);
+        (self.layout == FileLayout::Sharded && flat.exists()).then_some(flat)
+    }
+
+    /// Moves flat files into the sharded layout. Returns how many were moved.
+    pub fn migrate_to_sharded(&self) -> Result<usize, FileError> {
+        if self.layout != FileLayout::Sharded || !self.base_directory.exists() {
+            return Ok(0);
+        }
+
+        let mut moved = 0;
+        for entry in fs::read_dir(&self.base_directory)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<i64>().ok())
+            else {
+                continue;
+            };
+
+            let target = self.get_file_path(id);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(entry.path(), &target)?;
+            moved += 1;
+        }
+
+        Ok(moved)
     }
 }
 
 pub struct LocalFileTransaction {
     base_directory: PathBuf,
+    layout: FileLayout,
     staged_files: Vec<i64>,
     committed: bool,
 }
@@ -83,9 +155,15 @@ impl FileTransaction for LocalFileTransaction {
     fn commit(mut self) -> Result<(), FileError> {
         for id in &self.staged_files {
             let temp_path = self.base_directory.join(format!("{}.tmp", id));
-            let final_path = self.base_directory.join(id.to_string());
+            let final_path = layout_file_path(&self.base_directory, self.layout, *id);
+
+            let renamed = match final_path.parent() {
+                Some(parent) => fs::create_dir_all(parent),
+                None => Ok(()),
+            }
+            .and_then(|_| fs::rename(&temp_path, &final_path));
 
-            if let Err(e) = fs::rename(&temp_path, &final_path) {
+            if let Err(e) = renamed {
                 for cleanup_id in &self.staged_files {
                     let _ =
                         fs::remove_file(self.base_directory.join(format!("{}.tmp", cleanup_id)));
@@ -129,43 +207,260 @@ impl FileManager for LocalFileManager {
     fn begin(&self) -> Result<Self::Transaction, FileError> {
         Ok(LocalFileTransaction {
             base_directory: self.base_directory.clone(),
+            layout: self.layout,
             staged_files: Vec::new(),
             committed: false,
         })
     }
 
     fn upload_file(&self, id: i64, data: &[u8]) -> Result<(), FileError> {
-        fs::create_dir_all(&self.base_directory)?;
-
         let file_path = self.get_file_path(id);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
         fs::write(file_path, data)?;
         Ok(())
     }
 
     fn get_file(&self, id: i64) -> Result<Vec<u8>, FileError> {
-        let file_path = self.get_file_path(id);
-
-        if !file_path.exists() {
+        let Some(file_path) = self.find_file_path(id) else {
             return Err(FileError::NotFound(id));
-        }
+        };
 
         let data = fs::read(file_path)?;
         Ok(data)
     }
 
     fn delete_file(&self, id: i64) -> Result<(), FileError> {
-        let file_path = self.get_file_path(id);
-
-        if !file_path.exists() {
+        let Some(file_path) = self.find_file_path(id) else {
             return Err(FileError::NotFound(id));
-        }
+        };
 
         fs::remove_file(file_path)?;
         Ok(())
     }
 }
 
-use std::collections::HashMap;
+const ENCRYPTED_FILE_MAGIC: &[u8] = b"OCE1";
+const ENCRYPTED_FILE_NONCE_LEN: usize = 12;
+
+fn encrypt_file_data(cipher: Option<&Aes256Gcm>, data: &[u8]) -> Result<Vec<u8>, FileError> {
+    let Some(cipher) = cipher else {
+        return Ok(data.to_vec());
+    };
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|e| FileError::Encryption(e.to_string()))?;
+
+    let mut out = Vec::with_capacity(ENCRYPTED_FILE_MAGIC.len() + nonce.len() + ciphertext.len());
+    out.extend_from_slice(ENCRYPTED_FILE_MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt_file_data(cipher: Option<&Aes256Gcm>, data: Vec<u8>) -> Result<Vec<u8>, FileError> {
+    let Some(rest) = data.strip_prefix(ENCRYPTED_FILE_MAGIC) else {
+        return Ok(data);
+    };
+    let Some(cipher) = cipher else {
+        return Err(FileError::Encryption(
+            "File is encrypted but no key is configured".to_string(),
+        ));
+    };
+    if rest.len() < ENCRYPTED_FILE_NONCE_LEN {
+        return Err(FileError::Encryption(
+            "Truncated encrypted file".to_string(),
+        ));
+    }
+
+    let (nonce, ciphertext) = rest.split_at(ENCRYPTED_FILE_NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|e| FileError::Encryption(e.to_string()))
+}
+
+#[derive(Clone)]
+pub struct EncryptedFileManager<F: FileManager> {
+    inner: F,
+    cipher: Option<Aes256Gcm>,
+}
+
+impl<F: FileManager> EncryptedFileManager<F> {
+    pub fn new(inner: F, key: Option<[u8; 32]>) -> Self {
+        use aes_gcm::aead::KeyInit;
+
+        Self {
+            inner,
+            cipher: key.map(|key| Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))),
+        }
+    }
+}
+
+pub struct EncryptedFileTransaction<T: FileTransaction> {
+    inner: T,
+    cipher: Option<Aes256Gcm>,
+}
+
+impl<T: FileTransaction> FileTransaction for EncryptedFileTransaction<T> {
+    fn stage_upload(&mut self, id: i64, data: &[u8]) -> Result<(), FileError> {
+        let data = encrypt_file_data(self.cipher.as_ref(), data)?;
+        self.inner.stage_upload(id, &data)
+    }
+
+    fn commit(self) -> Result<(), FileError> {
+        self.inner.commit()
+    }
+
+    fn rollback(self) -> Result<(), FileError> {
+        self.inner.rollback()
+    }
+}
+
+impl<F: FileManager> FileManager for EncryptedFileManager<F> {
+    type Transaction = EncryptedFileTransaction<F::Transaction>;
+
+    fn begin(&self) -> Result<Self::Transaction, FileError> {
+        Ok(EncryptedFileTransaction {
+            inner: self.inner.begin()?,
+            cipher: self.cipher.clone(),
+        })
+    }
+
+    fn upload_file(&self, id: i64, data: &[u8]) -> Result<(), FileError> {
+        let data = encrypt_file_data(self.cipher.as_ref(), data)?;
+        self.inner.upload_file(id, &data)
+    }
+
+    fn get_file(&self, id: i64) -> Result<Vec<u8>, FileError> {
+        decrypt_file_data(self.cipher.as_ref(), self.inner.get_file(id)?)
+    }
+
+    fn delete_file(&self, id: i64) -> Result<(), FileError> {
+        self.inner.delete_file(id)
+    }
+}
+
+use std::process::Stdio;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+pub const DEFAULT_SCAN_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanResult {
+    Clean,
+    Rejected(String),
+}
+
+pub trait ScanManager: Send + Sync + Clone {
+    async fn scan(&self, data: &[u8]) -> ScanResult;
+}
+
+/// Pipes each new upload into an external scanner, e.g. `clamdscan --no-summary -`,
+/// and rejects it unless the command exits 0 within `timeout`. Without a command
+/// every file passes.
+#[derive(Clone)]
+pub struct DefaultScanManager {
+    command: Option<Vec<String>>,
+    timeout: Duration,
+}
+
+impl Default for DefaultScanManager {
+    fn default() -> Self {
+        Self {
+            command: None,
+            timeout: DEFAULT_SCAN_TIMEOUT,
+        }
+    }
+}
+
+impl DefaultScanManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_command(mut self, command: &str) -> Self {
+        let args: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+        self.command = (!args.is_empty()).then_some(args);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn run_command(
+        command: &[String],
+        data: &[u8],
+        timeout: Duration,
+    ) -> io::Result<std::process::Output> {
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+
+        let run = async {
+            if let Some(mut stdin) = stdin {
+                stdin.write_all(data).await?;
+            }
+            let mut output = Vec::new();
+            if let Some(mut stdout) = stdout {
+                stdout.read_to_end(&mut output).await?;
+            }
+            let status = child.wait().await?;
+
+            Ok(std::process::Output {
+                status,
+                stdout: output,
+                stderr: Vec::new(),
+            })
+        };
+
+        let result = tokio::time::timeout(timeout, run).await;
+        match result {
+            Ok(output) => output,
+            Err(_) => {
+                child.kill().await?;
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("scanner did not finish within {:?}", timeout),
+                ))
+            }
+        }
+    }
+}
+
+impl ScanManager for DefaultScanManager {
+    async fn scan(&self, data: &[u8]) -> ScanResult {
+        let Some(command) = &self.command else {
+            return ScanResult::Clean;
+        };
+
+        match Self::run_command(command, data, self.timeout).await {
+            Ok(output) if output.status.success() => ScanResult::Clean,
+            Ok(output) => {
+                ScanResult::Rejected(String::from_utf8_lossy(&output.stdout).trim().to_string())
+            }
+            Err(e) => {
+                tracing::error!("File scanner failed: {}", e);
+                ScanResult::Rejected("File could not be scanned".to_string())
+            }
+        }
+    }
+}
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -189,6 +484,7 @@ pub trait LockoutManager: Send + Sync + Clone {
 #[derive(Clone)]
 pub struct DefaultLockoutManager {
     attempts: Arc<Mutex<HashMap<String, LockoutAttempt>>>,
+    rejections: Arc<AtomicU64>,
     max_attempts: u32,
     lockout_duration: Duration,
     attempt_window: Duration,
@@ -202,6 +498,7 @@ impl DefaultLockoutManager {
     ) -> Self {
         Self {
             attempts: Arc::new(Mutex::new(HashMap::new())),
+            rejections: Arc::new(AtomicU64::new(0)),
             max_attempts,
             lockout_duration: Duration::from_secs(lockout_duration_minutes * 60),
             attempt_window: Duration::from_secs(attempt_window_minutes * 60),
@@ -211,6 +508,10 @@ impl DefaultLockoutManager {
     pub fn default() -> Self {
         Self::new(5, 15, 5)
     }
+
+    pub fn rejections(&self) -> u64 {
+        self.rejections.load(Ordering::Relaxed)
+    }
 }
 
 impl LockoutManager for DefaultLockoutManager {
@@ -259,6 +560,7 @@ impl LockoutManager for DefaultLockoutManager {
         if let Some(entry) = attempts.get_mut(identifier) {
             if let Some(locked_until) = entry.locked_until {
                 if now < locked_until {
+                    self.rejections.fetch_add(1, Ordering::Relaxed);
                     return Some(locked_until.duration_since(now).as_secs());
                 } else {
                     entry.attempts = 0;
@@ -376,6 +678,73 @@ impl RateLimiter for DefaultRateLimiter {
     }
 }
 
+struct DmRateEntry {
+    window_start: Instant,
+    messages: u32,
+    recipients: HashSet<i64>,
+}
+
+/// Per-sender DM throttle. Messages to recipients already contacted in the
+/// current window only count against `max_messages`; each new recipient also
+/// counts against `max_recipients`, which is what stops mass-DMing.
+#[derive(Clone)]
+pub struct DmRateLimiter {
+    entries: Arc<Mutex<HashMap<i64, DmRateEntry>>>,
+    max_messages: u32,
+    max_recipients: usize,
+    window_duration: Duration,
+}
+
+impl DmRateLimiter {
+    pub fn new(max_messages: u32, max_recipients: usize, window_duration_seconds: u64) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            max_messages,
+            max_recipients,
+            window_duration: Duration::from_secs(window_duration_seconds),
+        }
+    }
+
+    /// Records a DM from `sender_id` to `recipient_id`, or returns how long
+    /// the sender has to wait before the window resets.
+    pub fn check(&self, sender_id: i64, recipient_id: i64) -> Result<(), Duration> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+
+        let entry = entries.entry(sender_id).or_insert(DmRateEntry {
+            window_start: now,
+            messages: 0,
+            recipients: HashSet::new(),
+        });
+
+        if now.duration_since(entry.window_start) >= self.window_duration {
+            entry.window_start = now;
+            entry.messages = 0;
+            entry.recipients.clear();
+        }
+
+        let retry_after = self.window_duration - now.duration_since(entry.window_start);
+        let new_recipient = !entry.recipients.contains(&recipient_id);
+
+        if entry.messages >= self.max_messages
+            || (new_recipient && entry.recipients.len() >= self.max_recipients)
+        {
+            return Err(retry_after);
+        }
+
+        entry.messages += 1;
+        entry.recipients.insert(recipient_id);
+
+        Ok(())
+    }
+}
+
+impl Default for DmRateLimiter {
+    fn default() -> Self {
+        Self::new(30, 5, 60)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum PasswordValidationError {
     #[error("Password is too short (minimum {min} characters)")]
@@ -535,7 +904,7 @@ impl PasswordValidator for DefaultPasswordValidator {
 
         let mut complexity_score = 0;
 
-        let unique_chars = password.chars().collect::<std::collections::HashSet<_>>();
+        let unique_chars = password.chars().collect::<HashSet<_>>();
         if unique_chars.len() == password.len() {
             complexity_score += 10;
         }
@@ -565,7 +934,87 @@ impl PasswordValidator for DefaultPasswordValidator {
     }
 }
 
-use crate::transport::ServerMessage;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UsernameValidationError {
+    #[error("Username is too short (minimum {min} characters)")]
+    TooShort { min: usize },
+
+    #[error("Username is too long (maximum {max} characters)")]
+    TooLong { max: usize },
+
+    #[error("Username contains a character that is not allowed: {0:?}")]
+    ForbiddenChar(char),
+}
+
+#[derive(Clone)]
+pub struct UsernameRequirements {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub allowed_special_chars: String,
+}
+
+impl Default for UsernameRequirements {
+    fn default() -> Self {
+        Self {
+            min_length: 2,
+            max_length: 32,
+            allowed_special_chars: "_-.".to_string(),
+        }
+    }
+}
+
+/// Trims and NFC-normalizes usernames, then checks the length in characters
+/// and that every character is a letter, a digit or an allowed special.
+#[derive(Clone)]
+pub struct UsernameValidator {
+    requirements: UsernameRequirements,
+}
+
+impl UsernameValidator {
+    pub fn new(requirements: UsernameRequirements) -> Self {
+        Self { requirements }
+    }
+
+    pub fn normalize(&self, username: &str) -> Result<String, UsernameValidationError> {
+        let username: String = username.trim().nfc().collect();
+        let length = username.chars().count();
+
+        if length < self.requirements.min_length {
+            return Err(UsernameValidationError::TooShort {
+                min: self.requirements.min_length,
+            });
+        }
+
+        if length > self.requirements.max_length {
+            return Err(UsernameValidationError::TooLong {
+                max: self.requirements.max_length,
+            });
+        }
+
+        if let Some(c) = username
+            .chars()
+            .find(|&c| !c.is_alphanumeric() && !self.requirements.allowed_special_chars.contains(c))
+        {
+            return Err(UsernameValidationError::ForbiddenChar(c));
+        }
+
+        Ok(username)
+    }
+}
+
+impl Default for UsernameValidator {
+    fn default() -> Self {
+        Self::new(UsernameRequirements::default())
+    }
+}
+
+use crate::model::EventPayload;
+use crate::transport::{CloseReason, CommandPayload, ControlRoutingPolicy, ServerMessage};
+use crate::webhook::WebhookEvent;
+use futures_util::StreamExt;
+use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, thiserror::Error)]
@@ -582,29 +1031,305 @@ pub trait NotifierManager: Send + Sync + Clone {
 }
 
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+const NOTIFY_MAX_ATTEMPTS: u32 = 4;
+const NOTIFY_RETRY_DELAY: Duration = Duration::from_millis(25);
+const RELAY_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const RELAY_MAX_BACKOFF: Duration = Duration::from_secs(30);
 
 #[derive(Clone)]
 pub struct DefaultNotifierManager {
     sender: mpsc::Sender<ServerMessage>,
+    webhooks: Option<mpsc::Sender<WebhookEvent>>,
+    relay: Option<RedisNotifierManager>,
+    dropped: Arc<AtomicU64>,
 }
 
 impl DefaultNotifierManager {
     pub fn new(sender: mpsc::Sender<ServerMessage>) -> Self {
-        Self { sender }
+        Self {
+            sender,
+            webhooks: None,
+            relay: None,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Retries a full realtime queue a few times with a doubling delay, so a
+    /// burst does not lose events but a stalled server never blocks the caller
+    /// for more than a few hundred milliseconds.
+    async fn send_with_retry(&self, event: ServerMessage) -> Result<(), NotifierError> {
+        let mut event = event;
+        let mut delay = NOTIFY_RETRY_DELAY;
+
+        for attempt in 1..=NOTIFY_MAX_ATTEMPTS {
+            match self.sender.try_send(event) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    tracing::error!("Realtime channel closed, dropping event");
+                    return Err(NotifierError::ServiceUnavailable);
+                }
+                Err(TrySendError::Full(returned)) => {
+                    if attempt == NOTIFY_MAX_ATTEMPTS {
+                        break;
+                    }
+                    tracing::debug!("Realtime channel full, retrying in {:?}", delay);
+                    event = returned;
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(
+            "Realtime channel still full after {} attempts, dropping event",
+            NOTIFY_MAX_ATTEMPTS
+        );
+        Err(NotifierError::SendFailed)
+    }
+
+    pub fn with_webhooks(mut self, webhooks: mpsc::Sender<WebhookEvent>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    pub fn with_relay(mut self, relay: RedisNotifierManager) -> Self {
+        self.relay = Some(relay);
+        self
     }
 }
 
 impl NotifierManager for DefaultNotifierManager {
     async fn notify(&self, event: ServerMessage) -> Result<(), NotifierError> {
-        self.sender
-            .send(event)
+        if let (
+            Some(webhooks),
+            ServerMessage::Control(
+                payload @ EventPayload::MessageCreated { .. },
+                ControlRoutingPolicy::ChannelRights { channel_id, .. },
+            ),
+        ) = (&self.webhooks, &event)
+        {
+            let queued = webhooks.try_send(WebhookEvent {
+                channel_id: *channel_id,
+                payload: payload.clone(),
+            });
+            if queued.is_err() {
+                tracing::warn!("Webhook queue full, dropping event for channel {}", channel_id);
+            }
+        }
+
+        if let Some(relay) = &self.relay
+            && relay.publish(&event).await.is_err()
+        {
+            tracing::warn!("Failed to publish event to other nodes");
+        }
+
+        self.send_with_retry(event).await
+    }
+}
+
+/// Messages that can be replayed on another node. Commands carrying local
+/// channels (connect, queries) never leave the node that produced them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+pub enum RelayMessage {
+    Control {
+        payload: EventPayload,
+        policy: ControlRoutingPolicy,
+    },
+    #[serde(rename_all = "camelCase")]
+    Disconnect {
+        user_id: i64,
+        session_token: String,
+        #[serde(default)]
+        reason: CloseReason,
+    },
+    #[serde(rename_all = "camelCase")]
+    DisconnectUser {
+        user_id: i64,
+        #[serde(default)]
+        reason: CloseReason,
+    },
+    InvalidateVoip,
+    InvalidateAcl,
+    InvalidateUsers,
+    InvalidateMutes,
+}
+
+impl RelayMessage {
+    fn from_server_message(message: &ServerMessage) -> Option<Self> {
+        match message {
+            ServerMessage::Control(payload, policy) => Some(Self::Control {
+                payload: payload.clone(),
+                policy: policy.clone(),
+            }),
+            ServerMessage::Command(CommandPayload::Disconnect(user_id, session_token, reason)) => {
+                Some(Self::Disconnect {
+                    user_id: *user_id,
+                    session_token: session_token.clone(),
+                    reason: *reason,
+                })
+            }
+            ServerMessage::Command(CommandPayload::DisconnectUser(user_id, reason)) => {
+                Some(Self::DisconnectUser {
+                    user_id: *user_id,
+                    reason: *reason,
+                })
+            }
+            ServerMessage::InvalidateVoip => Some(Self::InvalidateVoip),
+            ServerMessage::InvalidateAcl => Some(Self::InvalidateAcl),
+            ServerMessage::InvalidateUsers => Some(Self::InvalidateUsers),
+            ServerMessage::InvalidateMutes => Some(Self::InvalidateMutes),
+            _ => None,
+        }
+    }
+}
+
+impl From<RelayMessage> for ServerMessage {
+    fn from(message: RelayMessage) -> Self {
+        match message {
+            RelayMessage::Control { payload, policy } => ServerMessage::Control(payload, policy),
+            RelayMessage::Disconnect {
+                user_id,
+                session_token,
+                reason,
+            } => ServerMessage::Command(CommandPayload::Disconnect(user_id, session_token, reason)),
+            RelayMessage::DisconnectUser { user_id, reason } => {
+                ServerMessage::Command(CommandPayload::DisconnectUser(user_id, reason))
+            }
+            RelayMessage::InvalidateVoip => ServerMessage::InvalidateVoip,
+            RelayMessage::InvalidateAcl => ServerMessage::InvalidateAcl,
+            RelayMessage::InvalidateUsers => ServerMessage::InvalidateUsers,
+            RelayMessage::InvalidateMutes => ServerMessage::InvalidateMutes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelayEnvelope {
+    node_id: String,
+    message: RelayMessage,
+}
+
+#[derive(Clone)]
+pub struct RedisNotifierManager {
+    client: redis::Client,
+    connection: redis::aio::MultiplexedConnection,
+    channel: String,
+    node_id: String,
+}
+
+impl RedisNotifierManager {
+    pub async fn connect(url: &str, channel: &str) -> Result<Self, NotifierError> {
+        let client = redis::Client::open(url).map_err(|_| NotifierError::ServiceUnavailable)?;
+        let connection = client
+            .get_multiplexed_async_connection()
             .await
-            .map_err(|_| NotifierError::SendFailed)?;
+            .map_err(|_| NotifierError::ServiceUnavailable)?;
 
-        Ok(())
+        Ok(Self {
+            client,
+            connection,
+            channel: channel.to_string(),
+            node_id: uuid::Uuid::new_v4().to_string(),
+        })
+    }
+
+    pub async fn publish(&self, event: &ServerMessage) -> Result<(), NotifierError> {
+        let Some(message) = RelayMessage::from_server_message(event) else {
+            return Ok(());
+        };
+        let envelope = RelayEnvelope {
+            node_id: self.node_id.clone(),
+            message,
+        };
+        let payload = serde_json::to_string(&envelope).map_err(|_| NotifierError::SendFailed)?;
+
+        let mut connection = self.connection.clone();
+        connection
+            .publish::<_, _, ()>(&self.channel, payload)
+            .await
+            .map_err(|_| NotifierError::SendFailed)
+    }
+
+    /// Feeds messages published by other nodes into the local realtime server,
+    /// resubscribing with a backoff whenever the Redis subscription is lost.
+    /// Returns once the local realtime server has shut down.
+    pub async fn run(self, local: mpsc::Sender<ServerMessage>) {
+        let mut delay = RELAY_INITIAL_BACKOFF;
+        loop {
+            match self.relay(&local, &mut delay).await {
+                Ok(()) => return,
+                Err(e) => {
+                    tracing::warn!(
+                        "Redis relay subscription lost, resubscribing in {:?}: {}",
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(RELAY_MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    async fn relay(
+        &self,
+        local: &mpsc::Sender<ServerMessage>,
+        delay: &mut Duration,
+    ) -> Result<(), NotifierError> {
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|_| NotifierError::ServiceUnavailable)?;
+        pubsub
+            .subscribe(&self.channel)
+            .await
+            .map_err(|_| NotifierError::ServiceUnavailable)?;
+        *delay = RELAY_INITIAL_BACKOFF;
+
+        let mut messages = pubsub.on_message();
+        while let Some(msg) = messages.next().await {
+            let Ok(payload) = msg.get_payload::<String>() else {
+                continue;
+            };
+            let envelope = match serde_json::from_str::<RelayEnvelope>(&payload) {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    tracing::warn!("Dropping malformed relay message: {}", e);
+                    continue;
+                }
+            };
+            if envelope.node_id == self.node_id {
+                continue;
+            }
+            if local.send(envelope.message.into()).await.is_err() {
+                return Ok(());
+            }
+        }
+
+        Err(NotifierError::ServiceUnavailable)
     }
 }
 
+impl NotifierManager for RedisNotifierManager {
+    async fn notify(&self, event: ServerMessage) -> Result<(), NotifierError> {
+        self.publish(&event).await
+    }
+}
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use time::OffsetDateTime;
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -644,15 +1369,93 @@ pub trait LogManager: Send + Sync + Clone {
     ) -> impl std::future::Future<Output = Result<u64, LogError>> + Send;
 }
 
+/// When `TextLogManager` rolls the active file over to `<file>.1`, shifting older
+/// files up and deleting anything past `retained_files`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogRotation {
+    pub max_bytes: Option<u64>,
+    pub daily: bool,
+    pub retained_files: usize,
+}
+
+/// Masks the values of `field=value` pairs in log lines before they are
+/// written. An empty `categories` set applies to every category. With a
+/// `hash_key` values are replaced by a keyed hash so entries stay correlatable.
+#[derive(Debug, Clone, Default)]
+pub struct LogRedaction {
+    pub fields: HashSet<String>,
+    pub categories: HashSet<String>,
+    pub hash_key: Option<String>,
+}
+
+impl LogRedaction {
+    fn applies_to(&self, category: &str) -> bool {
+        !self.fields.is_empty()
+            && (self.categories.is_empty() || self.categories.contains(category))
+    }
+
+    fn redact(&self, log: &str) -> String {
+        let mut redacted = String::with_capacity(log.len());
+        let mut rest = log;
+
+        while let Some(eq) = rest.find('=') {
+            let (head, tail) = rest.split_at(eq);
+            let key = &head[head
+                .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_')
+                .len()..];
+            let tail = &tail[1..];
+            let value_len = tail
+                .find(|c: char| c == ',' || c.is_whitespace())
+                .unwrap_or(tail.len());
+            let (value, after) = tail.split_at(value_len);
+
+            redacted.push_str(head);
+            redacted.push('=');
+            if !value.is_empty() && self.fields.contains(key) {
+                redacted.push_str(&self.mask(value));
+            } else {
+                redacted.push_str(value);
+            }
+            rest = after;
+        }
+
+        redacted.push_str(rest);
+        redacted
+    }
+
+    fn mask(&self, value: &str) -> String {
+        match &self.hash_key {
+            Some(key) => {
+                let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_bytes())
+                    .expect("HMAC accepts keys of any length");
+                mac.update(value.as_bytes());
+                mac.finalize()
+                    .into_bytes()
+                    .iter()
+                    .take(6)
+                    .map(|b| format!("{:02x}", b))
+                    .collect()
+            }
+            None => "***".to_string(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct TextLogManager {
     file_path: PathBuf,
+    rotation: LogRotation,
+    redaction: Arc<LogRedaction>,
+    write_lock: Arc<Mutex<()>>,
 }
 
 impl TextLogManager {
     pub fn new<P: AsRef<Path>>(file_path: P) -> Self {
         Self {
             file_path: file_path.as_ref().to_path_buf(),
+            rotation: LogRotation::default(),
+            redaction: Arc::new(LogRedaction::default()),
+            write_lock: Arc::new(Mutex::new(())),
         }
     }
 
@@ -660,6 +1463,75 @@ impl TextLogManager {
         Self::new("server/logs/app.log")
     }
 
+    pub fn with_rotation(mut self, rotation: LogRotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_redaction(mut self, redaction: LogRedaction) -> Self {
+        self.redaction = Arc::new(redaction);
+        self
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.file_path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    /// Existing log files, oldest first, ending with the active file.
+    fn log_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = (1..=self.rotation.retained_files)
+            .rev()
+            .map(|index| self.rotated_path(index))
+            .filter(|path| path.exists())
+            .collect();
+        files.push(self.file_path.clone());
+        files
+    }
+
+    fn should_rotate(&self, incoming_bytes: u64) -> io::Result<bool> {
+        let metadata = match fs::metadata(&self.file_path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        if let Some(max_bytes) = self.rotation.max_bytes {
+            if metadata.len() > 0 && metadata.len() + incoming_bytes > max_bytes {
+                return Ok(true);
+            }
+        }
+
+        if self.rotation.daily {
+            let modified = OffsetDateTime::from(metadata.modified()?);
+            if modified.date() != OffsetDateTime::now_utc().date() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        let retained = self.rotation.retained_files;
+        if retained == 0 {
+            return fs::remove_file(&self.file_path);
+        }
+
+        match fs::remove_file(self.rotated_path(retained)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        for index in (1..retained).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.file_path, self.rotated_path(1))
+    }
+
     fn parse_line(line: &str) -> Option<LogEntry> {
         let parts: Vec<&str> = line.splitn(4, '|').collect();
         if parts.len() != 4 {
@@ -696,6 +1568,12 @@ impl LogManager for TextLogManager {
     async fn log_entry(&self, log: String, category: String) -> Result<LogEntry, LogError> {
         use std::io::Write;
 
+        let log = if self.redaction.applies_to(&category) {
+            self.redaction.redact(&log)
+        } else {
+            log
+        };
+
         let entry = LogEntry {
             id: uuid::Uuid::new_v4().to_string(),
             log,
@@ -703,16 +1581,24 @@ impl LogManager for TextLogManager {
             category,
         };
 
+        let line = Self::format_entry(&entry);
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+
         if let Some(parent) = self.file_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
+        if self.should_rotate(line.len() as u64 + 1)? {
+            self.rotate()?;
+        }
+
         let mut file = fs::OpenOptions::new()
             .create(true)
             .append(true)
             .open(&self.file_path)?;
 
-        writeln!(file, "{}", Self::format_entry(&entry))?;
+        writeln!(file, "{}", line)?;
 
         Ok(entry)
     }
@@ -720,22 +1606,27 @@ impl LogManager for TextLogManager {
     async fn get_entries(&self, category: Option<String>) -> Result<Vec<LogEntry>, LogError> {
         use std::io::{BufRead, BufReader};
 
-        let file = match fs::File::open(&self.file_path) {
-            Ok(f) => f,
-            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
-            Err(e) => return Err(LogError::Io(e)),
-        };
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
 
-        let reader = BufReader::new(file);
         let mut entries = Vec::new();
 
-        for line in reader.lines() {
-            let line = line?;
-            if let Some(entry) = Self::parse_line(&line) {
-                match &category {
-                    Some(cat) if entry.category == *cat => entries.push(entry),
-                    None => entries.push(entry),
-                    _ => {}
+        for path in self.log_files() {
+            let file = match fs::File::open(&path) {
+                Ok(f) => f,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(LogError::Io(e)),
+            };
+
+            let reader = BufReader::new(file);
+
+            for line in reader.lines() {
+                let line = line?;
+                if let Some(entry) = Self::parse_line(&line) {
+                    match &category {
+                        Some(cat) if entry.category == *cat => entries.push(entry),
+                        None => entries.push(entry),
+                        _ => {}
+                    }
                 }
             }
         }
@@ -746,38 +1637,354 @@ impl LogManager for TextLogManager {
     async fn delete_entries(&self, category: Option<String>) -> Result<u64, LogError> {
         use std::io::{BufRead, BufReader, Write};
 
-        let file = match fs::File::open(&self.file_path) {
-            Ok(f) => f,
-            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
-            Err(e) => return Err(LogError::Io(e)),
-        };
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
 
-        let reader = BufReader::new(file);
-        let mut remaining_lines = Vec::new();
         let mut deleted_count = 0u64;
 
-        for line in reader.lines() {
-            let line = line?;
-            if let Some(entry) = Self::parse_line(&line) {
-                match &category {
-                    Some(cat) if entry.category == *cat => {
-                        deleted_count += 1;
-                    }
-                    None => {
-                        deleted_count += 1;
-                    }
-                    _ => {
-                        remaining_lines.push(line);
+        for path in self.log_files() {
+            let file = match fs::File::open(&path) {
+                Ok(f) => f,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(LogError::Io(e)),
+            };
+
+            let reader = BufReader::new(file);
+            let mut remaining_lines = Vec::new();
+
+            for line in reader.lines() {
+                let line = line?;
+                if let Some(entry) = Self::parse_line(&line) {
+                    match &category {
+                        Some(cat) if entry.category == *cat => {
+                            deleted_count += 1;
+                        }
+                        None => {
+                            deleted_count += 1;
+                        }
+                        _ => {
+                            remaining_lines.push(line);
+                        }
                     }
                 }
             }
-        }
 
-        let mut file = fs::File::create(&self.file_path)?;
-        for line in remaining_lines {
-            writeln!(file, "{}", line)?;
+            let mut file = fs::File::create(&path)?;
+            for line in remaining_lines {
+                writeln!(file, "{}", line)?;
+            }
         }
 
         Ok(deleted_count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanner(script: &str) -> DefaultScanManager {
+        DefaultScanManager {
+            command: Some(vec!["sh".to_string(), "-c".to_string(), script.to_string()]),
+            timeout: Duration::from_millis(500),
+        }
+    }
+
+    #[tokio::test]
+    async fn scan_passes_files_the_command_accepts() {
+        let result = scanner("cat > /dev/null").scan(b"hello").await;
+
+        assert_eq!(result, ScanResult::Clean);
+    }
+
+    #[tokio::test]
+    async fn scan_rejects_files_the_command_flags() {
+        let result = scanner("cat > /dev/null; echo 'stdin: Eicar FOUND'; exit 1")
+            .scan(b"hello")
+            .await;
+
+        assert_eq!(
+            result,
+            ScanResult::Rejected("stdin: Eicar FOUND".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn scan_rejects_files_when_the_command_times_out() {
+        let started = Instant::now();
+
+        let result = scanner("sleep 10").scan(b"hello").await;
+
+        assert_eq!(
+            result,
+            ScanResult::Rejected("File could not be scanned".to_string())
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn username_is_trimmed_and_nfc_normalized() {
+        let validator = UsernameValidator::default();
+
+        let username = validator.normalize("  cafe\u{301}_1 ").unwrap();
+
+        assert_eq!(username, "caf\u{e9}_1");
+    }
+
+    #[test]
+    fn username_length_is_counted_in_characters() {
+        let validator = UsernameValidator::new(UsernameRequirements {
+            min_length: 3,
+            max_length: 3,
+            ..UsernameRequirements::default()
+        });
+
+        assert!(validator.normalize("\u{e9}\u{e9}\u{e9}").is_ok());
+        assert!(matches!(
+            validator.normalize("ab"),
+            Err(UsernameValidationError::TooShort { min: 3 })
+        ));
+        assert!(matches!(
+            validator.normalize("abcd"),
+            Err(UsernameValidationError::TooLong { max: 3 })
+        ));
+    }
+
+    #[test]
+    fn username_rejects_characters_outside_the_allowed_set() {
+        let validator = UsernameValidator::default();
+
+        assert!(validator.normalize("jo.doe-1").is_ok());
+        assert!(matches!(
+            validator.normalize("jo doe"),
+            Err(UsernameValidationError::ForbiddenChar(' '))
+        ));
+        assert!(matches!(
+            validator.normalize("jo@doe"),
+            Err(UsernameValidationError::ForbiddenChar('@'))
+        ));
+    }
+
+    fn redaction(fields: &[&str], hash_key: Option<&str>) -> LogRedaction {
+        LogRedaction {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+            categories: HashSet::new(),
+            hash_key: hash_key.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn redaction_masks_only_the_listed_fields() {
+        let redaction = redaction(&["ip", "session"], None);
+
+        let log = redaction.redact("Login: user_id=5, ip=10.0.0.1 client_ip=10.0.0.2 session=");
+
+        assert_eq!(log, "Login: user_id=5, ip=*** client_ip=10.0.0.2 session=");
+    }
+
+    #[test]
+    fn redaction_with_a_hash_key_keeps_values_correlatable() {
+        let redaction = redaction(&["ip"], Some("secret"));
+
+        let first = redaction.redact("ip=10.0.0.1");
+        let again = redaction.redact("ip=10.0.0.1");
+        let other = redaction.redact("ip=10.0.0.2");
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert_eq!(first.len(), "ip=".len() + 12);
+        assert!(!first.contains("10.0.0.1"));
+    }
+
+    #[test]
+    fn redaction_applies_to_the_configured_categories() {
+        let mut redaction = redaction(&["ip"], None);
+        assert!(redaction.applies_to("auth"));
+
+        redaction.categories.insert("auth".to_string());
+        assert!(redaction.applies_to("auth"));
+        assert!(!redaction.applies_to("message"));
+
+        redaction.fields.clear();
+        assert!(!redaction.applies_to("auth"));
+    }
+
+    fn cipher(key: u8) -> Aes256Gcm {
+        use aes_gcm::aead::KeyInit;
+
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&[key; 32]))
+    }
+
+    #[test]
+    fn encrypted_file_round_trips() {
+        let cipher = cipher(7);
+
+        let encrypted = encrypt_file_data(Some(&cipher), b"file contents").unwrap();
+
+        assert!(encrypted.starts_with(ENCRYPTED_FILE_MAGIC));
+        assert!(!encrypted.windows(13).any(|w| w == b"file contents"));
+        assert_eq!(
+            decrypt_file_data(Some(&cipher), encrypted).unwrap(),
+            b"file contents"
+        );
+    }
+
+    #[test]
+    fn plain_files_pass_through_with_or_without_a_key() {
+        let stored = encrypt_file_data(None, b"legacy").unwrap();
+
+        assert_eq!(stored, b"legacy");
+        assert_eq!(
+            decrypt_file_data(Some(&cipher(7)), stored).unwrap(),
+            b"legacy"
+        );
+    }
+
+    #[test]
+    fn encrypted_file_fails_without_the_right_key() {
+        let encrypted = encrypt_file_data(Some(&cipher(7)), b"file contents").unwrap();
+
+        assert!(matches!(
+            decrypt_file_data(None, encrypted.clone()),
+            Err(FileError::Encryption(_))
+        ));
+        assert!(matches!(
+            decrypt_file_data(Some(&cipher(8)), encrypted),
+            Err(FileError::Encryption(_))
+        ));
+        assert!(matches!(
+            decrypt_file_data(Some(&cipher(7)), b"OCE1short".to_vec()),
+            Err(FileError::Encryption(_))
+        ));
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("opencord-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn committed_file_lands_in_the_sharded_path() {
+        let directory = scratch_dir("sharded-files");
+        let files = LocalFileManager::new(&directory).with_layout(FileLayout::Sharded);
+
+        let mut tx = files.begin().unwrap();
+        tx.stage_upload(0x1234, b"sharded").unwrap();
+        tx.commit().unwrap();
+
+        let path = directory.join("34").join("12").join("4660");
+        assert_eq!(fs::read(&path).unwrap(), b"sharded");
+        assert!(!directory.join("4660.tmp").exists());
+        assert_eq!(files.get_file(0x1234).unwrap(), b"sharded");
+
+        files.delete_file(0x1234).unwrap();
+        assert!(!path.exists());
+        assert!(matches!(
+            files.get_file(0x1234),
+            Err(FileError::NotFound(_))
+        ));
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn flat_files_stay_readable_until_migrated() {
+        let directory = scratch_dir("flat-files");
+        LocalFileManager::new(&directory)
+            .upload_file(7, b"legacy")
+            .unwrap();
+        let files = LocalFileManager::new(&directory).with_layout(FileLayout::Sharded);
+
+        assert_eq!(files.get_file(7).unwrap(), b"legacy");
+        assert_eq!(files.migrate_to_sharded().unwrap(), 1);
+
+        assert!(!directory.join("7").exists());
+        assert_eq!(
+            fs::read(directory.join("07").join("00").join("7")).unwrap(),
+            b"legacy"
+        );
+        assert_eq!(files.get_file(7).unwrap(), b"legacy");
+        assert_eq!(files.migrate_to_sharded().unwrap(), 0);
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    fn rotating_logger(name: &str, max_bytes: u64, retained_files: usize) -> TextLogManager {
+        TextLogManager::new(scratch_dir(name).join("app.log")).with_rotation(LogRotation {
+            max_bytes: Some(max_bytes),
+            daily: false,
+            retained_files,
+        })
+    }
+
+    async fn logged(logger: &TextLogManager) -> Vec<String> {
+        let entries = logger.get_entries(None).await.unwrap();
+        entries.into_iter().map(|entry| entry.log).collect()
+    }
+
+    #[tokio::test]
+    async fn log_rolls_over_once_the_size_threshold_is_exceeded() {
+        let logger = rotating_logger("log-roll", 200, 3);
+
+        logger
+            .log_entry("first".to_string(), "test".to_string())
+            .await
+            .unwrap();
+        assert!(!logger.rotated_path(1).exists());
+
+        logger
+            .log_entry("x".repeat(200), "test".to_string())
+            .await
+            .unwrap();
+
+        assert!(logger.rotated_path(1).exists());
+        assert_eq!(
+            logged(&logger).await,
+            vec!["first".to_string(), "x".repeat(200)]
+        );
+        let _ = fs::remove_dir_all(logger.file_path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn rotated_logs_beyond_the_retention_count_are_deleted() {
+        let logger = rotating_logger("log-retention", 1, 2);
+
+        for index in 1..=4 {
+            logger
+                .log_entry(format!("entry {}", index), "test".to_string())
+                .await
+                .unwrap();
+        }
+
+        assert!(logger.rotated_path(2).exists());
+        assert!(!logger.rotated_path(3).exists());
+        assert_eq!(logged(&logger).await, vec!["entry 2", "entry 3", "entry 4"]);
+        let _ = fs::remove_dir_all(logger.file_path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn concurrent_entries_survive_rotation() {
+        let logger = rotating_logger("log-concurrent", 64, 100);
+
+        let writers: Vec<_> = (0..20)
+            .map(|index| {
+                let logger = logger.clone();
+                tokio::spawn(async move {
+                    logger
+                        .log_entry(format!("entry {}", index), "test".to_string())
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        let mut entries = logged(&logger).await;
+        entries.sort();
+        let mut expected: Vec<String> = (0..20).map(|index| format!("entry {}", index)).collect();
+        expected.sort();
+        assert_eq!(entries, expected);
+        let _ = fs::remove_dir_all(logger.file_path.parent().unwrap());
+    }
+}
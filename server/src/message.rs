@@ -4,28 +4,35 @@
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use time::OffsetDateTime;
 use tracing::warn;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::acl::{ACL_RIGHTS, READ_RIGHTS, WRITE_RIGHTS};
 use crate::auth::Session;
 use crate::db::Postgre;
 use crate::error::{ApiError, DatabaseError};
+use crate::group::GroupStorage;
 use crate::managers::{
-    DefaultNotifierManager, FileError, FileManager, FileTransaction, LocalFileManager, LogManager,
-    NotifierManager, TextLogManager,
+    DefaultNotifierManager, DefaultScanManager, DmRateLimiter, EncryptedFileManager, FileError,
+    FileManager, FileTransaction, LocalFileManager, LogManager, NotifierManager, ScanManager,
+    ScanResult, TextLogManager,
 };
 use crate::middleware::{AuthorizeService, authorize};
-use crate::model::EventPayload;
+use crate::model::{EventPayload, Paginated};
 use crate::role::{ADMIN_ROLE_ID, OWNER_ROLE_ID};
+use crate::server::is_valid_emoji_name;
 use crate::transport::{ControlRoutingPolicy, ServerMessage};
+use crate::webhook::WebhookToken;
 
 use axum::Json;
-use axum::http::{HeaderMap, HeaderValue, header};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
 use axum::response::IntoResponse;
 use axum::{
-    extract::{Extension, Multipart, Path, Query, State},
+    extract::{Extension, Multipart, Path, Query, State, multipart::Field},
     middleware::from_fn_with_state,
 };
 use utoipa_axum::{router::OpenApiRouter, routes};
@@ -41,6 +48,8 @@ pub struct Message {
     pub sender_id: i64,
     pub channel_id: Option<i64>,
     pub recipient_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<i64>,
     pub message_text: Option<String>,
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
@@ -48,6 +57,12 @@ pub struct Message {
     pub modified_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forwarded_from_message_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub expires_at: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -59,6 +74,8 @@ pub struct File {
     pub file_name: String,
     pub file_size: i64,
     pub file_hash: String,
+    #[serde(skip)]
+    pub blob_id: i64,
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
     #[schema(value_type = FileMetadata)]
@@ -76,12 +93,70 @@ pub struct Reaction {
     pub created_at: OffsetDateTime,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyCount {
+    pub message_id: i64,
+    pub reply_count: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "PascalCase")]
 #[serde(tag = "type")]
 pub enum MessageType {
     Channel { channel_id: i64 },
     Direct { recipient_id: i64 },
+    Group { conversation_id: i64 },
+}
+
+impl MessageType {
+    fn contains(&self, message: &Message) -> bool {
+        match *self {
+            MessageType::Channel { channel_id } => message.channel_id == Some(channel_id),
+            MessageType::Direct { recipient_id } => message.recipient_id == Some(recipient_id),
+            MessageType::Group { conversation_id } => {
+                message.conversation_id == Some(conversation_id)
+            }
+        }
+    }
+}
+
+/// A message a create call stored, or the earlier one its idempotency key
+/// already produced.
+#[derive(Debug)]
+pub enum PostedMessage {
+    Created(Message, Vec<File>),
+    Replayed(Message, Vec<File>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupConversation {
+    pub conversation_id: i64,
+    pub name: Option<String>,
+    pub created_by: Option<i64>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    pub member_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[sqlx(type_name = "dm_request_status")]
+#[serde(rename_all = "PascalCase")]
+pub enum DmRequestStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DmRequest {
+    pub sender_id: i64,
+    pub recipient_id: i64,
+    pub status: DmRequestStatus,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -93,6 +168,8 @@ pub struct FileAttachment {
     pub file_name: String,
     pub file_size: i64,
     pub file_hash: String,
+    #[serde(skip)]
+    pub blob_id: i64,
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
     #[schema(value_type = FileMetadata)]
@@ -138,6 +215,9 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 
@@ -149,7 +229,8 @@ impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -173,6 +254,8 @@ pub trait MessageTransaction: Send + Sync {
         channel_id: i64,
         message_text: Option<String>,
         reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        expires_at: Option<OffsetDateTime>,
     ) -> Result<Message, DatabaseError>;
 
     async fn create_dm_message(
@@ -181,19 +264,56 @@ pub trait MessageTransaction: Send + Sync {
         recipient_id: i64,
         message_text: Option<String>,
         reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<Message, DatabaseError>;
+
+    async fn create_group_message(
+        &mut self,
+        sender_id: i64,
+        conversation_id: i64,
+        message_text: Option<String>,
+        reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        expires_at: Option<OffsetDateTime>,
     ) -> Result<Message, DatabaseError>;
 
+    async fn create_forwarded_message(
+        &mut self,
+        sender_id: i64,
+        target: &MessageType,
+        message_text: Option<String>,
+        forwarded_from_message_id: i64,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<Message, DatabaseError>;
+
+    async fn create_group_conversation(
+        &mut self,
+        created_by: i64,
+        name: Option<&str>,
+        member_ids: &[i64],
+    ) -> Result<GroupConversation, DatabaseError>;
+
     async fn create_file(
         &mut self,
         message_id: i64,
         file_name: &str,
         file_size: i64,
         file_hash: &str,
+        blob_id: Option<i64>,
         metadata: sqlx::types::Json<FileMetadata>,
     ) -> Result<File, DatabaseError>;
 
+    async fn find_blob_by_hash(
+        &mut self,
+        file_hash: &str,
+        file_size: i64,
+    ) -> Result<Option<i64>, DatabaseError>;
+
     async fn delete_message_files(&mut self, message_id: i64) -> Result<Vec<File>, DatabaseError>;
 
+    async fn find_unreferenced_blobs(&mut self, files: &[File]) -> Result<Vec<i64>, DatabaseError>;
+
     async fn edit_message(
         &mut self,
         message_id: i64,
@@ -203,6 +323,14 @@ pub trait MessageTransaction: Send + Sync {
 
     async fn delete_message(&mut self, message_id: i64) -> Result<Option<Message>, DatabaseError>;
 
+    async fn delete_expired_channel_files(&mut self) -> Result<Vec<File>, DatabaseError>;
+
+    async fn delete_expired_channel_messages(&mut self) -> Result<Vec<Message>, DatabaseError>;
+
+    async fn delete_ephemeral_files(&mut self) -> Result<Vec<File>, DatabaseError>;
+
+    async fn delete_ephemeral_messages(&mut self) -> Result<Vec<Message>, DatabaseError>;
+
     async fn create_reaction(
         &mut self,
         message_id: i64,
@@ -221,6 +349,41 @@ pub trait MessageTransaction: Send + Sync {
         &mut self,
         message_id: i64,
     ) -> Result<Vec<Reaction>, DatabaseError>;
+
+    async fn create_idempotency_key(
+        &mut self,
+        user_id: i64,
+        idempotency_key: &str,
+        message_id: i64,
+    ) -> Result<bool, DatabaseError>;
+
+    async fn lock_channel_group_storage(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<Option<GroupStorage>, DatabaseError>;
+
+    async fn lock_message_files(&mut self, message_id: i64) -> Result<Vec<File>, DatabaseError>;
+
+    async fn create_dm_request(
+        &mut self,
+        sender_id: i64,
+        recipient_id: i64,
+    ) -> Result<DmRequest, DatabaseError>;
+
+    async fn update_dm_request_status(
+        &mut self,
+        sender_id: i64,
+        recipient_id: i64,
+        status: DmRequestStatus,
+    ) -> Result<Option<DmRequest>, DatabaseError>;
+
+    async fn create_channel_mentions(
+        &mut self,
+        message_id: i64,
+        channel_id: i64,
+        sender_id: i64,
+        usernames: &[String],
+    ) -> Result<Vec<i64>, DatabaseError>;
 }
 
 pub trait MessageRepository: Send + Sync + Clone {
@@ -236,6 +399,7 @@ pub trait MessageRepository: Send + Sync + Clone {
         &self,
         channel_id: i64,
         timestamp: OffsetDateTime,
+        before_id: i64,
         limit: i64,
     ) -> Result<Vec<Message>, DatabaseError>;
 
@@ -244,9 +408,36 @@ pub trait MessageRepository: Send + Sync + Clone {
         user_id: i64,
         other_user_id: i64,
         timestamp: OffsetDateTime,
+        before_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Message>, DatabaseError>;
+
+    async fn find_visible_messages(
+        &self,
+        user_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Message>, DatabaseError>;
+
+    async fn find_group_messages_with_pagination(
+        &self,
+        conversation_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: i64,
         limit: i64,
     ) -> Result<Vec<Message>, DatabaseError>;
 
+    async fn find_group_conversations(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<GroupConversation>, DatabaseError>;
+
+    async fn find_group_conversation_members(
+        &self,
+        conversation_id: i64,
+    ) -> Result<Vec<i64>, DatabaseError>;
+
     async fn find_file_by_id(&self, file_id: i64) -> Result<Option<FileAttachment>, DatabaseError>;
 
     async fn find_user_channel_rights(
@@ -257,37 +448,71 @@ pub trait MessageRepository: Send + Sync + Clone {
 
     async fn find_user_role(&mut self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
 
+    async fn find_custom_emoji_id(&self, name: &str) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_webhook_token(&self, token: &str) -> Result<Option<WebhookToken>, DatabaseError>;
+
+    async fn is_blocked_between(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+    ) -> Result<bool, DatabaseError>;
+
+    async fn find_dm_request(
+        &self,
+        sender_id: i64,
+        recipient_id: i64,
+    ) -> Result<Option<DmRequest>, DatabaseError>;
+
+    async fn find_pending_dm_requests(
+        &self,
+        recipient_id: i64,
+    ) -> Result<Vec<DmRequest>, DatabaseError>;
+
+    async fn has_sent_dm(&self, sender_id: i64, recipient_id: i64) -> Result<bool, DatabaseError>;
+
     async fn find_message_by_id(&self, message_id: i64) -> Result<Option<Message>, DatabaseError>;
 
-    async fn find_channel_files(
+    async fn find_reply_depth(&self, message_id: i64, limit: i64) -> Result<i64, DatabaseError>;
+
+    async fn find_channel_messages_by_metadata(
         &self,
         channel_id: i64,
-        timestamp: OffsetDateTime,
+        key: &str,
+        value: Option<&str>,
         limit: i64,
-    ) -> Result<Vec<File>, DatabaseError>;
+    ) -> Result<Vec<Message>, DatabaseError>;
 
-    async fn find_dm_files(
+    async fn find_message_by_idempotency_key(
         &self,
         user_id: i64,
-        other_user_id: i64,
-        timestamp: OffsetDateTime,
-        limit: i64,
+        idempotency_key: &str,
+    ) -> Result<Option<Message>, DatabaseError>;
+
+    async fn find_message_files(&self, message_id: i64) -> Result<Vec<File>, DatabaseError>;
+
+    async fn find_message_reactions(&self, message_id: i64) -> Result<Vec<Reaction>, DatabaseError>;
+
+    async fn find_files_for_messages(
+        &self,
+        message_ids: &[i64],
     ) -> Result<Vec<File>, DatabaseError>;
 
-    async fn find_channel_reactions(
+    async fn find_reactions_for_messages(
         &self,
-        channel_id: i64,
-        timestamp: OffsetDateTime,
-        limit: i64,
+        message_ids: &[i64],
     ) -> Result<Vec<Reaction>, DatabaseError>;
 
-    async fn find_dm_reactions(
+    async fn find_thread_messages(
         &self,
-        user_id: i64,
-        other_user_id: i64,
-        timestamp: OffsetDateTime,
+        root_message_id: i64,
         limit: i64,
-    ) -> Result<Vec<Reaction>, DatabaseError>;
+    ) -> Result<Vec<Message>, DatabaseError>;
+
+    async fn find_reply_counts(
+        &self,
+        message_ids: &[i64],
+    ) -> Result<Vec<ReplyCount>, DatabaseError>;
 
     async fn find_channel_messages_range(
         &self,
@@ -346,16 +571,20 @@ impl MessageTransaction for PgMessageTransaction {
         channel_id: i64,
         message_text: Option<String>,
         reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        expires_at: Option<OffsetDateTime>,
     ) -> Result<Message, DatabaseError> {
         let created_message = sqlx::query_as!(
             Message,
-            r#"INSERT INTO messages (sender_id, channel_id, recipient_id, message_text, reply_to_message_id)
-               VALUES ($1, $2, NULL, $3, $4)
-               RETURNING id, sender_id, channel_id, recipient_id, message_text, created_at, modified_at, reply_to_message_id"#,
+            r#"INSERT INTO messages (sender_id, channel_id, recipient_id, message_text, reply_to_message_id, metadata, expires_at)
+               VALUES ($1, $2, NULL, $3, $4, $5, $6)
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#,
             sender_id,
             channel_id,
             message_text,
-            reply_to_message_id
+            reply_to_message_id,
+            metadata,
+            expires_at
         )
         .fetch_one(&mut *self.transaction)
         .await?;
@@ -369,16 +598,80 @@ impl MessageTransaction for PgMessageTransaction {
         recipient_id: i64,
         message_text: Option<String>,
         reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        expires_at: Option<OffsetDateTime>,
     ) -> Result<Message, DatabaseError> {
         let created_message = sqlx::query_as!(
             Message,
-            r#"INSERT INTO messages (sender_id, channel_id, recipient_id, message_text, reply_to_message_id)
-               VALUES ($1, NULL, $2, $3, $4)
-               RETURNING id, sender_id, channel_id, recipient_id, message_text, created_at, modified_at, reply_to_message_id"#,
+            r#"INSERT INTO messages (sender_id, channel_id, recipient_id, message_text, reply_to_message_id, metadata, expires_at)
+               VALUES ($1, NULL, $2, $3, $4, $5, $6)
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#,
             sender_id,
             recipient_id,
             message_text,
-            reply_to_message_id
+            reply_to_message_id,
+            metadata,
+            expires_at
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(created_message)
+    }
+
+    async fn create_group_message(
+        &mut self,
+        sender_id: i64,
+        conversation_id: i64,
+        message_text: Option<String>,
+        reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<Message, DatabaseError> {
+        let created_message = sqlx::query_as!(
+            Message,
+            r#"INSERT INTO messages (sender_id, conversation_id, message_text, reply_to_message_id, metadata, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#,
+            sender_id,
+            conversation_id,
+            message_text,
+            reply_to_message_id,
+            metadata,
+            expires_at
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(created_message)
+    }
+
+    async fn create_forwarded_message(
+        &mut self,
+        sender_id: i64,
+        target: &MessageType,
+        message_text: Option<String>,
+        forwarded_from_message_id: i64,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<Message, DatabaseError> {
+        let (channel_id, recipient_id, conversation_id) = match target {
+            MessageType::Channel { channel_id } => (Some(*channel_id), None, None),
+            MessageType::Direct { recipient_id } => (None, Some(*recipient_id), None),
+            MessageType::Group { conversation_id } => (None, None, Some(*conversation_id)),
+        };
+
+        let created_message = sqlx::query_as!(
+            Message,
+            r#"INSERT INTO messages (sender_id, channel_id, recipient_id, conversation_id, message_text, forwarded_from_message_id, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#,
+            sender_id,
+            channel_id,
+            recipient_id,
+            conversation_id,
+            message_text,
+            forwarded_from_message_id,
+            expires_at
         )
         .fetch_one(&mut *self.transaction)
         .await?;
@@ -386,6 +679,47 @@ impl MessageTransaction for PgMessageTransaction {
         Ok(created_message)
     }
 
+    async fn create_group_conversation(
+        &mut self,
+        created_by: i64,
+        name: Option<&str>,
+        member_ids: &[i64],
+    ) -> Result<GroupConversation, DatabaseError> {
+        let conversation_id = sqlx::query_scalar!(
+            r#"INSERT INTO group_conversations (name, created_by)
+               VALUES ($1, $2)
+               RETURNING conversation_id"#,
+            name,
+            created_by
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        sqlx::query!(
+            r#"INSERT INTO group_conversation_members (conversation_id, user_id)
+               SELECT $1, member FROM UNNEST($2::bigint[]) AS member"#,
+            conversation_id,
+            member_ids
+        )
+        .execute(&mut *self.transaction)
+        .await?;
+
+        let conversation = sqlx::query_as!(
+            GroupConversation,
+            r#"SELECT c.conversation_id, c.name, c.created_by, c.created_at,
+                      ARRAY(SELECT m.user_id FROM group_conversation_members m
+                            WHERE m.conversation_id = c.conversation_id
+                            ORDER BY m.user_id) AS "member_ids!"
+               FROM group_conversations c
+               WHERE c.conversation_id = $1"#,
+            conversation_id
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(conversation)
+    }
+
     async fn edit_message(
         &mut self,
         message_id: i64,
@@ -397,7 +731,7 @@ impl MessageTransaction for PgMessageTransaction {
             r#"UPDATE messages
                SET message_text = $1, modified_at = CURRENT_TIMESTAMP
                WHERE id = $2 AND sender_id = $3
-               RETURNING id, sender_id, channel_id, recipient_id, message_text, created_at, modified_at, reply_to_message_id"#,
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#,
             new_text,
             message_id,
             user_id
@@ -413,7 +747,7 @@ impl MessageTransaction for PgMessageTransaction {
             Message,
             r#"DELETE FROM messages
                WHERE id = $1
-               RETURNING id, sender_id, channel_id, recipient_id, message_text, created_at, modified_at, reply_to_message_id"#,
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#,
             message_id
         )
         .fetch_optional(&mut *self.transaction)
@@ -422,26 +756,87 @@ impl MessageTransaction for PgMessageTransaction {
         Ok(deleted_message)
     }
 
+    async fn delete_expired_channel_files(&mut self) -> Result<Vec<File>, DatabaseError> {
+        let deleted_files = sqlx::query_as!(
+            File,
+            r#"DELETE FROM files f
+               USING messages m, channel_retention cr
+               WHERE f.message_id = m.id
+                 AND m.channel_id = cr.channel_id
+                 AND m.created_at < NOW() - make_interval(days => cr.retention_days)
+               RETURNING f.file_id, f.file_uuid, f.message_id, f.file_name, f.file_size, f.file_hash, f.blob_id, f.created_at, f.metadata as "metadata: sqlx::types::Json<FileMetadata>""#
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(deleted_files)
+    }
+
+    async fn delete_expired_channel_messages(&mut self) -> Result<Vec<Message>, DatabaseError> {
+        let deleted_messages = sqlx::query_as!(
+            Message,
+            r#"DELETE FROM messages m
+               USING channel_retention cr
+               WHERE m.channel_id = cr.channel_id
+                 AND m.created_at < NOW() - make_interval(days => cr.retention_days)
+               RETURNING m.id, m.sender_id, m.channel_id, m.recipient_id, m.conversation_id, m.message_text, m.created_at, m.modified_at, m.reply_to_message_id, m.forwarded_from_message_id, m.metadata, m.expires_at"#
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(deleted_messages)
+    }
+
+    async fn delete_ephemeral_files(&mut self) -> Result<Vec<File>, DatabaseError> {
+        let deleted_files = sqlx::query_as!(
+            File,
+            r#"DELETE FROM files f
+               USING messages m
+               WHERE f.message_id = m.id
+                 AND m.expires_at <= NOW()
+               RETURNING f.file_id, f.file_uuid, f.message_id, f.file_name, f.file_size, f.file_hash, f.blob_id, f.created_at, f.metadata as "metadata: sqlx::types::Json<FileMetadata>""#
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(deleted_files)
+    }
+
+    async fn delete_ephemeral_messages(&mut self) -> Result<Vec<Message>, DatabaseError> {
+        let deleted_messages = sqlx::query_as!(
+            Message,
+            r#"DELETE FROM messages
+               WHERE expires_at <= NOW()
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(deleted_messages)
+    }
+
     async fn create_file(
         &mut self,
         message_id: i64,
         file_name: &str,
         file_size: i64,
         file_hash: &str,
+        blob_id: Option<i64>,
         metadata: sqlx::types::Json<FileMetadata>,
     ) -> Result<File, DatabaseError> {
         let file_uuid = Uuid::new_v4().to_string();
 
         let created_file = sqlx::query_as!(
             File,
-            r#"INSERT INTO files (file_uuid, message_id, file_name, file_size, file_hash, metadata)
-               VALUES ($1, $2, $3, $4, $5, $6)
-               RETURNING file_id, file_uuid, message_id, file_name, file_size, file_hash, created_at, metadata as "metadata: sqlx::types::Json<FileMetadata>""#,
+            r#"INSERT INTO files (file_uuid, message_id, file_name, file_size, file_hash, blob_id, metadata)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING file_id, file_uuid, message_id, file_name, file_size, file_hash, blob_id, created_at, metadata as "metadata: sqlx::types::Json<FileMetadata>""#,
             file_uuid,
             message_id,
             file_name,
             file_size,
             file_hash,
+            blob_id as _,
             metadata as _
         )
         .fetch_one(&mut *self.transaction)
@@ -455,7 +850,7 @@ impl MessageTransaction for PgMessageTransaction {
             File,
             r#"DELETE FROM files
                WHERE message_id = $1
-               RETURNING file_id, file_uuid, message_id, file_name, file_size, file_hash, created_at, metadata as "metadata: sqlx::types::Json<FileMetadata>""#,
+               RETURNING file_id, file_uuid, message_id, file_name, file_size, file_hash, blob_id, created_at, metadata as "metadata: sqlx::types::Json<FileMetadata>""#,
             message_id
         )
         .fetch_all(&mut *self.transaction)
@@ -464,6 +859,37 @@ impl MessageTransaction for PgMessageTransaction {
         Ok(deleted_files)
     }
 
+    async fn find_blob_by_hash(
+        &mut self,
+        file_hash: &str,
+        file_size: i64,
+    ) -> Result<Option<i64>, DatabaseError> {
+        let blob_id = sqlx::query_scalar!(
+            r#"SELECT blob_id FROM files
+               WHERE file_hash = $1 AND file_size = $2
+               LIMIT 1
+               FOR SHARE"#,
+            file_hash,
+            file_size
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+        Ok(blob_id)
+    }
+
+    async fn find_unreferenced_blobs(&mut self, files: &[File]) -> Result<Vec<i64>, DatabaseError> {
+        let blob_ids: Vec<i64> = files.iter().map(|file| file.blob_id).collect();
+        let blobs = sqlx::query_scalar!(
+            r#"SELECT DISTINCT blob AS "blob!"
+               FROM UNNEST($1::bigint[]) AS blob
+               WHERE NOT EXISTS (SELECT 1 FROM files WHERE files.blob_id = blob)"#,
+            &blob_ids
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+        Ok(blobs)
+    }
+
     async fn create_reaction(
         &mut self,
         message_id: i64,
@@ -523,22 +949,162 @@ impl MessageTransaction for PgMessageTransaction {
 
         Ok(reactions)
     }
-}
-
-impl MessageRepository for Postgre {
-    type Transaction = PgMessageTransaction;
 
-    async fn begin(&self) -> Result<Self::Transaction, DatabaseError> {
-        let tx = self.pool.begin().await?;
-        Ok(PgMessageTransaction { transaction: tx })
-    }
+    async fn create_idempotency_key(
+        &mut self,
+        user_id: i64,
+        idempotency_key: &str,
+        message_id: i64,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"INSERT INTO message_idempotency_keys (user_id, idempotency_key, message_id)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (user_id, idempotency_key)
+               DO UPDATE SET message_id = EXCLUDED.message_id, created_at = CURRENT_TIMESTAMP
+               WHERE message_idempotency_keys.created_at <= NOW() - INTERVAL '24 hours'
+               RETURNING message_id"#,
+            user_id,
+            idempotency_key,
+            message_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
 
-    async fn commit(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
-        transaction.transaction.commit().await?;
-        Ok(())
+        Ok(result.is_some())
     }
 
-    async fn rollback(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
+    async fn lock_channel_group_storage(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<Option<GroupStorage>, DatabaseError> {
+        let storage = sqlx::query_as!(
+            GroupStorage,
+            r#"SELECT
+                g.group_id,
+                COALESCE((
+                    SELECT SUM(f.file_size)
+                    FROM files f
+                    INNER JOIN messages m ON m.id = f.message_id
+                    INNER JOIN channels gc ON gc.channel_id = m.channel_id
+                    WHERE gc.group_id = g.group_id
+                ), 0)::BIGINT AS "used_bytes!",
+                g.storage_quota_bytes AS quota_bytes
+            FROM channels c
+            INNER JOIN groups g ON g.group_id = c.group_id
+            WHERE c.channel_id = $1
+            FOR UPDATE OF g"#,
+            channel_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(storage)
+    }
+
+    async fn lock_message_files(&mut self, message_id: i64) -> Result<Vec<File>, DatabaseError> {
+        let files = sqlx::query_as!(
+            File,
+            r#"SELECT file_id, file_uuid, message_id, file_name, file_size, file_hash, blob_id, created_at, metadata as "metadata: sqlx::types::Json<FileMetadata>"
+               FROM files
+               WHERE message_id = $1
+               ORDER BY file_id
+               FOR SHARE"#,
+            message_id
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(files)
+    }
+
+    async fn create_dm_request(
+        &mut self,
+        sender_id: i64,
+        recipient_id: i64,
+    ) -> Result<DmRequest, DatabaseError> {
+        let request = sqlx::query_as!(
+            DmRequest,
+            r#"INSERT INTO dm_requests (sender_id, recipient_id)
+               VALUES ($1, $2)
+               ON CONFLICT (sender_id, recipient_id) DO UPDATE SET sender_id = EXCLUDED.sender_id
+               RETURNING sender_id, recipient_id, status as "status: DmRequestStatus", created_at"#,
+            sender_id,
+            recipient_id
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(request)
+    }
+
+    async fn update_dm_request_status(
+        &mut self,
+        sender_id: i64,
+        recipient_id: i64,
+        status: DmRequestStatus,
+    ) -> Result<Option<DmRequest>, DatabaseError> {
+        let request = sqlx::query_as!(
+            DmRequest,
+            r#"UPDATE dm_requests SET status = $3
+               WHERE sender_id = $1 AND recipient_id = $2 AND status <> $3
+               RETURNING sender_id, recipient_id, status as "status: DmRequestStatus", created_at"#,
+            sender_id,
+            recipient_id,
+            status as DmRequestStatus
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(request)
+    }
+
+    async fn create_channel_mentions(
+        &mut self,
+        message_id: i64,
+        channel_id: i64,
+        sender_id: i64,
+        usernames: &[String],
+    ) -> Result<Vec<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"INSERT INTO message_mentions (message_id, user_id)
+               SELECT $1, u.user_id
+               FROM users u
+               INNER JOIN group_role_rights grr ON grr.role_id = u.role_id
+               INNER JOIN channels c ON c.group_id = grr.group_id
+               WHERE c.channel_id = $2
+                 AND grr.rights >= $5
+                 AND u.user_id <> $3
+                 AND (LOWER(u.username) = ANY(SELECT LOWER(name) FROM UNNEST($4::text[]) AS name)
+                   OR LOWER(u.display_name) = ANY(SELECT LOWER(name) FROM UNNEST($4::text[]) AS name))
+               ON CONFLICT (message_id, user_id) DO NOTHING
+               RETURNING user_id"#,
+            message_id,
+            channel_id,
+            sender_id,
+            usernames,
+            READ_RIGHTS
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+}
+
+impl MessageRepository for Postgre {
+    type Transaction = PgMessageTransaction;
+
+    async fn begin(&self) -> Result<Self::Transaction, DatabaseError> {
+        let tx = self.pool.begin().await?;
+        Ok(PgMessageTransaction { transaction: tx })
+    }
+
+    async fn commit(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
+        transaction.transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
         transaction.transaction.rollback().await?;
         Ok(())
     }
@@ -547,6 +1113,7 @@ impl MessageRepository for Postgre {
         &self,
         channel_id: i64,
         timestamp: OffsetDateTime,
+        before_id: i64,
         limit: i64,
     ) -> Result<Vec<Message>, DatabaseError> {
         let messages = sqlx::query_as!(
@@ -556,18 +1123,24 @@ impl MessageRepository for Postgre {
                 sender_id,
                 channel_id,
                 recipient_id,
+                conversation_id,
                 message_text,
                 created_at,
                 modified_at,
-                reply_to_message_id
+                reply_to_message_id,
+                forwarded_from_message_id,
+                metadata,
+                expires_at
             FROM messages
             WHERE channel_id = $1
-            AND created_at < $2
-            ORDER BY created_at DESC
+            AND (expires_at IS NULL OR expires_at > NOW())
+            AND (created_at, id) < ($2, $4)
+            ORDER BY created_at DESC, id DESC
             LIMIT $3"#,
             channel_id,
             timestamp,
-            limit
+            limit,
+            before_id
         )
         .fetch_all(&self.pool)
         .await?;
@@ -580,6 +1153,7 @@ impl MessageRepository for Postgre {
         user_id: i64,
         other_user_id: i64,
         timestamp: OffsetDateTime,
+        before_id: i64,
         limit: i64,
     ) -> Result<Vec<Message>, DatabaseError> {
         let messages = sqlx::query_as!(
@@ -589,23 +1163,137 @@ impl MessageRepository for Postgre {
                 sender_id,
                 channel_id,
                 recipient_id,
+                conversation_id,
                 message_text,
                 created_at,
                 modified_at,
-                reply_to_message_id
+                reply_to_message_id,
+                forwarded_from_message_id,
+                metadata,
+                expires_at
             FROM messages
             WHERE recipient_id IS NOT NULL
-            AND created_at < $1
+            AND (expires_at IS NULL OR expires_at > NOW())
+            AND (created_at, id) < ($1, $5)
             AND (
                 (sender_id = $2 AND recipient_id = $3)
                 OR (sender_id = $3 AND recipient_id = $2)
             )
-            ORDER BY created_at DESC
+            AND NOT (recipient_id = $2 AND EXISTS (
+                SELECT 1 FROM dm_requests req
+                WHERE req.sender_id = messages.sender_id
+                AND req.recipient_id = messages.recipient_id
+                AND req.status <> 'Accepted'
+            ))
+            ORDER BY created_at DESC, id DESC
             LIMIT $4"#,
             timestamp,
             user_id,
             other_user_id,
-            limit
+            limit,
+            before_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    async fn find_visible_messages(
+        &self,
+        user_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Message>, DatabaseError> {
+        let messages = sqlx::query_as!(
+            Message,
+            r#"SELECT
+                m.id,
+                m.sender_id,
+                m.channel_id,
+                m.recipient_id,
+                m.conversation_id,
+                m.message_text,
+                m.created_at,
+                m.modified_at,
+                m.reply_to_message_id,
+                m.forwarded_from_message_id,
+                m.metadata,
+                m.expires_at
+            FROM messages m
+            WHERE (m.created_at, m.id) < ($2, $5)
+            AND (m.expires_at IS NULL OR m.expires_at > NOW())
+            AND (
+                (m.channel_id IS NOT NULL AND EXISTS (
+                    SELECT 1
+                    FROM channels c
+                    INNER JOIN group_role_rights grr ON grr.group_id = c.group_id
+                    INNER JOIN users u ON u.role_id = grr.role_id
+                    WHERE c.channel_id = m.channel_id
+                    AND u.user_id = $1
+                    AND grr.rights >= $3
+                ))
+                OR (m.recipient_id IS NOT NULL AND (m.sender_id = $1 OR m.recipient_id = $1)
+                    AND NOT (m.recipient_id = $1 AND EXISTS (
+                        SELECT 1 FROM dm_requests req
+                        WHERE req.sender_id = m.sender_id
+                        AND req.recipient_id = m.recipient_id
+                        AND req.status <> 'Accepted'
+                    )))
+                OR (m.conversation_id IS NOT NULL AND EXISTS (
+                    SELECT 1
+                    FROM group_conversation_members gcm
+                    WHERE gcm.conversation_id = m.conversation_id
+                    AND gcm.user_id = $1
+                ))
+            )
+            ORDER BY m.created_at DESC, m.id DESC
+            LIMIT $4"#,
+            user_id,
+            timestamp,
+            READ_RIGHTS,
+            limit,
+            before_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    async fn find_group_messages_with_pagination(
+        &self,
+        conversation_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Message>, DatabaseError> {
+        let messages = sqlx::query_as!(
+            Message,
+            r#"SELECT
+                id,
+                sender_id,
+                channel_id,
+                recipient_id,
+                conversation_id,
+                message_text,
+                created_at,
+                modified_at,
+                reply_to_message_id,
+                forwarded_from_message_id,
+                metadata,
+                expires_at
+            FROM messages
+            WHERE conversation_id = $1
+            AND (expires_at IS NULL OR expires_at > NOW())
+            AND (created_at, id) < ($2, $4)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3"#,
+            conversation_id,
+            timestamp,
+            limit,
+            before_id
         )
         .fetch_all(&self.pool)
         .await?;
@@ -613,10 +1301,48 @@ impl MessageRepository for Postgre {
         Ok(messages)
     }
 
+    async fn find_group_conversations(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<GroupConversation>, DatabaseError> {
+        let conversations = sqlx::query_as!(
+            GroupConversation,
+            r#"SELECT c.conversation_id, c.name, c.created_by, c.created_at,
+                      ARRAY(SELECT m.user_id FROM group_conversation_members m
+                            WHERE m.conversation_id = c.conversation_id
+                            ORDER BY m.user_id) AS "member_ids!"
+               FROM group_conversations c
+               INNER JOIN group_conversation_members gm ON gm.conversation_id = c.conversation_id
+               WHERE gm.user_id = $1
+               ORDER BY c.created_at DESC"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(conversations)
+    }
+
+    async fn find_group_conversation_members(
+        &self,
+        conversation_id: i64,
+    ) -> Result<Vec<i64>, DatabaseError> {
+        let member_ids = sqlx::query_scalar!(
+            r#"SELECT user_id FROM group_conversation_members
+               WHERE conversation_id = $1
+               ORDER BY user_id"#,
+            conversation_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(member_ids)
+    }
+
     async fn find_file_by_id(&self, file_id: i64) -> Result<Option<FileAttachment>, DatabaseError> {
         let result = sqlx::query_as!(
             FileAttachment,
-            r#"SELECT f.file_id, f.file_uuid, f.message_id, f.file_name, f.file_size, f.file_hash, f.created_at, f.metadata as "metadata: sqlx::types::Json<FileMetadata>"
+            r#"SELECT f.file_id, f.file_uuid, f.message_id, f.file_name, f.file_size, f.file_hash, f.blob_id, f.created_at, f.metadata as "metadata: sqlx::types::Json<FileMetadata>"
                FROM files f
                WHERE f.file_id = $1"#,
             file_id
@@ -653,11 +1379,104 @@ impl MessageRepository for Postgre {
         Ok(result)
     }
 
+    async fn find_custom_emoji_id(&self, name: &str) -> Result<Option<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            "SELECT emoji_id FROM custom_emojis WHERE name = $1",
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_webhook_token(&self, token: &str) -> Result<Option<WebhookToken>, DatabaseError> {
+        let result = sqlx::query_as!(
+            WebhookToken,
+            r#"SELECT token, channel_id, bot_user_id, created_by, created_at
+               FROM webhook_tokens WHERE token = $1"#,
+            token
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn is_blocked_between(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM blocked_users
+                   WHERE (blocker_id = $1 AND blocked_id = $2)
+                      OR (blocker_id = $2 AND blocked_id = $1)
+               ) as "exists!""#,
+            user_id,
+            other_user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_dm_request(
+        &self,
+        sender_id: i64,
+        recipient_id: i64,
+    ) -> Result<Option<DmRequest>, DatabaseError> {
+        let request = sqlx::query_as!(
+            DmRequest,
+            r#"SELECT sender_id, recipient_id, status as "status: DmRequestStatus", created_at
+               FROM dm_requests
+               WHERE sender_id = $1 AND recipient_id = $2"#,
+            sender_id,
+            recipient_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(request)
+    }
+
+    async fn find_pending_dm_requests(
+        &self,
+        recipient_id: i64,
+    ) -> Result<Vec<DmRequest>, DatabaseError> {
+        let requests = sqlx::query_as!(
+            DmRequest,
+            r#"SELECT sender_id, recipient_id, status as "status: DmRequestStatus", created_at
+               FROM dm_requests
+               WHERE recipient_id = $1 AND status = 'Pending'
+               ORDER BY created_at DESC"#,
+            recipient_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(requests)
+    }
+
+    async fn has_sent_dm(&self, sender_id: i64, recipient_id: i64) -> Result<bool, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM messages
+                   WHERE sender_id = $1 AND recipient_id = $2
+               ) as "exists!""#,
+            sender_id,
+            recipient_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
     async fn find_message_by_id(&self, message_id: i64) -> Result<Option<Message>, DatabaseError> {
         let result = sqlx::query_as!(
             Message,
-            r#"SELECT id, sender_id, channel_id, recipient_id, message_text, created_at, modified_at, reply_to_message_id
-               FROM messages WHERE id = $1"#,
+            r#"SELECT id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at
+               FROM messages WHERE id = $1
+               AND (expires_at IS NULL OR expires_at > NOW())"#,
             message_id
         )
         .fetch_optional(&self.pool)
@@ -666,81 +1485,84 @@ impl MessageRepository for Postgre {
         Ok(result)
     }
 
-    async fn find_channel_files(
+    async fn find_reply_depth(&self, message_id: i64, limit: i64) -> Result<i64, DatabaseError> {
+        let depth = sqlx::query_scalar!(
+            r#"WITH RECURSIVE chain AS (
+                   SELECT id, reply_to_message_id, 1 AS depth FROM messages WHERE id = $1
+                   UNION ALL
+                   SELECT m.id, m.reply_to_message_id, c.depth + 1
+                   FROM messages m
+                   INNER JOIN chain c ON m.id = c.reply_to_message_id
+                   WHERE c.depth < $2
+               )
+               SELECT COALESCE(MAX(depth), 0)::BIGINT AS "depth!" FROM chain"#,
+            message_id,
+            limit as i32
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(depth)
+    }
+
+    async fn find_channel_messages_by_metadata(
         &self,
         channel_id: i64,
-        timestamp: OffsetDateTime,
+        key: &str,
+        value: Option<&str>,
         limit: i64,
-    ) -> Result<Vec<File>, DatabaseError> {
-        let files = sqlx::query_as!(
-            File,
-            r#"WITH limited_messages AS (
-                SELECT id as message_id
-                FROM messages
-                WHERE channel_id = $1
-                AND created_at < $2
-                ORDER BY created_at DESC
-                LIMIT $3
-            )
-            SELECT
-                f.file_id,
-                f.file_uuid,
-                f.message_id,
-                f.file_name,
-                f.file_size,
-                f.file_hash,
-                f.created_at,
-                f.metadata as "metadata: sqlx::types::Json<FileMetadata>"
-            FROM files f
-            WHERE f.message_id IN (SELECT message_id FROM limited_messages)
-            ORDER BY f.message_id, f.file_id"#,
+    ) -> Result<Vec<Message>, DatabaseError> {
+        let messages = sqlx::query_as!(
+            Message,
+            r#"SELECT id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at
+               FROM messages
+               WHERE channel_id = $1
+               AND (expires_at IS NULL OR expires_at > NOW())
+               AND metadata ? $2
+               AND ($3::TEXT IS NULL OR metadata->>$2 = $3)
+               ORDER BY created_at DESC
+               LIMIT $4"#,
             channel_id,
-            timestamp,
+            key,
+            value,
             limit
         )
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(files)
+        Ok(messages)
     }
 
-    async fn find_dm_files(
+    async fn find_message_by_idempotency_key(
         &self,
         user_id: i64,
-        other_user_id: i64,
-        timestamp: OffsetDateTime,
-        limit: i64,
-    ) -> Result<Vec<File>, DatabaseError> {
+        idempotency_key: &str,
+    ) -> Result<Option<Message>, DatabaseError> {
+        let result = sqlx::query_as!(
+            Message,
+            r#"SELECT m.id, m.sender_id, m.channel_id, m.recipient_id, m.conversation_id, m.message_text, m.created_at, m.modified_at, m.reply_to_message_id, m.forwarded_from_message_id, m.metadata, m.expires_at
+               FROM message_idempotency_keys k
+               INNER JOIN messages m ON m.id = k.message_id
+               WHERE k.user_id = $1
+               AND k.idempotency_key = $2
+               AND k.created_at > NOW() - INTERVAL '24 hours'"#,
+            user_id,
+            idempotency_key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_message_files(&self, message_id: i64) -> Result<Vec<File>, DatabaseError> {
         let files = sqlx::query_as!(
             File,
-            r#"WITH limited_messages AS (
-                SELECT id as message_id
-                FROM messages
-                WHERE recipient_id IS NOT NULL
-                AND created_at < $1
-                AND (
-                    (sender_id = $2 AND recipient_id = $3)
-                    OR (sender_id = $3 AND recipient_id = $2)
-                )
-                ORDER BY created_at DESC
-                LIMIT $4
-            )
-            SELECT
-                f.file_id,
-                f.file_uuid,
-                f.message_id,
-                f.file_name,
-                f.file_size,
-                f.file_hash,
-                f.created_at,
-                f.metadata as "metadata: sqlx::types::Json<FileMetadata>"
-            FROM files f
-            WHERE f.message_id IN (SELECT message_id FROM limited_messages)
-            ORDER BY f.message_id, f.file_id"#,
-            timestamp,
-            user_id,
-            other_user_id,
-            limit
+            r#"SELECT file_id, file_uuid, message_id, file_name, file_size, file_hash, blob_id, created_at, metadata as "metadata: sqlx::types::Json<FileMetadata>"
+               FROM files
+               WHERE message_id = $1
+               ORDER BY file_id"#,
+            message_id
         )
         .fetch_all(&self.pool)
         .await?;
@@ -748,75 +1570,103 @@ impl MessageRepository for Postgre {
         Ok(files)
     }
 
-    async fn find_channel_reactions(
+    async fn find_thread_messages(
         &self,
-        channel_id: i64,
-        timestamp: OffsetDateTime,
+        root_message_id: i64,
         limit: i64,
-    ) -> Result<Vec<Reaction>, DatabaseError> {
-        let reactions = sqlx::query_as!(
-            Reaction,
-            r#"WITH limited_messages AS (
-                SELECT id as message_id
-                FROM messages
-                WHERE channel_id = $1
-                AND created_at < $2
-                ORDER BY created_at DESC
-                LIMIT $3
-            )
-            SELECT
-                r.reaction_id,
-                r.message_id,
-                r.user_id,
-                r.emoji,
-                r.created_at
-            FROM reactions r
-            WHERE r.message_id IN (SELECT message_id FROM limited_messages)
-            ORDER BY r.message_id, r.created_at"#,
-            channel_id,
-            timestamp,
+    ) -> Result<Vec<Message>, DatabaseError> {
+        let messages = sqlx::query_as!(
+            Message,
+            r#"WITH RECURSIVE thread AS (
+                   SELECT id FROM messages WHERE reply_to_message_id = $1
+                   UNION
+                   SELECT m.id FROM messages m
+                   INNER JOIN thread t ON m.reply_to_message_id = t.id
+               )
+               SELECT id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at
+               FROM messages
+               WHERE id IN (SELECT id FROM thread)
+               AND (expires_at IS NULL OR expires_at > NOW())
+               AND id <> $1
+               ORDER BY created_at ASC
+               LIMIT $2"#,
+            root_message_id,
             limit
         )
         .fetch_all(&self.pool)
         .await?;
 
+        Ok(messages)
+    }
+
+    async fn find_reply_counts(
+        &self,
+        message_ids: &[i64],
+    ) -> Result<Vec<ReplyCount>, DatabaseError> {
+        let counts = sqlx::query_as!(
+            ReplyCount,
+            r#"WITH RECURSIVE thread AS (
+                   SELECT id AS root_id, id FROM messages WHERE id = ANY($1)
+                   UNION
+                   SELECT t.root_id, m.id FROM messages m
+                   INNER JOIN thread t ON m.reply_to_message_id = t.id
+               )
+               SELECT root_id as "message_id!", COUNT(*) - 1 as "reply_count!"
+               FROM thread
+               GROUP BY root_id
+               HAVING COUNT(*) > 1"#,
+            message_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    async fn find_message_reactions(&self, message_id: i64) -> Result<Vec<Reaction>, DatabaseError> {
+        let reactions = sqlx::query_as!(
+            Reaction,
+            r#"SELECT reaction_id, message_id, user_id, emoji, created_at
+               FROM reactions
+               WHERE message_id = $1
+               ORDER BY created_at"#,
+            message_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
         Ok(reactions)
     }
 
-    async fn find_dm_reactions(
+    async fn find_files_for_messages(
         &self,
-        user_id: i64,
-        other_user_id: i64,
-        timestamp: OffsetDateTime,
-        limit: i64,
+        message_ids: &[i64],
+    ) -> Result<Vec<File>, DatabaseError> {
+        let files = sqlx::query_as!(
+            File,
+            r#"SELECT file_id, file_uuid, message_id, file_name, file_size, file_hash, blob_id, created_at, metadata as "metadata: sqlx::types::Json<FileMetadata>"
+               FROM files
+               WHERE message_id = ANY($1)
+               ORDER BY message_id, file_id"#,
+            message_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    async fn find_reactions_for_messages(
+        &self,
+        message_ids: &[i64],
     ) -> Result<Vec<Reaction>, DatabaseError> {
         let reactions = sqlx::query_as!(
             Reaction,
-            r#"WITH limited_messages AS (
-                SELECT id as message_id
-                FROM messages
-                WHERE recipient_id IS NOT NULL
-                AND created_at < $1
-                AND (
-                    (sender_id = $2 AND recipient_id = $3)
-                    OR (sender_id = $3 AND recipient_id = $2)
-                )
-                ORDER BY created_at DESC
-                LIMIT $4
-            )
-            SELECT
-                r.reaction_id,
-                r.message_id,
-                r.user_id,
-                r.emoji,
-                r.created_at
-            FROM reactions r
-            WHERE r.message_id IN (SELECT message_id FROM limited_messages)
-            ORDER BY r.message_id, r.created_at"#,
-            timestamp,
-            user_id,
-            other_user_id,
-            limit
+            r#"SELECT reaction_id, message_id, user_id, emoji, created_at
+               FROM reactions
+               WHERE message_id = ANY($1)
+               ORDER BY message_id, created_at"#,
+            message_ids
         )
         .fetch_all(&self.pool)
         .await?;
@@ -837,12 +1687,17 @@ impl MessageRepository for Postgre {
                 sender_id,
                 channel_id,
                 recipient_id,
+                conversation_id,
                 message_text,
                 created_at,
                 modified_at,
-                reply_to_message_id
+                reply_to_message_id,
+                forwarded_from_message_id,
+                metadata,
+                expires_at
             FROM messages
             WHERE channel_id = $1
+            AND (expires_at IS NULL OR expires_at > NOW())
             AND id >= $2
             AND id < $3
             ORDER BY created_at DESC"#,
@@ -870,18 +1725,29 @@ impl MessageRepository for Postgre {
                 sender_id,
                 channel_id,
                 recipient_id,
+                conversation_id,
                 message_text,
                 created_at,
                 modified_at,
-                reply_to_message_id
+                reply_to_message_id,
+                forwarded_from_message_id,
+                metadata,
+                expires_at
             FROM messages
             WHERE recipient_id IS NOT NULL
+            AND (expires_at IS NULL OR expires_at > NOW())
             AND id >= $1
             AND id < $2
             AND (
                 (sender_id = $3 AND recipient_id = $4)
                 OR (sender_id = $4 AND recipient_id = $3)
             )
+            AND NOT (recipient_id = $3 AND EXISTS (
+                SELECT 1 FROM dm_requests req
+                WHERE req.sender_id = messages.sender_id
+                AND req.recipient_id = messages.recipient_id
+                AND req.status <> 'Accepted'
+            ))
             ORDER BY created_at DESC"#,
             up_to_message_id,
             from_message_id,
@@ -909,11 +1775,13 @@ impl MessageRepository for Postgre {
                 f.file_name,
                 f.file_size,
                 f.file_hash,
+                f.blob_id,
                 f.created_at,
                 f.metadata as "metadata: sqlx::types::Json<FileMetadata>"
             FROM files f
             JOIN messages m ON f.message_id = m.id
             WHERE m.channel_id = $1
+            AND (m.expires_at IS NULL OR m.expires_at > NOW())
             AND m.id >= $2
             AND m.id < $3
             ORDER BY f.message_id, f.file_id"#,
@@ -943,17 +1811,25 @@ impl MessageRepository for Postgre {
                 f.file_name,
                 f.file_size,
                 f.file_hash,
+                f.blob_id,
                 f.created_at,
                 f.metadata as "metadata: sqlx::types::Json<FileMetadata>"
             FROM files f
             JOIN messages m ON f.message_id = m.id
             WHERE m.recipient_id IS NOT NULL
+            AND (m.expires_at IS NULL OR m.expires_at > NOW())
             AND m.id >= $1
             AND m.id < $2
             AND (
                 (m.sender_id = $3 AND m.recipient_id = $4)
                 OR (m.sender_id = $4 AND m.recipient_id = $3)
             )
+            AND NOT (m.recipient_id = $3 AND EXISTS (
+                SELECT 1 FROM dm_requests req
+                WHERE req.sender_id = m.sender_id
+                AND req.recipient_id = m.recipient_id
+                AND req.status <> 'Accepted'
+            ))
             ORDER BY f.message_id, f.file_id"#,
             up_to_message_id,
             from_message_id,
@@ -983,6 +1859,7 @@ impl MessageRepository for Postgre {
             FROM reactions r
             JOIN messages m ON r.message_id = m.id
             WHERE m.channel_id = $1
+            AND (m.expires_at IS NULL OR m.expires_at > NOW())
             AND m.id >= $2
             AND m.id < $3
             ORDER BY r.message_id, r.created_at"#,
@@ -1014,12 +1891,19 @@ impl MessageRepository for Postgre {
             FROM reactions r
             JOIN messages m ON r.message_id = m.id
             WHERE m.recipient_id IS NOT NULL
+            AND (m.expires_at IS NULL OR m.expires_at > NOW())
             AND m.id >= $1
             AND m.id < $2
             AND (
                 (m.sender_id = $3 AND m.recipient_id = $4)
                 OR (m.sender_id = $4 AND m.recipient_id = $3)
             )
+            AND NOT (m.recipient_id = $3 AND EXISTS (
+                SELECT 1 FROM dm_requests req
+                WHERE req.sender_id = m.sender_id
+                AND req.recipient_id = m.recipient_id
+                AND req.status <> 'Accepted'
+            ))
             ORDER BY r.message_id, r.created_at"#,
             up_to_message_id,
             from_message_id,
@@ -1037,29 +1921,182 @@ impl MessageRepository for Postgre {
 // SERVICE
 // ═══════════════════════════════════════════════════════════════════════════════
 
+pub const DEFAULT_MAX_MESSAGE_LENGTH: usize = 4000;
+pub const MAX_MESSAGE_METADATA_BYTES: usize = 4096;
+pub const DEFAULT_MAX_REACTION_EMOJIS: usize = 20;
+pub const DEFAULT_MAX_REACTIONS_PER_USER: usize = 10;
+pub const DEFAULT_MAX_EMOJI_BYTES: usize = 64;
+pub const DEFAULT_MAX_MULTIPART_PARTS: usize = 32;
+pub const DEFAULT_MAX_MULTIPART_BYTES: usize = 512 * 1024 * 1024;
+pub const RETENTION_PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+pub const EPHEMERAL_PURGE_INTERVAL: Duration = Duration::from_secs(15);
+pub const MAX_MESSAGE_TTL: Duration = Duration::from_secs(7 * 24 * 3600);
+pub const MAX_GROUP_CONVERSATION_MEMBERS: usize = 10;
+const MAX_GROUP_CONVERSATION_NAME_LENGTH: usize = 64;
+
 #[derive(Clone)]
 pub struct MessageService<
     R: MessageRepository,
     F: FileManager + Clone + Send,
     N: NotifierManager,
     G: LogManager,
+    S: ScanManager,
 > {
     repository: R,
     file_manager: F,
+    scanner: S,
     notifier: N,
     logger: G,
+    max_message_length: usize,
+    dm_rate_limiter: DmRateLimiter,
+    max_reaction_emojis: usize,
+    max_reactions_per_user: usize,
+    max_emoji_bytes: usize,
+    max_reply_depth: Option<usize>,
+    max_multipart_parts: usize,
+    max_multipart_bytes: usize,
+    dm_requests: bool,
+}
+
+enum DmDelivery {
+    Deliver,
+    Hold,
+    OpenRequest,
 }
 
-impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G: LogManager>
-    MessageService<R, F, N, G>
+impl<
+    R: MessageRepository,
+    F: FileManager + Clone + Send,
+    N: NotifierManager,
+    G: LogManager,
+    S: ScanManager,
+> MessageService<R, F, N, G, S>
 {
-    pub fn new(repository: R, file_manager: F, notifier: N, logger: G) -> Self {
+    pub fn new(
+        repository: R,
+        file_manager: F,
+        scanner: S,
+        notifier: N,
+        logger: G,
+        max_message_length: usize,
+    ) -> Self {
         Self {
             repository,
             file_manager,
+            scanner,
             notifier,
             logger,
+            max_message_length,
+            dm_rate_limiter: DmRateLimiter::default(),
+            max_reaction_emojis: DEFAULT_MAX_REACTION_EMOJIS,
+            max_reactions_per_user: DEFAULT_MAX_REACTIONS_PER_USER,
+            max_emoji_bytes: DEFAULT_MAX_EMOJI_BYTES,
+            max_reply_depth: None,
+            max_multipart_parts: DEFAULT_MAX_MULTIPART_PARTS,
+            max_multipart_bytes: DEFAULT_MAX_MULTIPART_BYTES,
+            dm_requests: false,
+        }
+    }
+
+    /// Holds first DMs from users the recipient has never written to as a
+    /// message request until the recipient accepts it or writes back.
+    pub fn with_dm_requests(mut self, dm_requests: bool) -> Self {
+        self.dm_requests = dm_requests;
+        self
+    }
+
+    async fn dm_delivery(
+        &self,
+        sender_id: i64,
+        recipient_id: i64,
+    ) -> Result<DmDelivery, DomainError> {
+        if !self.dm_requests || sender_id == recipient_id {
+            return Ok(DmDelivery::Deliver);
+        }
+
+        match self
+            .repository
+            .find_dm_request(sender_id, recipient_id)
+            .await?
+        {
+            Some(request) => match request.status {
+                DmRequestStatus::Accepted => Ok(DmDelivery::Deliver),
+                DmRequestStatus::Pending => Ok(DmDelivery::Hold),
+                DmRequestStatus::Declined => Err(DomainError::PermissionDenied(
+                    "This user declined your message request".to_string(),
+                )),
+            },
+            None if self.repository.has_sent_dm(recipient_id, sender_id).await? => {
+                Ok(DmDelivery::Deliver)
+            }
+            None => Ok(DmDelivery::OpenRequest),
+        }
+    }
+
+    pub fn with_multipart_limits(
+        mut self,
+        max_multipart_parts: usize,
+        max_multipart_bytes: usize,
+    ) -> Self {
+        self.max_multipart_parts = max_multipart_parts;
+        self.max_multipart_bytes = max_multipart_bytes;
+        self
+    }
+
+    fn multipart_budget(&self) -> MultipartBudget {
+        MultipartBudget {
+            parts: 0,
+            bytes: 0,
+            max_parts: self.max_multipart_parts,
+            max_bytes: self.max_multipart_bytes,
+        }
+    }
+
+    /// Rejects replies that would make a reply chain longer than `max_reply_depth`.
+    pub fn with_max_reply_depth(mut self, max_reply_depth: Option<usize>) -> Self {
+        self.max_reply_depth = max_reply_depth;
+        self
+    }
+
+    async fn check_reply_depth(&self, reply_to_message_id: i64) -> Result<(), DomainError> {
+        let Some(max_reply_depth) = self.max_reply_depth else {
+            return Ok(());
+        };
+
+        let depth = self
+            .repository
+            .find_reply_depth(reply_to_message_id, max_reply_depth as i64)
+            .await?;
+        if depth as usize >= max_reply_depth {
+            return Err(DomainError::BadRequest(format!(
+                "Reply chains are limited to {} messages",
+                max_reply_depth
+            )));
         }
+
+        Ok(())
+    }
+
+    pub fn with_reaction_limits(
+        mut self,
+        max_reaction_emojis: usize,
+        max_reactions_per_user: usize,
+    ) -> Self {
+        self.max_reaction_emojis = max_reaction_emojis;
+        self.max_reactions_per_user = max_reactions_per_user;
+        self
+    }
+
+    /// Longest reaction accepted, in UTF-8 bytes. Checked before any lookup so
+    /// oversized strings never reach the database.
+    pub fn with_max_emoji_bytes(mut self, max_emoji_bytes: usize) -> Self {
+        self.max_emoji_bytes = max_emoji_bytes;
+        self
+    }
+
+    pub fn with_dm_rate_limiter(mut self, dm_rate_limiter: DmRateLimiter) -> Self {
+        self.dm_rate_limiter = dm_rate_limiter;
+        self
     }
 
     pub async fn create_channel_message(
@@ -1069,14 +2106,36 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         channel_id: i64,
         message_text: Option<String>,
         reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        ttl_secs: Option<u64>,
         files: Vec<NewFileAttachment>,
-    ) -> Result<(Message, Vec<File>), DomainError> {
-        if let Some(ref text) = message_text {
-            if text.len() > 4000 {
-                return Err(DomainError::BadRequest(
-                    "Message exceeds 4000 character limit".to_string(),
-                ));
-            }
+        idempotency_key: Option<String>,
+    ) -> Result<PostedMessage, DomainError> {
+        let message_text = match message_text {
+            Some(text) => Some(sanitize_message_text(&text, self.max_message_length)?)
+                .filter(|t| !t.is_empty()),
+            None => None,
+        };
+        validate_metadata(metadata.as_ref())?;
+        let expires_at = message_expiry(ttl_secs)?;
+
+        if message_text.is_none() && files.is_empty() {
+            return Err(DomainError::BadRequest(
+                "Message must contain text or files".to_string(),
+            ));
+        }
+
+        let target = MessageType::Channel { channel_id };
+        if let Some((message, files)) = self
+            .find_idempotent_message(
+                sender_id,
+                idempotency_key.as_deref(),
+                &target,
+                message_text.as_deref(),
+            )
+            .await?
+        {
+            return Ok(PostedMessage::Replayed(message, files));
         }
 
         let rights = self
@@ -1087,7 +2146,7 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                 "No access to channel".to_string(),
             ))?;
 
-        if rights < 4 {
+        if rights < WRITE_RIGHTS {
             return Err(DomainError::PermissionDenied(
                 "Insufficient permissions to send messages".to_string(),
             ));
@@ -1101,6 +2160,7 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                     ));
                 }
             }
+            self.check_reply_depth(reply_id).await?;
         }
 
         let mut db_tx = self.repository.begin().await?;
@@ -1111,6 +2171,8 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                 channel_id,
                 message_text.clone(),
                 reply_to_message_id,
+                metadata,
+                expires_at,
             )
             .await
             .map_err(|e| match &e {
@@ -1119,15 +2181,38 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                         "Reply message not found".to_string()
                     ),
                     "channel_id" => {
-                        DomainError::BadRequest(format!("Channel {} not found", channel_id))
+                        DomainError::NotFound(format!("Channel {} not found", channel_id))
                     }
                     _ => DomainError::InternalError(e),
                 },
                 _ => DomainError::InternalError(e),
             })?;
 
+        if let Some(ref key) = idempotency_key {
+            if !db_tx.create_idempotency_key(sender_id, key, message.id).await? {
+                self.repository.rollback(db_tx).await?;
+                return self
+                    .existing_idempotent_message(sender_id, key, &target, message_text.as_deref())
+                    .await;
+            }
+        }
+
+        if !files.is_empty() {
+            let incoming_bytes = files.iter().map(|f| f.data.len() as i64).sum();
+            check_group_storage_quota(&mut db_tx, channel_id, incoming_bytes).await?;
+        }
+
         let file_attachments = self.process_files(&mut db_tx, message.id, files).await?;
 
+        let mentions = extract_mentions(message_text.as_deref().unwrap_or_default());
+        let mentioned_user_ids = if mentions.is_empty() {
+            vec![]
+        } else {
+            db_tx
+                .create_channel_mentions(message.id, channel_id, sender_id, &mentions)
+                .await?
+        };
+
         self.repository.commit(db_tx).await?;
 
         let event = EventPayload::MessageCreated {
@@ -1136,8 +2221,12 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
             message_type: MessageType::Channel { channel_id },
             message_text: message.message_text.clone(),
             reply_to_message_id: message.reply_to_message_id,
+            forwarded_from_message_id: message.forwarded_from_message_id,
+            metadata: message.metadata.clone(),
             timestamp: message.created_at,
+            expires_at: message.expires_at,
             files: file_attachments.clone(),
+            muted: false,
         };
 
         let _ = self
@@ -1146,17 +2235,58 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                 event,
                 ControlRoutingPolicy::ChannelRights {
                     channel_id,
-                    minimun_rights: 2,
+                    minimun_rights: READ_RIGHTS,
                 },
             ))
             .await;
 
+        for user_id in mentioned_user_ids {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    EventPayload::Mentioned {
+                        message_id: message.id,
+                        user_id,
+                    },
+                    ControlRoutingPolicy::User { user_id },
+                ))
+                .await;
+        }
+
         let _ = self.logger.log_entry(
             format!("Channel message created: user_id={}, session_id={}, message_id={}, channel_id={}", sender_id, session_id, message.id, channel_id),
             "message".to_string(),
         ).await;
 
-        Ok((message, file_attachments))
+        Ok(PostedMessage::Created(message, file_attachments))
+    }
+
+    pub async fn create_webhook_message(
+        &mut self,
+        token: &str,
+        message_text: String,
+        idempotency_key: Option<String>,
+    ) -> Result<PostedMessage, DomainError> {
+        let webhook_token = self
+            .repository
+            .find_webhook_token(token)
+            .await?
+            .ok_or(DomainError::PermissionDenied(
+                "Invalid webhook token".to_string(),
+            ))?;
+
+        self.create_channel_message(
+            webhook_token.bot_user_id,
+            0,
+            webhook_token.channel_id,
+            Some(message_text),
+            None,
+            None,
+            None,
+            Vec::new(),
+            idempotency_key,
+        )
+        .await
     }
 
     pub async fn create_dm_message(
@@ -1166,14 +2296,55 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         recipient_id: i64,
         message_text: Option<String>,
         reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        ttl_secs: Option<u64>,
         files: Vec<NewFileAttachment>,
-    ) -> Result<(Message, Vec<File>), DomainError> {
-        if let Some(ref text) = message_text {
-            if text.len() > 4000 {
-                return Err(DomainError::BadRequest(
-                    "Message exceeds 4000 character limit".to_string(),
-                ));
-            }
+        idempotency_key: Option<String>,
+    ) -> Result<PostedMessage, DomainError> {
+        let message_text = match message_text {
+            Some(text) => Some(sanitize_message_text(&text, self.max_message_length)?)
+                .filter(|t| !t.is_empty()),
+            None => None,
+        };
+        validate_metadata(metadata.as_ref())?;
+        let expires_at = message_expiry(ttl_secs)?;
+
+        if message_text.is_none() && files.is_empty() {
+            return Err(DomainError::BadRequest(
+                "Message must contain text or files".to_string(),
+            ));
+        }
+
+        let target = MessageType::Direct { recipient_id };
+        if let Some((message, files)) = self
+            .find_idempotent_message(
+                sender_id,
+                idempotency_key.as_deref(),
+                &target,
+                message_text.as_deref(),
+            )
+            .await?
+        {
+            return Ok(PostedMessage::Replayed(message, files));
+        }
+
+        if self
+            .repository
+            .is_blocked_between(sender_id, recipient_id)
+            .await?
+        {
+            return Err(DomainError::PermissionDenied(
+                "Cannot message this user".to_string(),
+            ));
+        }
+
+        let delivery = self.dm_delivery(sender_id, recipient_id).await?;
+
+        if let Err(retry_after) = self.dm_rate_limiter.check(sender_id, recipient_id) {
+            return Err(DomainError::BadRequest(format!(
+                "Direct message rate limit exceeded, retry in {} seconds",
+                retry_after.as_secs().max(1)
+            )));
         }
 
         if let Some(reply_id) = reply_to_message_id {
@@ -1189,6 +2360,7 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                     ));
                 }
             }
+            self.check_reply_depth(reply_id).await?;
         }
 
         let mut db_tx = self.repository.begin().await?;
@@ -1199,6 +2371,8 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                 recipient_id,
                 message_text.clone(),
                 reply_to_message_id,
+                metadata,
+                expires_at,
             )
             .await
             .map_err(|e| match &e {
@@ -1207,14 +2381,38 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
                         "Reply message not found".to_string()
                     ),
                     "recipient_id" => {
-                        DomainError::BadRequest(format!("Recipient {} not found", recipient_id))
+                        DomainError::NotFound(format!("Recipient {} not found", recipient_id))
                     }
                     _ => DomainError::InternalError(e),
                 },
                 _ => DomainError::InternalError(e),
             })?;
 
+        if let Some(ref key) = idempotency_key {
+            if !db_tx.create_idempotency_key(sender_id, key, message.id).await? {
+                self.repository.rollback(db_tx).await?;
+                return self
+                    .existing_idempotent_message(sender_id, key, &target, message_text.as_deref())
+                    .await;
+            }
+        }
+
         let file_attachments = self.process_files(&mut db_tx, message.id, files).await?;
+
+        let opened_request = match delivery {
+            DmDelivery::OpenRequest => {
+                Some(db_tx.create_dm_request(sender_id, recipient_id).await?)
+            }
+            DmDelivery::Deliver | DmDelivery::Hold => None,
+        };
+        let accepted_request = if self.dm_requests && sender_id != recipient_id {
+            db_tx
+                .update_dm_request_status(recipient_id, sender_id, DmRequestStatus::Accepted)
+                .await?
+        } else {
+            None
+        };
+
         self.repository.commit(db_tx).await?;
 
         let event = EventPayload::MessageCreated {
@@ -1223,14 +2421,17 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
             message_type: MessageType::Direct { recipient_id },
             message_text: message.message_text.clone(),
             reply_to_message_id: message.reply_to_message_id,
+            forwarded_from_message_id: message.forwarded_from_message_id,
+            metadata: message.metadata.clone(),
             timestamp: message.created_at,
+            expires_at: message.expires_at,
             files: file_attachments.clone(),
+            muted: false,
         };
 
-        let user_ids = if sender_id != recipient_id {
-            vec![sender_id, recipient_id]
-        } else {
-            vec![sender_id]
+        let user_ids = match delivery {
+            DmDelivery::Deliver if sender_id != recipient_id => vec![sender_id, recipient_id],
+            _ => vec![sender_id],
         };
         let _ = self
             .notifier
@@ -1240,6 +2441,30 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
             ))
             .await;
 
+        if let Some(request) = opened_request {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    EventPayload::DmRequestCreated { request },
+                    ControlRoutingPolicy::User {
+                        user_id: recipient_id,
+                    },
+                ))
+                .await;
+        }
+
+        if let Some(request) = accepted_request {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    EventPayload::DmRequestUpdated { request },
+                    ControlRoutingPolicy::Users {
+                        user_ids: vec![sender_id, recipient_id],
+                    },
+                ))
+                .await;
+        }
+
         let _ = self
             .logger
             .log_entry(
@@ -1251,1146 +2476,4067 @@ impl<R: MessageRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
             )
             .await;
 
-        Ok((message, file_attachments))
+        Ok(PostedMessage::Created(message, file_attachments))
     }
 
-    async fn process_files(
+    pub async fn create_group_conversation(
         &self,
-        db_tx: &mut <R as MessageRepository>::Transaction,
-        message_id: i64,
-        files: Vec<NewFileAttachment>,
-    ) -> Result<Vec<File>, DomainError> {
-        let mut file_tx = self.file_manager.begin()?;
-        let mut file_attachments = Vec::new();
-
-        for f in &files {
-            let file_hash = format!("{:x}", Sha256::digest(&f.data));
-            let file_size = f.data.len() as i64;
+        user_id: i64,
+        session_id: i64,
+        member_ids: Vec<i64>,
+        name: Option<String>,
+    ) -> Result<GroupConversation, DomainError> {
+        let name = name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        if name
+            .as_ref()
+            .is_some_and(|name| name.chars().count() > MAX_GROUP_CONVERSATION_NAME_LENGTH)
+        {
+            return Err(DomainError::BadRequest(format!(
+                "Group name must be at most {} characters",
+                MAX_GROUP_CONVERSATION_NAME_LENGTH
+            )));
+        }
 
-            let metadata = if f.content_type.starts_with("image/") {
-                match imagesize::blob_size(&f.data) {
-                    Ok(dims) => FileMetadata::Image {
-                        mime: f.content_type.clone(),
-                        width: dims.width as u32,
-                        height: dims.height as u32,
-                    },
-                    Err(_) => FileMetadata::File { mime: f.content_type.clone() },
-                }
-            } else if f.content_type.starts_with("video/") {
-                FileMetadata::Video { mime: f.content_type.clone(), width: 0, height: 0 }
-            } else if f.content_type.starts_with("audio/") {
-                FileMetadata::Audio { mime: f.content_type.clone() }
-            } else {
-                FileMetadata::File { mime: f.content_type.clone() }
-            };
+        let mut member_ids: Vec<i64> = member_ids
+            .into_iter()
+            .filter(|member_id| *member_id != user_id)
+            .collect();
+        member_ids.sort_unstable();
+        member_ids.dedup();
 
-            let file_attachment = db_tx
-                .create_file(
-                    message_id,
-                    &f.file_name,
-                    file_size,
-                    &file_hash,
-                    sqlx::types::Json(metadata),
-                )
-                .await?;
+        if member_ids.len() < 2 {
+            return Err(DomainError::BadRequest(
+                "A group conversation needs at least two other members".to_string(),
+            ));
+        }
 
-            file_tx.stage_upload(file_attachment.file_id, &f.data)?;
+        if member_ids.len() + 1 > MAX_GROUP_CONVERSATION_MEMBERS {
+            return Err(DomainError::BadRequest(format!(
+                "A group conversation can have at most {} members",
+                MAX_GROUP_CONVERSATION_MEMBERS
+            )));
+        }
 
-            file_attachments.push(file_attachment);
+        for member_id in &member_ids {
+            if self
+                .repository
+                .is_blocked_between(user_id, *member_id)
+                .await?
+            {
+                return Err(DomainError::PermissionDenied(format!(
+                    "Cannot add user {} to a group conversation",
+                    member_id
+                )));
+            }
         }
+        member_ids.push(user_id);
 
-        file_tx.commit()?;
+        let mut tx = self.repository.begin().await?;
 
-        Ok(file_attachments)
-    }
+        let conversation = tx
+            .create_group_conversation(user_id, name.as_deref(), &member_ids)
+            .await
+            .map_err(|e| match e {
+                DatabaseError::ForeignKeyViolation { .. } => {
+                    DomainError::NotFound("One or more members not found".to_string())
+                }
+                _ => DomainError::InternalError(e),
+            })?;
 
-    pub async fn get_channel_messages(
-        &self,
-        user_id: i64,
-        channel_id: i64,
-        timestamp: OffsetDateTime,
-        limit: i64,
-    ) -> Result<MessagesResponse, DomainError> {
-        let mut repo = self.repository.clone();
-        let rights = repo
-            .find_user_channel_rights(channel_id, user_id)
-            .await?
-            .ok_or(DomainError::PermissionDenied(
-                "No access to channel".to_string(),
-            ))?;
-
-        if rights < 2 {
-            return Err(DomainError::PermissionDenied(
-                "Insufficient permissions to read messages".to_string(),
-            ));
-        }
-
-        let messages = self
-            .repository
-            .find_channel_messages_with_pagination(channel_id, timestamp, limit)
-            .await?;
+        self.repository.commit(tx).await?;
 
-        let files = self
-            .repository
-            .find_channel_files(channel_id, timestamp, limit)
-            .await?;
+        let event = EventPayload::GroupConversationCreated {
+            conversation: conversation.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Users {
+                    user_ids: conversation.member_ids.clone(),
+                },
+            ))
+            .await;
 
-        let reactions = self
-            .repository
-            .find_channel_reactions(channel_id, timestamp, limit)
-            .await?;
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Group conversation created: user_id={}, session_id={}, conversation_id={}, members={}",
+                    user_id,
+                    session_id,
+                    conversation.conversation_id,
+                    conversation.member_ids.len()
+                ),
+                "message".to_string(),
+            )
+            .await;
 
-        Ok(MessagesResponse {
-            messages,
-            files,
-            reactions,
-        })
+        Ok(conversation)
     }
 
-    pub async fn get_dm_messages(
+    pub async fn get_group_conversations(
         &self,
         user_id: i64,
-        other_user_id: i64,
-        timestamp: OffsetDateTime,
-        limit: i64,
-    ) -> Result<MessagesResponse, DomainError> {
-        let messages = self
-            .repository
-            .find_dm_messages_with_pagination(user_id, other_user_id, timestamp, limit)
-            .await?;
-
-        let files = self
-            .repository
-            .find_dm_files(user_id, other_user_id, timestamp, limit)
-            .await?;
-
-        let reactions = self
-            .repository
-            .find_dm_reactions(user_id, other_user_id, timestamp, limit)
-            .await?;
+    ) -> Result<Vec<GroupConversation>, DomainError> {
+        let conversations = self.repository.find_group_conversations(user_id).await?;
+        Ok(conversations)
+    }
 
-        Ok(MessagesResponse {
-            messages,
-            files,
-            reactions,
-        })
+    pub async fn get_dm_requests(&self, user_id: i64) -> Result<Vec<DmRequest>, DomainError> {
+        let requests = self.repository.find_pending_dm_requests(user_id).await?;
+        Ok(requests)
     }
 
-    pub async fn get_channel_messages_range(
+    pub async fn respond_to_dm_request(
         &self,
         user_id: i64,
-        channel_id: i64,
-        from_message_id: i64,
-        up_to_message_id: i64,
-    ) -> Result<MessagesResponse, DomainError> {
-        let mut repo = self.repository.clone();
-        let rights = repo
-            .find_user_channel_rights(channel_id, user_id)
-            .await?
-            .ok_or(DomainError::PermissionDenied(
-                "No access to channel".to_string(),
-            ))?;
-
-        if rights < 2 {
-            return Err(DomainError::PermissionDenied(
-                "Insufficient permissions to read messages".to_string(),
-            ));
-        }
-
-        let messages = self
-            .repository
-            .find_channel_messages_range(channel_id, from_message_id, up_to_message_id)
-            .await?;
-
-        let files = self
-            .repository
-            .find_channel_files_range(channel_id, from_message_id, up_to_message_id)
-            .await?;
+        session_id: i64,
+        sender_id: i64,
+        accept: bool,
+    ) -> Result<DmRequest, DomainError> {
+        let status = if accept {
+            DmRequestStatus::Accepted
+        } else {
+            DmRequestStatus::Declined
+        };
 
-        let reactions = self
-            .repository
-            .find_channel_reactions_range(channel_id, from_message_id, up_to_message_id)
-            .await?;
+        let mut tx = self.repository.begin().await?;
 
-        Ok(MessagesResponse {
-            messages,
-            files,
-            reactions,
-        })
-    }
+        let request = match tx
+            .update_dm_request_status(sender_id, user_id, status)
+            .await?
+        {
+            Some(request) => request,
+            None => self
+                .repository
+                .find_dm_request(sender_id, user_id)
+                .await?
+                .ok_or(DomainError::NotFound(format!(
+                    "No message request from user {}",
+                    sender_id
+                )))?,
+        };
 
-    pub async fn get_dm_messages_range(
-        &self,
-        user_id: i64,
-        other_user_id: i64,
-        from_message_id: i64,
-        up_to_message_id: i64,
-    ) -> Result<MessagesResponse, DomainError> {
-        let messages = self
-            .repository
-            .find_dm_messages_range(user_id, other_user_id, from_message_id, up_to_message_id)
-            .await?;
+        self.repository.commit(tx).await?;
 
-        let files = self
-            .repository
-            .find_dm_files_range(user_id, other_user_id, from_message_id, up_to_message_id)
-            .await?;
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                EventPayload::DmRequestUpdated {
+                    request: request.clone(),
+                },
+                ControlRoutingPolicy::Users {
+                    user_ids: vec![sender_id, user_id],
+                },
+            ))
+            .await;
 
-        let reactions = self
-            .repository
-            .find_dm_reactions_range(user_id, other_user_id, from_message_id, up_to_message_id)
-            .await?;
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "DM request {:?}: user_id={}, session_id={}, sender_id={}",
+                    status, user_id, session_id, sender_id
+                ),
+                "message".to_string(),
+            )
+            .await;
 
-        Ok(MessagesResponse {
-            messages,
-            files,
-            reactions,
-        })
+        Ok(request)
     }
 
-    pub async fn edit_message(
-        &self,
-        user_id: i64,
+    pub async fn create_group_message(
+        &mut self,
+        sender_id: i64,
         session_id: i64,
-        message_id: i64,
-        new_text: String,
-    ) -> Result<Message, DomainError> {
-        if new_text.len() > 4000 {
+        conversation_id: i64,
+        message_text: Option<String>,
+        reply_to_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
+        ttl_secs: Option<u64>,
+        files: Vec<NewFileAttachment>,
+        idempotency_key: Option<String>,
+    ) -> Result<PostedMessage, DomainError> {
+        let message_text = match message_text {
+            Some(text) => Some(sanitize_message_text(&text, self.max_message_length)?)
+                .filter(|t| !t.is_empty()),
+            None => None,
+        };
+        validate_metadata(metadata.as_ref())?;
+        let expires_at = message_expiry(ttl_secs)?;
+
+        if message_text.is_none() && files.is_empty() {
             return Err(DomainError::BadRequest(
-                "Message exceeds 4000 character limit".to_string(),
+                "Message must contain text or files".to_string(),
             ));
         }
 
-        let mut tx = self.repository.begin().await?;
-
-        let message = tx
-            .edit_message(message_id, &new_text, user_id)
+        let target = MessageType::Group { conversation_id };
+        if let Some((message, files)) = self
+            .find_idempotent_message(
+                sender_id,
+                idempotency_key.as_deref(),
+                &target,
+                message_text.as_deref(),
+            )
             .await?
-            .ok_or(DomainError::BadRequest(format!(
-                "Message {} not found or not owned by user",
-                message_id
-            )))?;
-
-        self.repository.commit(tx).await?;
+        {
+            return Ok(PostedMessage::Replayed(message, files));
+        }
 
-        let event = EventPayload::MessageUpdated {
-            message_id: message.id,
-            message_text: new_text.clone(),
-        };
+        let member_ids = self
+            .require_group_member(conversation_id, sender_id)
+            .await?;
 
-        if let Some(channel_id) = message.channel_id {
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event.clone(),
-                    ControlRoutingPolicy::ChannelRights {
-                        channel_id,
-                        minimun_rights: 2,
-                    },
-                ))
-                .await;
+        if let Some(reply_id) = reply_to_message_id {
+            if let Some(reply_msg) = self.repository.find_message_by_id(reply_id).await? {
+                if reply_msg.conversation_id != Some(conversation_id) {
+                    return Err(DomainError::BadRequest(
+                        "Reply must reference a message in the same conversation".to_string(),
+                    ));
+                }
+            }
+            self.check_reply_depth(reply_id).await?;
         }
 
-        if let Some(recipient_id) = message.recipient_id {
-            let user_ids = if user_id != recipient_id {
-                vec![user_id, recipient_id]
-            } else {
-                vec![user_id]
-            };
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event,
-                    ControlRoutingPolicy::Users { user_ids },
-                ))
-                .await;
+        let mut db_tx = self.repository.begin().await?;
+
+        let message = db_tx
+            .create_group_message(
+                sender_id,
+                conversation_id,
+                message_text.clone(),
+                reply_to_message_id,
+                metadata,
+                expires_at,
+            )
+            .await
+            .map_err(|e| match &e {
+                DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
+                    "reply_to_message_id" => {
+                        DomainError::BadRequest("Reply message not found".to_string())
+                    }
+                    _ => DomainError::InternalError(e),
+                },
+                _ => DomainError::InternalError(e),
+            })?;
+
+        if let Some(ref key) = idempotency_key {
+            if !db_tx
+                .create_idempotency_key(sender_id, key, message.id)
+                .await?
+            {
+                self.repository.rollback(db_tx).await?;
+                return self
+                    .existing_idempotent_message(sender_id, key, &target, message_text.as_deref())
+                    .await;
+            }
         }
 
+        let file_attachments = self.process_files(&mut db_tx, message.id, files).await?;
+        self.repository.commit(db_tx).await?;
+
+        let event = EventPayload::MessageCreated {
+            message_id: message.id,
+            sender_id: message.sender_id,
+            message_type: MessageType::Group { conversation_id },
+            message_text: message.message_text.clone(),
+            reply_to_message_id: message.reply_to_message_id,
+            forwarded_from_message_id: message.forwarded_from_message_id,
+            metadata: message.metadata.clone(),
+            timestamp: message.created_at,
+            expires_at: message.expires_at,
+            files: file_attachments.clone(),
+            muted: false,
+        };
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Users {
+                    user_ids: member_ids,
+                },
+            ))
+            .await;
+
         let _ = self
             .logger
             .log_entry(
                 format!(
-                    "Message edited: user_id={}, session_id={}, message_id={}",
-                    user_id, session_id, message_id
+                    "Group message created: user_id={}, session_id={}, message_id={}, conversation_id={}",
+                    sender_id, session_id, message.id, conversation_id
                 ),
                 "message".to_string(),
             )
             .await;
 
-        Ok(message)
+        Ok(PostedMessage::Created(message, file_attachments))
     }
 
-    pub async fn delete_message(
+    async fn require_group_member(
         &self,
+        conversation_id: i64,
         user_id: i64,
-        session_id: i64,
-        message_id: i64,
-    ) -> Result<Message, DomainError> {
-        let mut tx = self.repository.begin().await?;
+    ) -> Result<Vec<i64>, DomainError> {
+        let member_ids = self
+            .repository
+            .find_group_conversation_members(conversation_id)
+            .await?;
 
-        let files = tx.delete_message_files(message_id).await?;
+        if !member_ids.contains(&user_id) {
+            return Err(DomainError::PermissionDenied(
+                "Not a member of this conversation".to_string(),
+            ));
+        }
 
-        let message = tx
-            .delete_message(message_id)
+        Ok(member_ids)
+    }
+
+    async fn find_idempotent_message(
+        &self,
+        sender_id: i64,
+        idempotency_key: Option<&str>,
+        target: &MessageType,
+        message_text: Option<&str>,
+    ) -> Result<Option<(Message, Vec<File>)>, DomainError> {
+        let Some(key) = idempotency_key else {
+            return Ok(None);
+        };
+
+        if key.is_empty() || key.len() > 255 {
+            return Err(DomainError::BadRequest(
+                "Idempotency key must be between 1 and 255 characters".to_string(),
+            ));
+        }
+
+        match self
+            .repository
+            .find_message_by_idempotency_key(sender_id, key)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
-                "Message {} not found",
-                message_id
-            )))?;
+        {
+            Some(message) if !target.contains(&message) => Err(DomainError::BadRequest(
+                "Idempotency key was already used for a different conversation".to_string(),
+            )),
+            Some(message) if message.message_text.as_deref() != message_text => {
+                Err(DomainError::BadRequest(
+                    "Idempotency key was already used for a different message".to_string(),
+                ))
+            }
+            Some(message) => {
+                let files = self.repository.find_message_files(message.id).await?;
+                Ok(Some((message, files)))
+            }
+            None => Ok(None),
+        }
+    }
 
-        if message.sender_id != user_id {
-            if let Some(channel_id) = message.channel_id {
-                let mut repo = self.repository.clone();
-                let rights = repo
-                    .find_user_channel_rights(channel_id, user_id)
-                    .await?
-                    .unwrap_or(0);
+    async fn check_reaction_limits(
+        &self,
+        message_id: i64,
+        user_id: i64,
+        emoji: &str,
+    ) -> Result<(), DomainError> {
+        let reactions = self.repository.find_message_reactions(message_id).await?;
 
-                if rights < 8 {
-                    self.repository.rollback(tx).await?;
-                    return Err(DomainError::PermissionDenied(
-                        "Insufficient permissions to delete this message".to_string(),
-                    ));
-                }
+        if reactions
+            .iter()
+            .any(|r| r.user_id == user_id && r.emoji == emoji)
+        {
+            return Ok(());
+        }
 
-                let user_role = repo
-                    .find_user_role(user_id)
-                    .await?
-                    .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
-                let sender_role = repo.find_user_role(message.sender_id).await?.ok_or(
-                    DomainError::PermissionDenied("Sender not found".to_string()),
-                )?;
+        let user_reactions = reactions.iter().filter(|r| r.user_id == user_id).count();
+        if user_reactions >= self.max_reactions_per_user {
+            return Err(DomainError::BadRequest(format!(
+                "Cannot add more than {} reactions to a message",
+                self.max_reactions_per_user
+            )));
+        }
 
-                if sender_role == OWNER_ROLE_ID && user_role > OWNER_ROLE_ID {
-                    self.repository.rollback(tx).await?;
-                    return Err(DomainError::PermissionDenied(
-                        "Only owner can delete owner's messages".to_string(),
-                    ));
-                }
-                if sender_role == ADMIN_ROLE_ID && user_role > ADMIN_ROLE_ID {
-                    self.repository.rollback(tx).await?;
-                    return Err(DomainError::PermissionDenied(
-                        "Only owner or admin can delete admin's messages".to_string(),
-                    ));
-                }
-            } else {
-                self.repository.rollback(tx).await?;
-                return Err(DomainError::PermissionDenied(
-                    "Cannot delete other users' direct messages".to_string(),
-                ));
-            }
+        let emojis: HashSet<&str> = reactions.iter().map(|r| r.emoji.as_str()).collect();
+        if !emojis.contains(emoji) && emojis.len() >= self.max_reaction_emojis {
+            return Err(DomainError::BadRequest(format!(
+                "Message already has the maximum of {} distinct reactions",
+                self.max_reaction_emojis
+            )));
         }
 
-        self.repository.commit(tx).await?;
+        Ok(())
+    }
 
-        for file in &files {
-            if let Err(e) = self.file_manager.delete_file(file.file_id) {
-                warn!("Failed to delete file {} from storage: {}", file.file_id, e);
-            }
+    async fn validate_emoji(&self, emoji: &str) -> Result<(), DomainError> {
+        if emoji.len() > self.max_emoji_bytes {
+            return Err(DomainError::BadRequest(format!(
+                "Emoji exceeds {} bytes",
+                self.max_emoji_bytes
+            )));
         }
 
-        let event = EventPayload::MessageDeleted { message_id };
+        if emojis::get(emoji).is_some() {
+            return Ok(());
+        }
 
-        if let Some(channel_id) = message.channel_id {
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event.clone(),
-                    ControlRoutingPolicy::ChannelRights {
-                        channel_id,
-                        minimun_rights: 2,
+        let name = emoji
+            .strip_prefix(':')
+            .and_then(|e| e.strip_suffix(':'))
+            .filter(|name| is_valid_emoji_name(name))
+            .ok_or(DomainError::BadRequest("Invalid emoji".to_string()))?;
+
+        self.repository
+            .find_custom_emoji_id(name)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Custom emoji :{}: not found",
+                name
+            )))?;
+
+        Ok(())
+    }
+
+    async fn existing_idempotent_message(
+        &self,
+        sender_id: i64,
+        idempotency_key: &str,
+        target: &MessageType,
+        message_text: Option<&str>,
+    ) -> Result<PostedMessage, DomainError> {
+        let (message, files) = self
+            .find_idempotent_message(sender_id, Some(idempotency_key), target, message_text)
+            .await?
+            .ok_or(DomainError::BadRequest(
+                "Duplicate request is still being processed".to_string(),
+            ))?;
+        Ok(PostedMessage::Replayed(message, files))
+    }
+
+    async fn process_files(
+        &self,
+        db_tx: &mut <R as MessageRepository>::Transaction,
+        message_id: i64,
+        files: Vec<NewFileAttachment>,
+    ) -> Result<Vec<File>, DomainError> {
+        let mut file_tx = self.file_manager.begin()?;
+        let mut file_attachments = Vec::new();
+
+        for f in &files {
+            let file_hash = format!("{:x}", Sha256::digest(&f.data));
+            let file_size = f.data.len() as i64;
+
+            let metadata = if f.content_type.starts_with("image/") {
+                match imagesize::blob_size(&f.data) {
+                    Ok(dims) => FileMetadata::Image {
+                        mime: f.content_type.clone(),
+                        width: dims.width as u32,
+                        height: dims.height as u32,
                     },
-                ))
-                .await;
-        }
-        if let Some(recipient_id) = message.recipient_id {
-            let user_ids = if user_id != recipient_id {
-                vec![user_id, recipient_id]
+                    Err(_) => FileMetadata::File { mime: f.content_type.clone() },
+                }
+            } else if f.content_type.starts_with("video/") {
+                FileMetadata::Video { mime: f.content_type.clone(), width: 0, height: 0 }
+            } else if f.content_type.starts_with("audio/") {
+                FileMetadata::Audio { mime: f.content_type.clone() }
             } else {
-                vec![user_id]
+                FileMetadata::File { mime: f.content_type.clone() }
             };
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event,
-                    ControlRoutingPolicy::Users { user_ids },
-                ))
-                .await;
+
+            let existing_blob = db_tx.find_blob_by_hash(&file_hash, file_size).await?;
+
+            if existing_blob.is_none()
+                && let ScanResult::Rejected(reason) = self.scanner.scan(&f.data).await
+            {
+                let _ = self
+                    .logger
+                    .log_entry(
+                        format!("Upload {} rejected by scanner: {}", f.file_name, reason),
+                        "message".to_string(),
+                    )
+                    .await;
+                return Err(DomainError::BadRequest(format!(
+                    "File {} was rejected by the scanner",
+                    f.file_name
+                )));
+            }
+
+            let file_attachment = db_tx
+                .create_file(
+                    message_id,
+                    &f.file_name,
+                    file_size,
+                    &file_hash,
+                    existing_blob,
+                    sqlx::types::Json(metadata),
+                )
+                .await?;
+
+            if existing_blob.is_none() {
+                file_tx.stage_upload(file_attachment.blob_id, &f.data)?;
+            }
+
+            file_attachments.push(file_attachment);
         }
 
-        let _ = self
-            .logger
-            .log_entry(
-                format!(
-                    "Message deleted: user_id={}, session_id={}, message_id={}",
-                    user_id, session_id, message_id
-                ),
-                "message".to_string(),
-            )
-            .await;
+        file_tx.commit()?;
 
-        Ok(message)
+        Ok(file_attachments)
     }
 
-    pub async fn get_file(
+    pub async fn get_channel_messages_by_metadata(
         &self,
         user_id: i64,
-        file_id: i64,
-    ) -> Result<(FileAttachment, Vec<u8>), DomainError> {
-        let file =
-            self.repository
-                .find_file_by_id(file_id)
-                .await?
-                .ok_or(DomainError::BadRequest(format!(
-                    "File {} not found",
-                    file_id
-                )))?;
-
-        let message = self
-            .repository
-            .find_message_by_id(file.message_id)
+        channel_id: i64,
+        key: &str,
+        value: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Message>, DomainError> {
+        let mut repo = self.repository.clone();
+        let rights = repo
+            .find_user_channel_rights(channel_id, user_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
-                "Message {} not found",
-                file.message_id
-            )))?;
-
-        if let Some(channel_id) = message.channel_id {
-            let mut repo = self.repository.clone();
-            let rights = repo
-                .find_user_channel_rights(channel_id, user_id)
-                .await?
-                .ok_or(DomainError::PermissionDenied(
-                    "No access to channel".to_string(),
-                ))?;
+            .ok_or(DomainError::PermissionDenied(
+                "No access to channel".to_string(),
+            ))?;
 
-            if rights < 2 {
-                return Err(DomainError::PermissionDenied(
-                    "Insufficient permissions to access files".to_string(),
-                ));
-            }
-        } else if let Some(recipient_id) = message.recipient_id {
-            if message.sender_id != user_id && recipient_id != user_id {
-                return Err(DomainError::PermissionDenied(
-                    "No access to this direct message".to_string(),
-                ));
-            }
-        } else {
+        if rights < READ_RIGHTS {
             return Err(DomainError::PermissionDenied(
-                "Invalid message type".to_string(),
+                "Insufficient permissions to read messages".to_string(),
             ));
         }
 
-        let raw_data = self.file_manager.get_file(file_id).map_err(|e| match e {
-            FileError::NotFound(_) => {
-                DomainError::BadRequest(format!("File {} not found", file_id))
-            }
-            _ => DomainError::FileManagerError(e),
-        })?;
+        let messages = self
+            .repository
+            .find_channel_messages_by_metadata(channel_id, key, value, limit.clamp(1, 100))
+            .await?;
 
-        Ok((file, raw_data))
+        Ok(messages)
     }
 
-    pub async fn add_reaction(
-        &mut self,
+    async fn find_reply_counts(
+        &self,
+        messages: &[Message],
+    ) -> Result<Vec<ReplyCount>, DomainError> {
+        let message_ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+        let reply_counts = self.repository.find_reply_counts(&message_ids).await?;
+        Ok(reply_counts)
+    }
+
+    async fn find_files(&self, messages: &[Message]) -> Result<Vec<File>, DomainError> {
+        let message_ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+        let files = self
+            .repository
+            .find_files_for_messages(&message_ids)
+            .await?;
+        Ok(files)
+    }
+
+    async fn find_reactions(&self, messages: &[Message]) -> Result<Vec<Reaction>, DomainError> {
+        let message_ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+        let reactions = self
+            .repository
+            .find_reactions_for_messages(&message_ids)
+            .await?;
+        Ok(reactions)
+    }
+
+    pub async fn get_thread(
+        &self,
         user_id: i64,
-        session_id: i64,
         message_id: i64,
-        emoji: String,
-    ) -> Result<Reaction, DomainError> {
-        if emojis::get(&emoji).is_none() {
-            return Err(DomainError::BadRequest("Invalid emoji".to_string()));
-        }
+        limit: i64,
+    ) -> Result<MessagesResponse, DomainError> {
+        let no_access = || DomainError::PermissionDenied("No access to message".to_string());
 
-        let message = self
+        let root = self
             .repository
             .find_message_by_id(message_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
-                "Message {} not found",
-                message_id
-            )))?;
+            .ok_or_else(no_access)?;
 
-        if let Some(channel_id) = message.channel_id {
-            let rights = self
-                .repository
+        if let Some(channel_id) = root.channel_id {
+            let mut repo = self.repository.clone();
+            let rights = repo
                 .find_user_channel_rights(channel_id, user_id)
                 .await?
-                .ok_or(DomainError::PermissionDenied(
-                    "No access to channel".to_string(),
-                ))?;
+                .unwrap_or(0);
 
-            if rights < 4 {
-                return Err(DomainError::PermissionDenied(
-                    "Insufficient permissions to react".to_string(),
-                ));
-            }
-        } else if let Some(recipient_id) = message.recipient_id {
-            if message.sender_id != user_id && recipient_id != user_id {
-                return Err(DomainError::PermissionDenied(
-                    "No access to this direct message".to_string(),
-                ));
+            if rights < READ_RIGHTS {
+                return Err(no_access());
             }
+        } else if let Some(conversation_id) = root.conversation_id {
+            self.require_group_member(conversation_id, user_id).await?;
+        } else if root.sender_id != user_id && root.recipient_id != Some(user_id) {
+            return Err(no_access());
         }
 
-        let mut tx = self.repository.begin().await?;
-        let reaction = tx.create_reaction(message_id, user_id, &emoji).await?;
-        self.repository.commit(tx).await?;
-
-        let message_type = if let Some(channel_id) = message.channel_id {
-            MessageType::Channel { channel_id }
-        } else {
-            MessageType::Direct {
-                recipient_id: message.recipient_id.unwrap(),
-            }
-        };
-
-        let event = EventPayload::ReactionAdded {
-            reaction: reaction.clone(),
-            message_type: message_type.clone(),
-        };
-
-        if let Some(channel_id) = message.channel_id {
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event,
-                    ControlRoutingPolicy::ChannelRights {
-                        channel_id,
-                        minimun_rights: 2,
-                    },
-                ))
-                .await;
-        } else if let Some(recipient_id) = message.recipient_id {
-            let user_ids = if message.sender_id != recipient_id {
-                vec![message.sender_id, recipient_id]
-            } else {
-                vec![message.sender_id]
-            };
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event,
-                    ControlRoutingPolicy::Users { user_ids },
-                ))
-                .await;
-        }
+        let messages = self
+            .repository
+            .find_thread_messages(message_id, limit.clamp(1, 100))
+            .await?;
 
-        let _ = self
-            .logger
-            .log_entry(
-                format!(
-                    "Reaction added: user_id={}, session_id={}, message_id={}, emoji={}",
-                    user_id, session_id, message_id, emoji
-                ),
-                "message".to_string(),
-            )
-            .await;
+        let files = self.find_files(&messages).await?;
+        let reactions = self.find_reactions(&messages).await?;
+        let reply_counts = self.find_reply_counts(&messages).await?;
 
-        Ok(reaction)
+        Ok(MessagesResponse {
+            messages,
+            files,
+            reactions,
+            reply_counts,
+        })
     }
 
-    pub async fn remove_reaction(
-        &mut self,
+    pub async fn get_message(
+        &self,
         user_id: i64,
-        session_id: i64,
         message_id: i64,
-        emoji: String,
-    ) -> Result<(), DomainError> {
-        if emojis::get(&emoji).is_none() {
-            return Err(DomainError::BadRequest("Invalid emoji".to_string()));
-        }
+    ) -> Result<MessageResponse, DomainError> {
+        let no_access = || DomainError::PermissionDenied("No access to message".to_string());
 
         let message = self
             .repository
             .find_message_by_id(message_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
-                "Message {} not found",
-                message_id
-            )))?;
+            .ok_or_else(no_access)?;
 
-        let mut tx = self.repository.begin().await?;
-        let deleted = tx.delete_reaction(message_id, user_id, &emoji).await?;
+        if let Some(channel_id) = message.channel_id {
+            let mut repo = self.repository.clone();
+            let rights = repo
+                .find_user_channel_rights(channel_id, user_id)
+                .await?
+                .unwrap_or(0);
 
-        if deleted.is_none() {
-            self.repository.rollback(tx).await?;
-            return Err(DomainError::BadRequest("Reaction not found".to_string()));
+            if rights < READ_RIGHTS {
+                return Err(no_access());
+            }
+        } else if let Some(conversation_id) = message.conversation_id {
+            self.require_group_member(conversation_id, user_id).await?;
+        } else if message.sender_id != user_id && message.recipient_id != Some(user_id) {
+            return Err(no_access());
         }
 
-        self.repository.commit(tx).await?;
+        let files = self.repository.find_message_files(message_id).await?;
+        let reactions = self.repository.find_message_reactions(message_id).await?;
 
-        let message_type = if let Some(channel_id) = message.channel_id {
-            MessageType::Channel { channel_id }
-        } else {
-            MessageType::Direct {
-                recipient_id: message.recipient_id.unwrap(),
-            }
-        };
+        Ok(MessageResponse {
+            message,
+            files,
+            reactions,
+        })
+    }
 
-        let event = EventPayload::ReactionRemoved {
-            message_id,
-            user_id,
-            emoji: emoji.clone(),
-            message_type,
-        };
+    pub async fn get_channel_messages(
+        &self,
+        user_id: i64,
+        channel_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Paginated<MessagesResponse>, DomainError> {
+        let mut repo = self.repository.clone();
+        let rights = repo
+            .find_user_channel_rights(channel_id, user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied(
+                "No access to channel".to_string(),
+            ))?;
 
-        if let Some(channel_id) = message.channel_id {
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event,
-                    ControlRoutingPolicy::ChannelRights {
-                        channel_id,
-                        minimun_rights: 2,
-                    },
-                ))
-                .await;
-        } else if let Some(recipient_id) = message.recipient_id {
-            let user_ids = if message.sender_id != recipient_id {
-                vec![message.sender_id, recipient_id]
-            } else {
-                vec![message.sender_id]
-            };
-            let _ = self
-                .notifier
-                .notify(ServerMessage::Control(
-                    event,
-                    ControlRoutingPolicy::Users { user_ids },
-                ))
-                .await;
+        if rights < READ_RIGHTS {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to read messages".to_string(),
+            ));
         }
 
-        let _ = self
-            .logger
-            .log_entry(
-                format!(
-                    "Reaction removed: user_id={}, session_id={}, message_id={}, emoji={}",
-                    user_id, session_id, message_id, emoji
-                ),
-                "message".to_string(),
-            )
-            .await;
+        let limit = limit.clamp(1, 100);
+        let before_id = before_id.unwrap_or(0);
+        let mut messages = self
+            .repository
+            .find_channel_messages_with_pagination(channel_id, timestamp, before_id, limit + 1)
+            .await?;
 
-        Ok(())
+        let next_cursor = split_page(&mut messages, limit);
+
+        let files = self.find_files(&messages).await?;
+        let reactions = self.find_reactions(&messages).await?;
+
+        let reply_counts = self.find_reply_counts(&messages).await?;
+
+        Ok(Paginated {
+            items: MessagesResponse {
+                messages,
+                files,
+                reactions,
+                reply_counts,
+            },
+            next_cursor: next_cursor.map(|(created_at, _)| created_at),
+            next_cursor_id: next_cursor.map(|(_, id)| id),
+            has_more: next_cursor.is_some(),
+        })
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// REQUEST/RESPONSE
-// ═══════════════════════════════════════════════════════════════════════════════
+    pub async fn get_dm_messages(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Paginated<MessagesResponse>, DomainError> {
+        let limit = limit.clamp(1, 100);
+        let before_id = before_id.unwrap_or(0);
+        let mut messages = self
+            .repository
+            .find_dm_messages_with_pagination(
+                user_id,
+                other_user_id,
+                timestamp,
+                before_id,
+                limit + 1,
+            )
+            .await?;
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct MessagesResponse {
-    pub messages: Vec<Message>,
-    pub files: Vec<File>,
-    pub reactions: Vec<Reaction>,
-}
+        let next_cursor = split_page(&mut messages, limit);
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct EditMessageRequest {
-    pub message_text: String,
-}
+        let files = self.find_files(&messages).await?;
+        let reactions = self.find_reactions(&messages).await?;
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct AddReactionRequest {
-    pub emoji: String,
-}
+        let reply_counts = self.find_reply_counts(&messages).await?;
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct MessageQuery {
-    pub limit: Option<i64>,
-    #[serde(with = "time::serde::iso8601")]
-    pub timestamp: OffsetDateTime,
-}
+        Ok(Paginated {
+            items: MessagesResponse {
+                messages,
+                files,
+                reactions,
+                reply_counts,
+            },
+            next_cursor: next_cursor.map(|(created_at, _)| created_at),
+            next_cursor_id: next_cursor.map(|(_, id)| id),
+            has_more: next_cursor.is_some(),
+        })
+    }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct MessageRangeQuery {
-    pub from_message_id: i64,
-    pub up_to_message_id: i64,
-}
+    /// Channel messages the user can read plus their direct and group
+    /// conversation messages, newest first across all of them.
+    pub async fn get_feed(
+        &self,
+        user_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Paginated<MessagesResponse>, DomainError> {
+        let limit = limit.clamp(1, 100);
+        let before_id = before_id.unwrap_or(0);
+        let mut messages = self
+            .repository
+            .find_visible_messages(user_id, timestamp, before_id, limit + 1)
+            .await?;
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// ROUTES
-// ═══════════════════════════════════════════════════════════════════════════════
+        let next_cursor = split_page(&mut messages, limit);
+
+        let files = self.find_files(&messages).await?;
+        let reactions = self.find_reactions(&messages).await?;
+        let reply_counts = self.find_reply_counts(&messages).await?;
+
+        Ok(Paginated {
+            items: MessagesResponse {
+                messages,
+                files,
+                reactions,
+                reply_counts,
+            },
+            next_cursor: next_cursor.map(|(created_at, _)| created_at),
+            next_cursor_id: next_cursor.map(|(_, id)| id),
+            has_more: next_cursor.is_some(),
+        })
+    }
 
-type AppMessageService =
-    MessageService<Postgre, LocalFileManager, DefaultNotifierManager, TextLogManager>;
+    pub async fn get_group_messages(
+        &self,
+        user_id: i64,
+        conversation_id: i64,
+        timestamp: OffsetDateTime,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Paginated<MessagesResponse>, DomainError> {
+        self.require_group_member(conversation_id, user_id).await?;
 
-pub fn message_routes(
-    message_service: AppMessageService,
-    authorize_service: AuthorizeService<Postgre>,
-) -> OpenApiRouter<Postgre> {
-    OpenApiRouter::new()
-        .routes(routes!(create_channel_message_handler))
-        .routes(routes!(create_dm_message_handler))
-        .routes(routes!(get_channel_messages_handler))
-        .routes(routes!(get_dm_messages_handler))
-        .routes(routes!(get_channel_messages_range_handler))
-        .routes(routes!(get_dm_messages_range_handler))
-        .routes(routes!(add_reaction_handler))
-        .routes(routes!(remove_reaction_handler))
-        .routes(routes!(edit_message_handler))
-        .routes(routes!(delete_message_handler))
-        .routes(routes!(get_file_handler))
-        .layer(from_fn_with_state(authorize_service, authorize))
-        .with_state(message_service)
-}
+        let limit = limit.clamp(1, 100);
+        let before_id = before_id.unwrap_or(0);
+        let mut messages = self
+            .repository
+            .find_group_messages_with_pagination(conversation_id, timestamp, before_id, limit + 1)
+            .await?;
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// HANDLERS
-// ═══════════════════════════════════════════════════════════════════════════════
+        let next_cursor = split_page(&mut messages, limit);
+
+        let files = self.find_files(&messages).await?;
+        let reactions = self.find_reactions(&messages).await?;
+        let reply_counts = self.find_reply_counts(&messages).await?;
+
+        Ok(Paginated {
+            items: MessagesResponse {
+                messages,
+                files,
+                reactions,
+                reply_counts,
+            },
+            next_cursor: next_cursor.map(|(created_at, _)| created_at),
+            next_cursor_id: next_cursor.map(|(_, id)| id),
+            has_more: next_cursor.is_some(),
+        })
+    }
 
-#[utoipa::path(
-    post,
-    tag = "message",
-    path = "/channel/{channel_id}/messages",
-    description = "Send message to channel",
-    params(("channel_id" = i64, Path, description = "Channel ID")),
-    request_body(content_type = "multipart/form-data"),
-    responses(
-        (status = 201, description = "Created"),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn create_channel_message_handler(
-    State(mut service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(channel_id): Path<i64>,
-    mut multipart: Multipart,
-) -> Result<(), ApiError> {
-    let mut message_text: Option<String> = None;
-    let mut reply_to_message_id: Option<i64> = None;
-    let mut files: Vec<NewFileAttachment> = Vec::new();
+    pub async fn get_channel_messages_range(
+        &self,
+        user_id: i64,
+        channel_id: i64,
+        from_message_id: i64,
+        up_to_message_id: i64,
+    ) -> Result<MessagesResponse, DomainError> {
+        let mut repo = self.repository.clone();
+        let rights = repo
+            .find_user_channel_rights(channel_id, user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied(
+                "No access to channel".to_string(),
+            ))?;
 
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        ApiError::UnprocessableEntity(format!("Failed to read multipart field: {}", e))
-    })? {
-        let name = field.name().unwrap_or_default().to_string();
+        if rights < READ_RIGHTS {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to read messages".to_string(),
+            ));
+        }
 
-        match name.as_str() {
-            "messageText" => {
-                message_text = Some(field.text().await.map_err(|e| {
-                    ApiError::UnprocessableEntity(format!("Invalid message text: {}", e))
-                })?);
-            }
-            "replyToMessageId" => {
-                let text = field.text().await.map_err(|e| {
-                    ApiError::UnprocessableEntity(format!("Invalid reply ID: {}", e))
-                })?;
-                reply_to_message_id = text.parse().ok();
-            }
-            "files" => {
-                let file_name = field.file_name().unwrap_or("unnamed").to_string();
-                let content_type = field
-                    .content_type()
-                    .unwrap_or("application/octet-stream")
-                    .to_string();
-                let data = field.bytes().await.map_err(|e| {
-                    ApiError::UnprocessableEntity(format!("Failed to read file: {}", e))
-                })?;
+        let messages = self
+            .repository
+            .find_channel_messages_range(channel_id, from_message_id, up_to_message_id)
+            .await?;
 
-                files.push(NewFileAttachment {
-                    file_name,
-                    content_type,
-                    data: data.to_vec(),
-                });
-            }
-            _ => {}
-        }
-    }
+        let files = self
+            .repository
+            .find_channel_files_range(channel_id, from_message_id, up_to_message_id)
+            .await?;
 
-    if message_text.is_none() && files.is_empty() {
-        return Err(ApiError::UnprocessableEntity(
-            "Message must have text or files".to_string(),
-        ));
+        let reactions = self
+            .repository
+            .find_channel_reactions_range(channel_id, from_message_id, up_to_message_id)
+            .await?;
+
+        let reply_counts = self.find_reply_counts(&messages).await?;
+
+        Ok(MessagesResponse {
+            messages,
+            files,
+            reactions,
+            reply_counts,
+        })
     }
 
-    service
-        .create_channel_message(
-            session.user_id,
-            session.session_id,
-            channel_id,
-            message_text,
-            reply_to_message_id,
+    pub async fn get_dm_messages_range(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+        from_message_id: i64,
+        up_to_message_id: i64,
+    ) -> Result<MessagesResponse, DomainError> {
+        let messages = self
+            .repository
+            .find_dm_messages_range(user_id, other_user_id, from_message_id, up_to_message_id)
+            .await?;
+
+        let files = self
+            .repository
+            .find_dm_files_range(user_id, other_user_id, from_message_id, up_to_message_id)
+            .await?;
+
+        let reactions = self
+            .repository
+            .find_dm_reactions_range(user_id, other_user_id, from_message_id, up_to_message_id)
+            .await?;
+
+        let reply_counts = self.find_reply_counts(&messages).await?;
+
+        Ok(MessagesResponse {
+            messages,
             files,
-        )
-        .await
-        .map_err(ApiError::from)?;
+            reactions,
+            reply_counts,
+        })
+    }
 
-    Ok(())
-}
+    pub async fn forward_message(
+        &mut self,
+        user_id: i64,
+        session_id: i64,
+        message_id: i64,
+        target: MessageType,
+    ) -> Result<(Message, Vec<File>), DomainError> {
+        let no_access = || DomainError::PermissionDenied("No access to message".to_string());
 
-#[utoipa::path(
-    post,
-    tag = "message",
-    path = "/dm/{user_id}/messages",
-    description = "Send direct message",
-    params(("user_id" = i64, Path, description = "Recipient user ID")),
-    request_body(content_type = "multipart/form-data"),
-    responses(
-        (status = 201, description = "Created"),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn create_dm_message_handler(
-    State(mut service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(recipient_id): Path<i64>,
-    mut multipart: Multipart,
-) -> Result<(), ApiError> {
-    let mut message_text: Option<String> = None;
-    let mut reply_to_message_id: Option<i64> = None;
-    let mut files: Vec<NewFileAttachment> = Vec::new();
+        let source = self
+            .repository
+            .find_message_by_id(message_id)
+            .await?
+            .ok_or_else(no_access)?;
 
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        ApiError::UnprocessableEntity(format!("Failed to read multipart field: {}", e))
-    })? {
-        let name = field.name().unwrap_or_default().to_string();
+        if let Some(channel_id) = source.channel_id {
+            let rights = self
+                .repository
+                .find_user_channel_rights(channel_id, user_id)
+                .await?
+                .unwrap_or(0);
 
-        match name.as_str() {
-            "messageText" => {
-                message_text = Some(field.text().await.map_err(|e| {
-                    ApiError::UnprocessableEntity(format!("Invalid message text: {}", e))
-                })?);
+            if rights < READ_RIGHTS {
+                return Err(no_access());
             }
-            "replyToMessageId" => {
-                let text = field.text().await.map_err(|e| {
-                    ApiError::UnprocessableEntity(format!("Invalid reply ID: {}", e))
-                })?;
-                reply_to_message_id = text.parse().ok();
+        } else if let Some(conversation_id) = source.conversation_id {
+            self.require_group_member(conversation_id, user_id).await?;
+        } else if source.sender_id != user_id && source.recipient_id != Some(user_id) {
+            return Err(no_access());
+        }
+
+        match target {
+            MessageType::Channel { channel_id } => {
+                let rights = self
+                    .repository
+                    .find_user_channel_rights(channel_id, user_id)
+                    .await?
+                    .ok_or(DomainError::PermissionDenied(
+                        "No access to channel".to_string(),
+                    ))?;
+
+                if rights < WRITE_RIGHTS {
+                    return Err(DomainError::PermissionDenied(
+                        "Insufficient permissions to send messages".to_string(),
+                    ));
+                }
             }
-            "files" => {
-                let file_name = field.file_name().unwrap_or("unnamed").to_string();
-                let content_type = field
-                    .content_type()
-                    .unwrap_or("application/octet-stream")
-                    .to_string();
-                let data = field.bytes().await.map_err(|e| {
-                    ApiError::UnprocessableEntity(format!("Failed to read file: {}", e))
-                })?;
+            MessageType::Direct { recipient_id } => {
+                if self
+                    .repository
+                    .is_blocked_between(user_id, recipient_id)
+                    .await?
+                {
+                    return Err(DomainError::PermissionDenied(
+                        "Cannot message this user".to_string(),
+                    ));
+                }
 
-                files.push(NewFileAttachment {
-                    file_name,
-                    content_type,
-                    data: data.to_vec(),
-                });
+                if !matches!(
+                    self.dm_delivery(user_id, recipient_id).await?,
+                    DmDelivery::Deliver
+                ) {
+                    return Err(DomainError::PermissionDenied(
+                        "Cannot forward until this user accepts your message request".to_string(),
+                    ));
+                }
+
+                if let Err(retry_after) = self.dm_rate_limiter.check(user_id, recipient_id) {
+                    return Err(DomainError::BadRequest(format!(
+                        "Direct message rate limit exceeded, retry in {} seconds",
+                        retry_after.as_secs().max(1)
+                    )));
+                }
+            }
+            MessageType::Group { conversation_id } => {
+                self.require_group_member(conversation_id, user_id).await?;
             }
-            _ => {}
         }
-    }
 
-    if message_text.is_none() && files.is_empty() {
-        return Err(ApiError::UnprocessableEntity(
-            "Message must have text or files".to_string(),
-        ));
-    }
+        let mut db_tx = self.repository.begin().await?;
 
-    service
-        .create_dm_message(
-            session.user_id,
-            session.session_id,
-            recipient_id,
-            message_text,
-            reply_to_message_id,
-            files,
-        )
-        .await
-        .map_err(ApiError::from)?;
+        let message = db_tx
+            .create_forwarded_message(
+                user_id,
+                &target,
+                source.message_text.clone(),
+                source.id,
+                source.expires_at,
+            )
+            .await
+            .map_err(|e| match &e {
+                DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
+                    "channel_id" => DomainError::NotFound("Channel not found".to_string()),
+                    "recipient_id" => DomainError::NotFound("Recipient not found".to_string()),
+                    "conversation_id" => {
+                        DomainError::NotFound("Conversation not found".to_string())
+                    }
+                    "forwarded_from_message_id" => no_access(),
+                    _ => DomainError::InternalError(e),
+                },
+                _ => DomainError::InternalError(e),
+            })?;
 
-    Ok(())
-}
+        let source_files = db_tx.lock_message_files(source.id).await?;
 
-#[utoipa::path(
-    get,
-    tag = "message",
-    path = "/channel/{channel_id}/messages",
-    description = "Get channel messages",
-    params(("channel_id" = i64, Path, description = "Channel ID")),
-    responses(
-        (status = 200, body = MessagesResponse),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn get_channel_messages_handler(
-    State(service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(channel_id): Path<i64>,
-    Query(query): Query<MessageQuery>,
-) -> Result<Json<MessagesResponse>, ApiError> {
-    let user_id = session.user_id;
-    let limit = query.limit.unwrap_or(50);
+        if let MessageType::Channel { channel_id } = target {
+            if !source_files.is_empty() {
+                let incoming_bytes = source_files.iter().map(|f| f.file_size).sum();
+                check_group_storage_quota(&mut db_tx, channel_id, incoming_bytes).await?;
+            }
+        }
 
-    let response = service
-        .get_channel_messages(user_id, channel_id, query.timestamp, limit)
-        .await
-        .map_err(ApiError::from)?;
+        let mut file_attachments = Vec::with_capacity(source_files.len());
+        for file in &source_files {
+            let file_attachment = db_tx
+                .create_file(
+                    message.id,
+                    &file.file_name,
+                    file.file_size,
+                    &file.file_hash,
+                    Some(file.blob_id),
+                    file.metadata.clone(),
+                )
+                .await?;
+            file_attachments.push(file_attachment);
+        }
 
-    Ok(Json(response))
+        self.repository.commit(db_tx).await?;
+
+        let event = EventPayload::MessageCreated {
+            message_id: message.id,
+            sender_id: message.sender_id,
+            message_type: target.clone(),
+            message_text: message.message_text.clone(),
+            reply_to_message_id: message.reply_to_message_id,
+            forwarded_from_message_id: message.forwarded_from_message_id,
+            metadata: message.metadata.clone(),
+            timestamp: message.created_at,
+            expires_at: message.expires_at,
+            files: file_attachments.clone(),
+            muted: false,
+        };
+
+        let policy = match target {
+            MessageType::Channel { channel_id } => ControlRoutingPolicy::ChannelRights {
+                channel_id,
+                minimun_rights: READ_RIGHTS,
+            },
+            MessageType::Direct { recipient_id } => ControlRoutingPolicy::Users {
+                user_ids: if user_id != recipient_id {
+                    vec![user_id, recipient_id]
+                } else {
+                    vec![user_id]
+                },
+            },
+            MessageType::Group { conversation_id } => ControlRoutingPolicy::Users {
+                user_ids: self
+                    .repository
+                    .find_group_conversation_members(conversation_id)
+                    .await?,
+            },
+        };
+
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(event, policy))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Message forwarded: user_id={}, session_id={}, message_id={}, forwarded_from_message_id={}",
+                    user_id, session_id, message.id, message_id
+                ),
+                "message".to_string(),
+            )
+            .await;
+
+        Ok((message, file_attachments))
+    }
+
+    pub async fn edit_message(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        message_id: i64,
+        new_text: String,
+    ) -> Result<Message, DomainError> {
+        let new_text = sanitize_message_text(&new_text, self.max_message_length)?;
+
+        let mut tx = self.repository.begin().await?;
+
+        let message = tx
+            .edit_message(message_id, &new_text, user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Message {} not found or not owned by user",
+                message_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::MessageUpdated {
+            message_id: message.id,
+            message_text: new_text.clone(),
+        };
+
+        if let Some(channel_id) = message.channel_id {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event.clone(),
+                    ControlRoutingPolicy::ChannelRights {
+                        channel_id,
+                        minimun_rights: READ_RIGHTS,
+                    },
+                ))
+                .await;
+        }
+
+        if let Some(conversation_id) = message.conversation_id
+            && let Ok(user_ids) = self
+                .repository
+                .find_group_conversation_members(conversation_id)
+                .await
+        {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event.clone(),
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        }
+
+        if let Some(recipient_id) = message.recipient_id {
+            let user_ids = if user_id != recipient_id {
+                vec![user_id, recipient_id]
+            } else {
+                vec![user_id]
+            };
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        }
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Message edited: user_id={}, session_id={}, message_id={}",
+                    user_id, session_id, message_id
+                ),
+                "message".to_string(),
+            )
+            .await;
+
+        Ok(message)
+    }
+
+    pub async fn delete_message(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        message_id: i64,
+    ) -> Result<Message, DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let files = tx.delete_message_files(message_id).await?;
+        let blobs = tx.find_unreferenced_blobs(&files).await?;
+
+        let message = tx
+            .delete_message(message_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Message {} not found",
+                message_id
+            )))?;
+
+        if message.sender_id != user_id {
+            if let Some(channel_id) = message.channel_id {
+                let mut repo = self.repository.clone();
+                let rights = repo
+                    .find_user_channel_rights(channel_id, user_id)
+                    .await?
+                    .unwrap_or(0);
+
+                if rights < ACL_RIGHTS {
+                    self.repository.rollback(tx).await?;
+                    return Err(DomainError::PermissionDenied(
+                        "Insufficient permissions to delete this message".to_string(),
+                    ));
+                }
+
+                let user_role = repo
+                    .find_user_role(user_id)
+                    .await?
+                    .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+                let sender_role = repo.find_user_role(message.sender_id).await?.ok_or(
+                    DomainError::PermissionDenied("Sender not found".to_string()),
+                )?;
+
+                if sender_role == OWNER_ROLE_ID && user_role > OWNER_ROLE_ID {
+                    self.repository.rollback(tx).await?;
+                    return Err(DomainError::PermissionDenied(
+                        "Only owner can delete owner's messages".to_string(),
+                    ));
+                }
+                if sender_role == ADMIN_ROLE_ID && user_role > ADMIN_ROLE_ID {
+                    self.repository.rollback(tx).await?;
+                    return Err(DomainError::PermissionDenied(
+                        "Only owner or admin can delete admin's messages".to_string(),
+                    ));
+                }
+            } else {
+                self.repository.rollback(tx).await?;
+                return Err(DomainError::PermissionDenied(
+                    "Cannot delete other users' direct messages".to_string(),
+                ));
+            }
+        }
+
+        self.repository.commit(tx).await?;
+
+        for blob_id in blobs {
+            if let Err(e) = self.file_manager.delete_file(blob_id) {
+                warn!("Failed to delete blob {} from storage: {}", blob_id, e);
+            }
+        }
+
+        self.notify_message_deleted(&message).await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Message deleted: user_id={}, session_id={}, message_id={}",
+                    user_id, session_id, message_id
+                ),
+                "message".to_string(),
+            )
+            .await;
+
+        Ok(message)
+    }
+
+    async fn notify_message_deleted(&self, message: &Message) {
+        let event = EventPayload::MessageDeleted {
+            message_id: message.id,
+        };
+
+        if let Some(channel_id) = message.channel_id {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event.clone(),
+                    ControlRoutingPolicy::ChannelRights {
+                        channel_id,
+                        minimun_rights: READ_RIGHTS,
+                    },
+                ))
+                .await;
+        }
+        if let Some(conversation_id) = message.conversation_id
+            && let Ok(user_ids) = self
+                .repository
+                .find_group_conversation_members(conversation_id)
+                .await
+        {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event.clone(),
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        }
+        if let Some(recipient_id) = message.recipient_id {
+            let user_ids = if message.sender_id != recipient_id {
+                vec![message.sender_id, recipient_id]
+            } else {
+                vec![recipient_id]
+            };
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        }
+    }
+
+    pub async fn purge_ephemeral_messages(&self) -> Result<usize, DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let files = tx.delete_ephemeral_files().await?;
+        let blobs = tx.find_unreferenced_blobs(&files).await?;
+        let messages = tx.delete_ephemeral_messages().await?;
+
+        self.repository.commit(tx).await?;
+
+        for blob_id in blobs {
+            if let Err(e) = self.file_manager.delete_file(blob_id) {
+                warn!("Failed to delete blob {} from storage: {}", blob_id, e);
+            }
+        }
+
+        for message in &messages {
+            self.notify_message_deleted(message).await;
+        }
+
+        if !messages.is_empty() {
+            let _ = self
+                .logger
+                .log_entry(
+                    format!(
+                        "Expired ephemeral messages purged: count={}",
+                        messages.len()
+                    ),
+                    "message".to_string(),
+                )
+                .await;
+        }
+
+        Ok(messages.len())
+    }
+
+    pub async fn purge_expired_messages(&self) -> Result<usize, DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let files = tx.delete_expired_channel_files().await?;
+        let blobs = tx.find_unreferenced_blobs(&files).await?;
+        let messages = tx.delete_expired_channel_messages().await?;
+
+        self.repository.commit(tx).await?;
+
+        for blob_id in blobs {
+            if let Err(e) = self.file_manager.delete_file(blob_id) {
+                warn!("Failed to delete blob {} from storage: {}", blob_id, e);
+            }
+        }
+
+        let mut purged: HashMap<i64, Vec<i64>> = HashMap::new();
+        for message in &messages {
+            if let Some(channel_id) = message.channel_id {
+                purged.entry(channel_id).or_default().push(message.id);
+            }
+        }
+
+        for (channel_id, message_ids) in purged {
+            let count = message_ids.len();
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    EventPayload::MessagesPurged {
+                        channel_id,
+                        message_ids,
+                    },
+                    ControlRoutingPolicy::ChannelRights {
+                        channel_id,
+                        minimun_rights: READ_RIGHTS,
+                    },
+                ))
+                .await;
+
+            let _ = self
+                .logger
+                .log_entry(
+                    format!(
+                        "Messages purged by retention: channel_id={}, count={}",
+                        channel_id, count
+                    ),
+                    "message".to_string(),
+                )
+                .await;
+        }
+
+        Ok(messages.len())
+    }
+
+    pub async fn run_retention_purge(self) {
+        let mut purge_interval = tokio::time::interval(RETENTION_PURGE_INTERVAL);
+        let mut ephemeral_interval = tokio::time::interval(EPHEMERAL_PURGE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = purge_interval.tick() => {
+                    if let Err(e) = self.purge_expired_messages().await {
+                        warn!("Retention purge failed: {}", e);
+                    }
+                }
+                _ = ephemeral_interval.tick() => {
+                    if let Err(e) = self.purge_ephemeral_messages().await {
+                        warn!("Ephemeral message purge failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn get_file(
+        &self,
+        user_id: i64,
+        file_id: i64,
+    ) -> Result<(FileAttachment, Vec<u8>), DomainError> {
+        let file =
+            self.repository
+                .find_file_by_id(file_id)
+                .await?
+                .ok_or(DomainError::NotFound(format!(
+                    "File {} not found",
+                    file_id
+                )))?;
+
+        let message = self
+            .repository
+            .find_message_by_id(file.message_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Message {} not found",
+                file.message_id
+            )))?;
+
+        if let Some(channel_id) = message.channel_id {
+            let mut repo = self.repository.clone();
+            let rights = repo
+                .find_user_channel_rights(channel_id, user_id)
+                .await?
+                .ok_or(DomainError::PermissionDenied(
+                    "No access to channel".to_string(),
+                ))?;
+
+            if rights < READ_RIGHTS {
+                return Err(DomainError::PermissionDenied(
+                    "Insufficient permissions to access files".to_string(),
+                ));
+            }
+        } else if let Some(recipient_id) = message.recipient_id {
+            if message.sender_id != user_id && recipient_id != user_id {
+                return Err(DomainError::PermissionDenied(
+                    "No access to this direct message".to_string(),
+                ));
+            }
+        } else if let Some(conversation_id) = message.conversation_id {
+            self.require_group_member(conversation_id, user_id).await?;
+        } else {
+            return Err(DomainError::PermissionDenied(
+                "Invalid message type".to_string(),
+            ));
+        }
+
+        let raw_data = self.file_manager.get_file(file.blob_id).map_err(|e| match e {
+            FileError::NotFound(_) => {
+                DomainError::NotFound(format!("File {} not found", file_id))
+            }
+            _ => DomainError::FileManagerError(e),
+        })?;
+
+        Ok((file, raw_data))
+    }
+
+    pub async fn add_reaction(
+        &mut self,
+        user_id: i64,
+        session_id: i64,
+        message_id: i64,
+        emoji: String,
+    ) -> Result<Reaction, DomainError> {
+        self.validate_emoji(&emoji).await?;
+
+        let message = self
+            .repository
+            .find_message_by_id(message_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Message {} not found",
+                message_id
+            )))?;
+
+        if let Some(channel_id) = message.channel_id {
+            let rights = self
+                .repository
+                .find_user_channel_rights(channel_id, user_id)
+                .await?
+                .ok_or(DomainError::PermissionDenied(
+                    "No access to channel".to_string(),
+                ))?;
+
+            if rights < WRITE_RIGHTS {
+                return Err(DomainError::PermissionDenied(
+                    "Insufficient permissions to react".to_string(),
+                ));
+            }
+        } else if let Some(recipient_id) = message.recipient_id {
+            if message.sender_id != user_id && recipient_id != user_id {
+                return Err(DomainError::PermissionDenied(
+                    "No access to this direct message".to_string(),
+                ));
+            }
+        } else if let Some(conversation_id) = message.conversation_id {
+            self.require_group_member(conversation_id, user_id).await?;
+        }
+
+        self.check_reaction_limits(message_id, user_id, &emoji)
+            .await?;
+
+        let mut tx = self.repository.begin().await?;
+        let reaction = tx.create_reaction(message_id, user_id, &emoji).await?;
+        self.repository.commit(tx).await?;
+
+        let message_type = if let Some(channel_id) = message.channel_id {
+            MessageType::Channel { channel_id }
+        } else if let Some(conversation_id) = message.conversation_id {
+            MessageType::Group { conversation_id }
+        } else {
+            MessageType::Direct {
+                recipient_id: message.recipient_id.unwrap(),
+            }
+        };
+
+        let event = EventPayload::ReactionAdded {
+            reaction: reaction.clone(),
+            message_type: message_type.clone(),
+        };
+
+        if let Some(channel_id) = message.channel_id {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::ChannelRights {
+                        channel_id,
+                        minimun_rights: READ_RIGHTS,
+                    },
+                ))
+                .await;
+        } else if let Some(conversation_id) = message.conversation_id
+            && let Ok(user_ids) = self
+                .repository
+                .find_group_conversation_members(conversation_id)
+                .await
+        {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        } else if let Some(recipient_id) = message.recipient_id {
+            let user_ids = if message.sender_id != recipient_id {
+                vec![message.sender_id, recipient_id]
+            } else {
+                vec![message.sender_id]
+            };
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        }
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Reaction added: user_id={}, session_id={}, message_id={}, emoji={}",
+                    user_id, session_id, message_id, emoji
+                ),
+                "message".to_string(),
+            )
+            .await;
+
+        Ok(reaction)
+    }
+
+    pub async fn remove_reaction(
+        &mut self,
+        user_id: i64,
+        session_id: i64,
+        message_id: i64,
+        emoji: String,
+    ) -> Result<(), DomainError> {
+        self.validate_emoji(&emoji).await?;
+
+        let message = self
+            .repository
+            .find_message_by_id(message_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Message {} not found",
+                message_id
+            )))?;
+
+        let mut tx = self.repository.begin().await?;
+        let deleted = tx.delete_reaction(message_id, user_id, &emoji).await?;
+
+        if deleted.is_none() {
+            self.repository.rollback(tx).await?;
+            return Err(DomainError::NotFound("Reaction not found".to_string()));
+        }
+
+        self.repository.commit(tx).await?;
+
+        let message_type = if let Some(channel_id) = message.channel_id {
+            MessageType::Channel { channel_id }
+        } else if let Some(conversation_id) = message.conversation_id {
+            MessageType::Group { conversation_id }
+        } else {
+            MessageType::Direct {
+                recipient_id: message.recipient_id.unwrap(),
+            }
+        };
+
+        let event = EventPayload::ReactionRemoved {
+            message_id,
+            user_id,
+            emoji: emoji.clone(),
+            message_type,
+        };
+
+        if let Some(channel_id) = message.channel_id {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::ChannelRights {
+                        channel_id,
+                        minimun_rights: READ_RIGHTS,
+                    },
+                ))
+                .await;
+        } else if let Some(conversation_id) = message.conversation_id
+            && let Ok(user_ids) = self
+                .repository
+                .find_group_conversation_members(conversation_id)
+                .await
+        {
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        } else if let Some(recipient_id) = message.recipient_id {
+            let user_ids = if message.sender_id != recipient_id {
+                vec![message.sender_id, recipient_id]
+            } else {
+                vec![message.sender_id]
+            };
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::Users { user_ids },
+                ))
+                .await;
+        }
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Reaction removed: user_id={}, session_id={}, message_id={}, emoji={}",
+                    user_id, session_id, message_id, emoji
+                ),
+                "message".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+}
+
+fn sanitize_message_text(text: &str, max_length: usize) -> Result<String, DomainError> {
+    if text.contains('\0') {
+        return Err(DomainError::BadRequest(
+            "Message must not contain null bytes".to_string(),
+        ));
+    }
+
+    let sanitized = text.replace("\r\n", "\n").replace('\r', "\n");
+    let sanitized = sanitized.trim_end();
+
+    if sanitized.chars().count() > max_length {
+        return Err(DomainError::BadRequest(format!(
+            "Message exceeds {} character limit",
+            max_length
+        )));
+    }
+
+    Ok(sanitized.to_string())
+}
+
+const MAX_MENTIONS_PER_MESSAGE: usize = 20;
+
+fn extract_mentions(text: &str) -> Vec<String> {
+    let is_username_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '-');
+    let mut mentions: Vec<String> = Vec::new();
+    let mut prev: Option<char> = None;
+
+    for (i, c) in text.char_indices() {
+        if c == '@' && !prev.is_some_and(is_username_char) {
+            let rest = &text[i + 1..];
+            let end = rest.find(|c: char| !is_username_char(c)).unwrap_or(rest.len());
+            let name = rest[..end].trim_end_matches(['.', '-']);
+            if !name.is_empty() && !mentions.iter().any(|m| m == name) {
+                mentions.push(name.to_string());
+                if mentions.len() == MAX_MENTIONS_PER_MESSAGE {
+                    break;
+                }
+            }
+        }
+        prev = Some(c);
+    }
+
+    mentions
+}
+
+fn message_expiry(ttl_secs: Option<u64>) -> Result<Option<OffsetDateTime>, DomainError> {
+    let Some(ttl_secs) = ttl_secs else {
+        return Ok(None);
+    };
+
+    let ttl = Duration::from_secs(ttl_secs);
+    if ttl.is_zero() || ttl > MAX_MESSAGE_TTL {
+        return Err(DomainError::BadRequest(format!(
+            "Message TTL must be between 1 and {} seconds",
+            MAX_MESSAGE_TTL.as_secs()
+        )));
+    }
+
+    Ok(Some(OffsetDateTime::now_utc() + ttl))
+}
+
+/// Trims a page fetched with one extra row back to `limit`, returning the
+/// `(created_at, id)` cursor of the last message kept when another page follows.
+fn split_page(messages: &mut Vec<Message>, limit: i64) -> Option<(OffsetDateTime, i64)> {
+    let has_more = messages.len() as i64 > limit;
+    messages.truncate(limit as usize);
+    messages
+        .last()
+        .filter(|_| has_more)
+        .map(|m| (m.created_at, m.id))
+}
+
+fn validate_metadata(metadata: Option<&serde_json::Value>) -> Result<(), DomainError> {
+    let Some(metadata) = metadata else {
+        return Ok(());
+    };
+
+    if !metadata.is_object() {
+        return Err(DomainError::BadRequest(
+            "Message metadata must be a JSON object".to_string(),
+        ));
+    }
+
+    if metadata.to_string().len() > MAX_MESSAGE_METADATA_BYTES {
+        return Err(DomainError::BadRequest(format!(
+            "Message metadata exceeds {} bytes",
+            MAX_MESSAGE_METADATA_BYTES
+        )));
+    }
+
+    Ok(())
+}
+
+/// Locks the channel's group row so concurrent uploads can't both fit under
+/// the quota, then rejects `incoming_bytes` if they would push usage past it.
+async fn check_group_storage_quota<T: MessageTransaction>(
+    tx: &mut T,
+    channel_id: i64,
+    incoming_bytes: i64,
+) -> Result<(), DomainError> {
+    let Some(storage) = tx.lock_channel_group_storage(channel_id).await? else {
+        return Ok(());
+    };
+    let Some(quota_bytes) = storage.quota_bytes else {
+        return Ok(());
+    };
+
+    if storage.used_bytes + incoming_bytes > quota_bytes {
+        return Err(DomainError::BadRequest(format!(
+            "Group storage quota exceeded: {} of {} bytes used",
+            storage.used_bytes, quota_bytes
+        )));
+    }
+
+    Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// REQUEST/RESPONSE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesResponse {
+    pub messages: Vec<Message>,
+    pub files: Vec<File>,
+    pub reactions: Vec<Reaction>,
+    pub reply_counts: Vec<ReplyCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageResponse {
+    pub message: Message,
+    pub files: Vec<File>,
+    pub reactions: Vec<Reaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EditMessageRequest {
+    pub message_text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookMessageRequest {
+    pub message_text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ForwardMessageRequest {
+    pub target: MessageType,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGroupConversationRequest {
+    pub member_ids: Vec<i64>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AddReactionRequest {
+    pub emoji: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageQuery {
+    pub limit: Option<i64>,
+    #[serde(with = "time::serde::iso8601")]
+    pub timestamp: OffsetDateTime,
+    pub before_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageRangeQuery {
+    pub from_message_id: i64,
+    pub up_to_message_id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MessageMetadataQuery {
+    pub key: String,
+    pub value: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadQuery {
+    pub limit: Option<i64>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ROUTES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+type AppMessageService = MessageService<
+    Postgre,
+    EncryptedFileManager<LocalFileManager>,
+    DefaultNotifierManager,
+    TextLogManager,
+    DefaultScanManager,
+>;
+
+pub fn message_routes(
+    message_service: AppMessageService,
+    authorize_service: AuthorizeService<Postgre>,
+) -> OpenApiRouter<Postgre> {
+    OpenApiRouter::new()
+        .routes(routes!(create_channel_message_handler))
+        .routes(routes!(create_dm_message_handler))
+        .routes(routes!(get_channel_messages_handler))
+        .routes(routes!(get_dm_messages_handler))
+        .routes(routes!(get_feed_handler))
+        .routes(routes!(
+            get_group_conversations_handler,
+            create_group_conversation_handler
+        ))
+        .routes(routes!(create_group_message_handler))
+        .routes(routes!(get_dm_requests_handler))
+        .routes(routes!(accept_dm_request_handler))
+        .routes(routes!(decline_dm_request_handler))
+        .routes(routes!(get_group_messages_handler))
+        .routes(routes!(get_channel_messages_range_handler))
+        .routes(routes!(get_dm_messages_range_handler))
+        .routes(routes!(get_channel_messages_by_metadata_handler))
+        .routes(routes!(add_reaction_handler))
+        .routes(routes!(remove_reaction_handler))
+        .routes(routes!(get_message_handler))
+        .routes(routes!(get_thread_handler))
+        .routes(routes!(edit_message_handler))
+        .routes(routes!(forward_message_handler))
+        .routes(routes!(delete_message_handler))
+        .routes(routes!(get_file_handler))
+        .layer(from_fn_with_state(authorize_service, authorize))
+        .routes(routes!(create_webhook_message_handler))
+        .with_state(message_service)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HANDLERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Caps how many parts and bytes a message upload may carry, checked while the
+/// body is streamed so an oversized request is rejected before it is buffered.
+struct MultipartBudget {
+    parts: usize,
+    bytes: usize,
+    max_parts: usize,
+    max_bytes: usize,
+}
+
+impl MultipartBudget {
+    fn next_part(&mut self) -> Result<(), ApiError> {
+        self.parts += 1;
+        if self.parts > self.max_parts {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Too many multipart fields (max {})",
+                self.max_parts
+            )));
+        }
+        Ok(())
+    }
+
+    fn consume(&mut self, len: usize) -> Result<(), ApiError> {
+        self.bytes += len;
+        if self.bytes > self.max_bytes {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Request body exceeds {} bytes",
+                self.max_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    async fn read_text(&mut self, field: Field<'_>, what: &str) -> Result<String, ApiError> {
+        let text = field
+            .text()
+            .await
+            .map_err(|e| ApiError::UnprocessableEntity(format!("Invalid {}: {}", what, e)))?;
+        self.consume(text.len())?;
+        Ok(text)
+    }
+
+    async fn read_bytes(&mut self, mut field: Field<'_>) -> Result<Vec<u8>, ApiError> {
+        let mut data = Vec::new();
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|e| ApiError::UnprocessableEntity(format!("Failed to read file: {}", e)))?
+        {
+            self.consume(chunk.len())?;
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+}
+
+fn idempotency_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("Idempotency-Key")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+}
+
+fn posted_response(posted: PostedMessage) -> (StatusCode, Json<MessageResponse>) {
+    let (status, message, files) = match posted {
+        PostedMessage::Created(message, files) => (StatusCode::CREATED, message, files),
+        PostedMessage::Replayed(message, files) => (StatusCode::OK, message, files),
+    };
+    let response = MessageResponse {
+        message,
+        files,
+        reactions: Vec::new(),
+    };
+    (status, Json(response))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/channel/{channel_id}/messages",
+    description = "Send message to channel",
+    params(
+        ("channel_id" = i64, Path, description = "Channel ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Client key used to deduplicate retries"),
+    ),
+    request_body(content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Created", body = MessageResponse),
+        (status = 200, description = "Replayed an earlier request with the same Idempotency-Key", body = MessageResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn create_channel_message_handler(
+    State(mut service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(channel_id): Path<i64>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<MessageResponse>), ApiError> {
+    let mut message_text: Option<String> = None;
+    let mut reply_to_message_id: Option<i64> = None;
+    let mut metadata: Option<serde_json::Value> = None;
+    let mut ttl_secs: Option<u64> = None;
+    let mut files: Vec<NewFileAttachment> = Vec::new();
+    let mut budget = service.multipart_budget();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        ApiError::UnprocessableEntity(format!("Failed to read multipart field: {}", e))
+    })? {
+        budget.next_part()?;
+        let name = field.name().unwrap_or_default().to_string();
+
+        match name.as_str() {
+            "messageText" => {
+                message_text = Some(budget.read_text(field, "message text").await?);
+            }
+            "replyToMessageId" => {
+                let text = budget.read_text(field, "reply ID").await?;
+                reply_to_message_id = text.parse().ok();
+            }
+            "metadata" => {
+                let text = budget.read_text(field, "metadata").await?;
+                metadata = Some(serde_json::from_str(&text).map_err(|e| {
+                    ApiError::UnprocessableEntity(format!("Invalid metadata: {}", e))
+                })?);
+            }
+            "ttlSeconds" => {
+                let text = budget.read_text(field, "TTL").await?;
+                ttl_secs = Some(
+                    text.trim()
+                        .parse()
+                        .map_err(|_| ApiError::UnprocessableEntity("Invalid TTL".to_string()))?,
+                );
+            }
+            "files" => {
+                let file_name = field.file_name().unwrap_or("unnamed").to_string();
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = budget.read_bytes(field).await?;
+
+                files.push(NewFileAttachment {
+                    file_name,
+                    content_type,
+                    data,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if message_text.is_none() && files.is_empty() {
+        return Err(ApiError::UnprocessableEntity(
+            "Message must have text or files".to_string(),
+        ));
+    }
+
+    let posted = service
+        .create_channel_message(
+            session.user_id,
+            session.session_id,
+            channel_id,
+            message_text,
+            reply_to_message_id,
+            metadata,
+            ttl_secs,
+            files,
+            idempotency_key(&headers),
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(posted_response(posted))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/webhooks/{token}",
+    description = "Post a message to a channel as the bot user bound to a webhook token",
+    params(
+        ("token" = String, Path, description = "Webhook token"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Client key used to deduplicate retries"),
+    ),
+    request_body = WebhookMessageRequest,
+    responses(
+        (status = 201, description = "Created", body = MessageResponse),
+        (status = 200, description = "Replayed an earlier request with the same Idempotency-Key", body = MessageResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    )
+)]
+async fn create_webhook_message_handler(
+    State(mut service): State<AppMessageService>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<WebhookMessageRequest>,
+) -> Result<(StatusCode, Json<MessageResponse>), ApiError> {
+    let posted = service
+        .create_webhook_message(&token, payload.message_text, idempotency_key(&headers))
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(posted_response(posted))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/dm/{user_id}/messages",
+    description = "Send direct message",
+    params(
+        ("user_id" = i64, Path, description = "Recipient user ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Client key used to deduplicate retries"),
+    ),
+    request_body(content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Created", body = MessageResponse),
+        (status = 200, description = "Replayed an earlier request with the same Idempotency-Key", body = MessageResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn create_dm_message_handler(
+    State(mut service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(recipient_id): Path<i64>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<MessageResponse>), ApiError> {
+    let mut message_text: Option<String> = None;
+    let mut reply_to_message_id: Option<i64> = None;
+    let mut metadata: Option<serde_json::Value> = None;
+    let mut ttl_secs: Option<u64> = None;
+    let mut files: Vec<NewFileAttachment> = Vec::new();
+    let mut budget = service.multipart_budget();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        ApiError::UnprocessableEntity(format!("Failed to read multipart field: {}", e))
+    })? {
+        budget.next_part()?;
+        let name = field.name().unwrap_or_default().to_string();
+
+        match name.as_str() {
+            "messageText" => {
+                message_text = Some(budget.read_text(field, "message text").await?);
+            }
+            "replyToMessageId" => {
+                let text = budget.read_text(field, "reply ID").await?;
+                reply_to_message_id = text.parse().ok();
+            }
+            "metadata" => {
+                let text = budget.read_text(field, "metadata").await?;
+                metadata = Some(serde_json::from_str(&text).map_err(|e| {
+                    ApiError::UnprocessableEntity(format!("Invalid metadata: {}", e))
+                })?);
+            }
+            "ttlSeconds" => {
+                let text = budget.read_text(field, "TTL").await?;
+                ttl_secs = Some(
+                    text.trim()
+                        .parse()
+                        .map_err(|_| ApiError::UnprocessableEntity("Invalid TTL".to_string()))?,
+                );
+            }
+            "files" => {
+                let file_name = field.file_name().unwrap_or("unnamed").to_string();
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = budget.read_bytes(field).await?;
+
+                files.push(NewFileAttachment {
+                    file_name,
+                    content_type,
+                    data,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if message_text.is_none() && files.is_empty() {
+        return Err(ApiError::UnprocessableEntity(
+            "Message must have text or files".to_string(),
+        ));
+    }
+
+    let posted = service
+        .create_dm_message(
+            session.user_id,
+            session.session_id,
+            recipient_id,
+            message_text,
+            reply_to_message_id,
+            metadata,
+            ttl_secs,
+            files,
+            idempotency_key(&headers),
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(posted_response(posted))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/group-dm",
+    description = "Create a group conversation",
+    request_body = CreateGroupConversationRequest,
+    responses(
+        (status = 200, body = GroupConversation),
+        (status = 403, body = ApiError),
+        (status = 404, body = ApiError),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn create_group_conversation_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<CreateGroupConversationRequest>,
+) -> Result<Json<GroupConversation>, ApiError> {
+    let conversation = service
+        .create_group_conversation(
+            session.user_id,
+            session.session_id,
+            payload.member_ids,
+            payload.name,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(conversation))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/dm-requests",
+    description = "Get pending message requests sent to the current user",
+    responses(
+        (status = 200, body = Vec<DmRequest>),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_dm_requests_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<DmRequest>>, ApiError> {
+    let requests = service
+        .get_dm_requests(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(requests))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/dm-requests/{sender_id}/accept",
+    description = "Accept a message request so the sender's DMs are delivered",
+    params(
+        ("sender_id", Path, description = "User who sent the request"),
+    ),
+    responses(
+        (status = 200, body = DmRequest),
+        (status = 404, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn accept_dm_request_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(sender_id): Path<i64>,
+) -> Result<Json<DmRequest>, ApiError> {
+    let request = service
+        .respond_to_dm_request(session.user_id, session.session_id, sender_id, true)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(request))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/dm-requests/{sender_id}/decline",
+    description = "Decline a message request; the sender can no longer message the current user",
+    params(
+        ("sender_id", Path, description = "User who sent the request"),
+    ),
+    responses(
+        (status = 200, body = DmRequest),
+        (status = 404, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn decline_dm_request_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(sender_id): Path<i64>,
+) -> Result<Json<DmRequest>, ApiError> {
+    let request = service
+        .respond_to_dm_request(session.user_id, session.session_id, sender_id, false)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(request))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/group-dm",
+    description = "Get the current user's group conversations",
+    responses(
+        (status = 200, body = Vec<GroupConversation>),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_group_conversations_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<GroupConversation>>, ApiError> {
+    let conversations = service
+        .get_group_conversations(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(conversations))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/group-dm/{conversation_id}/messages",
+    description = "Send group conversation message",
+    params(
+        ("conversation_id" = i64, Path, description = "Conversation ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Client key used to deduplicate retries"),
+    ),
+    request_body(content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Created", body = MessageResponse),
+        (status = 200, description = "Replayed an earlier request with the same Idempotency-Key", body = MessageResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn create_group_message_handler(
+    State(mut service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(conversation_id): Path<i64>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<MessageResponse>), ApiError> {
+    let mut message_text: Option<String> = None;
+    let mut reply_to_message_id: Option<i64> = None;
+    let mut metadata: Option<serde_json::Value> = None;
+    let mut ttl_secs: Option<u64> = None;
+    let mut files: Vec<NewFileAttachment> = Vec::new();
+    let mut budget = service.multipart_budget();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        ApiError::UnprocessableEntity(format!("Failed to read multipart field: {}", e))
+    })? {
+        budget.next_part()?;
+        let name = field.name().unwrap_or_default().to_string();
+
+        match name.as_str() {
+            "messageText" => {
+                message_text = Some(budget.read_text(field, "message text").await?);
+            }
+            "replyToMessageId" => {
+                let text = budget.read_text(field, "reply ID").await?;
+                reply_to_message_id = text.parse().ok();
+            }
+            "metadata" => {
+                let text = budget.read_text(field, "metadata").await?;
+                metadata = Some(serde_json::from_str(&text).map_err(|e| {
+                    ApiError::UnprocessableEntity(format!("Invalid metadata: {}", e))
+                })?);
+            }
+            "ttlSeconds" => {
+                let text = budget.read_text(field, "TTL").await?;
+                ttl_secs = Some(
+                    text.trim()
+                        .parse()
+                        .map_err(|_| ApiError::UnprocessableEntity("Invalid TTL".to_string()))?,
+                );
+            }
+            "files" => {
+                let file_name = field.file_name().unwrap_or("unnamed").to_string();
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = budget.read_bytes(field).await?;
+
+                files.push(NewFileAttachment {
+                    file_name,
+                    content_type,
+                    data,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if message_text.is_none() && files.is_empty() {
+        return Err(ApiError::UnprocessableEntity(
+            "Message must have text or files".to_string(),
+        ));
+    }
+
+    let posted = service
+        .create_group_message(
+            session.user_id,
+            session.session_id,
+            conversation_id,
+            message_text,
+            reply_to_message_id,
+            metadata,
+            ttl_secs,
+            files,
+            idempotency_key(&headers),
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(posted_response(posted))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/channel/{channel_id}/messages",
+    description = "Get channel messages",
+    params(("channel_id" = i64, Path, description = "Channel ID")),
+    responses(
+        (status = 200, body = Paginated<MessagesResponse>),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_channel_messages_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(channel_id): Path<i64>,
+    Query(query): Query<MessageQuery>,
+) -> Result<Json<Paginated<MessagesResponse>>, ApiError> {
+    let user_id = session.user_id;
+    let limit = query.limit.unwrap_or(50);
+
+    let response = service
+        .get_channel_messages(user_id, channel_id, query.timestamp, query.before_id, limit)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/channel/{channel_id}/messages/metadata",
+    description = "Find channel messages tagged with a metadata key, optionally matching a value",
+    params(
+        ("channel_id" = i64, Path, description = "Channel ID"),
+        ("key" = String, Query, description = "Metadata key"),
+        ("value" = Option<String>, Query, description = "Metadata value to match"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of messages"),
+    ),
+    responses(
+        (status = 200, body = Vec<Message>),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_channel_messages_by_metadata_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(channel_id): Path<i64>,
+    Query(query): Query<MessageMetadataQuery>,
+) -> Result<Json<Vec<Message>>, ApiError> {
+    let messages = service
+        .get_channel_messages_by_metadata(
+            session.user_id,
+            channel_id,
+            &query.key,
+            query.value.as_deref(),
+            query.limit.unwrap_or(50),
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(messages))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/dm/{user_id}/messages",
+    description = "Get direct messages",
+    params(("user_id" = i64, Path, description = "Other user ID")),
+    responses(
+        (status = 200, body = Paginated<MessagesResponse>),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_dm_messages_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(other_user_id): Path<i64>,
+    Query(query): Query<MessageQuery>,
+) -> Result<Json<Paginated<MessagesResponse>>, ApiError> {
+    let user_id = session.user_id;
+    let limit = query.limit.unwrap_or(50);
+
+    let response = service
+        .get_dm_messages(
+            user_id,
+            other_user_id,
+            query.timestamp,
+            query.before_id,
+            limit,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/feed",
+    description = "Get messages from every readable channel, direct and group conversation, newest first",
+    responses(
+        (status = 200, body = Paginated<MessagesResponse>),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_feed_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Query(query): Query<MessageQuery>,
+) -> Result<Json<Paginated<MessagesResponse>>, ApiError> {
+    let limit = query.limit.unwrap_or(50);
+
+    let response = service
+        .get_feed(session.user_id, query.timestamp, query.before_id, limit)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/group-dm/{conversation_id}/messages",
+    description = "Get group conversation messages",
+    params(("conversation_id" = i64, Path, description = "Conversation ID")),
+    responses(
+        (status = 200, body = Paginated<MessagesResponse>),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_group_messages_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(conversation_id): Path<i64>,
+    Query(query): Query<MessageQuery>,
+) -> Result<Json<Paginated<MessagesResponse>>, ApiError> {
+    let user_id = session.user_id;
+    let limit = query.limit.unwrap_or(50);
+
+    let response = service
+        .get_group_messages(
+            user_id,
+            conversation_id,
+            query.timestamp,
+            query.before_id,
+            limit,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/channel/{channel_id}/messages/range",
+    description = "Get channel messages in range",
+    params(("channel_id" = i64, Path, description = "Channel ID")),
+    responses(
+        (status = 200, body = MessagesResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_channel_messages_range_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(channel_id): Path<i64>,
+    Query(query): Query<MessageRangeQuery>,
+) -> Result<Json<MessagesResponse>, ApiError> {
+    let response = service
+        .get_channel_messages_range(
+            session.user_id,
+            channel_id,
+            query.from_message_id,
+            query.up_to_message_id,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/dm/{user_id}/messages/range",
+    description = "Get direct messages in range",
+    params(("user_id" = i64, Path, description = "Other user ID")),
+    responses(
+        (status = 200, body = MessagesResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_dm_messages_range_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(other_user_id): Path<i64>,
+    Query(query): Query<MessageRangeQuery>,
+) -> Result<Json<MessagesResponse>, ApiError> {
+    let response = service
+        .get_dm_messages_range(
+            session.user_id,
+            other_user_id,
+            query.from_message_id,
+            query.up_to_message_id,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/{message_id}",
+    description = "Get a single message with its files and reactions",
+    params(("message_id" = i64, Path, description = "Message ID")),
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_message_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(message_id): Path<i64>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let response = service
+        .get_message(session.user_id, message_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/{message_id}/thread",
+    description = "Get the replies in the thread rooted at a message, oldest first",
+    params(
+        ("message_id" = i64, Path, description = "Root message ID"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of replies"),
+    ),
+    responses(
+        (status = 200, body = MessagesResponse),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_thread_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(message_id): Path<i64>,
+    Query(query): Query<ThreadQuery>,
+) -> Result<Json<MessagesResponse>, ApiError> {
+    let response = service
+        .get_thread(session.user_id, message_id, query.limit.unwrap_or(50))
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    put,
+    tag = "message",
+    path = "/{message_id}",
+    description = "Edit message",
+    params(("message_id" = i64, Path, description = "Message ID")),
+    request_body = EditMessageRequest,
+    responses(
+        (status = 204, description = "Updated"),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn edit_message_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(message_id): Path<i64>,
+    Json(payload): Json<EditMessageRequest>,
+) -> Result<(), ApiError> {
+    service
+        .edit_message(
+            session.user_id,
+            session.session_id,
+            message_id,
+            payload.message_text,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/{message_id}/forward",
+    description = "Forward a message to a channel or direct conversation",
+    params(("message_id" = i64, Path, description = "Message ID")),
+    request_body = ForwardMessageRequest,
+    responses(
+        (status = 201, description = "Created"),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn forward_message_handler(
+    State(mut service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(message_id): Path<i64>,
+    Json(payload): Json<ForwardMessageRequest>,
+) -> Result<(), ApiError> {
+    service
+        .forward_message(
+            session.user_id,
+            session.session_id,
+            message_id,
+            payload.target,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    delete,
+    tag = "message",
+    path = "/{message_id}",
+    description = "Delete message",
+    params(("message_id" = i64, Path, description = "Message ID")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn delete_message_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(message_id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .delete_message(session.user_id, session.session_id, message_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "message",
+    path = "/files/{file_id}",
+    description = "Download file",
+    params(("file_id" = i64, Path, description = "File ID")),
+    responses(
+        (status = 200, description = "File content"),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_file_handler(
+    State(service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(file_id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user_id = session.user_id;
+    let file = service
+        .get_file(user_id, file_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(file.0.metadata.mime())
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("inline; filename=\"{}\"", file.0.file_name))
+            .unwrap_or_else(|_| HeaderValue::from_static("inline")),
+    );
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=3600"),
+    );
+
+    Ok((headers, file.1))
+}
+
+#[utoipa::path(
+    post,
+    tag = "message",
+    path = "/{message_id}/reactions",
+    description = "Add reaction",
+    params(("message_id" = i64, Path, description = "Message ID")),
+    request_body = AddReactionRequest,
+    responses(
+        (status = 201, description = "Created"),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn add_reaction_handler(
+    State(mut service): State<AppMessageService>,
+    Extension(session): Extension<Session>,
+    Path(message_id): Path<i64>,
+    Json(payload): Json<AddReactionRequest>,
+) -> Result<(), ApiError> {
+    service
+        .add_reaction(
+            session.user_id,
+            session.session_id,
+            message_id,
+            payload.emoji,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(())
 }
 
 #[utoipa::path(
-    get,
+    delete,
     tag = "message",
-    path = "/dm/{user_id}/messages",
-    description = "Get direct messages",
-    params(("user_id" = i64, Path, description = "Other user ID")),
+    path = "/{message_id}/reactions/{emoji}",
+    description = "Remove reaction",
+    params(
+        ("message_id" = i64, Path, description = "Message ID"),
+        ("emoji" = String, Path, description = "Emoji")
+    ),
     responses(
-        (status = 200, body = MessagesResponse),
+        (status = 204, description = "Deleted"),
         (status = 422, body = ApiError),
         (status = 500, body = ApiError),
     ),
     security(("api_key" = []))
 )]
-async fn get_dm_messages_handler(
-    State(service): State<AppMessageService>,
+async fn remove_reaction_handler(
+    State(mut service): State<AppMessageService>,
     Extension(session): Extension<Session>,
-    Path(other_user_id): Path<i64>,
-    Query(query): Query<MessageQuery>,
-) -> Result<Json<MessagesResponse>, ApiError> {
-    let user_id = session.user_id;
-    let limit = query.limit.unwrap_or(50);
+    Path((message_id, emoji)): Path<(i64, String)>,
+) -> Result<(), ApiError> {
+    service
+        .remove_reaction(session.user_id, session.session_id, message_id, emoji)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acl::ACKNOWLEDGE_RIGHTS;
+    use crate::role::DEFAULT_ROLE_ID;
+    use sqlx::PgPool;
+
+    type TestMessageService = MessageService<
+        Postgre,
+        LocalFileManager,
+        DefaultNotifierManager,
+        TextLogManager,
+        DefaultScanManager,
+    >;
+
+    async fn create_user(pool: &PgPool, username: &str) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO users (username) VALUES ($1) RETURNING user_id",
+            username
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn create_channel(pool: &PgPool, channel_name: &str) -> i64 {
+        sqlx::query_scalar!(
+            r#"WITH new_group AS (
+                   INSERT INTO groups (group_name) VALUES ($1) RETURNING group_id
+               )
+               INSERT INTO channels (channel_name, group_id, channel_type)
+               SELECT $1, group_id, 'Text' FROM new_group
+               RETURNING channel_id"#,
+            channel_name
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    fn message(id: i64) -> Message {
+        Message {
+            id,
+            sender_id: 1,
+            channel_id: None,
+            recipient_id: None,
+            conversation_id: None,
+            message_text: None,
+            created_at: OffsetDateTime::now_utc(),
+            modified_at: None,
+            reply_to_message_id: None,
+            forwarded_from_message_id: None,
+            metadata: None,
+            expires_at: None,
+        }
+    }
+
+    async fn post(repository: &Postgre, sender_id: i64, channel_id: i64, text: &str) -> Message {
+        let mut tx = repository.begin().await.unwrap();
+        let message = tx
+            .create_channel_message(
+                sender_id,
+                channel_id,
+                Some(text.to_string()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+        message
+    }
+
+    async fn dm_history(repository: &Postgre, viewer: i64, other: i64) -> Vec<Message> {
+        let now = OffsetDateTime::now_utc() + time::Duration::seconds(1);
+        repository
+            .find_dm_messages_with_pagination(viewer, other, now, 0, 10)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn idempotency_key_replays_the_first_message(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let sender_id = create_user(&pool, "alice").await;
+        let channel_id = create_channel(&pool, "general").await;
+        let first = post(&repository, sender_id, channel_id, "hello").await;
+        let retry = post(&repository, sender_id, channel_id, "hello").await;
+
+        let mut tx = repository.begin().await.unwrap();
+        let claimed = tx.create_idempotency_key(sender_id, "key", first.id).await;
+        let reclaimed = tx.create_idempotency_key(sender_id, "key", retry.id).await;
+        repository.commit(tx).await.unwrap();
+
+        assert_eq!(claimed, Ok(true));
+        assert_eq!(reclaimed, Ok(false));
+
+        let replayed = repository
+            .find_message_by_idempotency_key(sender_id, "key")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(replayed.id, first.id);
+    }
+
+    #[sqlx::test]
+    async fn expired_idempotency_key_can_be_reused(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let sender_id = create_user(&pool, "alice").await;
+        let channel_id = create_channel(&pool, "general").await;
+        let first = post(&repository, sender_id, channel_id, "hello").await;
+        let later = post(&repository, sender_id, channel_id, "hello again").await;
+
+        let mut tx = repository.begin().await.unwrap();
+        tx.create_idempotency_key(sender_id, "key", first.id)
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+        sqlx::query!(
+            "UPDATE message_idempotency_keys SET created_at = NOW() - INTERVAL '25 hours'"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert!(
+            repository
+                .find_message_by_idempotency_key(sender_id, "key")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let mut tx = repository.begin().await.unwrap();
+        let reclaimed = tx.create_idempotency_key(sender_id, "key", later.id).await;
+        repository.commit(tx).await.unwrap();
+        assert_eq!(reclaimed, Ok(true));
+
+        let replayed = repository
+            .find_message_by_idempotency_key(sender_id, "key")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(replayed.id, later.id);
+    }
+
+    #[sqlx::test]
+    async fn feed_includes_group_conversations_of_members_only(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+
+        let mut tx = repository.begin().await.unwrap();
+        let conversation = tx
+            .create_group_conversation(alice, None, &[alice, bob])
+            .await
+            .unwrap();
+        let message = tx
+            .create_group_message(
+                alice,
+                conversation.conversation_id,
+                Some("hi".to_string()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+
+        let now = OffsetDateTime::now_utc() + time::Duration::seconds(1);
+        let bob_feed = repository
+            .find_visible_messages(bob, now, 10)
+            .await
+            .unwrap();
+        let carol_feed = repository
+            .find_visible_messages(carol, now, 10)
+            .await
+            .unwrap();
+
+        assert!(bob_feed.iter().any(|m| m.id == message.id));
+        assert!(carol_feed.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn pagination_walks_messages_sharing_a_timestamp(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let sender_id = create_user(&pool, "alice").await;
+        let channel_id = create_channel(&pool, "general").await;
+        let mut posted = Vec::new();
+        for text in ["a", "b", "c", "d", "e"] {
+            posted.push(post(&repository, sender_id, channel_id, text).await.id);
+        }
+        sqlx::query!("UPDATE messages SET created_at = '2026-01-01T00:00:00Z'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut seen = Vec::new();
+        let mut cursor = (OffsetDateTime::now_utc(), 0);
+        loop {
+            let mut page = repository
+                .find_channel_messages_with_pagination(channel_id, cursor.0, cursor.1, 3)
+                .await
+                .unwrap();
+            let next = split_page(&mut page, 2);
+            seen.extend(page.iter().map(|m| m.id));
+            match next {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+
+        posted.reverse();
+        assert_eq!(seen, posted);
+    }
+
+    #[sqlx::test]
+    async fn pending_message_requests_are_hidden_from_the_recipient(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+
+        let mut tx = repository.begin().await.unwrap();
+        let message = tx
+            .create_dm_message(alice, bob, Some("hi".to_string()), None, None, None)
+            .await
+            .unwrap();
+        tx.create_dm_request(alice, bob).await.unwrap();
+        repository.commit(tx).await.unwrap();
+
+        assert_eq!(dm_history(&repository, alice, bob).await.len(), 1);
+        assert!(dm_history(&repository, bob, alice).await.is_empty());
+
+        let mut tx = repository.begin().await.unwrap();
+        tx.update_dm_request_status(alice, bob, DmRequestStatus::Accepted)
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+
+        let accepted = dm_history(&repository, bob, alice).await;
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].id, message.id);
+    }
+
+    #[test]
+    fn split_page_returns_a_cursor_only_when_more_remain() {
+        let mut full = vec![message(3), message(2), message(1)];
+        let mut last = vec![message(2), message(1)];
+
+        let next = split_page(&mut full, 2);
+
+        assert_eq!(full.len(), 2);
+        assert_eq!(next.map(|(_, id)| id), Some(2));
+        assert_eq!(split_page(&mut last, 2), None);
+        assert_eq!(last.len(), 2);
+    }
+
+    #[test]
+    fn idempotency_key_only_replays_for_the_same_target() {
+        let message = Message {
+            recipient_id: Some(2),
+            ..message(1)
+        };
+
+        assert!(MessageType::Direct { recipient_id: 2 }.contains(&message));
+        assert!(!MessageType::Direct { recipient_id: 3 }.contains(&message));
+        assert!(!MessageType::Channel { channel_id: 2 }.contains(&message));
+        assert!(!MessageType::Group { conversation_id: 2 }.contains(&message));
+    }
+
+    #[sqlx::test]
+    async fn forwarded_message_keeps_the_source_expiry(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let sender_id = create_user(&pool, "alice").await;
+        let channel_id = create_channel(&pool, "general").await;
+        let source = post(&repository, sender_id, channel_id, "ephemeral").await;
+        let expires_at = OffsetDateTime::now_utc() + time::Duration::hours(1);
+
+        let mut tx = repository.begin().await.unwrap();
+        let forwarded = tx
+            .create_forwarded_message(
+                sender_id,
+                &MessageType::Channel { channel_id },
+                source.message_text.clone(),
+                source.id,
+                Some(expires_at),
+            )
+            .await
+            .unwrap();
+        let files = tx.lock_message_files(source.id).await.unwrap();
+        repository.commit(tx).await.unwrap();
+
+        assert_eq!(forwarded.forwarded_from_message_id, Some(source.id));
+        assert_eq!(
+            forwarded.expires_at.map(|at| at.unix_timestamp()),
+            Some(expires_at.unix_timestamp())
+        );
+        assert!(files.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn identical_uploads_share_a_blob_until_the_last_reference_is_deleted(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let sender_id = create_user(&pool, "alice").await;
+        let channel_id = create_channel(&pool, "general").await;
+        let first = post(&repository, sender_id, channel_id, "one").await;
+        let second = post(&repository, sender_id, channel_id, "two").await;
+        let metadata = || {
+            sqlx::types::Json(FileMetadata::File {
+                mime: "text/plain".to_string(),
+            })
+        };
+
+        let mut tx = repository.begin().await.unwrap();
+        let original = tx
+            .create_file(first.id, "a.txt", 5, "hash", None, metadata())
+            .await
+            .unwrap();
+        let existing_blob = tx.find_blob_by_hash("hash", 5).await.unwrap();
+        let duplicate = tx
+            .create_file(second.id, "b.txt", 5, "hash", existing_blob, metadata())
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+
+        assert_eq!(existing_blob, Some(original.blob_id));
+        assert_eq!(duplicate.blob_id, original.blob_id);
+
+        let mut tx = repository.begin().await.unwrap();
+        let deleted = tx.delete_message_files(first.id).await.unwrap();
+        let still_referenced = tx.find_unreferenced_blobs(&deleted).await.unwrap();
+        let deleted = tx.delete_message_files(second.id).await.unwrap();
+        let unreferenced = tx.find_unreferenced_blobs(&deleted).await.unwrap();
+        repository.commit(tx).await.unwrap();
+
+        assert!(still_referenced.is_empty());
+        assert_eq!(unreferenced, vec![original.blob_id]);
+    }
+
+    #[test]
+    fn mentions_are_extracted_once_in_order() {
+        let mentions = extract_mentions("@bob hi @alice.w, and @bob again (@carol-)");
+
+        assert_eq!(mentions, vec!["bob", "alice.w", "carol"]);
+    }
+
+    #[test]
+    fn mentions_skip_email_addresses_and_bare_signs() {
+        assert!(extract_mentions("mail bob@example.com or @ or @@").is_empty());
+    }
+
+    #[test]
+    fn mentions_are_capped_per_message() {
+        let text: Vec<String> = (0..30).map(|i| format!("@user{}", i)).collect();
+
+        let mentions = extract_mentions(&text.join(" "));
+
+        assert_eq!(mentions.len(), MAX_MENTIONS_PER_MESSAGE);
+        assert_eq!(mentions[0], "user0");
+    }
+
+    #[test]
+    fn message_text_line_endings_are_normalized_and_trailing_space_trimmed() {
+        let text = sanitize_message_text("  one\r\ntwo\rthree \n\t", 100).unwrap();
+
+        assert_eq!(text, "  one\ntwo\nthree");
+    }
+
+    #[test]
+    fn message_text_rejects_null_bytes() {
+        assert!(matches!(
+            sanitize_message_text("a\0b", 100),
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn message_text_limit_counts_characters_after_trimming() {
+        assert!(sanitize_message_text("\u{e9}\u{e9}\u{e9}   ", 3).is_ok());
+        assert!(matches!(
+            sanitize_message_text("abcd", 3),
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn metadata_must_be_a_json_object() {
+        assert!(validate_metadata(None).is_ok());
+        assert!(validate_metadata(Some(&serde_json::json!({ "kind": "poll" }))).is_ok());
+        assert!(matches!(
+            validate_metadata(Some(&serde_json::json!(["kind", "poll"]))),
+            Err(DomainError::BadRequest(_))
+        ));
+        assert!(matches!(
+            validate_metadata(Some(&serde_json::json!("poll"))),
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn metadata_is_limited_in_serialized_size() {
+        let fits = "a".repeat(MAX_MESSAGE_METADATA_BYTES - 16);
+        let too_big = "a".repeat(MAX_MESSAGE_METADATA_BYTES);
+
+        assert!(validate_metadata(Some(&serde_json::json!({ "v": fits }))).is_ok());
+        assert!(matches!(
+            validate_metadata(Some(&serde_json::json!({ "v": too_big }))),
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn message_expiry_is_bounded() {
+        let before = OffsetDateTime::now_utc();
+        let expires_at = message_expiry(Some(60)).unwrap().unwrap();
+
+        assert_eq!(message_expiry(None).unwrap(), None);
+        assert!(expires_at >= before + time::Duration::seconds(60));
+        assert!(expires_at <= OffsetDateTime::now_utc() + time::Duration::seconds(60));
+        assert!(matches!(
+            message_expiry(Some(0)),
+            Err(DomainError::BadRequest(_))
+        ));
+        assert!(matches!(
+            message_expiry(Some(MAX_MESSAGE_TTL.as_secs() + 1)),
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn expired_messages_are_hidden_then_purged(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let sender_id = create_user(&pool, "alice").await;
+        let channel_id = create_channel(&pool, "general").await;
+        let expired = post(&repository, sender_id, channel_id, "gone").await;
+        let pending = post(&repository, sender_id, channel_id, "soon").await;
+        let kept = post(&repository, sender_id, channel_id, "kept").await;
+        sqlx::query!(
+            r#"UPDATE messages SET expires_at = CASE id
+                   WHEN $1 THEN NOW() - INTERVAL '1 second'
+                   ELSE NOW() + INTERVAL '1 hour'
+               END
+               WHERE id IN ($1, $2)"#,
+            expired.id,
+            pending.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let now = OffsetDateTime::now_utc() + time::Duration::seconds(1);
+        let visible: Vec<i64> = repository
+            .find_channel_messages_with_pagination(channel_id, now, 0, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|message| message.id)
+            .collect();
+
+        assert_eq!(visible, vec![kept.id, pending.id]);
+        assert!(
+            repository
+                .find_message_by_id(expired.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let mut tx = repository.begin().await.unwrap();
+        let purged = tx.delete_ephemeral_messages().await.unwrap();
+        repository.commit(tx).await.unwrap();
+
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, expired.id);
+    }
+
+    fn service(pool: PgPool) -> TestMessageService {
+        service_with_events(pool).0
+    }
+
+    fn service_with_events(
+        pool: PgPool,
+    ) -> (
+        TestMessageService,
+        tokio::sync::mpsc::Receiver<ServerMessage>,
+    ) {
+        let (sender, receiver) = tokio::sync::mpsc::channel(16);
+        let service = MessageService::new(
+            Postgre { pool },
+            LocalFileManager::new(std::env::temp_dir().join("opencord-message-test-files")),
+            DefaultScanManager::new(),
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-message-test.log")),
+            DEFAULT_MAX_MESSAGE_LENGTH,
+        );
+        (service, receiver)
+    }
+
+    async fn block(pool: &PgPool, blocker_id: i64, blocked_id: i64) {
+        sqlx::query!(
+            "INSERT INTO blocked_users (blocker_id, blocked_id) VALUES ($1, $2)",
+            blocker_id,
+            blocked_id
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn send_dm(
+        service: &mut TestMessageService,
+        sender_id: i64,
+        recipient_id: i64,
+    ) -> Result<PostedMessage, DomainError> {
+        service
+            .create_dm_message(
+                sender_id,
+                1,
+                recipient_id,
+                Some("hi".to_string()),
+                None,
+                None,
+                None,
+                Vec::new(),
+                None,
+            )
+            .await
+    }
+
+    #[sqlx::test]
+    async fn blocked_pairs_cannot_message_each_other(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        block(&pool, alice, bob).await;
+        let mut service = service(pool);
+
+        assert!(matches!(
+            send_dm(&mut service, bob, alice).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            send_dm(&mut service, alice, bob).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn refused_direct_messages_do_not_use_up_the_rate_limit(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        block(&pool, alice, bob).await;
+        let mut service = service(pool).with_dm_rate_limiter(DmRateLimiter::new(1, 10, 60));
+
+        for _ in 0..3 {
+            assert!(matches!(
+                send_dm(&mut service, bob, alice).await,
+                Err(DomainError::PermissionDenied(_))
+            ));
+        }
+        assert!(matches!(
+            send_dm(&mut service, bob, carol).await,
+            Ok(PostedMessage::Created(..))
+        ));
+        assert!(matches!(
+            send_dm(&mut service, bob, carol).await,
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    async fn send_keyed_dm(
+        service: &mut TestMessageService,
+        sender_id: i64,
+        recipient_id: i64,
+        text: &str,
+    ) -> Result<(StatusCode, MessageResponse), DomainError> {
+        let posted = service
+            .create_dm_message(
+                sender_id,
+                1,
+                recipient_id,
+                Some(text.to_string()),
+                None,
+                None,
+                None,
+                Vec::new(),
+                Some("retry".to_string()),
+            )
+            .await?;
+        let (status, Json(response)) = posted_response(posted);
+        Ok((status, response))
+    }
+
+    #[sqlx::test]
+    async fn retried_request_replays_the_original_message(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let mut service = service(pool.clone());
+
+        let (status, first) = send_keyed_dm(&mut service, alice, bob, "hello")
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, retry) = send_keyed_dm(&mut service, alice, bob, "hello")
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(retry.message.id, first.message.id);
+
+        let stored = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM messages WHERE sender_id = $1"#,
+            alice
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(stored, 1);
+    }
+
+    #[sqlx::test]
+    async fn reused_idempotency_key_with_a_different_body_is_rejected(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let mut service = service(pool);
+
+        send_keyed_dm(&mut service, alice, bob, "hello")
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            send_keyed_dm(&mut service, alice, bob, "goodbye").await,
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+
+    async fn send_reply(
+        pool: &PgPool,
+        sender_id: i64,
+        recipient_id: i64,
+        reply_to_message_id: Option<i64>,
+    ) -> i64 {
+        sqlx::query_scalar!(
+            r#"INSERT INTO messages (sender_id, recipient_id, message_text, reply_to_message_id)
+               VALUES ($1, $2, 'hi', $3)
+               RETURNING id"#,
+            sender_id,
+            recipient_id,
+            reply_to_message_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn thread_follows_replies_to_replies(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let root = send_reply(&pool, alice, bob, None).await;
+        let reply = send_reply(&pool, bob, alice, Some(root)).await;
+        let nested = send_reply(&pool, alice, bob, Some(reply)).await;
+        send_reply(&pool, alice, bob, None).await;
+        let service = service(pool);
+
+        let thread = service.get_thread(bob, root, 10).await.unwrap();
+
+        let ids: Vec<i64> = thread.messages.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![reply, nested]);
+        assert_eq!(thread.reply_counts.len(), 1);
+        assert_eq!(thread.reply_counts[0].message_id, reply);
+        assert_eq!(thread.reply_counts[0].reply_count, 1);
+    }
+
+    #[sqlx::test]
+    async fn reply_count_includes_nested_replies(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let root = send_reply(&pool, alice, bob, None).await;
+        let reply = send_reply(&pool, bob, alice, Some(root)).await;
+        send_reply(&pool, alice, bob, Some(reply)).await;
+        let lonely = send_reply(&pool, alice, bob, None).await;
+
+        let counts = repository.find_reply_counts(&[root, lonely]).await.unwrap();
+
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].message_id, root);
+        assert_eq!(counts[0].reply_count, 2);
+    }
+
+    async fn grant(pool: &PgPool, channel_id: i64, rights: i64) {
+        sqlx::query!(
+            r#"INSERT INTO group_role_rights (group_id, role_id, rights)
+               SELECT group_id, $2, $3 FROM channels WHERE channel_id = $1"#,
+            channel_id,
+            DEFAULT_ROLE_ID,
+            rights
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn forward(
+        service: &mut TestMessageService,
+        user_id: i64,
+        message_id: i64,
+        target: MessageType,
+    ) -> Result<(Message, Vec<File>), DomainError> {
+        service
+            .forward_message(user_id, 1, message_id, target)
+            .await
+    }
+
+    async fn attach_file(repository: &Postgre, message_id: i64) -> File {
+        let mut tx = repository.begin().await.unwrap();
+        let file = tx
+            .create_file(
+                message_id,
+                "a.txt",
+                5,
+                "hash",
+                None,
+                sqlx::types::Json(FileMetadata::File {
+                    mime: "text/plain".to_string(),
+                }),
+            )
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+        file
+    }
+
+    #[sqlx::test]
+    async fn forwarding_requires_read_access_to_the_source(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let secret = create_channel(&pool, "secret").await;
+        let general = create_channel(&pool, "general").await;
+        grant(&pool, general, WRITE_RIGHTS).await;
+        let source = post(&repository, alice, secret, "hidden").await;
+        let mut service = service(pool);
+
+        assert!(matches!(
+            forward(
+                &mut service,
+                bob,
+                source.id,
+                MessageType::Channel {
+                    channel_id: general
+                }
+            )
+            .await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn forwarding_requires_write_access_to_the_target(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let announcements = create_channel(&pool, "announcements").await;
+        grant(&pool, announcements, READ_RIGHTS).await;
+        let source = send_reply(&pool, alice, bob, None).await;
+        let mut service = service(pool);
+
+        assert!(matches!(
+            forward(
+                &mut service,
+                bob,
+                source,
+                MessageType::Channel {
+                    channel_id: announcements
+                }
+            )
+            .await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn forwarding_to_a_blocked_or_unaccepted_user_is_denied(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let dave = create_user(&pool, "dave").await;
+        block(&pool, carol, bob).await;
+        let source = send_reply(&pool, alice, bob, None).await;
+        let mut service = service(pool).with_dm_requests(true);
+        send_dm(&mut service, bob, dave).await.unwrap();
+
+        assert!(matches!(
+            forward(
+                &mut service,
+                bob,
+                source,
+                MessageType::Direct {
+                    recipient_id: carol
+                }
+            )
+            .await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            forward(
+                &mut service,
+                bob,
+                source,
+                MessageType::Direct { recipient_id: dave }
+            )
+            .await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
 
-    let response = service
-        .get_dm_messages(user_id, other_user_id, query.timestamp, limit)
+    #[sqlx::test]
+    async fn forwarded_message_references_the_source_and_its_files(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let general = create_channel(&pool, "general").await;
+        grant(&pool, general, WRITE_RIGHTS).await;
+        let source = send_reply(&pool, alice, bob, None).await;
+        let original = attach_file(&repository, source).await;
+        let mut service = service(pool);
+
+        let (message, files) = forward(
+            &mut service,
+            bob,
+            source,
+            MessageType::Channel {
+                channel_id: general,
+            },
+        )
         .await
-        .map_err(ApiError::from)?;
+        .unwrap();
+
+        assert_eq!(message.channel_id, Some(general));
+        assert_eq!(message.forwarded_from_message_id, Some(source));
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].message_id, message.id);
+        assert_eq!(files[0].blob_id, original.blob_id);
+        assert_ne!(files[0].file_id, original.file_id);
+    }
 
-    Ok(Json(response))
-}
+    #[sqlx::test]
+    async fn readable_channel_message_comes_with_files_and_reactions(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let general = create_channel(&pool, "general").await;
+        grant(&pool, general, READ_RIGHTS).await;
+        let message = post(&repository, alice, general, "hello").await;
+        let file = attach_file(&repository, message.id).await;
+        let mut tx = repository.begin().await.unwrap();
+        tx.create_reaction(message.id, alice, "\u{1f44d}")
+            .await
+            .unwrap();
+        repository.commit(tx).await.unwrap();
+        let service = service(pool);
 
-#[utoipa::path(
-    get,
-    tag = "message",
-    path = "/channel/{channel_id}/messages/range",
-    description = "Get channel messages in range",
-    params(("channel_id" = i64, Path, description = "Channel ID")),
-    responses(
-        (status = 200, body = MessagesResponse),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn get_channel_messages_range_handler(
-    State(service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(channel_id): Path<i64>,
-    Query(query): Query<MessageRangeQuery>,
-) -> Result<Json<MessagesResponse>, ApiError> {
-    let response = service
-        .get_channel_messages_range(
-            session.user_id,
-            channel_id,
-            query.from_message_id,
-            query.up_to_message_id,
-        )
-        .await
-        .map_err(ApiError::from)?;
+        let response = service.get_message(bob, message.id).await.unwrap();
 
-    Ok(Json(response))
-}
+        assert_eq!(response.message.id, message.id);
+        assert_eq!(response.files.len(), 1);
+        assert_eq!(response.files[0].file_id, file.file_id);
+        assert_eq!(response.reactions.len(), 1);
+        assert_eq!(response.reactions[0].emoji, "\u{1f44d}");
+    }
 
-#[utoipa::path(
-    get,
-    tag = "message",
-    path = "/dm/{user_id}/messages/range",
-    description = "Get direct messages in range",
-    params(("user_id" = i64, Path, description = "Other user ID")),
-    responses(
-        (status = 200, body = MessagesResponse),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn get_dm_messages_range_handler(
-    State(service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(other_user_id): Path<i64>,
-    Query(query): Query<MessageRangeQuery>,
-) -> Result<Json<MessagesResponse>, ApiError> {
-    let response = service
-        .get_dm_messages_range(
-            session.user_id,
-            other_user_id,
-            query.from_message_id,
-            query.up_to_message_id,
+    #[sqlx::test]
+    async fn channel_message_without_read_rights_is_denied(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let secret = create_channel(&pool, "secret").await;
+        grant(&pool, secret, ACKNOWLEDGE_RIGHTS).await;
+        let message = post(&repository, alice, secret, "hidden").await;
+        let service = service(pool);
+
+        assert!(matches!(
+            service.get_message(bob, message.id).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            service.get_message(bob, message.id + 1000).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn direct_message_is_only_visible_to_its_participants(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let message = send_reply(&pool, alice, bob, None).await;
+        let service = service(pool);
+
+        assert!(service.get_message(alice, message).await.is_ok());
+        assert!(service.get_message(bob, message).await.is_ok());
+        assert!(matches!(
+            service.get_message(carol, message).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+
+    async fn react(
+        service: &mut TestMessageService,
+        user_id: i64,
+        message_id: i64,
+        emoji: &str,
+    ) -> Result<Reaction, DomainError> {
+        service
+            .add_reaction(user_id, 1, message_id, emoji.to_string())
+            .await
+    }
+
+    #[sqlx::test]
+    async fn reactions_accept_unicode_and_registered_custom_emojis(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let message = send_reply(&pool, alice, bob, None).await;
+        sqlx::query!(
+            r#"WITH image AS (
+                   INSERT INTO avatar_files (file_uuid, file_name, file_type, file_size, file_hash)
+                   VALUES ('party', 'party.png', 'image/png', 1, 'hash')
+                   RETURNING file_id
+               )
+               INSERT INTO custom_emojis (name, avatar_file_id)
+               SELECT 'party', file_id FROM image"#
         )
+        .execute(&pool)
         .await
-        .map_err(ApiError::from)?;
-
-    Ok(Json(response))
-}
+        .unwrap();
+        let mut service = service(pool);
+
+        assert!(react(&mut service, bob, message, "\u{1f44d}").await.is_ok());
+        assert!(react(&mut service, bob, message, ":party:").await.is_ok());
+        assert!(matches!(
+            react(&mut service, bob, message, ":missing:").await,
+            Err(DomainError::NotFound(_))
+        ));
+        for garbage in ["lol", ":Party:", "party", "\u{1f44d}\u{1f44d}"] {
+            assert!(matches!(
+                react(&mut service, bob, message, garbage).await,
+                Err(DomainError::BadRequest(_))
+            ));
+        }
+    }
 
-#[utoipa::path(
-    put,
-    tag = "message",
-    path = "/{message_id}",
-    description = "Edit message",
-    params(("message_id" = i64, Path, description = "Message ID")),
-    request_body = EditMessageRequest,
-    responses(
-        (status = 204, description = "Updated"),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn edit_message_handler(
-    State(service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(message_id): Path<i64>,
-    Json(payload): Json<EditMessageRequest>,
-) -> Result<(), ApiError> {
-    service
-        .edit_message(
-            session.user_id,
-            session.session_id,
-            message_id,
-            payload.message_text,
+    #[sqlx::test]
+    async fn webhook_token_posts_as_its_bot_user(pool: PgPool) {
+        let bot = create_user(&pool, "deploy-bot").await;
+        let general = create_channel(&pool, "general").await;
+        grant(&pool, general, WRITE_RIGHTS).await;
+        sqlx::query!(
+            "INSERT INTO webhook_tokens (token, channel_id, bot_user_id) VALUES ('secret', $1, $2)",
+            general,
+            bot
         )
+        .execute(&pool)
         .await
-        .map_err(ApiError::from)?;
+        .unwrap();
+        let mut service = service(pool);
 
-    Ok(())
-}
+        let posted = service
+            .create_webhook_message("secret", "deployed".to_string(), None)
+            .await
+            .unwrap();
+        let PostedMessage::Created(message, _) = posted else {
+            panic!("expected a new message");
+        };
+        assert_eq!(message.sender_id, bot);
+        assert_eq!(message.channel_id, Some(general));
+        assert_eq!(message.message_text.as_deref(), Some("deployed"));
+
+        assert!(matches!(
+            service
+                .create_webhook_message("wrong", "deployed".to_string(), None)
+                .await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
 
-#[utoipa::path(
-    delete,
-    tag = "message",
-    path = "/{message_id}",
-    description = "Delete message",
-    params(("message_id" = i64, Path, description = "Message ID")),
-    responses(
-        (status = 204, description = "Deleted"),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn delete_message_handler(
-    State(service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(message_id): Path<i64>,
-) -> Result<(), ApiError> {
-    service
-        .delete_message(session.user_id, session.session_id, message_id)
-        .await
-        .map_err(ApiError::from)?;
+    #[sqlx::test]
+    async fn reactions_are_capped_per_message_and_per_user(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let general = create_channel(&pool, "general").await;
+        grant(&pool, general, WRITE_RIGHTS).await;
+        let message = post(&repository, alice, general, "vote").await.id;
+        let mut service = service(pool).with_reaction_limits(3, 2);
+        let (thumbs, party, fire, eyes) = ("\u{1f44d}", "\u{1f389}", "\u{1f525}", "\u{1f440}");
+
+        react(&mut service, alice, message, thumbs).await.unwrap();
+        react(&mut service, alice, message, party).await.unwrap();
+        assert!(matches!(
+            react(&mut service, alice, message, fire).await,
+            Err(DomainError::BadRequest(_))
+        ));
+        assert!(react(&mut service, alice, message, thumbs).await.is_ok());
 
-    Ok(())
-}
+        react(&mut service, bob, message, fire).await.unwrap();
+        assert!(matches!(
+            react(&mut service, bob, message, eyes).await,
+            Err(DomainError::BadRequest(_))
+        ));
+        assert!(react(&mut service, bob, message, thumbs).await.is_ok());
+    }
 
-#[utoipa::path(
-    get,
-    tag = "message",
-    path = "/files/{file_id}",
-    description = "Download file",
-    params(("file_id" = i64, Path, description = "File ID")),
-    responses(
-        (status = 200, description = "File content"),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn get_file_handler(
-    State(service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(file_id): Path<i64>,
-) -> Result<impl IntoResponse, ApiError> {
-    let user_id = session.user_id;
-    let file = service
-        .get_file(user_id, file_id)
-        .await
-        .map_err(ApiError::from)?;
+    fn recipients(receiver: &mut tokio::sync::mpsc::Receiver<ServerMessage>) -> Vec<i64> {
+        match receiver.try_recv() {
+            Ok(ServerMessage::Control(_, ControlRoutingPolicy::Users { mut user_ids })) => {
+                user_ids.sort_unstable();
+                user_ids
+            }
+            _ => panic!("expected an event routed to users"),
+        }
+    }
 
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        header::CONTENT_TYPE,
-        HeaderValue::from_str(file.0.metadata.mime())
-            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
-    );
-    headers.insert(
-        header::CONTENT_DISPOSITION,
-        HeaderValue::from_str(&format!("inline; filename=\"{}\"", file.0.file_name))
-            .unwrap_or_else(|_| HeaderValue::from_static("inline")),
-    );
-    headers.insert(
-        header::CACHE_CONTROL,
-        HeaderValue::from_static("public, max-age=3600"),
-    );
+    #[sqlx::test]
+    async fn group_conversation_events_reach_every_member(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let (mut service, mut receiver) = service_with_events(pool);
 
-    Ok((headers, file.1))
-}
+        let conversation = service
+            .create_group_conversation(alice, 0, vec![bob, carol, bob], None)
+            .await
+            .unwrap();
+        assert_eq!(recipients(&mut receiver), vec![alice, bob, carol]);
+
+        service
+            .create_group_message(
+                bob,
+                0,
+                conversation.conversation_id,
+                Some("hi all".to_string()),
+                None,
+                None,
+                None,
+                vec![],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(recipients(&mut receiver), vec![alice, bob, carol]);
+    }
 
-#[utoipa::path(
-    post,
-    tag = "message",
-    path = "/{message_id}/reactions",
-    description = "Add reaction",
-    params(("message_id" = i64, Path, description = "Message ID")),
-    request_body = AddReactionRequest,
-    responses(
-        (status = 201, description = "Created"),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn add_reaction_handler(
-    State(mut service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path(message_id): Path<i64>,
-    Json(payload): Json<AddReactionRequest>,
-) -> Result<(), ApiError> {
-    service
-        .add_reaction(
-            session.user_id,
-            session.session_id,
-            message_id,
-            payload.emoji,
-        )
-        .await
-        .map_err(ApiError::from)?;
+    #[sqlx::test]
+    async fn non_members_cannot_post_to_a_group_conversation(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let mallory = create_user(&pool, "mallory").await;
+        let (mut service, mut receiver) = service_with_events(pool);
+        let conversation = service
+            .create_group_conversation(alice, 0, vec![bob, carol], None)
+            .await
+            .unwrap();
+        recipients(&mut receiver);
+
+        let result = service
+            .create_group_message(
+                mallory,
+                0,
+                conversation.conversation_id,
+                Some("let me in".to_string()),
+                None,
+                None,
+                None,
+                vec![],
+                None,
+            )
+            .await;
 
-    Ok(())
-}
+        assert!(matches!(result, Err(DomainError::PermissionDenied(_))));
+        assert!(receiver.try_recv().is_err());
+    }
 
-#[utoipa::path(
-    delete,
-    tag = "message",
-    path = "/{message_id}/reactions/{emoji}",
-    description = "Remove reaction",
-    params(
-        ("message_id" = i64, Path, description = "Message ID"),
-        ("emoji" = String, Path, description = "Emoji")
-    ),
-    responses(
-        (status = 204, description = "Deleted"),
-        (status = 422, body = ApiError),
-        (status = 500, body = ApiError),
-    ),
-    security(("api_key" = []))
-)]
-async fn remove_reaction_handler(
-    State(mut service): State<AppMessageService>,
-    Extension(session): Extension<Session>,
-    Path((message_id, emoji)): Path<(i64, String)>,
-) -> Result<(), ApiError> {
-    service
-        .remove_reaction(session.user_id, session.session_id, message_id, emoji)
-        .await
-        .map_err(ApiError::from)?;
+    #[sqlx::test]
+    async fn reaction_emoji_length_is_bounded_in_bytes(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let message = send_reply(&pool, alice, bob, None).await;
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}";
+        let flag = "\u{1f1ec}\u{1f1f7}";
+        assert_eq!(family.len(), 25);
+
+        let mut service = service(pool).with_max_emoji_bytes(family.len());
+        assert!(react(&mut service, bob, message, family).await.is_ok());
+        assert!(react(&mut service, bob, message, flag).await.is_ok());
+
+        let mut service = service.with_max_emoji_bytes(family.len() - 1);
+        assert!(matches!(
+            react(&mut service, alice, message, family).await,
+            Err(DomainError::BadRequest(_))
+        ));
 
-    Ok(())
+        let mut service = service.with_max_emoji_bytes(DEFAULT_MAX_EMOJI_BYTES);
+        let oversized = format!(":{}:", "a".repeat(4096));
+        assert!(matches!(
+            react(&mut service, alice, message, &oversized).await,
+            Err(DomainError::BadRequest(_))
+        ));
+    }
 }
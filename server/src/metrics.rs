@@ -0,0 +1,270 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// IMPORTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use crate::managers::{DefaultLockoutManager, DefaultNotifierManager};
+use crate::transport::{QueryPayload, RealtimeMetrics, ServerMessage};
+
+use axum::Router;
+use axum::extract::Extension;
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// COUNTERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Default)]
+struct TransportCounters {
+    frames_sent: AtomicU64,
+    frames_received: AtomicU64,
+    send_failures: AtomicU64,
+    receive_failures: AtomicU64,
+    missed_pongs: AtomicU64,
+    rtt_total_ms: AtomicU64,
+    rtt_samples: AtomicU64,
+}
+
+#[derive(Clone, Default)]
+pub struct TransportMetrics {
+    counters: Arc<TransportCounters>,
+}
+
+impl TransportMetrics {
+    pub fn record_frame_sent(&self) {
+        self.counters.frames_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_frame_received(&self) {
+        self.counters
+            .frames_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_send_failure(&self) {
+        self.counters.send_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_receive_failure(&self) {
+        self.counters
+            .receive_failures
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_missed_pongs(&self, count: usize) {
+        self.counters
+            .missed_pongs
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_rtt(&self, rtt: Duration) {
+        self.counters
+            .rtt_total_ms
+            .fetch_add(rtt.as_millis() as u64, Ordering::Relaxed);
+        self.counters.rtt_samples.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn average_rtt_ms(&self) -> f64 {
+        let samples = self.counters.rtt_samples.load(Ordering::Relaxed);
+        if samples == 0 {
+            return 0.0;
+        }
+        self.counters.rtt_total_ms.load(Ordering::Relaxed) as f64 / samples as f64
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SERVICE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Clone)]
+pub struct MetricsService {
+    observer_tx: mpsc::Sender<ServerMessage>,
+    transport: TransportMetrics,
+    lockout_manager: DefaultLockoutManager,
+    notifier_manager: DefaultNotifierManager,
+}
+
+impl MetricsService {
+    pub fn new(
+        observer_tx: mpsc::Sender<ServerMessage>,
+        transport: TransportMetrics,
+        lockout_manager: DefaultLockoutManager,
+        notifier_manager: DefaultNotifierManager,
+    ) -> Self {
+        Self {
+            observer_tx,
+            transport,
+            lockout_manager,
+            notifier_manager,
+        }
+    }
+
+    async fn realtime_snapshot(&self) -> Option<RealtimeMetrics> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.observer_tx
+            .send(ServerMessage::Query(QueryPayload::Metrics(reply_tx)))
+            .await
+            .ok()?;
+        reply_rx.await.ok()
+    }
+
+    pub async fn render(&self) -> Option<String> {
+        let realtime = self.realtime_snapshot().await?;
+        let counters = &self.transport.counters;
+        let mut out = String::new();
+
+        write_metric(
+            &mut out,
+            "opencord_active_connections",
+            "gauge",
+            "Open WebSocket connections",
+            realtime.active_connections as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_online_users",
+            "gauge",
+            "Users with at least one open connection",
+            realtime.online_users as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_resumable_sessions",
+            "gauge",
+            "Dropped connections still inside the resume window",
+            realtime.resumable_sessions as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_voip_participants",
+            "gauge",
+            "Active VoIP participants",
+            realtime.voip_participants as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_speaking_users",
+            "gauge",
+            "VoIP participants currently speaking",
+            realtime.speaking_users as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_events_routed_total",
+            "counter",
+            "Control events routed by the realtime server",
+            realtime.events_routed as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_dropped_events_total",
+            "counter",
+            "Control events dropped because a subscriber queue was full",
+            realtime.dropped_events as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_backfill_resyncs_total",
+            "counter",
+            "Backfill requests too far behind to replay, answered with a resync",
+            realtime.backfill_resyncs as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_frames_sent_total",
+            "counter",
+            "WebSocket frames sent",
+            counters.frames_sent.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_frames_received_total",
+            "counter",
+            "WebSocket frames received",
+            counters.frames_received.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_send_failures_total",
+            "counter",
+            "WebSocket frames that failed to send",
+            counters.send_failures.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_receive_failures_total",
+            "counter",
+            "WebSocket frames that failed to read or decode",
+            counters.receive_failures.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_missed_pongs_total",
+            "counter",
+            "Pings that timed out without a pong",
+            counters.missed_pongs.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_average_rtt_milliseconds",
+            "gauge",
+            "Average ping round-trip time",
+            self.transport.average_rtt_ms(),
+        );
+        write_metric(
+            &mut out,
+            "opencord_login_lockout_rejections_total",
+            "counter",
+            "Login attempts rejected by account lockout",
+            self.lockout_manager.rejections() as f64,
+        );
+        write_metric(
+            &mut out,
+            "opencord_notifier_dropped_events_total",
+            "counter",
+            "Events the notifier gave up on after retrying a full or closed realtime queue",
+            self.notifier_manager.dropped_events() as f64,
+        );
+
+        Some(out)
+    }
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ROUTES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub fn metrics_routes(metrics_service: MetricsService) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .layer(Extension(metrics_service))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HANDLERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn metrics_handler(Extension(service): Extension<MetricsService>) -> impl IntoResponse {
+    match service.render().await {
+        Some(body) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            body,
+        )
+            .into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
@@ -1,13 +1,18 @@
 use crate::auth::{AuthRepository, Session};
 use crate::managers::RateLimiter;
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{HeaderMap, StatusCode, header, request::Parts},
     middleware::Next,
     response::Response,
 };
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 use time::OffsetDateTime;
 
+pub const API_KEY_HEADER: &str = "x-api-key";
+
 #[derive(Clone)]
 pub struct AuthorizeService<T: AuthRepository> {
     auth_repo: T,
@@ -37,14 +42,37 @@ impl<T: AuthRepository> AuthorizeService<T> {
     }
 
     pub fn extract_session_from_headers(&self, headers: &HeaderMap) -> Option<String> {
-        headers
-            .get("Authorization")
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.strip_prefix("Bearer "))
-            .map(|t| t.to_string())
+        let bearer = match headers.get(header::AUTHORIZATION) {
+            Some(value) => {
+                let (scheme, token) = value.to_str().ok()?.trim().split_once(' ')?;
+                if !scheme.eq_ignore_ascii_case("Bearer") {
+                    return None;
+                }
+                Some(normalize_token(token)?)
+            }
+            None => None,
+        };
+
+        let api_key = match headers.get(API_KEY_HEADER) {
+            Some(value) => Some(normalize_token(value.to_str().ok()?)?),
+            None => None,
+        };
+
+        match (bearer, api_key) {
+            (Some(bearer), Some(api_key)) if bearer != api_key => None,
+            (bearer, api_key) => bearer.or(api_key),
+        }
     }
 }
 
+fn normalize_token(token: &str) -> Option<String> {
+    let token = token.trim();
+    if token.is_empty() || token.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(token.to_string())
+}
+
 #[derive(Clone)]
 pub struct RateLimitService<R: RateLimiter> {
     rate_limiter: R,
@@ -86,28 +114,77 @@ pub async fn authorize<T: AuthRepository + Clone + Send + Sync + 'static>(
 
 pub async fn rate_limit<R: RateLimiter + Clone + Send + Sync + 'static>(
     State(rate_limit_service): State<RateLimitService<R>>,
+    ClientAddr(client_addr): ClientAddr,
     request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    
-    let client_ip = request
-        .headers()
-        .get("x-forwarded-for")
-        .and_then(|hv| hv.to_str().ok())
-        .or_else(|| {
-            request
-                .headers()
-                .get("x-real-ip")
-                .and_then(|hv| hv.to_str().ok())
-        })
-        .unwrap_or("unknown");
-
-    
-    rate_limit_service.check_rate_limit(client_ip)?;
+    rate_limit_service.check_rate_limit(&client_addr.to_string())?;
 
     Ok(next.run(request).await)
 }
 
+/// Proxies whose `X-Forwarded-For`/`X-Real-IP` headers are believed. Any other
+/// peer is identified by its socket address alone.
+#[derive(Clone, Default)]
+pub struct TrustedProxies(Arc<HashSet<IpAddr>>);
+
+impl TrustedProxies {
+    pub fn new(proxies: impl IntoIterator<Item = IpAddr>) -> Self {
+        Self(Arc::new(proxies.into_iter().collect()))
+    }
+
+    /// Walks `X-Forwarded-For` from the right, past our own proxies, to the
+    /// first hop they did not vouch for.
+    pub fn client_addr(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.0.contains(&peer) {
+            return peer;
+        }
+
+        if let Some(forwarded) = headers
+            .get("x-forwarded-for")
+            .and_then(|hv| hv.to_str().ok())
+        {
+            for hop in forwarded.rsplit(',') {
+                match hop.trim().parse::<IpAddr>() {
+                    Ok(addr) if self.0.contains(&addr) => continue,
+                    Ok(addr) => return addr,
+                    Err(_) => return peer,
+                }
+            }
+        }
+
+        headers
+            .get("x-real-ip")
+            .and_then(|hv| hv.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(peer)
+    }
+}
+
+/// Address of the client that opened the request: the socket peer, or the
+/// forwarded address when the peer is a trusted proxy.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientAddr(pub IpAddr);
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientAddr {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let ConnectInfo(peer) = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .copied()
+            .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+        let proxies = parts
+            .extensions
+            .get::<TrustedProxies>()
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(ClientAddr(proxies.client_addr(peer.ip(), &parts.headers)))
+    }
+}
+
 
 #[derive(Clone, Copy)]
 pub struct UserId(pub i64);
@@ -122,3 +199,108 @@ impl RequestExt for Request {
         self.extensions().get::<UserId>().map(|id| id.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Postgre;
+    use crate::managers::DefaultRateLimiter;
+    use axum::http::HeaderValue;
+    use sqlx::PgPool;
+
+    fn forwarded(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_forwarded_headers() {
+        let proxies = TrustedProxies::default();
+        let peer: IpAddr = "203.0.113.7".parse().unwrap();
+
+        assert_eq!(proxies.client_addr(peer, &forwarded("198.51.100.1")), peer);
+    }
+
+    #[test]
+    fn trusted_proxy_uses_first_untrusted_hop() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let proxies = TrustedProxies::new([proxy]);
+        let headers = forwarded("1.2.3.4, 198.51.100.1, 10.0.0.1");
+
+        assert_eq!(
+            proxies.client_addr(proxy, &headers),
+            "198.51.100.1".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn different_peers_get_separate_buckets() {
+        let proxies = TrustedProxies::default();
+        let limiter = DefaultRateLimiter::per_minute(1);
+        let headers = forwarded("198.51.100.1");
+
+        let first = proxies.client_addr("203.0.113.7".parse().unwrap(), &headers);
+        let second = proxies.client_addr("203.0.113.8".parse().unwrap(), &headers);
+
+        assert!(limiter.is_allowed(&first.to_string()).is_ok());
+        assert!(limiter.is_allowed(&second.to_string()).is_ok());
+        assert!(limiter.is_allowed(&first.to_string()).is_err());
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[sqlx::test]
+    async fn bearer_and_api_key_headers_authenticate_the_same_session(pool: PgPool) {
+        let user_id =
+            sqlx::query_scalar!("INSERT INTO users (username) VALUES ('alice') RETURNING user_id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        sqlx::query!(
+            "INSERT INTO sessions (session_token, user_id, expires_at) VALUES ('token', $1, NOW() + INTERVAL '1 day')",
+            user_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let service = AuthorizeService::new(Postgre { pool });
+
+        for headers in [
+            headers(&[("authorization", "Bearer token")]),
+            headers(&[("authorization", "bearer  token ")]),
+            headers(&[(API_KEY_HEADER, "token")]),
+            headers(&[("authorization", "Bearer token"), (API_KEY_HEADER, "token")]),
+        ] {
+            let token = service.extract_session_from_headers(&headers).unwrap();
+            let session = service.validate_session(&token).await.unwrap();
+            assert_eq!(session.user_id, user_id);
+        }
+        assert_eq!(
+            service.validate_session("unknown").await.err(),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[sqlx::test]
+    async fn malformed_authorization_headers_are_rejected(pool: PgPool) {
+        let service = AuthorizeService::new(Postgre { pool });
+
+        for headers in [
+            HeaderMap::new(),
+            headers(&[("authorization", "Basic token")]),
+            headers(&[("authorization", "Bearer")]),
+            headers(&[("authorization", "Bearer two tokens")]),
+            headers(&[(API_KEY_HEADER, " ")]),
+            headers(&[("authorization", "Bearer token"), (API_KEY_HEADER, "other")]),
+        ] {
+            assert_eq!(service.extract_session_from_headers(&headers), None);
+        }
+    }
+}
@@ -2,12 +2,14 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
 use crate::{
-    channel::Channel,
+    channel::{Channel, ChannelMute},
     group::{Group, GroupRoleRights},
-    message::{File, MessageType, Reaction},
+    message::{DmRequest, File, GroupConversation, MessageType, Reaction},
     role::Role,
-    user::User,
-    voip::VoipParticipant,
+    server::CustomEmoji,
+    sync::SyncCursor,
+    user::{BlockedUser, User},
+    voip::{VoipCodecParams, VoipParticipant},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -15,9 +17,24 @@ use crate::{
 pub struct ServerConfig {
     pub id: i64,
     pub server_name: String,
+    pub description: String,
     pub avatar_file_id: Option<i64>,
     pub max_file_size_mb: i32,
     pub max_files_per_message: i32,
+    pub max_voip_participants: i32,
+    pub require_registration_approval: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Paginated<T> {
+    pub items: T,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub next_cursor: Option<OffsetDateTime>,
+    /// Id of the last item, passed back with `next_cursor` so rows sharing a
+    /// timestamp aren't skipped.
+    pub next_cursor_id: Option<i64>,
+    pub has_more: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +49,12 @@ pub enum EventPayload {
     #[serde(rename = "channelDeleted")]
     #[serde(rename_all = "camelCase")]
     ChannelDeleted { channel_id: i64 },
+    #[serde(rename = "channelMuteUpdated")]
+    #[serde(rename_all = "camelCase")]
+    ChannelMuteUpdated {
+        channel_id: i64,
+        mute: Option<ChannelMute>,
+    },
     #[serde(rename = "groupCreated")]
     #[serde(rename_all = "camelCase")]
     GroupCreated { group: Group },
@@ -62,6 +85,15 @@ pub enum EventPayload {
     #[serde(rename = "serverUpdated")]
     #[serde(rename_all = "camelCase")]
     ServerUpdated { server: ServerConfig },
+    #[serde(rename = "customEmojiCreated")]
+    #[serde(rename_all = "camelCase")]
+    CustomEmojiCreated { emoji: CustomEmoji },
+    #[serde(rename = "userBlocked")]
+    #[serde(rename_all = "camelCase")]
+    UserBlocked { block: BlockedUser },
+    #[serde(rename = "userUnblocked")]
+    #[serde(rename_all = "camelCase")]
+    UserUnblocked { blocked_id: i64 },
     #[serde(rename = "groupRoleRightUpdated")]
     #[serde(rename_all = "camelCase")]
     GroupRoleRightUpdated { right: GroupRoleRights },
@@ -74,6 +106,9 @@ pub enum EventPayload {
     #[serde(rename = "voipParticipantDeleted")]
     #[serde(rename_all = "camelCase")]
     VoipParticipantDeleted { user_id: i64 },
+    #[serde(rename = "voipCodecUpdated")]
+    #[serde(rename_all = "camelCase")]
+    VoipCodecUpdated { codec: VoipCodecParams },
     #[serde(rename = "messageCreated")]
     #[serde(rename_all = "camelCase")]
     MessageCreated {
@@ -82,10 +117,27 @@ pub enum EventPayload {
         message_type: MessageType,
         message_text: Option<String>,
         reply_to_message_id: Option<i64>,
+        forwarded_from_message_id: Option<i64>,
+        metadata: Option<serde_json::Value>,
         #[serde(with = "time::serde::iso8601")]
         timestamp: OffsetDateTime,
+        #[serde(with = "time::serde::iso8601::option")]
+        expires_at: Option<OffsetDateTime>,
         files: Vec<File>,
+        muted: bool,
     },
+    #[serde(rename = "groupConversationCreated")]
+    #[serde(rename_all = "camelCase")]
+    GroupConversationCreated { conversation: GroupConversation },
+    #[serde(rename = "dmRequestCreated")]
+    #[serde(rename_all = "camelCase")]
+    DmRequestCreated { request: DmRequest },
+    #[serde(rename = "dmRequestUpdated")]
+    #[serde(rename_all = "camelCase")]
+    DmRequestUpdated { request: DmRequest },
+    #[serde(rename = "mentioned")]
+    #[serde(rename_all = "camelCase")]
+    Mentioned { message_id: i64, user_id: i64 },
     #[serde(rename = "messageUpdated")]
     #[serde(rename_all = "camelCase")]
     MessageUpdated {
@@ -95,6 +147,12 @@ pub enum EventPayload {
     #[serde(rename = "messageDeleted")]
     #[serde(rename_all = "camelCase")]
     MessageDeleted { message_id: i64 },
+    #[serde(rename = "messagesPurged")]
+    #[serde(rename_all = "camelCase")]
+    MessagesPurged {
+        channel_id: i64,
+        message_ids: Vec<i64>,
+    },
     #[serde(rename = "reactionAdded")]
     #[serde(rename_all = "camelCase")]
     ReactionAdded {
@@ -120,4 +178,17 @@ pub enum EventPayload {
     #[serde(rename = "speakStatusUpdated")]
     #[serde(rename_all = "camelCase")]
     SpeakStatusUpdated { user_id: i64, speaking: bool },
+    #[serde(rename = "syncCursorUpdated")]
+    #[serde(rename_all = "camelCase")]
+    SyncCursorUpdated { cursor: SyncCursor },
+    /// Pushed once after connecting with the state the client would otherwise
+    /// fetch over HTTP, limited to what the user can see.
+    #[serde(rename = "snapshot")]
+    #[serde(rename_all = "camelCase")]
+    Snapshot {
+        users: Vec<User>,
+        channels: Vec<Channel>,
+        group_role_rights: Vec<GroupRoleRights>,
+        voip_participants: Vec<VoipParticipant>,
+    },
 }
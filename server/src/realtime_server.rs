@@ -1,26 +1,41 @@
+use crate::acl::{ACKNOWLEDGE_RIGHTS, READ_RIGHTS};
 use crate::auth::Session;
-use crate::channel::Channel;
+use crate::channel::{Channel, ChannelMute};
 use crate::db::Postgre;
 use crate::error::DatabaseError;
 use crate::group::GroupRoleRights;
-use crate::managers::LogManager;
+use crate::managers::{DefaultRateLimiter, LogManager, RateLimiter};
+use crate::message::MessageType;
+use crate::middleware::ClientAddr;
 use crate::model::EventPayload;
-use crate::subscriber_session::SessionService;
+use crate::subscriber_session::{ConnectionLimiter, KeepAliveConfig, SessionService};
 use crate::transport::{
-    CommandPayload, ControlRoutingPolicy, DomainError, ServerMessage, SubscriberHandler,
-    SubscriberMessage,
+    ActiveConnection, CloseReason, CommandPayload, ControlRoutingPolicy, DomainError,
+    OverflowPolicy, QueryPayload, RealtimeMetrics, SendError, ServerMessage, SubscriberHandler,
+    SubscriberMessage, SubscriberSenders,
 };
+use crate::metrics::TransportMetrics;
 use crate::user::{User, UserStatusType};
 use crate::voip::VoipParticipant;
-use axum::extract::ws::{CloseFrame, WebSocket, WebSocketUpgrade};
+use axum::extract::ws::{WebSocket, WebSocketUpgrade};
 use axum::extract::{Query, State};
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use time::OffsetDateTime;
 use tokio::sync::mpsc;
-use tokio::time::{Duration, interval};
+use tokio::time::{Duration, Instant, interval};
+use tracing::Instrument;
 use uuid::Uuid;
 
-const CLOSE_CODE_DISCONNECTED: u16 = 4002;
+const RESUME_WINDOW: Duration = Duration::from_secs(15);
+pub const DEFAULT_OVERFLOW_GRACE: Duration = Duration::from_secs(5);
+pub const DEFAULT_BACKLOG_CAPACITY: usize = 1024;
+pub const DEFAULT_BACKFILL_RESYNC_THRESHOLD: u64 = 256;
+pub const DEFAULT_CACHE_WARMUP_ATTEMPTS: u32 = 5;
+const CACHE_WARMUP_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const CACHE_WARMUP_MAX_DELAY: Duration = Duration::from_secs(8);
 
 pub struct ServerError;
 
@@ -55,6 +70,7 @@ pub trait ServerRepository: Send + Sync + Clone {
     async fn find_all_group_role_rights(&self) -> Result<Vec<GroupRoleRights>, DatabaseError>;
     async fn find_all_users(&self) -> Result<Vec<User>, DatabaseError>;
     async fn find_all_channels(&self) -> Result<Vec<Channel>, DatabaseError>;
+    async fn find_all_channel_mutes(&self) -> Result<Vec<ChannelMute>, DatabaseError>;
 }
 
 impl ServerRepository for Postgre {
@@ -79,11 +95,14 @@ impl ServerRepository for Postgre {
         let result = sqlx::query_as!(
             User,
             r#"UPDATE users
-               SET status = $2
+               SET status = $2,
+                   last_seen_at = CASE WHEN $2::user_status_type = 'Offline' THEN NOW() ELSE last_seen_at END
                WHERE user_id = $1
                RETURNING
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    CASE WHEN status = 'Offline' THEN status ELSE COALESCE(manual_status, status) END as "status!: UserStatusType",
                    avatar_file_id,
                    created_at,
@@ -149,7 +168,7 @@ impl ServerRepository for Postgre {
     async fn find_all_users(&self) -> Result<Vec<User>, DatabaseError> {
         let result = sqlx::query_as!(
             User,
-            r#"SELECT user_id, username, created_at, avatar_file_id, role_id,
+            r#"SELECT user_id, username, display_name, last_seen_at, created_at, avatar_file_id, role_id,
                       status as "status: UserStatusType", server_mute, server_deafen
                FROM users"#
         )
@@ -169,6 +188,16 @@ impl ServerRepository for Postgre {
         .await?;
         Ok(result)
     }
+
+    async fn find_all_channel_mutes(&self) -> Result<Vec<ChannelMute>, DatabaseError> {
+        let result = sqlx::query_as!(
+            ChannelMute,
+            r#"SELECT user_id, channel_id, suppress, created_at FROM channel_mutes"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -270,6 +299,13 @@ impl<R: ServerRepository, L: LogManager> ServerService<R, L> {
             .await
             .map_err(DomainError::from)
     }
+
+    pub async fn get_all_channel_mutes(&self) -> Result<Vec<ChannelMute>, DomainError> {
+        self.repository
+            .find_all_channel_mutes()
+            .await
+            .map_err(DomainError::from)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -280,22 +316,70 @@ impl<R: ServerRepository, L: LogManager> ServerService<R, L> {
 pub struct WebSocketState<L: LogManager> {
     pub session_service: SessionService<Postgre, L>,
     pub observer_tx: mpsc::Sender<ServerMessage>,
+    pub keepalive: KeepAliveConfig,
+    pub metrics: TransportMetrics,
+    pub connection_limiter: ConnectionLimiter,
+    pub connection_rate_limiter: DefaultRateLimiter,
+    pub compression_min_bytes: Option<usize>,
 }
 
 #[derive(Deserialize)]
 pub struct WebSocketParams {
     token: String,
+    pong_timeout_ms: Option<u64>,
+    resume: Option<String>,
+    #[serde(default)]
+    compress: bool,
 }
 
 pub async fn websocket_handler<L: LogManager + 'static>(
     ws: WebSocketUpgrade,
     State(state): State<WebSocketState<L>>,
     Query(params): Query<WebSocketParams>,
+    ClientAddr(client_addr): ClientAddr,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state, params.token))
+    let remote = client_addr.to_string();
+    if state.connection_rate_limiter.is_allowed(&remote).is_err() {
+        tracing::warn!(
+            "Rejecting WebSocket connection: rate limit exceeded for {}",
+            remote
+        );
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    let Some(permit) = state.connection_limiter.try_acquire() else {
+        tracing::warn!("Rejecting WebSocket connection: connection limit reached");
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    };
+
+    let keepalive = state.keepalive.with_pong_timeout_ms(params.pong_timeout_ms);
+    let remote_address = client_addr.to_string();
+    let compression_min_bytes = state.compression_min_bytes.filter(|_| params.compress);
+    ws.on_upgrade(move |socket| async move {
+        let _permit = permit;
+        handle_socket(
+            socket,
+            state,
+            params.token,
+            params.resume,
+            remote_address,
+            keepalive,
+            compression_min_bytes,
+        )
+        .await
+    })
+    .into_response()
 }
 
-async fn handle_socket<L: LogManager>(mut socket: WebSocket, state: WebSocketState<L>, token: String) {
+async fn handle_socket<L: LogManager>(
+    mut socket: WebSocket,
+    state: WebSocketState<L>,
+    token: String,
+    resume_token: Option<String>,
+    remote_address: String,
+    keepalive: KeepAliveConfig,
+    compression_min_bytes: Option<usize>,
+) {
     use crate::subscriber_session::SubscriberSession;
     use axum::extract::ws::Message;
 
@@ -304,31 +388,61 @@ async fn handle_socket<L: LogManager>(mut socket: WebSocket, state: WebSocketSta
     let session = match state.session_service.authenticate_session(&token).await {
         Ok(Some(session)) => session,
         _ => {
-            let answer = rmp_serde::to_vec_named(&ConnectionMessage::Answer { ok: false })
-                .expect("serialization");
+            let answer = rmp_serde::to_vec_named(&ConnectionMessage::Answer {
+                ok: false,
+                resume_token: None,
+            })
+            .expect("serialization");
             let _ = socket.send(Message::Binary(answer.into())).await;
             return;
         }
     };
 
-    let answer =
-        rmp_serde::to_vec_named(&ConnectionMessage::Answer { ok: true }).expect("serialization");
+    let identifier = Uuid::new_v4().to_string();
+    let answer = rmp_serde::to_vec_named(&ConnectionMessage::Answer {
+        ok: true,
+        resume_token: Some(identifier.clone()),
+    })
+    .expect("serialization");
     let _ = socket.send(Message::Binary(answer.into())).await;
 
-    let identifier = Uuid::new_v4().to_string();
+    // Every log line emitted while this connection is alive carries its id.
+    let span = tracing::info_span!(
+        "connection",
+        identifier = %identifier,
+        user_id = session.user_id,
+        session_id = session.session_id,
+    );
     let mut subscriber_session = SubscriberSession::new(
         state.observer_tx,
         state.session_service,
         identifier,
+        resume_token,
+        remote_address,
         session,
-    );
-    subscriber_session.run(socket).await;
+        keepalive,
+        state.metrics,
+    )
+    .with_compression(compression_min_bytes);
+    subscriber_session.run(socket).instrument(span).await;
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SERVER
 // ═══════════════════════════════════════════════════════════════════════════════
 
+struct ResumableSession {
+    user_id: i64,
+    session_token: String,
+    expires_at: Instant,
+}
+
+struct BacklogEntry {
+    cursor: u64,
+    payload: EventPayload,
+    policy: ControlRoutingPolicy,
+}
+
 pub struct RealtimeServer<L: LogManager> {
     observers: Vec<SubscriberHandler>,
     service: ServerService<Postgre, L>,
@@ -338,6 +452,22 @@ pub struct RealtimeServer<L: LogManager> {
     acl_cache: Vec<GroupRoleRights>,
     user_cache: Vec<User>,
     channel_cache: Vec<Channel>,
+    mute_cache: Vec<ChannelMute>,
+    speaking_users: HashSet<i64>,
+    voip_activity: HashMap<i64, Instant>,
+    voip_idle_timeout: Option<Duration>,
+    private_voip_idle_timeout: Option<Duration>,
+    resumable_sessions: HashMap<String, ResumableSession>,
+    events_routed: u64,
+    dropped_events: u64,
+    overflow_policy: OverflowPolicy,
+    overflow_grace: Duration,
+    backlog: VecDeque<BacklogEntry>,
+    backlog_capacity: usize,
+    backlog_floor: u64,
+    backfill_resync_threshold: u64,
+    backfill_resyncs: u64,
+    caches_warm: bool,
 }
 
 impl<L: LogManager + 'static> RealtimeServer<L> {
@@ -356,9 +486,55 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
             acl_cache: vec![],
             user_cache: vec![],
             channel_cache: vec![],
+            mute_cache: vec![],
+            speaking_users: HashSet::new(),
+            voip_activity: HashMap::new(),
+            voip_idle_timeout: None,
+            private_voip_idle_timeout: None,
+            resumable_sessions: HashMap::new(),
+            events_routed: 0,
+            dropped_events: 0,
+            overflow_policy: OverflowPolicy::Disconnect,
+            overflow_grace: DEFAULT_OVERFLOW_GRACE,
+            backlog: VecDeque::new(),
+            backlog_capacity: DEFAULT_BACKLOG_CAPACITY,
+            backlog_floor: 0,
+            backfill_resync_threshold: DEFAULT_BACKFILL_RESYNC_THRESHOLD,
+            backfill_resyncs: 0,
+            caches_warm: false,
         }
     }
 
+    pub fn with_backlog_capacity(mut self, backlog_capacity: usize) -> Self {
+        self.backlog_capacity = backlog_capacity;
+        self
+    }
+
+    /// Backfill requests further behind than this are answered with a truncated
+    /// completion instead of replaying the gap, so the client refetches. 0 disables.
+    pub fn with_backfill_resync_threshold(mut self, threshold: u64) -> Self {
+        self.backfill_resync_threshold = threshold;
+        self
+    }
+
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy, grace: Duration) -> Self {
+        self.overflow_policy = policy;
+        self.overflow_grace = grace;
+        self
+    }
+
+    /// Channel participants that stay silent for `channel` are removed from
+    /// VoIP; private calls use `private`. `None` disables the check.
+    pub fn with_voip_idle_timeout(
+        mut self,
+        channel: Option<Duration>,
+        private: Option<Duration>,
+    ) -> Self {
+        self.voip_idle_timeout = channel;
+        self.private_voip_idle_timeout = private;
+        self
+    }
+
     pub fn sender(&self) -> mpsc::Sender<ServerMessage> {
         self.sender.clone()
     }
@@ -370,10 +546,139 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
             .count() as i64
     }
 
+    /// Loads every cache, retrying with backoff. Returns false when the database
+    /// stayed unreachable; the caches are then retried on every session check.
+    pub async fn warm_up(&mut self, attempts: u32) -> bool {
+        let mut delay = CACHE_WARMUP_INITIAL_DELAY;
+        for attempt in 1..=attempts.max(1) {
+            match self.load_caches().await {
+                Ok(()) => {
+                    self.caches_warm = true;
+                    tracing::info!(
+                        "Realtime caches loaded: {} users, {} channels, {} voip participants",
+                        self.user_cache.len(),
+                        self.channel_cache.len(),
+                        self.voip_cache.len()
+                    );
+                    return true;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Loading realtime caches failed (attempt {}/{}): {}",
+                        attempt,
+                        attempts.max(1),
+                        e
+                    );
+                    if attempt < attempts {
+                        tokio::time::sleep(delay).await;
+                        delay = (delay * 2).min(CACHE_WARMUP_MAX_DELAY);
+                    }
+                }
+            }
+        }
+
+        tracing::error!(
+            "Realtime caches could not be loaded, running degraded: channel events are withheld until the database is reachable"
+        );
+        let _ = self
+            .service
+            .logger
+            .log_entry(
+                "Realtime caches could not be loaded at startup, running degraded".to_string(),
+                "websocket".to_string(),
+            )
+            .await;
+        false
+    }
+
+    async fn load_caches(&mut self) -> Result<(), DomainError> {
+        let participants = self.service.get_all_voip_participants().await?;
+        let rights = self.service.get_all_group_role_rights().await?;
+        let users = self.service.get_all_users().await?;
+        let channels = self.service.get_all_channels().await?;
+        let mutes = self.service.get_all_channel_mutes().await?;
+
+        self.voip_cache = participants;
+        self.acl_cache = rights;
+        self.user_cache = users;
+        self.channel_cache = channels;
+        self.mute_cache = mutes;
+        self.track_voip_activity();
+        Ok(())
+    }
+
+    async fn retry_cold_caches(&mut self) {
+        if self.caches_warm || self.load_caches().await.is_err() {
+            return;
+        }
+        self.caches_warm = true;
+        tracing::info!("Realtime caches loaded, leaving degraded mode");
+        let _ = self
+            .service
+            .logger
+            .log_entry(
+                "Realtime caches loaded after degraded startup".to_string(),
+                "websocket".to_string(),
+            )
+            .await;
+    }
+
     async fn reload_voip_cache(&mut self) {
         if let Ok(participants) = self.service.get_all_voip_participants().await {
             self.voip_cache = participants;
         }
+        self.track_voip_activity();
+    }
+
+    fn track_voip_activity(&mut self) {
+        let now = Instant::now();
+        self.voip_activity
+            .retain(|user_id, _| self.voip_cache.iter().any(|p| p.user_id == *user_id));
+        for participant in &self.voip_cache {
+            self.voip_activity.entry(participant.user_id).or_insert(now);
+        }
+    }
+
+    async fn reap_idle_voip_participants(&mut self) -> Result<(), ServerError> {
+        if self.voip_idle_timeout.is_none() && self.private_voip_idle_timeout.is_none() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let idle: Vec<i64> = self
+            .voip_cache
+            .iter()
+            .filter(|p| !self.speaking_users.contains(&p.user_id))
+            .filter(|p| {
+                let timeout = if p.channel_id.is_some() {
+                    self.voip_idle_timeout
+                } else {
+                    self.private_voip_idle_timeout
+                };
+                let last_activity = self.voip_activity.get(&p.user_id).copied().unwrap_or(now);
+                timeout.is_some_and(|timeout| now.duration_since(last_activity) >= timeout)
+            })
+            .map(|p| p.user_id)
+            .collect();
+
+        if idle.is_empty() {
+            return Ok(());
+        }
+
+        for user_id in idle {
+            let _ = self
+                .service
+                .logger
+                .log_entry(
+                    format!("Removed user {} from voip after inactivity", user_id),
+                    "voip".to_string(),
+                )
+                .await;
+            self.handle_voip_participant_removal(user_id).await?;
+        }
+        self.reload_voip_cache().await;
+
+        Ok(())
     }
 
     async fn reload_acl_cache(&mut self) {
@@ -388,6 +693,12 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
         }
     }
 
+    async fn reload_mute_cache(&mut self) {
+        if let Ok(mutes) = self.service.get_all_channel_mutes().await {
+            self.mute_cache = mutes;
+        }
+    }
+
     async fn reload_user_cache(&mut self) {
         if let Ok(users) = self.service.get_all_users().await {
             self.user_cache = users;
@@ -435,6 +746,12 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
         }
     }
 
+    fn get_cached_channel_mute(&self, channel_id: i64, user_id: i64) -> Option<&ChannelMute> {
+        self.mute_cache
+            .iter()
+            .find(|m| m.channel_id == channel_id && m.user_id == user_id)
+    }
+
     fn get_cached_user_role(&self, user_id: i64) -> Option<i64> {
         self.user_cache
             .iter()
@@ -442,20 +759,119 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
             .map(|u| u.role_id)
     }
 
+    fn shares_group(&self, user_id: i64, other_user_id: i64) -> bool {
+        let (Some(role_id), Some(other_role_id)) = (
+            self.get_cached_user_role(user_id),
+            self.get_cached_user_role(other_user_id),
+        ) else {
+            return false;
+        };
+
+        self.acl_cache
+            .iter()
+            .filter(|a| a.role_id == role_id && a.rights >= READ_RIGHTS)
+            .any(|a| {
+                self.acl_cache.iter().any(|b| {
+                    b.group_id == a.group_id
+                        && b.role_id == other_role_id
+                        && b.rights >= READ_RIGHTS
+                })
+            })
+    }
+
+    fn handle_subscribe_presence(&mut self, identifier: String, user_ids: Vec<i64>) {
+        if let Some(subscriber) = self
+            .observers
+            .iter_mut()
+            .find(|subscriber| subscriber.identifier == identifier)
+        {
+            subscriber.presence = Some(user_ids.into_iter().collect());
+        }
+    }
+
+    fn handle_backfill(&mut self, identifier: String, since_cursor: u64) {
+        let Some(subscriber) = self
+            .observers
+            .iter()
+            .find(|subscriber| subscriber.identifier == identifier)
+        else {
+            return;
+        };
+
+        let resync = needs_resync(&self.backlog, since_cursor, self.backfill_resync_threshold);
+        if resync {
+            self.backfill_resyncs += 1;
+        }
+
+        let mut truncated =
+            resync || since_cursor < self.backlog_floor || since_cursor > self.events_routed;
+        if !truncated {
+            let events = self
+                .backlog
+                .iter()
+                .filter(|entry| entry.cursor > since_cursor)
+                .filter(|entry| self.can_receive(subscriber, &entry.policy))
+                .filter_map(|entry| {
+                    self.prepare_event(subscriber, &entry.payload)
+                        .map(|event| (event, entry.cursor))
+                });
+            truncated = !deliver_backfill(subscriber, events);
+        }
+
+        let snapshot = truncated.then(|| self.build_snapshot(subscriber));
+        complete_backfill(subscriber, self.events_routed, snapshot);
+    }
+
+    fn record_backlog(&mut self, payload: &EventPayload, policy: &ControlRoutingPolicy) {
+        if self.backlog_capacity == 0 || !is_replayable(payload) {
+            return;
+        }
+        while self.backlog.len() >= self.backlog_capacity {
+            if let Some(evicted) = self.backlog.pop_front() {
+                self.backlog_floor = evicted.cursor;
+            }
+        }
+        self.backlog.push_back(BacklogEntry {
+            cursor: self.events_routed,
+            payload: payload.clone(),
+            policy: policy.clone(),
+        });
+    }
+
+    /// Drops a finished session. Only a connection that was lost, rather than
+    /// closed by the client, stays resumable for `RESUME_WINDOW`.
     async fn handle_timeout(
         &mut self,
         user_id: i64,
         identifier: String,
+        closed_by_client: bool,
     ) -> Result<(), ServerError> {
-        self.observers
-            .retain(|subscriber| subscriber.identifier != identifier);
+        let Some(index) = self
+            .observers
+            .iter()
+            .position(|subscriber| subscriber.identifier == identifier)
+        else {
+            return Ok(());
+        };
+        let subscriber = self.observers.remove(index);
 
-        if self.count_user_sessions(user_id) == 0 {
-            let _ = self
-                .handle_user_status_update(user_id, UserStatusType::Offline)
-                .await?;
+        if self.count_user_sessions(user_id) > 0 {
+            return Ok(());
+        }
 
-            let _ = self.handle_voip_participant_removal(user_id).await?;
+        if closed_by_client {
+            self.handle_user_status_update(user_id, UserStatusType::Offline)
+                .await?;
+            self.handle_voip_participant_removal(user_id).await?;
+        } else {
+            self.resumable_sessions.insert(
+                identifier,
+                ResumableSession {
+                    user_id,
+                    session_token: subscriber.session_token,
+                    expires_at: Instant::now() + RESUME_WINDOW,
+                },
+            );
         }
 
         Ok(())
@@ -465,22 +881,143 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
         &mut self,
         user_id: i64,
         session_id: i64,
-        sender: mpsc::Sender<SubscriberMessage>,
+        senders: SubscriberSenders,
         session_token: String,
         identifier: String,
+        resume_token: Option<String>,
+        remote_address: String,
     ) -> Result<(), ServerError> {
+        let resumed = resume_token.is_some_and(|token| {
+            let matches = self.resumable_sessions.get(&token).is_some_and(|resumable| {
+                resumable.user_id == user_id
+                    && resumable.session_token == session_token
+                    && resumable.expires_at > Instant::now()
+            });
+            if matches {
+                self.resumable_sessions.remove(&token);
+            }
+            matches
+        });
         let subscriber = SubscriberHandler {
             user_id,
             session_id,
-            sender,
-            identifier,
+            sender: senders.events,
+            control: senders.control,
+            identifier: identifier.clone(),
             session_token,
+            presence: None,
+            remote_address,
+            connected_at: OffsetDateTime::now_utc(),
+            dropped_events: 0,
+            overflow_since: None,
         };
         self.observers.push(subscriber);
+
+        if resumed {
+            let _ = self
+                .service
+                .logger
+                .log_entry(
+                    format!("User {} resumed connection", user_id),
+                    "websocket".to_string(),
+                )
+                .await;
+            return Ok(());
+        }
+
+        self.resumable_sessions
+            .retain(|_, resumable| resumable.user_id != user_id);
         let _ = self
             .handle_user_status_update(user_id, UserStatusType::Online)
             .await;
         let _ = self.handle_voip_participant_removal(user_id).await;
+
+        if let Some(subscriber) = self
+            .observers
+            .iter()
+            .find(|subscriber| subscriber.identifier == identifier)
+        {
+            let snapshot = self.build_snapshot(subscriber);
+            let _ = subscriber.try_send(SubscriberMessage::Event(snapshot, self.events_routed));
+        }
+        Ok(())
+    }
+
+    /// Users are scoped like presence events: the subscriber, anyone sharing a
+    /// readable group with them, and anyone they subscribed to explicitly.
+    fn build_snapshot(&self, subscriber: &SubscriberHandler) -> EventPayload {
+        let user_id = subscriber.user_id();
+        let role_id = self.get_cached_user_role(user_id);
+
+        let users = self
+            .user_cache
+            .iter()
+            .filter(|u| {
+                u.user_id == user_id
+                    || self.shares_group(user_id, u.user_id)
+                    || subscriber
+                        .presence
+                        .as_ref()
+                        .is_some_and(|user_ids| user_ids.contains(&u.user_id))
+            })
+            .cloned()
+            .collect();
+
+        let channels = self
+            .channel_cache
+            .iter()
+            .filter(|c| self.get_cached_channel_rights(c.channel_id, user_id) >= ACKNOWLEDGE_RIGHTS)
+            .cloned()
+            .collect();
+
+        let group_role_rights = self
+            .acl_cache
+            .iter()
+            .filter(|a| Some(a.role_id) == role_id)
+            .cloned()
+            .collect();
+
+        let voip_participants = self
+            .voip_cache
+            .iter()
+            .filter(|p| match (p.channel_id, p.recipient_id) {
+                (Some(channel_id), _) => {
+                    self.get_cached_channel_rights(channel_id, user_id) >= ACKNOWLEDGE_RIGHTS
+                }
+                (_, Some(recipient_id)) => p.user_id == user_id || recipient_id == user_id,
+                _ => false,
+            })
+            .cloned()
+            .collect();
+
+        EventPayload::Snapshot {
+            users,
+            channels,
+            group_role_rights,
+            voip_participants,
+        }
+    }
+
+    async fn expire_resumable_sessions(&mut self) -> Result<(), ServerError> {
+        let now = Instant::now();
+        let mut expired_users: Vec<i64> = Vec::new();
+
+        self.resumable_sessions.retain(|_, resumable| {
+            if resumable.expires_at > now {
+                return true;
+            }
+            expired_users.push(resumable.user_id);
+            false
+        });
+
+        for user_id in expired_users {
+            if self.count_user_sessions(user_id) == 0 {
+                self.handle_user_status_update(user_id, UserStatusType::Offline)
+                    .await?;
+                self.handle_voip_participant_removal(user_id).await?;
+            }
+        }
+
         Ok(())
     }
 
@@ -488,16 +1025,19 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
         &mut self,
         user_id: i64,
         session_token: String,
+        reason: CloseReason,
     ) -> Result<(), ServerError> {
         for o in self
             .observers
             .iter()
             .filter(|o| o.session_token == session_token)
         {
-            o.send(SubscriberMessage::Close).await;
+            o.close(reason);
         }
         self.observers
             .retain(|subscriber| subscriber.session_token != session_token);
+        self.resumable_sessions
+            .retain(|_, resumable| resumable.session_token != session_token);
         if self.count_user_sessions(user_id) == 0 {
             self.handle_user_status_update(user_id, UserStatusType::Offline)
                 .await?;
@@ -506,85 +1046,295 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
         Ok(())
     }
 
-    async fn handle_disconnect_user(&mut self, user_id: i64) -> Result<(), ServerError> {
+    async fn handle_disconnect_user(
+        &mut self,
+        user_id: i64,
+        reason: CloseReason,
+    ) -> Result<(), ServerError> {
         for o in self.observers.iter().filter(|o| o.user_id() == user_id) {
-            o.send(SubscriberMessage::Close).await;
+            o.close(reason);
         }
         self.observers
             .retain(|subscriber| subscriber.user_id() != user_id);
+        self.resumable_sessions
+            .retain(|_, resumable| resumable.user_id != user_id);
         self.handle_user_status_update(user_id, UserStatusType::Offline)
             .await?;
         self.handle_voip_participant_removal(user_id).await?;
         Ok(())
     }
 
+    async fn handle_terminate(&mut self, identifier: String) -> Result<(), ServerError> {
+        let Some(index) = self
+            .observers
+            .iter()
+            .position(|subscriber| subscriber.identifier == identifier)
+        else {
+            return Ok(());
+        };
+        let subscriber = self.observers.remove(index);
+        subscriber.close(CloseReason::Terminated);
+        self.resumable_sessions.remove(&identifier);
+
+        let user_id = subscriber.user_id();
+        if self.count_user_sessions(user_id) == 0 {
+            self.handle_user_status_update(user_id, UserStatusType::Offline)
+                .await?;
+            self.handle_voip_participant_removal(user_id).await?;
+        }
+        Ok(())
+    }
+
     async fn handle_control(
-        &self,
+        &mut self,
         payload: EventPayload,
         policy: ControlRoutingPolicy,
     ) -> Result<(), ServerError> {
+        match &payload {
+            EventPayload::SpeakStatusUpdated { user_id, speaking } => {
+                self.voip_activity.insert(*user_id, Instant::now());
+                if *speaking {
+                    self.speaking_users.insert(*user_id);
+                } else {
+                    self.speaking_users.remove(user_id);
+                }
+            }
+            EventPayload::VoipParticipantDeleted { user_id } => {
+                self.speaking_users.remove(user_id);
+                self.voip_activity.remove(user_id);
+            }
+            _ => {}
+        }
+        self.events_routed += 1;
+        self.record_backlog(&payload, &policy);
         self.route_control(payload, policy).await?;
         Ok(())
     }
 
+    fn handle_query(&self, payload: QueryPayload) {
+        match payload {
+            QueryPayload::SpeakingUsers(reply) => {
+                let _ = reply.send(self.speaking_users.iter().copied().collect());
+            }
+            QueryPayload::Metrics(reply) => {
+                let online_users: HashSet<i64> =
+                    self.observers.iter().map(|o| o.user_id).collect();
+                let _ = reply.send(RealtimeMetrics {
+                    active_connections: self.observers.len(),
+                    online_users: online_users.len(),
+                    resumable_sessions: self.resumable_sessions.len(),
+                    voip_participants: self.voip_cache.len(),
+                    speaking_users: self.speaking_users.len(),
+                    events_routed: self.events_routed,
+                    dropped_events: self.dropped_events,
+                    backfill_resyncs: self.backfill_resyncs,
+                });
+            }
+            QueryPayload::Recipients(policy, reply) => {
+                let mut user_ids: Vec<i64> = self
+                    .observers
+                    .iter()
+                    .filter(|subscriber| self.can_receive(subscriber, &policy))
+                    .map(|subscriber| subscriber.user_id())
+                    .collect();
+                user_ids.sort_unstable();
+                user_ids.dedup();
+                let _ = reply.send(user_ids);
+            }
+            QueryPayload::Connections(reply) => {
+                let connections = self
+                    .observers
+                    .iter()
+                    .map(|subscriber| ActiveConnection {
+                        user_id: subscriber.user_id(),
+                        identifier: subscriber.identifier.clone(),
+                        remote_address: subscriber.remote_address.clone(),
+                        connected_at: subscriber.connected_at,
+                    })
+                    .collect();
+                let _ = reply.send(connections);
+            }
+        }
+    }
+
     async fn handle_command(&mut self, payload: CommandPayload) -> Result<(), ServerError> {
         match payload {
-            CommandPayload::Connect(user_id, session_id, sender, identifier, session_token) => {
-                self.handle_connect(user_id, session_id, sender, session_token, identifier)
+            CommandPayload::Connect(
+                user_id,
+                session_id,
+                senders,
+                identifier,
+                session_token,
+                resume_token,
+                remote_address,
+            ) => {
+                self.handle_connect(
+                    user_id,
+                    session_id,
+                    senders,
+                    session_token,
+                    identifier,
+                    resume_token,
+                    remote_address,
+                )
+                .await?
+            }
+            CommandPayload::Timeout(user_id, token, closed_by_client) => {
+                self.handle_timeout(user_id, token, closed_by_client)
+                    .await?;
+            }
+            CommandPayload::Disconnect(user_id, session_token, reason) => {
+                self.handle_disconnect(user_id, session_token, reason)
                     .await?
             }
-            CommandPayload::Timeout(user_id, token) => {
-                self.handle_timeout(user_id, token).await?;
+            CommandPayload::DisconnectUser(user_id, reason) => {
+                self.handle_disconnect_user(user_id, reason).await?
+            }
+            CommandPayload::SubscribePresence(identifier, user_ids) => {
+                self.handle_subscribe_presence(identifier, user_ids)
             }
-            CommandPayload::Disconnect(user_id, session_token) => {
-                self.handle_disconnect(user_id, session_token).await?
+            CommandPayload::Backfill(identifier, since_cursor) => {
+                self.handle_backfill(identifier, since_cursor)
             }
-            CommandPayload::DisconnectUser(user_id) => self.handle_disconnect_user(user_id).await?,
+            CommandPayload::Terminate(identifier) => self.handle_terminate(identifier).await?,
         }
         Ok(())
     }
 
-    async fn route_control(
+    fn can_receive(&self, subscriber: &SubscriberHandler, policy: &ControlRoutingPolicy) -> bool {
+        match policy {
+            ControlRoutingPolicy::GroupRights {
+                group_id,
+                minimun_rights,
+            } => self.get_cached_group_rights(*group_id, subscriber.user_id()) >= *minimun_rights,
+            ControlRoutingPolicy::ChannelRights {
+                channel_id,
+                minimun_rights,
+            } => {
+                self.get_cached_channel_rights(*channel_id, subscriber.user_id()) >= *minimun_rights
+            }
+            ControlRoutingPolicy::User { user_id } => subscriber.user_id() == *user_id,
+            ControlRoutingPolicy::Users { user_ids } => user_ids.contains(&subscriber.user_id()),
+            ControlRoutingPolicy::Role { role_id } => {
+                self.get_cached_user_role(subscriber.user_id()) == Some(*role_id)
+            }
+            ControlRoutingPolicy::Presence { user_id } => match &subscriber.presence {
+                Some(user_ids) => {
+                    subscriber.user_id() == *user_id
+                        || user_ids.contains(user_id)
+                        || self.shares_group(subscriber.user_id(), *user_id)
+                }
+                None => true,
+            },
+            ControlRoutingPolicy::Broadcast => true,
+        }
+    }
+
+    fn prepare_event(
         &self,
+        subscriber: &SubscriberHandler,
+        payload: &EventPayload,
+    ) -> Option<EventPayload> {
+        let mut event = payload.clone();
+        if let EventPayload::MessageCreated {
+            message_type: MessageType::Channel { channel_id },
+            muted,
+            ..
+        } = &mut event
+            && let Some(mute) = self.get_cached_channel_mute(*channel_id, subscriber.user_id())
+        {
+            if mute.suppress {
+                return None;
+            }
+            *muted = true;
+        }
+        Some(event)
+    }
+
+    async fn route_control(
+        &mut self,
         payload: EventPayload,
         policy: ControlRoutingPolicy,
     ) -> Result<(), ServerError> {
-        for subscriber in &self.observers {
-            let can_receive = match &policy {
-                ControlRoutingPolicy::GroupRights {
-                    group_id,
-                    minimun_rights,
-                } => {
-                    self.get_cached_group_rights(*group_id, subscriber.user_id()) >= *minimun_rights
-                }
-                ControlRoutingPolicy::ChannelRights {
-                    channel_id,
-                    minimun_rights,
-                } => {
-                    self.get_cached_channel_rights(*channel_id, subscriber.user_id())
-                        >= *minimun_rights
-                }
-                ControlRoutingPolicy::User { user_id } => subscriber.user_id() == *user_id,
-                ControlRoutingPolicy::Users { user_ids } => user_ids.contains(&subscriber.user_id()),
-                ControlRoutingPolicy::Role { role_id } => {
-                    self.get_cached_user_role(subscriber.user_id()) == Some(*role_id)
-                }
-                ControlRoutingPolicy::Broadcast => true,
+        let lossy = matches!(payload, EventPayload::SpeakStatusUpdated { .. });
+        let mut overflowed: Vec<String> = Vec::new();
+
+        for index in 0..self.observers.len() {
+            let subscriber = &self.observers[index];
+            if !self.can_receive(subscriber, &policy) {
+                continue;
+            }
+            let Some(event) = self.prepare_event(subscriber, &payload) else {
+                continue;
             };
 
-            if can_receive {
-                subscriber
-                    .send(SubscriberMessage::Event(payload.clone()))
-                    .await;
+            let cursor = self.events_routed;
+            let subscriber = &mut self.observers[index];
+            match subscriber.try_send(SubscriberMessage::Event(event, cursor)) {
+                Ok(()) => subscriber.overflow_since = None,
+                Err(SendError::Full) => {
+                    subscriber.dropped_events += 1;
+                    self.dropped_events += 1;
+                    let since = *subscriber.overflow_since.get_or_insert_with(Instant::now);
+                    if !lossy
+                        && self.overflow_policy == OverflowPolicy::Disconnect
+                        && since.elapsed() >= self.overflow_grace
+                    {
+                        overflowed.push(subscriber.identifier.clone());
+                    } else {
+                        tracing::warn!(
+                            "Subscriber queue full, dropping event: user_id={}, identifier={}",
+                            subscriber.user_id(),
+                            subscriber.identifier
+                        );
+                    }
+                }
+                Err(SendError::Closed) => {}
             }
         }
+
+        for identifier in overflowed {
+            self.evict_subscriber(&identifier).await?;
+        }
         Ok(())
     }
 
-    pub async fn run(mut self) -> Result<(), ServerError> {
-        self.reload_voip_cache().await;
-        self.reload_acl_cache().await;
+    async fn evict_subscriber(&mut self, identifier: &str) -> Result<(), ServerError> {
+        let Some(subscriber) = self
+            .observers
+            .iter()
+            .find(|subscriber| subscriber.identifier == identifier)
+        else {
+            return Ok(());
+        };
+        let user_id = subscriber.user_id;
+        let dropped_events = subscriber.dropped_events;
+        subscriber.close(CloseReason::Overloaded);
 
+        tracing::warn!(
+            "Disconnecting subscriber with sustained backlog: user_id={}, identifier={}, dropped_events={}",
+            user_id,
+            identifier,
+            dropped_events
+        );
+        let _ = self
+            .service
+            .logger
+            .log_entry(
+                format!(
+                    "Subscriber disconnected for sustained backlog: user_id={}, dropped_events={}",
+                    user_id, dropped_events
+                ),
+                "websocket".to_string(),
+            )
+            .await;
+
+        self.resumable_sessions.remove(identifier);
+        self.handle_timeout(user_id, identifier.to_string(), true)
+            .await
+    }
+
+    pub async fn run(mut self) -> Result<(), ServerError> {
         let mut session_check_interval = interval(Duration::from_secs(5));
 
         loop {
@@ -595,13 +1345,18 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
                         ServerMessage::Control(control_payload, control_routing_policy) => {
                             self.handle_control(control_payload, control_routing_policy).await?
                         }
+                        ServerMessage::Query(payload) => self.handle_query(payload),
                         ServerMessage::InvalidateVoip => self.reload_voip_cache().await,
                         ServerMessage::InvalidateAcl => self.reload_acl_cache().await,
                         ServerMessage::InvalidateUsers => self.reload_user_cache().await,
+                        ServerMessage::InvalidateMutes => self.reload_mute_cache().await,
                     }
                 }
                 _ = session_check_interval.tick() => {
+                    self.retry_cold_caches().await;
                     let _ = self.check_expired_sessions().await;
+                    let _ = self.expire_resumable_sessions().await;
+                    let _ = self.reap_idle_voip_participants().await;
                 }
             }
         }
@@ -615,7 +1370,7 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
         if let Some(ref user) = self.service.update_user_status(user_id, status).await? {
             self.route_control(
                 EventPayload::UserUpdated { user: user.clone() },
-                ControlRoutingPolicy::Broadcast,
+                ControlRoutingPolicy::Presence { user_id },
             )
             .await?;
         }
@@ -626,11 +1381,12 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
         &mut self,
         user_id: i64,
     ) -> Result<(), ServerError> {
+        self.speaking_users.remove(&user_id);
         if let Some(ref participant) = self.service.remove_voip_participant(user_id).await? {
             let policy = if let Some(channel_id) = participant.channel_id {
                 ControlRoutingPolicy::ChannelRights {
                     channel_id,
-                    minimun_rights: 2,
+                    minimun_rights: READ_RIGHTS,
                 }
             } else {
                 ControlRoutingPolicy::User {
@@ -671,9 +1427,487 @@ impl<L: LogManager + 'static> RealtimeServer<L> {
                     "websocket".to_string(),
                 )
                 .await;
-            self.handle_disconnect(user_id, session_token).await?;
+            self.handle_disconnect(user_id, session_token, CloseReason::SessionExpired)
+                .await?;
         }
 
         Ok(())
     }
 }
+
+fn is_replayable(payload: &EventPayload) -> bool {
+    matches!(
+        payload,
+        EventPayload::MessageCreated { .. }
+            | EventPayload::MessageUpdated { .. }
+            | EventPayload::MessageDeleted { .. }
+            | EventPayload::MessagesPurged { .. }
+            | EventPayload::Mentioned { .. }
+            | EventPayload::DmRequestCreated { .. }
+            | EventPayload::DmRequestUpdated { .. }
+            | EventPayload::ReactionAdded { .. }
+            | EventPayload::ReactionRemoved { .. }
+    )
+}
+
+/// Whether a client at `since_cursor` is missing more replayable entries than
+/// the threshold. Events that never enter the backlog don't count.
+fn needs_resync(backlog: &VecDeque<BacklogEntry>, since_cursor: u64, threshold: u64) -> bool {
+    let replayed = backlog.partition_point(|entry| entry.cursor <= since_cursor);
+    threshold > 0 && (backlog.len() - replayed) as u64 > threshold
+}
+
+/// Queues replayed events for a subscriber, returning false if its queue
+/// filled up before every event went out.
+fn deliver_backfill(
+    subscriber: &SubscriberHandler,
+    events: impl IntoIterator<Item = (EventPayload, u64)>,
+) -> bool {
+    events.into_iter().all(|(event, cursor)| {
+        subscriber
+            .try_send(SubscriberMessage::Event(event, cursor))
+            .is_ok()
+    })
+}
+
+/// A truncated backfill is followed by a fresh snapshot the client resyncs
+/// from. Both ride the control queue where a full event queue can't lose them.
+fn complete_backfill(subscriber: &SubscriberHandler, cursor: u64, snapshot: Option<EventPayload>) {
+    let truncated = snapshot.is_some();
+    let complete = SubscriberMessage::BackfillComplete { cursor, truncated };
+    match snapshot {
+        Some(snapshot) => {
+            let _ = subscriber
+                .control
+                .try_send(SubscriberMessage::Event(snapshot, cursor));
+            let _ = subscriber.control.try_send(complete);
+        }
+        None => {
+            let _ = subscriber.try_send(complete);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::TextLogManager;
+    use sqlx::PgPool;
+
+    fn subscriber(
+        capacity: usize,
+    ) -> (
+        SubscriberHandler,
+        mpsc::Receiver<SubscriberMessage>,
+        mpsc::Receiver<SubscriberMessage>,
+    ) {
+        let (sender, events) = mpsc::channel(capacity);
+        let (control, control_rx) = mpsc::channel(8);
+        let handler = SubscriberHandler {
+            user_id: 1,
+            session_id: 1,
+            sender,
+            control,
+            session_token: "token".to_string(),
+            identifier: "connection".to_string(),
+            presence: None,
+            remote_address: "127.0.0.1".to_string(),
+            connected_at: OffsetDateTime::now_utc(),
+            dropped_events: 0,
+            overflow_since: None,
+        };
+        (handler, events, control_rx)
+    }
+
+    fn server(pool: PgPool) -> RealtimeServer<TextLogManager> {
+        let (sender, receiver) = mpsc::channel(16);
+        RealtimeServer::new(
+            Postgre { pool },
+            TextLogManager::new(std::env::temp_dir().join("opencord-realtime-test.log")),
+            receiver,
+            sender,
+        )
+    }
+
+    async fn connect(
+        server: &mut RealtimeServer<TextLogManager>,
+        identifier: &str,
+        resume_token: Option<&str>,
+    ) -> mpsc::Receiver<SubscriberMessage> {
+        let (events, queue) = mpsc::channel(16);
+        let (control, _) = mpsc::channel(16);
+        let _ = server
+            .handle_connect(
+                1,
+                1,
+                SubscriberSenders { events, control },
+                "token".to_string(),
+                identifier.to_string(),
+                resume_token.map(str::to_string),
+                "127.0.0.1".to_string(),
+            )
+            .await;
+        queue
+    }
+
+    fn events(cursors: std::ops::RangeInclusive<u64>) -> Vec<(EventPayload, u64)> {
+        cursors
+            .map(|cursor| (EventPayload::ChannelDeleted { channel_id: 1 }, cursor))
+            .collect()
+    }
+
+    fn snapshot() -> EventPayload {
+        EventPayload::Snapshot {
+            users: Vec::new(),
+            channels: Vec::new(),
+            group_role_rights: Vec::new(),
+            voip_participants: Vec::new(),
+        }
+    }
+
+    fn backlog(cursors: &[u64]) -> VecDeque<BacklogEntry> {
+        cursors
+            .iter()
+            .map(|&cursor| BacklogEntry {
+                cursor,
+                payload: EventPayload::ChannelDeleted { channel_id: 1 },
+                policy: ControlRoutingPolicy::Broadcast,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resync_counts_missing_backlog_entries() {
+        // Non-replayable events between these cursors must not count.
+        let backlog = backlog(&[20, 40, 60, 80, 100]);
+
+        assert!(!needs_resync(&backlog, 40, 3));
+        assert!(needs_resync(&backlog, 20, 3));
+        assert!(!needs_resync(&backlog, 100, 3));
+        assert!(!needs_resync(&backlog, 0, 0));
+    }
+
+    #[test]
+    fn backfill_into_full_queue_is_truncated() {
+        let (handler, mut queue, mut control) = subscriber(2);
+
+        let delivered = deliver_backfill(&handler, events(1..=5));
+        complete_backfill(&handler, 5, (!delivered).then(snapshot));
+
+        assert!(!delivered);
+        assert!(matches!(
+            queue.try_recv(),
+            Ok(SubscriberMessage::Event(_, 1))
+        ));
+        assert!(matches!(
+            queue.try_recv(),
+            Ok(SubscriberMessage::Event(_, 2))
+        ));
+        assert!(queue.try_recv().is_err());
+        assert!(matches!(
+            control.try_recv(),
+            Ok(SubscriberMessage::Event(EventPayload::Snapshot { .. }, 5))
+        ));
+        assert!(matches!(
+            control.try_recv(),
+            Ok(SubscriberMessage::BackfillComplete {
+                cursor: 5,
+                truncated: true
+            })
+        ));
+    }
+
+    #[test]
+    fn backfill_that_fits_completes_in_order() {
+        let (handler, mut queue, mut control) = subscriber(8);
+
+        let delivered = deliver_backfill(&handler, events(1..=3));
+        complete_backfill(&handler, 3, (!delivered).then(snapshot));
+
+        assert!(delivered);
+        for cursor in 1..=3 {
+            let Ok(SubscriberMessage::Event(_, received)) = queue.try_recv() else {
+                panic!("expected a replayed event");
+            };
+            assert_eq!(received, cursor);
+        }
+        assert!(matches!(
+            queue.try_recv(),
+            Ok(SubscriberMessage::BackfillComplete {
+                cursor: 3,
+                truncated: false
+            })
+        ));
+        assert!(control.try_recv().is_err());
+    }
+
+    #[sqlx::test]
+    async fn lost_connection_resumes_within_the_window(pool: PgPool) {
+        let mut server = server(pool);
+        connect(&mut server, "first", None).await;
+        let _ = server.handle_timeout(1, "first".to_string(), false).await;
+
+        let mut queue = connect(&mut server, "second", Some("first")).await;
+
+        assert!(queue.try_recv().is_err());
+        assert!(server.resumable_sessions.is_empty());
+        assert_eq!(server.count_user_sessions(1), 1);
+    }
+
+    #[sqlx::test]
+    async fn expired_or_unknown_resume_token_connects_fresh(pool: PgPool) {
+        let mut server = server(pool);
+        connect(&mut server, "first", None).await;
+        let _ = server.handle_timeout(1, "first".to_string(), false).await;
+
+        let mut queue = connect(&mut server, "second", Some("unknown")).await;
+        assert!(matches!(
+            queue.try_recv(),
+            Ok(SubscriberMessage::Event(EventPayload::Snapshot { .. }, _))
+        ));
+
+        let _ = server.handle_timeout(1, "second".to_string(), false).await;
+        server
+            .resumable_sessions
+            .get_mut("second")
+            .unwrap()
+            .expires_at = Instant::now();
+
+        let mut queue = connect(&mut server, "third", Some("second")).await;
+        assert!(matches!(
+            queue.try_recv(),
+            Ok(SubscriberMessage::Event(EventPayload::Snapshot { .. }, _))
+        ));
+        assert!(server.resumable_sessions.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn client_closed_connection_is_not_resumable(pool: PgPool) {
+        let mut server = server(pool);
+        connect(&mut server, "first", None).await;
+        let _ = server.handle_timeout(1, "first".to_string(), true).await;
+
+        assert!(server.resumable_sessions.is_empty());
+
+        let mut queue = connect(&mut server, "second", Some("first")).await;
+        assert!(matches!(
+            queue.try_recv(),
+            Ok(SubscriberMessage::Event(EventPayload::Snapshot { .. }, _))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn going_offline_records_last_seen(pool: PgPool) {
+        let mut repository = Postgre { pool: pool.clone() };
+        let user_id =
+            sqlx::query_scalar!("INSERT INTO users (username) VALUES ('alice') RETURNING user_id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        let online = repository
+            .update_user_status(user_id, UserStatusType::Online)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(online.last_seen_at.is_none());
+
+        let offline = repository
+            .update_user_status(user_id, UserStatusType::Offline)
+            .await
+            .unwrap()
+            .unwrap();
+        let last_seen_at = offline.last_seen_at.unwrap();
+
+        let online = repository
+            .update_user_status(user_id, UserStatusType::Online)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(online.last_seen_at, Some(last_seen_at));
+    }
+
+    #[sqlx::test]
+    async fn overflowing_subscriber_is_evicted_without_a_resume_entry(pool: PgPool) {
+        let mut server = server(pool);
+        server.overflow_grace = Duration::ZERO;
+        let _queue = connect(&mut server, "first", None).await;
+        while server.observers[0]
+            .try_send(SubscriberMessage::Event(snapshot(), 0))
+            .is_ok()
+        {}
+        server.resumable_sessions.insert(
+            "first".to_string(),
+            ResumableSession {
+                user_id: 1,
+                session_token: "token".to_string(),
+                expires_at: Instant::now() + RESUME_WINDOW,
+            },
+        );
+
+        let _ = server
+            .route_control(
+                EventPayload::ChannelDeleted { channel_id: 1 },
+                ControlRoutingPolicy::Broadcast,
+            )
+            .await;
+
+        assert!(server.observers.is_empty());
+        assert!(server.resumable_sessions.is_empty());
+    }
+
+    fn user(user_id: i64, role_id: i64) -> User {
+        User {
+            user_id,
+            username: format!("user{}", user_id),
+            display_name: None,
+            last_seen_at: None,
+            created_at: OffsetDateTime::now_utc(),
+            avatar_file_id: None,
+            role_id,
+            status: UserStatusType::Online,
+            server_mute: false,
+            server_deafen: false,
+        }
+    }
+
+    #[sqlx::test]
+    async fn snapshot_only_lists_users_sharing_a_group(pool: PgPool) {
+        let mut server = server(pool);
+        server.user_cache = vec![user(1, 3), user(2, 4), user(3, 5), user(4, 5)];
+        server.acl_cache = vec![
+            GroupRoleRights {
+                group_id: 10,
+                role_id: 3,
+                rights: READ_RIGHTS,
+            },
+            GroupRoleRights {
+                group_id: 10,
+                role_id: 4,
+                rights: READ_RIGHTS,
+            },
+            GroupRoleRights {
+                group_id: 11,
+                role_id: 5,
+                rights: READ_RIGHTS,
+            },
+        ];
+        let (mut handler, _queue, _control) = subscriber(1);
+        handler.presence = Some(HashSet::from([4]));
+
+        let EventPayload::Snapshot {
+            users,
+            group_role_rights,
+            ..
+        } = server.build_snapshot(&handler)
+        else {
+            panic!("expected a snapshot");
+        };
+
+        let user_ids: Vec<i64> = users.iter().map(|u| u.user_id).collect();
+        assert_eq!(user_ids, vec![1, 2, 4]);
+        assert_eq!(group_role_rights.len(), 1);
+        assert_eq!(group_role_rights[0].group_id, 10);
+    }
+
+    fn channel_message(channel_id: i64) -> EventPayload {
+        EventPayload::MessageCreated {
+            message_id: 1,
+            sender_id: 2,
+            message_type: MessageType::Channel { channel_id },
+            message_text: Some("hi".to_string()),
+            reply_to_message_id: None,
+            forwarded_from_message_id: None,
+            metadata: None,
+            timestamp: OffsetDateTime::now_utc(),
+            expires_at: None,
+            files: Vec::new(),
+            muted: false,
+        }
+    }
+
+    fn mute(channel_id: i64, suppress: bool) -> ChannelMute {
+        ChannelMute {
+            user_id: 1,
+            channel_id,
+            suppress,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn muted_channels_flag_or_suppress_new_messages(pool: PgPool) {
+        let mut server = server(pool);
+        server.mute_cache = vec![mute(5, false), mute(6, true)];
+        let (handler, _queue, _control) = subscriber(1);
+
+        assert!(matches!(
+            server.prepare_event(&handler, &channel_message(5)),
+            Some(EventPayload::MessageCreated { muted: true, .. })
+        ));
+        assert!(
+            server
+                .prepare_event(&handler, &channel_message(6))
+                .is_none()
+        );
+        assert!(matches!(
+            server.prepare_event(&handler, &channel_message(7)),
+            Some(EventPayload::MessageCreated { muted: false, .. })
+        ));
+    }
+
+    async fn delete_message(server: &mut RealtimeServer<TextLogManager>, message_id: i64) {
+        let event = EventPayload::MessageDeleted { message_id };
+        let policy = ControlRoutingPolicy::User { user_id: 1 };
+        assert!(server.handle_control(event, policy).await.is_ok());
+    }
+
+    /// Drains the client queue, returning the deleted message ids with their cursors.
+    fn deleted_messages(queue: &mut mpsc::Receiver<SubscriberMessage>) -> Vec<(i64, u64)> {
+        let mut received = Vec::new();
+        while let Ok(message) = queue.try_recv() {
+            match message {
+                SubscriberMessage::Event(EventPayload::MessageDeleted { message_id }, cursor) => {
+                    received.push((message_id, cursor))
+                }
+                SubscriberMessage::BackfillComplete { truncated, .. } => assert!(!truncated),
+                _ => {}
+            }
+        }
+        received
+    }
+
+    #[sqlx::test]
+    async fn backfill_recovers_dropped_frames_and_events_missed_while_disconnected(pool: PgPool) {
+        let mut server = server(pool);
+        let mut queue = connect(&mut server, "first", None).await;
+        deleted_messages(&mut queue);
+
+        for message_id in 1..=3 {
+            delete_message(&mut server, message_id).await;
+        }
+        let delivered = deleted_messages(&mut queue);
+        assert_eq!(
+            delivered.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        // The client lost frame 2, noticed the cursor gap at frame 3 and asks
+        // for everything after the last frame it kept.
+        server.handle_backfill("first".to_string(), delivered[0].1);
+        assert_eq!(deleted_messages(&mut queue), delivered[1..]);
+
+        let _ = server.handle_timeout(1, "first".to_string(), false).await;
+        delete_message(&mut server, 4).await;
+        delete_message(&mut server, 5).await;
+
+        let mut queue = connect(&mut server, "second", Some("first")).await;
+        assert!(deleted_messages(&mut queue).is_empty());
+        server.handle_backfill("second".to_string(), delivered[2].1);
+        let recovered = deleted_messages(&mut queue);
+        assert_eq!(
+            recovered.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+    }
+}
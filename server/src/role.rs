@@ -51,6 +51,9 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 }
@@ -59,7 +62,8 @@ impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -296,7 +300,7 @@ impl<R: RoleRepository, N: NotifierManager, G: LogManager> RoleService<R, N, G>
             self.repository
                 .find_by_id(role_id)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "Role {} not found",
                     role_id
                 )))?;
@@ -362,7 +366,7 @@ impl<R: RoleRepository, N: NotifierManager, G: LogManager> RoleService<R, N, G>
                 ),
                 other => DomainError::InternalError(other),
             })?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Role {} not found",
                 role_id
             )))?;
@@ -429,7 +433,7 @@ impl<R: RoleRepository, N: NotifierManager, G: LogManager> RoleService<R, N, G>
         let deleted = tx
             .delete(role_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Role {} not found",
                 role_id
             )))?;
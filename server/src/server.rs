@@ -11,12 +11,34 @@ use crate::managers::{
 use crate::middleware::{AuthorizeService, authorize};
 use crate::model::{EventPayload, ServerConfig};
 use crate::role::ADMIN_ROLE_ID;
-use crate::user::AvatarFile;
-use crate::transport::{ControlRoutingPolicy, ServerMessage};
+use crate::user::{AvatarFile, avatar_response};
+use crate::transport::{
+    ActiveConnection, CommandPayload, ControlRoutingPolicy, QueryPayload, ServerMessage,
+};
 
 use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use tokio::sync::oneshot;
 use uuid::Uuid;
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomEmoji {
+    pub emoji_id: i64,
+    pub name: String,
+    pub avatar_file_id: i64,
+    pub created_by: Option<i64>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+pub fn is_valid_emoji_name(name: &str) -> bool {
+    (2..=30).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DomainError {
     #[error("Bad request: {0}")]
@@ -25,12 +47,17 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 
     #[error("File manager error")]
     FileManagerError(#[from] FileError),
 
+    #[error("Realtime server unavailable")]
+    RealtimeUnavailable,
 }
 
 pub trait ServerTransaction: Send + Sync {
@@ -53,11 +80,33 @@ pub trait ServerTransaction: Send + Sync {
         name: &str,
     ) -> Result<Option<ServerConfig>, DatabaseError>;
 
+    async fn update_server_description(
+        &mut self,
+        description: &str,
+    ) -> Result<Option<ServerConfig>, DatabaseError>;
+
     async fn update_file_limits(
         &mut self,
         max_file_size_mb: i32,
         max_files_per_message: i32,
     ) -> Result<Option<ServerConfig>, DatabaseError>;
+
+    async fn update_voip_limits(
+        &mut self,
+        max_voip_participants: i32,
+    ) -> Result<Option<ServerConfig>, DatabaseError>;
+
+    async fn update_registration_approval(
+        &mut self,
+        require_registration_approval: bool,
+    ) -> Result<Option<ServerConfig>, DatabaseError>;
+
+    async fn create_custom_emoji(
+        &mut self,
+        name: &str,
+        avatar_file_id: i64,
+        created_by: i64,
+    ) -> Result<CustomEmoji, DatabaseError>;
 }
 
 pub trait ServerRepository: Send + Sync + Clone {
@@ -74,6 +123,8 @@ pub trait ServerRepository: Send + Sync + Clone {
     async fn find_avatar_file(&self, avatar_id: i64) -> Result<Option<AvatarFile>, DatabaseError>;
 
     async fn find_user_role(&mut self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_custom_emojis(&self) -> Result<Vec<CustomEmoji>, DatabaseError>;
 }
 
 #[derive(Clone)]
@@ -122,7 +173,7 @@ impl<R: ServerRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         let role_id = repo
             .find_user_role(user_id)
             .await?
-            .ok_or(DomainError::BadRequest("User not found".to_string()))?;
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
 
         if role_id > ADMIN_ROLE_ID {
             return Err(DomainError::PermissionDenied(
@@ -166,6 +217,67 @@ impl<R: ServerRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         Ok(config)
     }
 
+    pub async fn update_description(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        description: String,
+    ) -> Result<ServerConfig, DomainError> {
+        let mut repo = self.repository.clone();
+        let role_id = repo
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
+
+        if role_id > ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only admins can update server settings".to_string(),
+            ));
+        }
+
+        let description = description.trim();
+        if description.chars().count() > 500 {
+            return Err(DomainError::BadRequest(
+                "Server description too long".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let config = tx
+            .update_server_description(description)
+            .await?
+            .ok_or(DomainError::BadRequest(
+                "Server config not found".to_string(),
+            ))?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::ServerUpdated {
+            server: config.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Broadcast,
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Server description updated: user_id={}, session_id={}",
+                    user_id, session_id
+                ),
+                "server".to_string(),
+            )
+            .await;
+
+        Ok(config)
+    }
+
     pub async fn update_file_limits(
         &self,
         user_id: i64,
@@ -177,7 +289,7 @@ impl<R: ServerRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         let role_id = repo
             .find_user_role(user_id)
             .await?
-            .ok_or(DomainError::BadRequest("User not found".to_string()))?;
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
 
         if role_id > ADMIN_ROLE_ID {
             return Err(DomainError::PermissionDenied(
@@ -221,6 +333,120 @@ impl<R: ServerRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         Ok(config)
     }
 
+    pub async fn update_voip_limits(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        max_voip_participants: i32,
+    ) -> Result<ServerConfig, DomainError> {
+        let mut repo = self.repository.clone();
+        let role_id = repo
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
+
+        if role_id > ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only admins can update server settings".to_string(),
+            ));
+        }
+
+        if max_voip_participants < 1 {
+            return Err(DomainError::BadRequest(
+                "Max voice participants must be at least 1".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let config = tx
+            .update_voip_limits(max_voip_participants)
+            .await?
+            .ok_or(DomainError::BadRequest(
+                "Server config not found".to_string(),
+            ))?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::ServerUpdated {
+            server: config.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Broadcast,
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "VoIP limits updated: user_id={}, session_id={}, max_voip_participants={}",
+                    user_id, session_id, max_voip_participants
+                ),
+                "server".to_string(),
+            )
+            .await;
+
+        Ok(config)
+    }
+
+    pub async fn update_registration_approval(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        require_registration_approval: bool,
+    ) -> Result<ServerConfig, DomainError> {
+        let mut repo = self.repository.clone();
+        let role_id = repo
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
+
+        if role_id > ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only admins can update server settings".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let config = tx
+            .update_registration_approval(require_registration_approval)
+            .await?
+            .ok_or(DomainError::BadRequest(
+                "Server config not found".to_string(),
+            ))?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::ServerUpdated {
+            server: config.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Broadcast,
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Registration approval updated: user_id={}, session_id={}, require_registration_approval={}",
+                    user_id, session_id, require_registration_approval
+                ),
+                "server".to_string(),
+            )
+            .await;
+
+        Ok(config)
+    }
+
     pub async fn update_avatar(
         &self,
         user_id: i64,
@@ -232,7 +458,7 @@ impl<R: ServerRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         let role_id = repo
             .find_user_role(user_id)
             .await?
-            .ok_or(DomainError::BadRequest("User not found".to_string()))?;
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
 
         if role_id > ADMIN_ROLE_ID {
             return Err(DomainError::PermissionDenied(
@@ -303,6 +529,103 @@ impl<R: ServerRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
         Ok(config)
     }
 
+    pub async fn get_custom_emojis(&self) -> Result<Vec<CustomEmoji>, DomainError> {
+        let emojis = self.repository.find_custom_emojis().await?;
+        Ok(emojis)
+    }
+
+    pub async fn create_custom_emoji(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        name: String,
+        file_name: String,
+        file_data: Vec<u8>,
+    ) -> Result<CustomEmoji, DomainError> {
+        let mut repo = self.repository.clone();
+        let role_id = repo
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
+
+        if role_id > ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only admins can upload custom emojis".to_string(),
+            ));
+        }
+
+        if !is_valid_emoji_name(&name) {
+            return Err(DomainError::BadRequest(
+                "Emoji name must be 2-30 characters of a-z, 0-9 or _".to_string(),
+            ));
+        }
+
+        if file_data.len() > 256 * 1024 {
+            return Err(DomainError::BadRequest(
+                "Emoji exceeds 256 KB limit".to_string(),
+            ));
+        }
+
+        let content_type = infer::get(&file_data)
+            .filter(|kind| kind.mime_type().starts_with("image/"))
+            .map(|kind| kind.mime_type().to_string())
+            .ok_or(DomainError::BadRequest("Emoji must be an image".to_string()))?;
+
+        let file_uuid = Uuid::new_v4().to_string();
+        let file_hash = format!("{:x}", Sha256::digest(&file_data));
+
+        let mut tx = self.repository.begin().await?;
+
+        let avatar_file = tx
+            .create_avatar(
+                &file_uuid,
+                &file_name,
+                &content_type,
+                file_data.len() as i64,
+                &file_hash,
+            )
+            .await?;
+
+        let emoji = tx
+            .create_custom_emoji(&name, avatar_file.file_id, user_id)
+            .await
+            .map_err(|e| match e {
+                DatabaseError::UniqueConstraintViolation { .. } => {
+                    DomainError::BadRequest(format!("Emoji :{}: already exists", name))
+                }
+                _ => DomainError::InternalError(e),
+            })?;
+
+        self.repository.commit(tx).await?;
+
+        self.file_manager
+            .upload_file(avatar_file.file_id, &file_data)?;
+
+        let event = EventPayload::CustomEmojiCreated {
+            emoji: emoji.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Broadcast,
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Custom emoji created: user_id={}, session_id={}, emoji_id={}, name={}",
+                    user_id, session_id, emoji.emoji_id, emoji.name
+                ),
+                "server".to_string(),
+            )
+            .await;
+
+        Ok(emoji)
+    }
+
     pub async fn get_avatar_by_id(
         &self,
         avatar_id: i64,
@@ -311,20 +634,98 @@ impl<R: ServerRepository, F: FileManager + Clone + Send, N: NotifierManager, G:
             self.repository
                 .find_avatar_file(avatar_id)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "Avatar {} not found",
                     avatar_id
                 )))?;
 
         let file_data = self.file_manager.get_file(avatar_id).map_err(|e| match e {
             FileError::NotFound(_) => {
-                DomainError::BadRequest(format!("Avatar {} not found", avatar_id))
+                DomainError::NotFound(format!("Avatar {} not found", avatar_id))
             }
             _ => DomainError::FileManagerError(e),
         })?;
 
         Ok((avatar_file, file_data))
     }
+
+    pub async fn list_connections(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<ActiveConnection>, DomainError> {
+        let mut repo = self.repository.clone();
+        let role_id = repo
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
+
+        if role_id > ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only admins can inspect active connections".to_string(),
+            ));
+        }
+
+        self.active_connections().await
+    }
+
+    pub async fn terminate_connection(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        identifier: String,
+    ) -> Result<(), DomainError> {
+        let mut repo = self.repository.clone();
+        let role_id = repo
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
+
+        if role_id > ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Only admins can terminate connections".to_string(),
+            ));
+        }
+
+        let connection = self
+            .active_connections()
+            .await?
+            .into_iter()
+            .find(|connection| connection.identifier == identifier)
+            .ok_or(DomainError::NotFound(format!(
+                "Connection {} not found",
+                identifier
+            )))?;
+
+        self.notifier
+            .notify(ServerMessage::Command(CommandPayload::Terminate(
+                identifier.clone(),
+            )))
+            .await
+            .map_err(|_| DomainError::RealtimeUnavailable)?;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Connection terminated: user_id={}, session_id={}, target_user_id={}, identifier={}",
+                    user_id, session_id, connection.user_id, identifier
+                ),
+                "server".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    async fn active_connections(&self) -> Result<Vec<ActiveConnection>, DomainError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.notifier
+            .notify(ServerMessage::Query(QueryPayload::Connections(reply_tx)))
+            .await
+            .map_err(|_| DomainError::RealtimeUnavailable)?;
+
+        reply_rx.await.map_err(|_| DomainError::RealtimeUnavailable)
+    }
 }
 
 pub struct PgServerTransaction {
@@ -366,7 +767,7 @@ impl ServerTransaction for PgServerTransaction {
             r#"UPDATE server_config
                SET avatar_file_id = $1
                WHERE id = 1
-               RETURNING id, server_name, avatar_file_id, max_file_size_mb, max_files_per_message"#,
+               RETURNING id, server_name, description, avatar_file_id, max_file_size_mb, max_files_per_message, max_voip_participants, require_registration_approval"#,
             avatar_file_id
         )
         .fetch_optional(&mut *self.transaction)
@@ -384,7 +785,7 @@ impl ServerTransaction for PgServerTransaction {
             r#"UPDATE server_config
                SET server_name = $1
                WHERE id = 1
-               RETURNING id, server_name, avatar_file_id, max_file_size_mb, max_files_per_message"#,
+               RETURNING id, server_name, description, avatar_file_id, max_file_size_mb, max_files_per_message, max_voip_participants, require_registration_approval"#,
             name
         )
         .fetch_optional(&mut *self.transaction)
@@ -393,6 +794,24 @@ impl ServerTransaction for PgServerTransaction {
         Ok(result)
     }
 
+    async fn update_server_description(
+        &mut self,
+        description: &str,
+    ) -> Result<Option<ServerConfig>, DatabaseError> {
+        let result = sqlx::query_as!(
+            ServerConfig,
+            r#"UPDATE server_config
+               SET description = $1
+               WHERE id = 1
+               RETURNING id, server_name, description, avatar_file_id, max_file_size_mb, max_files_per_message, max_voip_participants, require_registration_approval"#,
+            description
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
     async fn update_file_limits(
         &mut self,
         max_file_size_mb: i32,
@@ -403,7 +822,7 @@ impl ServerTransaction for PgServerTransaction {
             r#"UPDATE server_config
                SET max_file_size_mb = $1, max_files_per_message = $2
                WHERE id = 1
-               RETURNING id, server_name, avatar_file_id, max_file_size_mb, max_files_per_message"#,
+               RETURNING id, server_name, description, avatar_file_id, max_file_size_mb, max_files_per_message, max_voip_participants, require_registration_approval"#,
             max_file_size_mb,
             max_files_per_message
         )
@@ -412,6 +831,63 @@ impl ServerTransaction for PgServerTransaction {
 
         Ok(result)
     }
+
+    async fn update_voip_limits(
+        &mut self,
+        max_voip_participants: i32,
+    ) -> Result<Option<ServerConfig>, DatabaseError> {
+        let result = sqlx::query_as!(
+            ServerConfig,
+            r#"UPDATE server_config
+               SET max_voip_participants = $1
+               WHERE id = 1
+               RETURNING id, server_name, description, avatar_file_id, max_file_size_mb, max_files_per_message, max_voip_participants, require_registration_approval"#,
+            max_voip_participants
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn update_registration_approval(
+        &mut self,
+        require_registration_approval: bool,
+    ) -> Result<Option<ServerConfig>, DatabaseError> {
+        let result = sqlx::query_as!(
+            ServerConfig,
+            r#"UPDATE server_config
+               SET require_registration_approval = $1
+               WHERE id = 1
+               RETURNING id, server_name, description, avatar_file_id, max_file_size_mb, max_files_per_message, max_voip_participants, require_registration_approval"#,
+            require_registration_approval
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn create_custom_emoji(
+        &mut self,
+        name: &str,
+        avatar_file_id: i64,
+        created_by: i64,
+    ) -> Result<CustomEmoji, DatabaseError> {
+        let result = sqlx::query_as!(
+            CustomEmoji,
+            r#"INSERT INTO custom_emojis (name, avatar_file_id, created_by)
+               VALUES ($1, $2, $3)
+               RETURNING emoji_id, name, avatar_file_id, created_by, created_at"#,
+            name,
+            avatar_file_id,
+            created_by
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(result)
+    }
 }
 
 impl ServerRepository for Postgre {
@@ -435,7 +911,7 @@ impl ServerRepository for Postgre {
     async fn get_server_config(&self) -> Result<Option<ServerConfig>, DatabaseError> {
         let result = sqlx::query_as!(
             ServerConfig,
-            r#"SELECT id, server_name, avatar_file_id, max_file_size_mb, max_files_per_message
+            r#"SELECT id, server_name, description, avatar_file_id, max_file_size_mb, max_files_per_message, max_voip_participants, require_registration_approval
                FROM server_config
                WHERE id = 1"#
         )
@@ -470,6 +946,19 @@ impl ServerRepository for Postgre {
         .await?;
         Ok(result.map(|r| r.0))
     }
+
+    async fn find_custom_emojis(&self) -> Result<Vec<CustomEmoji>, DatabaseError> {
+        let result = sqlx::query_as!(
+            CustomEmoji,
+            r#"SELECT emoji_id, name, avatar_file_id, created_by, created_at
+               FROM custom_emojis
+               ORDER BY name"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -478,6 +967,12 @@ pub struct UpdateServerNameRequest {
     pub server_name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateServerDescriptionRequest {
+    pub description: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateFileLimitsRequest {
@@ -485,11 +980,24 @@ pub struct UpdateFileLimitsRequest {
     pub max_files_per_message: i32,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateVoipLimitsRequest {
+    pub max_voip_participants: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRegistrationApprovalRequest {
+    pub require_registration_approval: bool,
+}
+
 impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -498,6 +1006,10 @@ impl From<DomainError> for ApiError {
                 tracing::error!("File manager error: {}", file_err);
                 ApiError::InternalServerError("File system error".to_string())
             }
+            DomainError::RealtimeUnavailable => {
+                tracing::error!("Realtime server unavailable");
+                ApiError::InternalServerError("Internal server error".to_string())
+            }
         }
     }
 }
@@ -505,6 +1017,7 @@ impl From<DomainError> for ApiError {
 use axum::{
     Json,
     extract::{Extension, Multipart, Path, State},
+    http::HeaderMap,
     middleware::from_fn_with_state,
     response::IntoResponse,
 };
@@ -517,13 +1030,23 @@ pub fn server_routes(
     server_service: AppServerService,
     authorize_service: AuthorizeService<Postgre>,
 ) -> OpenApiRouter<Postgre> {
-    OpenApiRouter::new()
-        .routes(routes!(get_server_config_handler))
+    let public_routes = OpenApiRouter::new().routes(routes!(get_server_config_handler));
+
+    let protected_routes = OpenApiRouter::new()
         .routes(routes!(get_server_avatar_handler))
         .routes(routes!(update_server_name_handler))
+        .routes(routes!(update_server_description_handler))
         .routes(routes!(update_server_avatar_handler))
         .routes(routes!(update_file_limits_handler))
-        .layer(from_fn_with_state(authorize_service, authorize))
+        .routes(routes!(update_voip_limits_handler))
+        .routes(routes!(update_registration_approval_handler))
+        .routes(routes!(get_custom_emojis_handler, create_custom_emoji_handler))
+        .routes(routes!(get_connections_handler))
+        .routes(routes!(terminate_connection_handler))
+        .layer(from_fn_with_state(authorize_service, authorize));
+
+    public_routes
+        .merge(protected_routes)
         .with_state(server_service)
 }
 
@@ -534,12 +1057,10 @@ pub fn server_routes(
     responses(
         (status = 200, description = "Server config retrieved successfully", body = ServerConfig),
         (status = 500, description = "Internal Server Error", body = ApiError),
-    ),
-    security(("api_key" = []))
+    )
 )]
 async fn get_server_config_handler(
     State(service): State<AppServerService>,
-    Extension(_session): Extension<Session>,
 ) -> Result<Json<ServerConfig>, ApiError> {
     let config = service.get_config().await.map_err(ApiError::from)?;
     Ok(Json(config))
@@ -569,6 +1090,31 @@ async fn update_server_name_handler(
     Ok(Json(config))
 }
 
+#[utoipa::path(
+    put,
+    tag = "server",
+    path = "/description",
+    request_body = UpdateServerDescriptionRequest,
+    responses(
+        (status = 200, description = "Server description updated successfully", body = ServerConfig),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 422, description = "Invalid input", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn update_server_description_handler(
+    State(service): State<AppServerService>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<UpdateServerDescriptionRequest>,
+) -> Result<Json<ServerConfig>, ApiError> {
+    let config = service
+        .update_description(session.user_id, session.session_id, payload.description)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(config))
+}
+
 #[utoipa::path(
     post,
     tag = "server",
@@ -640,6 +1186,63 @@ async fn update_file_limits_handler(
     Ok(())
 }
 
+#[utoipa::path(
+    put,
+    tag = "server",
+    path = "/voip-limits",
+    request_body = UpdateVoipLimitsRequest,
+    responses(
+        (status = 200, description = "VoIP limits updated successfully"),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 422, description = "Invalid input", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn update_voip_limits_handler(
+    State(service): State<AppServerService>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<UpdateVoipLimitsRequest>,
+) -> Result<(), ApiError> {
+    service
+        .update_voip_limits(
+            session.user_id,
+            session.session_id,
+            payload.max_voip_participants,
+        )
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    tag = "server",
+    path = "/registration-approval",
+    request_body = UpdateRegistrationApprovalRequest,
+    responses(
+        (status = 200, description = "Registration approval setting updated successfully"),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn update_registration_approval_handler(
+    State(service): State<AppServerService>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<UpdateRegistrationApprovalRequest>,
+) -> Result<(), ApiError> {
+    service
+        .update_registration_approval(
+            session.user_id,
+            session.session_id,
+            payload.require_registration_approval,
+        )
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
 #[utoipa::path(
     get,
     tag = "server",
@@ -649,7 +1252,10 @@ async fn update_file_limits_handler(
     ),
     responses(
         (status = 200, description = "Avatar retrieved successfully"),
+        (status = 206, description = "Partial avatar content"),
+        (status = 304, description = "Avatar not modified"),
         (status = 404, description = "Avatar not found", body = ApiError),
+        (status = 416, description = "Range not satisfiable"),
         (status = 500, description = "Internal Server Error", body = ApiError),
     ),
     security(("api_key" = []))
@@ -658,20 +1264,130 @@ async fn get_server_avatar_handler(
     State(service): State<AppServerService>,
     Extension(_session): Extension<Session>,
     Path(avatar_id): Path<i64>,
+    request_headers: HeaderMap,
 ) -> Result<impl IntoResponse, ApiError> {
     let (avatar_file, file_data) = service
         .get_avatar_by_id(avatar_id)
         .await
         .map_err(ApiError::from)?;
 
-    let headers = [
-        ("Content-Type", avatar_file.file_type),
-        (
-            "Content-Disposition",
-            format!("inline; filename=\"{}\"", avatar_file.file_name),
-        ),
-        ("Cache-Control", "public, max-age=3600".to_string()),
-    ];
+    Ok(avatar_response(avatar_file, file_data, &request_headers))
+}
+
+#[utoipa::path(
+    get,
+    tag = "server",
+    path = "/emojis",
+    responses(
+        (status = 200, description = "Custom emojis retrieved successfully", body = Vec<CustomEmoji>),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_custom_emojis_handler(
+    State(service): State<AppServerService>,
+    Extension(_session): Extension<Session>,
+) -> Result<Json<Vec<CustomEmoji>>, ApiError> {
+    let emojis = service.get_custom_emojis().await.map_err(ApiError::from)?;
+    Ok(Json(emojis))
+}
+
+#[utoipa::path(
+    post,
+    tag = "server",
+    path = "/emojis",
+    request_body(content_type = "multipart/form-data"),
+    responses(
+        (status = 200, body = CustomEmoji),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn create_custom_emoji_handler(
+    State(service): State<AppServerService>,
+    Extension(session): Extension<Session>,
+    mut multipart: Multipart,
+) -> Result<Json<CustomEmoji>, ApiError> {
+    let mut name: Option<String> = None;
+    let mut file: Option<(String, Vec<u8>)> = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        ApiError::UnprocessableEntity(format!("Failed to read multipart field: {}", e))
+    })? {
+        match field.name().unwrap_or_default() {
+            "name" => {
+                name = Some(field.text().await.map_err(|e| {
+                    ApiError::UnprocessableEntity(format!("Invalid emoji name: {}", e))
+                })?);
+            }
+            "file" => {
+                let file_name = field.file_name().unwrap_or("emoji").to_string();
+                let data = field.bytes().await.map_err(|e| {
+                    ApiError::UnprocessableEntity(format!("Failed to read file: {}", e))
+                })?;
+                file = Some((file_name, data.to_vec()));
+            }
+            _ => {}
+        }
+    }
+
+    let name = name.ok_or(ApiError::UnprocessableEntity(
+        "No emoji name provided".to_string(),
+    ))?;
+    let (file_name, data) =
+        file.ok_or(ApiError::UnprocessableEntity("No file provided".to_string()))?;
+
+    let emoji = service
+        .create_custom_emoji(session.user_id, session.session_id, name, file_name, data)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(emoji))
+}
+
+#[utoipa::path(
+    get,
+    tag = "server",
+    path = "/connections",
+    responses(
+        (status = 200, description = "Active connections retrieved successfully", body = Vec<ActiveConnection>),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_connections_handler(
+    State(service): State<AppServerService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<ActiveConnection>>, ApiError> {
+    let connections = service
+        .list_connections(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(connections))
+}
 
-    Ok((headers, file_data))
+#[utoipa::path(
+    post,
+    tag = "server",
+    path = "/connections/{identifier}/terminate",
+    params(("identifier" = String, Path, description = "Connection identifier")),
+    responses(
+        (status = 200, description = "Connection terminated successfully"),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 404, description = "Connection not found", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn terminate_connection_handler(
+    State(service): State<AppServerService>,
+    Extension(session): Extension<Session>,
+    Path(identifier): Path<String>,
+) -> Result<(), ApiError> {
+    service
+        .terminate_connection(session.user_id, session.session_id, identifier)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
 }
@@ -1,28 +1,126 @@
+use crate::acl::READ_RIGHTS;
 use crate::auth::Session;
 use crate::db::Postgre;
 use crate::error::DatabaseError;
 use crate::managers::LogManager;
+use crate::metrics::TransportMetrics;
 use crate::model::EventPayload;
 use crate::transport::{
-    CommandPayload, ConnectionMessage, ControlRoutingPolicy, DomainError, ServerMessage,
-    SubscriberMessage,
+    CloseReason, CommandPayload, ConnectionMessage, ControlRoutingPolicy, DomainError,
+    ServerMessage, SubscriberMessage, SubscriberSenders,
 };
 use crate::voip::VoipParticipant;
 use axum::extract::ws::{CloseFrame, Message, WebSocket};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
 use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
 use tokio::time::interval;
 
-const CLOSE_CODE_DISCONNECTED: u16 = 4002;
-
 // ═══════════════════════════════════════════════════════════════════════════════
 // CONSTANTS
 // ═══════════════════════════════════════════════════════════════════════════════
 
 pub const PING_INTERVAL_MS: u64 = 5000;
 pub const PONG_TIMEOUT_MS: u64 = 10000;
+pub const MAX_PONG_TIMEOUT_MS: u64 = 60000;
 pub const MAX_MISSED_PONGS: usize = 3;
+pub const DEFAULT_MAX_CONNECTIONS: usize = 10000;
+pub const DEFAULT_CONNECTIONS_PER_MINUTE: u32 = 30;
+pub const MAX_ECHO_PAYLOAD_LENGTH: usize = 1024;
+pub const CONGESTION_ENTER_RTT_MS: u64 = 500;
+pub const CONGESTION_EXIT_RTT_MS: u64 = 250;
+pub const DEFAULT_COMPRESSION_MIN_BYTES: usize = 1024;
+/// Never emitted by MessagePack, so it marks a frame as zlib-compressed.
+pub const COMPRESSED_FRAME_FLAG: u8 = 0xc1;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONFIG
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeepAliveConfig {
+    pub ping_interval: Duration,
+    pub pong_timeout: Duration,
+    pub max_missed_pongs: usize,
+}
+
+impl KeepAliveConfig {
+    pub fn new(ping_interval: Duration, pong_timeout: Duration, max_missed_pongs: usize) -> Self {
+        Self {
+            ping_interval,
+            pong_timeout,
+            max_missed_pongs,
+        }
+    }
+
+    pub fn with_pong_timeout_ms(self, pong_timeout_ms: Option<u64>) -> Self {
+        let Some(ms) = pong_timeout_ms else {
+            return self;
+        };
+        let min = self.pong_timeout.as_millis() as u64;
+        Self {
+            pong_timeout: Duration::from_millis(ms.clamp(min, MAX_PONG_TIMEOUT_MS.max(min))),
+            ..self
+        }
+    }
+}
+
+impl Default for KeepAliveConfig {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_millis(PING_INTERVAL_MS),
+            Duration::from_millis(PONG_TIMEOUT_MS),
+            MAX_MISSED_PONGS,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionLimiter {
+    active: Arc<AtomicUsize>,
+    max_connections: usize,
+}
+
+impl ConnectionLimiter {
+    pub fn new(max_connections: usize) -> Self {
+        Self {
+            active: Arc::new(AtomicUsize::new(0)),
+            max_connections,
+        }
+    }
+
+    pub fn try_acquire(&self) -> Option<ConnectionPermit> {
+        self.active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < self.max_connections).then_some(active + 1)
+            })
+            .ok()?;
+
+        Some(ConnectionPermit {
+            active: self.active.clone(),
+        })
+    }
+}
+
+impl Default for ConnectionLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONNECTIONS)
+    }
+}
+
+pub struct ConnectionPermit {
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // ERROR
@@ -113,6 +211,13 @@ impl<R: SessionRepository, L: LogManager> SessionService<R, L> {
 // PING TRACKING
 // ═══════════════════════════════════════════════════════════════════════════════
 
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
 struct PendingPing {
     timestamp: u64,
     sent_at: Instant,
@@ -122,15 +227,49 @@ struct PendingPing {
 // SESSION
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Falls back to the plain frame when compression does not make it smaller.
+fn compress_frame(bytes: Vec<u8>) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = ZlibEncoder::new(vec![COMPRESSED_FRAME_FLAG], Compression::fast());
+    if encoder.write_all(&bytes).is_err() {
+        return bytes;
+    }
+    match encoder.finish() {
+        Ok(compressed) if compressed.len() < bytes.len() => compressed,
+        _ => bytes,
+    }
+}
+
+async fn send_close(
+    ws_sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    reason: CloseReason,
+) {
+    let close_frame = CloseFrame {
+        code: reason.code(),
+        reason: reason.description().into(),
+    };
+    let _ = ws_sender.send(Message::Close(Some(close_frame))).await;
+}
+
 pub struct SubscriberSession<R: SessionRepository, L: LogManager> {
     session: Session,
     observer_tx: mpsc::Sender<ServerMessage>,
-    server_tx: mpsc::Sender<SubscriberMessage>,
+    server_tx: Option<mpsc::Sender<SubscriberMessage>>,
     server_rx: mpsc::Receiver<SubscriberMessage>,
+    control_tx: Option<mpsc::Sender<SubscriberMessage>>,
+    control_rx: mpsc::Receiver<SubscriberMessage>,
     service: SessionService<R, L>,
     identifier: String,
+    resume_token: Option<String>,
+    remote_address: String,
     pending_pings: Vec<PendingPing>,
     missed_pongs: usize,
+    smoothed_rtt_ms: Option<u64>,
+    congested: bool,
+    compression_min_bytes: Option<usize>,
+    keepalive: KeepAliveConfig,
+    metrics: TransportMetrics,
 }
 
 impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
@@ -138,41 +277,98 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
         observer_tx: mpsc::Sender<ServerMessage>,
         service: SessionService<R, L>,
         identifier: String,
+        resume_token: Option<String>,
+        remote_address: String,
         session: Session,
+        keepalive: KeepAliveConfig,
+        metrics: TransportMetrics,
     ) -> Self {
         let (server_tx, server_rx) = mpsc::channel(10000);
+        let (control_tx, control_rx) = mpsc::channel(8);
         Self {
             session,
             observer_tx,
-            server_tx,
+            server_tx: Some(server_tx),
             server_rx,
+            control_tx: Some(control_tx),
+            control_rx,
             service,
             identifier,
+            resume_token,
+            remote_address,
             pending_pings: Vec::new(),
             missed_pongs: 0,
+            smoothed_rtt_ms: None,
+            congested: false,
+            compression_min_bytes: None,
+            keepalive,
+            metrics,
         }
     }
 
+    /// Frames of at least `min_bytes` are sent compressed. Only enabled for
+    /// clients that asked for it when connecting.
+    pub fn with_compression(mut self, min_bytes: Option<usize>) -> Self {
+        self.compression_min_bytes = min_bytes;
+        self
+    }
+
     pub async fn run(&mut self, socket: WebSocket) {
         let (mut ws_sender, mut ws_receiver) = socket.split();
+        let (Some(server_tx), Some(control_tx)) = (self.server_tx.take(), self.control_tx.take())
+        else {
+            return;
+        };
 
+        // The router holds the only senders, so dropping this subscriber there
+        // (e.g. for a sustained backlog) closes the queue and ends the session.
         let _ = self
             .observer_tx
             .send(ServerMessage::Command(CommandPayload::Connect(
                 self.session.user_id,
                 self.session.session_id,
-                self.server_tx.clone(),
+                SubscriberSenders {
+                    events: server_tx,
+                    control: control_tx,
+                },
                 self.identifier.clone(),
                 self.session.session_token.clone(),
+                self.resume_token.take(),
+                self.remote_address.clone(),
             )))
             .await;
 
-        let mut ping_interval = interval(Duration::from_millis(PING_INTERVAL_MS));
+        tracing::info!("WebSocket session started");
+
+        let mut ping_interval = interval(self.keepalive.ping_interval);
         let mut pong_check_interval = interval(Duration::from_secs(1));
+        let mut closed_by_client = false;
 
         loop {
             tokio::select! {
-                Some(msg) = self.server_rx.recv() => {
+                msg = self.control_rx.recv() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    if self.handle_server_message(msg, &mut ws_sender).await.is_err() {
+                        break;
+                    }
+                }
+                msg = self.server_rx.recv() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    // A pending close or error goes out before the next queued
+                    // event, however deep the event backlog is.
+                    let msg = match self.control_rx.try_recv() {
+                        Ok(control) => {
+                            if self.handle_server_message(control, &mut ws_sender).await.is_err() {
+                                break;
+                            }
+                            msg
+                        }
+                        Err(_) => msg,
+                    };
                     if self.handle_server_message(msg, &mut ws_sender).await.is_err() {
                         break;
                     }
@@ -184,8 +380,26 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
                                 break;
                             }
                         }
-                        Some(Ok(Message::Close(_))) => break,
-                        Some(Err(_)) => break,
+                        Some(Ok(Message::Close(_))) => {
+                            closed_by_client = true;
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            self.metrics.record_receive_failure();
+                            tracing::warn!("WebSocket read failed: {}", e);
+                            let _ = self
+                                .service
+                                .logger
+                                .log_entry(
+                                    format!(
+                                        "Connection of user {} failed mid-read: {}",
+                                        self.session.user_id, e
+                                    ),
+                                    "websocket".to_string(),
+                                )
+                                .await;
+                            break;
+                        }
                         None => break,
                         _ => {}
                     }
@@ -197,12 +411,21 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
                 }
                 _ = pong_check_interval.tick() => {
                     if self.check_pong_timeouts() {
+                        tracing::warn!(
+                            "Closing WebSocket session after {} missed pongs",
+                            self.missed_pongs
+                        );
+                        send_close(&mut ws_sender, CloseReason::PingTimeout).await;
+                        break;
+                    }
+                    if self.update_congestion(&mut ws_sender).await.is_err() {
                         break;
                     }
                 }
             }
         }
 
+        tracing::info!("WebSocket session ended");
         let _ = self
             .service
             .logger
@@ -216,6 +439,7 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
             .send(ServerMessage::Command(CommandPayload::Timeout(
                 self.session.user_id,
                 self.identifier.clone(),
+                closed_by_client,
             )))
             .await;
     }
@@ -225,8 +449,12 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
         data: &[u8],
         ws_sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
     ) -> Result<(), SessionError> {
-        let message: ConnectionMessage = rmp_serde::from_slice(data)
-            .map_err(|_| SessionError("Invalid message format".to_string()))?;
+        self.metrics.record_frame_received();
+        let message: ConnectionMessage = rmp_serde::from_slice(data).map_err(|e| {
+            self.metrics.record_receive_failure();
+            tracing::warn!("Rejecting malformed frame: {}", e);
+            SessionError("Invalid message format".to_string())
+        })?;
 
         match message {
             ConnectionMessage::Ping { timestamp } => {
@@ -234,11 +462,54 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
                     .await?;
             }
             ConnectionMessage::Pong { timestamp } => {
+                if let Some(ping) = self.pending_pings.iter().find(|p| p.timestamp == timestamp) {
+                    let rtt = ping.sent_at.elapsed();
+                    self.metrics.record_rtt(rtt);
+                    let sample = rtt.as_millis() as u64;
+                    self.smoothed_rtt_ms = Some(match self.smoothed_rtt_ms {
+                        Some(smoothed) => (smoothed * 7 + sample) / 8,
+                        None => sample,
+                    });
+                }
                 self.pending_pings.retain(|p| p.timestamp != timestamp);
                 self.missed_pongs = 0;
+                self.update_congestion(ws_sender).await?;
+            }
+            ConnectionMessage::Echo { timestamp, payload } => {
+                if payload.len() > MAX_ECHO_PAYLOAD_LENGTH {
+                    return Ok(());
+                }
+                let reply = ConnectionMessage::EchoReply {
+                    timestamp,
+                    payload,
+                    received_at: unix_millis(),
+                    remote_address: self.remote_address.clone(),
+                };
+                self.send(ws_sender, reply).await?;
             }
-            ConnectionMessage::Answer { .. } => {}
-            ConnectionMessage::Event { payload } => {
+            ConnectionMessage::Answer { .. }
+            | ConnectionMessage::EchoReply { .. }
+            | ConnectionMessage::Congestion { .. }
+            | ConnectionMessage::BackfillComplete { .. } => {}
+            ConnectionMessage::Backfill { since_cursor } => {
+                let _ = self
+                    .observer_tx
+                    .send(ServerMessage::Command(CommandPayload::Backfill(
+                        self.identifier.clone(),
+                        since_cursor,
+                    )))
+                    .await;
+            }
+            ConnectionMessage::SubscribePresence { user_ids } => {
+                let _ = self
+                    .observer_tx
+                    .send(ServerMessage::Command(CommandPayload::SubscribePresence(
+                        self.identifier.clone(),
+                        user_ids,
+                    )))
+                    .await;
+            }
+            ConnectionMessage::Event { payload, .. } => {
                 if let EventPayload::SpeakStatusUpdated { user_id, speaking } = payload {
                     if user_id == self.session.user_id {
                         if let Ok(Some(participant)) =
@@ -253,7 +524,7 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
                                         event,
                                         ControlRoutingPolicy::ChannelRights {
                                             channel_id,
-                                            minimun_rights: 2,
+                                            minimun_rights: READ_RIGHTS,
                                         },
                                     ))
                                     .await;
@@ -282,19 +553,22 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
         ws_sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
     ) -> Result<(), SessionError> {
         match msg {
-            SubscriberMessage::Event(payload) => {
-                self.send(ws_sender, ConnectionMessage::Event { payload })
+            SubscriberMessage::Event(payload, cursor) => {
+                self.send(ws_sender, ConnectionMessage::Event { payload, cursor })
                     .await?;
             }
+            SubscriberMessage::BackfillComplete { cursor, truncated } => {
+                self.send(
+                    ws_sender,
+                    ConnectionMessage::BackfillComplete { cursor, truncated },
+                )
+                .await?;
+            }
             SubscriberMessage::Error(reason) => {
                 return Err(SessionError(reason));
             }
-            SubscriberMessage::Close => {
-                let close_frame = CloseFrame {
-                    code: CLOSE_CODE_DISCONNECTED,
-                    reason: "Disconnected".into(),
-                };
-                let _ = ws_sender.send(Message::Close(Some(close_frame))).await;
+            SubscriberMessage::Close(reason) => {
+                send_close(ws_sender, reason).await;
                 return Err(SessionError("Close".to_string()));
             }
         }
@@ -306,21 +580,25 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
         ws_sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
         message: ConnectionMessage,
     ) -> Result<(), SessionError> {
-        let bytes = rmp_serde::to_vec_named(&message).expect("serialization");
-        ws_sender
-            .send(Message::Binary(bytes.into()))
-            .await
-            .map_err(|_| SessionError("Send failed".to_string()))
+        let mut bytes = rmp_serde::to_vec_named(&message).expect("serialization");
+        if let Some(min_bytes) = self.compression_min_bytes {
+            if bytes.len() >= min_bytes {
+                bytes = compress_frame(bytes);
+            }
+        }
+        let result = ws_sender.send(Message::Binary(bytes.into())).await;
+        match result {
+            Ok(()) => self.metrics.record_frame_sent(),
+            Err(_) => self.metrics.record_send_failure(),
+        }
+        result.map_err(|_| SessionError("Send failed".to_string()))
     }
 
     async fn send_ping(
         &mut self,
         ws_sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
     ) -> Result<(), SessionError> {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let timestamp = unix_millis();
 
         self.pending_pings.push(PendingPing {
             timestamp,
@@ -333,7 +611,7 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
 
     fn check_pong_timeouts(&mut self) -> bool {
         let now = Instant::now();
-        let timeout = Duration::from_millis(PONG_TIMEOUT_MS);
+        let timeout = self.keepalive.pong_timeout;
 
         let timed_out = self
             .pending_pings
@@ -344,7 +622,62 @@ impl<R: SessionRepository, L: LogManager> SubscriberSession<R, L> {
         self.pending_pings
             .retain(|p| now.duration_since(p.sent_at) <= timeout);
         self.missed_pongs += timed_out;
+        self.metrics.record_missed_pongs(timed_out);
+
+        self.missed_pongs >= self.keepalive.max_missed_pongs
+    }
+
+    /// Enters congestion above `CONGESTION_ENTER_RTT_MS` or on a missed pong,
+    /// and only leaves below `CONGESTION_EXIT_RTT_MS` so the state can't flap.
+    async fn update_congestion(
+        &mut self,
+        ws_sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    ) -> Result<(), SessionError> {
+        let rtt_ms = self.smoothed_rtt_ms.unwrap_or(0);
+        let congested = if self.congested {
+            self.missed_pongs > 0 || rtt_ms >= CONGESTION_EXIT_RTT_MS
+        } else {
+            self.missed_pongs > 0 || rtt_ms > CONGESTION_ENTER_RTT_MS
+        };
+        if congested == self.congested {
+            return Ok(());
+        }
+
+        self.congested = congested;
+        tracing::debug!(congested, rtt_ms, "Connection congestion changed");
+        self.send(
+            ws_sender,
+            ConnectionMessage::Congestion { congested, rtt_ms },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn compressed_frame_is_flagged_and_round_trips() {
+        let frame = b"event ".repeat(100);
+
+        let compressed = compress_frame(frame.clone());
+
+        assert_eq!(compressed[0], COMPRESSED_FRAME_FLAG);
+        assert!(compressed.len() < frame.len());
+        let mut decompressed = Vec::new();
+        ZlibDecoder::new(&compressed[1..])
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, frame);
+    }
+
+    #[test]
+    fn incompressible_frame_is_sent_as_is() {
+        let frame = vec![0x92, 0x01, 0x02];
 
-        self.missed_pongs >= MAX_MISSED_PONGS
+        assert_eq!(compress_frame(frame.clone()), frame);
     }
 }
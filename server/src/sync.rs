@@ -0,0 +1,741 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// IMPORTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::acl::READ_RIGHTS;
+use crate::auth::Session;
+use crate::db::Postgre;
+use crate::error::{ApiError, DatabaseError};
+use crate::managers::{DefaultNotifierManager, NotifierManager};
+use crate::message::Message;
+use crate::middleware::{AuthorizeService, authorize};
+use crate::model::EventPayload;
+use crate::transport::{ControlRoutingPolicy, ServerMessage};
+
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::middleware::from_fn_with_state;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MODELS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncCursor {
+    pub channel_id: Option<i64>,
+    pub recipient_id: Option<i64>,
+    pub last_message_id: i64,
+    #[serde(with = "time::serde::iso8601")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreadCount {
+    pub channel_id: Option<i64>,
+    pub recipient_id: Option<i64>,
+    pub unread: i64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ERROR
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, thiserror::Error)]
+pub enum DomainError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Internal error")]
+    InternalError(#[from] DatabaseError),
+}
+
+impl From<DomainError> for ApiError {
+    fn from(err: DomainError) -> Self {
+        match err {
+            DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
+            DomainError::InternalError(db_err) => {
+                tracing::error!("Database error: {}", db_err);
+                ApiError::InternalServerError("Internal server error".to_string())
+            }
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// REPOSITORY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub trait SyncTransaction: Send + Sync {
+    async fn upsert_channel_cursor(
+        &mut self,
+        user_id: i64,
+        channel_id: i64,
+        last_message_id: i64,
+    ) -> Result<SyncCursor, DatabaseError>;
+
+    async fn upsert_dm_cursor(
+        &mut self,
+        user_id: i64,
+        recipient_id: i64,
+        last_message_id: i64,
+    ) -> Result<SyncCursor, DatabaseError>;
+
+    async fn advance_all_channel_cursors(
+        &mut self,
+        user_id: i64,
+    ) -> Result<Vec<SyncCursor>, DatabaseError>;
+
+    async fn advance_all_dm_cursors(
+        &mut self,
+        user_id: i64,
+    ) -> Result<Vec<SyncCursor>, DatabaseError>;
+}
+
+pub trait SyncRepository: Send + Sync + Clone {
+    type Transaction: SyncTransaction;
+
+    async fn begin(&self) -> Result<Self::Transaction, DatabaseError>;
+
+    async fn commit(&self, transaction: Self::Transaction) -> Result<(), DatabaseError>;
+
+    async fn rollback(&self, transaction: Self::Transaction) -> Result<(), DatabaseError>;
+
+    async fn find_cursors(&self, user_id: i64) -> Result<Vec<SyncCursor>, DatabaseError>;
+
+    async fn find_unread_counts(&self, user_id: i64) -> Result<Vec<UnreadCount>, DatabaseError>;
+
+    async fn find_user_channel_rights(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+    ) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_message_by_id(&self, message_id: i64) -> Result<Option<Message>, DatabaseError>;
+}
+
+pub struct PgSyncTransaction {
+    transaction: sqlx::Transaction<'static, sqlx::Postgres>,
+}
+
+impl SyncTransaction for PgSyncTransaction {
+    async fn upsert_channel_cursor(
+        &mut self,
+        user_id: i64,
+        channel_id: i64,
+        last_message_id: i64,
+    ) -> Result<SyncCursor, DatabaseError> {
+        let cursor = sqlx::query_as!(
+            SyncCursor,
+            r#"INSERT INTO sync_cursors (user_id, channel_id, recipient_id, last_message_id)
+               VALUES ($1, $2, NULL, $3)
+               ON CONFLICT (user_id, channel_id) WHERE channel_id IS NOT NULL
+               DO UPDATE SET
+                   last_message_id = GREATEST(sync_cursors.last_message_id, EXCLUDED.last_message_id),
+                   updated_at = CURRENT_TIMESTAMP
+               RETURNING channel_id, recipient_id, last_message_id, updated_at"#,
+            user_id,
+            channel_id,
+            last_message_id
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(cursor)
+    }
+
+    async fn upsert_dm_cursor(
+        &mut self,
+        user_id: i64,
+        recipient_id: i64,
+        last_message_id: i64,
+    ) -> Result<SyncCursor, DatabaseError> {
+        let cursor = sqlx::query_as!(
+            SyncCursor,
+            r#"INSERT INTO sync_cursors (user_id, channel_id, recipient_id, last_message_id)
+               VALUES ($1, NULL, $2, $3)
+               ON CONFLICT (user_id, recipient_id) WHERE recipient_id IS NOT NULL
+               DO UPDATE SET
+                   last_message_id = GREATEST(sync_cursors.last_message_id, EXCLUDED.last_message_id),
+                   updated_at = CURRENT_TIMESTAMP
+               RETURNING channel_id, recipient_id, last_message_id, updated_at"#,
+            user_id,
+            recipient_id,
+            last_message_id
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(cursor)
+    }
+
+    async fn advance_all_channel_cursors(
+        &mut self,
+        user_id: i64,
+    ) -> Result<Vec<SyncCursor>, DatabaseError> {
+        let cursors = sqlx::query_as!(
+            SyncCursor,
+            r#"INSERT INTO sync_cursors (user_id, channel_id, recipient_id, last_message_id)
+               SELECT u.user_id, m.channel_id, NULL, MAX(m.id)
+               FROM messages m
+               INNER JOIN channels c ON c.channel_id = m.channel_id
+               INNER JOIN group_role_rights grr ON grr.group_id = c.group_id
+               INNER JOIN users u ON u.role_id = grr.role_id
+               WHERE u.user_id = $1 AND grr.rights >= $2
+               GROUP BY u.user_id, m.channel_id
+               ON CONFLICT (user_id, channel_id) WHERE channel_id IS NOT NULL
+               DO UPDATE SET
+                   last_message_id = GREATEST(sync_cursors.last_message_id, EXCLUDED.last_message_id),
+                   updated_at = CURRENT_TIMESTAMP
+               RETURNING channel_id, recipient_id, last_message_id, updated_at"#,
+            user_id,
+            READ_RIGHTS
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(cursors)
+    }
+
+    async fn advance_all_dm_cursors(
+        &mut self,
+        user_id: i64,
+    ) -> Result<Vec<SyncCursor>, DatabaseError> {
+        let cursors = sqlx::query_as!(
+            SyncCursor,
+            r#"INSERT INTO sync_cursors (user_id, channel_id, recipient_id, last_message_id)
+               SELECT $1, NULL,
+                      CASE WHEN m.sender_id = $1 THEN m.recipient_id ELSE m.sender_id END,
+                      MAX(m.id)
+               FROM messages m
+               WHERE m.recipient_id = $1 OR (m.sender_id = $1 AND m.recipient_id IS NOT NULL)
+               GROUP BY CASE WHEN m.sender_id = $1 THEN m.recipient_id ELSE m.sender_id END
+               ON CONFLICT (user_id, recipient_id) WHERE recipient_id IS NOT NULL
+               DO UPDATE SET
+                   last_message_id = GREATEST(sync_cursors.last_message_id, EXCLUDED.last_message_id),
+                   updated_at = CURRENT_TIMESTAMP
+               RETURNING channel_id, recipient_id, last_message_id, updated_at"#,
+            user_id
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(cursors)
+    }
+}
+
+impl SyncRepository for Postgre {
+    type Transaction = PgSyncTransaction;
+
+    async fn begin(&self) -> Result<Self::Transaction, DatabaseError> {
+        let tx = self.pool.begin().await?;
+        Ok(PgSyncTransaction { transaction: tx })
+    }
+
+    async fn commit(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
+        transaction.transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
+        transaction.transaction.rollback().await?;
+        Ok(())
+    }
+
+    async fn find_cursors(&self, user_id: i64) -> Result<Vec<SyncCursor>, DatabaseError> {
+        let results = sqlx::query_as!(
+            SyncCursor,
+            r#"SELECT channel_id, recipient_id, last_message_id, updated_at
+               FROM sync_cursors
+               WHERE user_id = $1
+               ORDER BY updated_at DESC"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_unread_counts(&self, user_id: i64) -> Result<Vec<UnreadCount>, DatabaseError> {
+        let results = sqlx::query_as!(
+            UnreadCount,
+            r#"SELECT m.channel_id AS "channel_id?", NULL::BIGINT AS "recipient_id?", COUNT(*) AS "unread!"
+               FROM messages m
+               INNER JOIN channels c ON c.channel_id = m.channel_id
+               INNER JOIN group_role_rights grr ON grr.group_id = c.group_id
+               INNER JOIN users u ON u.role_id = grr.role_id
+               LEFT JOIN sync_cursors sc ON sc.user_id = u.user_id AND sc.channel_id = m.channel_id
+               WHERE u.user_id = $1 AND grr.rights >= $2 AND m.sender_id <> $1
+                 AND m.id > COALESCE(sc.last_message_id, 0)
+               GROUP BY m.channel_id
+               UNION ALL
+               SELECT NULL::BIGINT, m.sender_id, COUNT(*)
+               FROM messages m
+               LEFT JOIN sync_cursors sc ON sc.user_id = m.recipient_id AND sc.recipient_id = m.sender_id
+               WHERE m.recipient_id = $1 AND m.id > COALESCE(sc.last_message_id, 0)
+                 AND NOT EXISTS (
+                     SELECT 1 FROM dm_requests req
+                     WHERE req.sender_id = m.sender_id
+                     AND req.recipient_id = m.recipient_id
+                     AND req.status <> 'Accepted'
+                 )
+               GROUP BY m.sender_id"#,
+            user_id,
+            READ_RIGHTS
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_user_channel_rights(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+    ) -> Result<Option<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT grr.rights
+            FROM group_role_rights grr
+            INNER JOIN users u ON u.role_id = grr.role_id
+            INNER JOIN channels c ON c.group_id = grr.group_id
+            WHERE c.channel_id = $1 AND u.user_id = $2"#,
+            channel_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn find_message_by_id(&self, message_id: i64) -> Result<Option<Message>, DatabaseError> {
+        let result = sqlx::query_as!(
+            Message,
+            r#"SELECT id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at
+               FROM messages WHERE id = $1
+               AND (expires_at IS NULL OR expires_at > NOW())"#,
+            message_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SERVICE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Clone)]
+pub struct SyncService<R: SyncRepository, N: NotifierManager> {
+    repository: R,
+    notifier: N,
+}
+
+impl<R: SyncRepository, N: NotifierManager> SyncService<R, N> {
+    pub fn new(repository: R, notifier: N) -> Self {
+        Self {
+            repository,
+            notifier,
+        }
+    }
+
+    pub async fn get_sync_state(&self, user_id: i64) -> Result<Vec<SyncCursor>, DomainError> {
+        let cursors = self.repository.find_cursors(user_id).await?;
+
+        Ok(cursors)
+    }
+
+    pub async fn get_unread_counts(&self, user_id: i64) -> Result<Vec<UnreadCount>, DomainError> {
+        let counts = self.repository.find_unread_counts(user_id).await?;
+
+        Ok(counts)
+    }
+
+    pub async fn mark_all_read(&self, user_id: i64) -> Result<Vec<SyncCursor>, DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let mut cursors = tx.advance_all_channel_cursors(user_id).await?;
+        cursors.extend(tx.advance_all_dm_cursors(user_id).await?);
+
+        self.repository.commit(tx).await?;
+
+        for cursor in &cursors {
+            self.notify_cursor(user_id, cursor.clone()).await;
+        }
+
+        Ok(cursors)
+    }
+
+    pub async fn update_channel_cursor(
+        &self,
+        user_id: i64,
+        channel_id: i64,
+        last_message_id: i64,
+    ) -> Result<SyncCursor, DomainError> {
+        let rights = self
+            .repository
+            .find_user_channel_rights(channel_id, user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied(
+                "No access to channel".to_string(),
+            ))?;
+
+        if rights < READ_RIGHTS {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to read channel".to_string(),
+            ));
+        }
+
+        let message = self
+            .repository
+            .find_message_by_id(last_message_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Message {} not found",
+                last_message_id
+            )))?;
+
+        if message.channel_id != Some(channel_id) {
+            return Err(DomainError::BadRequest(
+                "Message does not belong to this channel".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let cursor = tx
+            .upsert_channel_cursor(user_id, channel_id, last_message_id)
+            .await?;
+
+        self.repository.commit(tx).await?;
+
+        self.notify_cursor(user_id, cursor.clone()).await;
+
+        Ok(cursor)
+    }
+
+    pub async fn update_dm_cursor(
+        &self,
+        user_id: i64,
+        recipient_id: i64,
+        last_message_id: i64,
+    ) -> Result<SyncCursor, DomainError> {
+        let message = self
+            .repository
+            .find_message_by_id(last_message_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Message {} not found",
+                last_message_id
+            )))?;
+
+        let in_conversation = (message.sender_id == user_id
+            && message.recipient_id == Some(recipient_id))
+            || (message.sender_id == recipient_id && message.recipient_id == Some(user_id));
+
+        if !in_conversation {
+            return Err(DomainError::BadRequest(
+                "Message does not belong to this conversation".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let cursor = tx
+            .upsert_dm_cursor(user_id, recipient_id, last_message_id)
+            .await?;
+
+        self.repository.commit(tx).await?;
+
+        self.notify_cursor(user_id, cursor.clone()).await;
+
+        Ok(cursor)
+    }
+
+    async fn notify_cursor(&self, user_id: i64, cursor: SyncCursor) {
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                EventPayload::SyncCursorUpdated { cursor },
+                ControlRoutingPolicy::User { user_id },
+            ))
+            .await;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// REQUEST/RESPONSE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSyncCursorRequest {
+    pub last_message_id: i64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ROUTES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+type AppSyncService = SyncService<Postgre, DefaultNotifierManager>;
+
+pub fn sync_routes(
+    sync_service: AppSyncService,
+    authorize_service: AuthorizeService<Postgre>,
+) -> OpenApiRouter<Postgre> {
+    OpenApiRouter::new()
+        .routes(routes!(get_sync_state_handler))
+        .routes(routes!(get_unread_counts_handler))
+        .routes(routes!(mark_all_read_handler))
+        .routes(routes!(update_channel_cursor_handler))
+        .routes(routes!(update_dm_cursor_handler))
+        .layer(from_fn_with_state(authorize_service, authorize))
+        .with_state(sync_service)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HANDLERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[utoipa::path(
+    get,
+    tag = "sync",
+    path = "/state",
+    description = "Get sync cursors for all conversations of the current user",
+    responses(
+        (status = 200, body = Vec<SyncCursor>),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_sync_state_handler(
+    State(service): State<AppSyncService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<SyncCursor>>, ApiError> {
+    let cursors = service
+        .get_sync_state(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(cursors))
+}
+
+#[utoipa::path(
+    get,
+    tag = "sync",
+    path = "/unread",
+    description = "Get unread message counts per channel and direct conversation",
+    responses(
+        (status = 200, body = Vec<UnreadCount>),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_unread_counts_handler(
+    State(service): State<AppSyncService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<UnreadCount>>, ApiError> {
+    let counts = service
+        .get_unread_counts(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(counts))
+}
+
+#[utoipa::path(
+    post,
+    tag = "sync",
+    path = "/read-all",
+    description = "Advance every sync cursor of the current user to the latest message",
+    responses(
+        (status = 200, body = Vec<SyncCursor>),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn mark_all_read_handler(
+    State(service): State<AppSyncService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<SyncCursor>>, ApiError> {
+    let cursors = service
+        .mark_all_read(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(cursors))
+}
+
+#[utoipa::path(
+    put,
+    tag = "sync",
+    path = "/channel/{channel_id}",
+    description = "Advance the sync cursor of a channel",
+    params(("channel_id" = i64, Path, description = "Channel ID")),
+    request_body = UpdateSyncCursorRequest,
+    responses(
+        (status = 200, body = SyncCursor),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn update_channel_cursor_handler(
+    State(service): State<AppSyncService>,
+    Extension(session): Extension<Session>,
+    Path(channel_id): Path<i64>,
+    Json(payload): Json<UpdateSyncCursorRequest>,
+) -> Result<Json<SyncCursor>, ApiError> {
+    let cursor = service
+        .update_channel_cursor(session.user_id, channel_id, payload.last_message_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(cursor))
+}
+
+#[utoipa::path(
+    put,
+    tag = "sync",
+    path = "/dm/{user_id}",
+    description = "Advance the sync cursor of a direct conversation",
+    params(("user_id" = i64, Path, description = "Other user ID")),
+    request_body = UpdateSyncCursorRequest,
+    responses(
+        (status = 200, body = SyncCursor),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn update_dm_cursor_handler(
+    State(service): State<AppSyncService>,
+    Extension(session): Extension<Session>,
+    Path(recipient_id): Path<i64>,
+    Json(payload): Json<UpdateSyncCursorRequest>,
+) -> Result<Json<SyncCursor>, ApiError> {
+    let cursor = service
+        .update_dm_cursor(session.user_id, recipient_id, payload.last_message_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    async fn create_users(pool: &PgPool) -> (i64, i64, i64) {
+        let user_ids: Vec<i64> = sqlx::query_scalar!(
+            "INSERT INTO users (username) VALUES ('alice'), ('bob'), ('carol') RETURNING user_id"
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap();
+        (user_ids[0], user_ids[1], user_ids[2])
+    }
+
+    async fn send_dm(pool: &PgPool, sender_id: i64, recipient_id: i64) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO messages (sender_id, recipient_id, message_text) VALUES ($1, $2, 'hi') RETURNING id",
+            sender_id,
+            recipient_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    fn service(pool: PgPool) -> SyncService<Postgre, DefaultNotifierManager> {
+        let (sender, _) = tokio::sync::mpsc::channel(16);
+        SyncService::new(Postgre { pool }, DefaultNotifierManager::new(sender))
+    }
+
+    #[sqlx::test]
+    async fn dm_cursor_only_moves_forward(pool: PgPool) {
+        let (alice, bob, _) = create_users(&pool).await;
+        let first = send_dm(&pool, alice, bob).await;
+        let second = send_dm(&pool, bob, alice).await;
+        let third = send_dm(&pool, alice, bob).await;
+        let service = service(pool);
+
+        let cursor = service.update_dm_cursor(bob, alice, second).await.unwrap();
+        assert_eq!(cursor.last_message_id, second);
+
+        let cursor = service.update_dm_cursor(bob, alice, first).await.unwrap();
+        assert_eq!(cursor.last_message_id, second);
+
+        service.update_dm_cursor(bob, alice, third).await.unwrap();
+        let state = service.get_sync_state(bob).await.unwrap();
+        assert_eq!(state.len(), 1);
+        assert_eq!(state[0].recipient_id, Some(alice));
+        assert_eq!(state[0].last_message_id, third);
+        assert!(service.get_sync_state(alice).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn dm_cursor_rejects_messages_from_other_conversations(pool: PgPool) {
+        let (alice, bob, carol) = create_users(&pool).await;
+        let elsewhere = send_dm(&pool, carol, alice).await;
+        let service = service(pool);
+
+        assert!(matches!(
+            service.update_dm_cursor(bob, alice, elsewhere).await,
+            Err(DomainError::BadRequest(_))
+        ));
+        assert!(matches!(
+            service.update_dm_cursor(bob, alice, elsewhere + 1000).await,
+            Err(DomainError::NotFound(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn unread_counts_skip_pending_message_requests(pool: PgPool) {
+        let user_ids: Vec<i64> = sqlx::query_scalar!(
+            "INSERT INTO users (username) VALUES ('alice'), ('bob'), ('carol') RETURNING user_id"
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let (alice, bob, carol) = (user_ids[0], user_ids[1], user_ids[2]);
+        sqlx::query!(
+            r#"INSERT INTO messages (sender_id, recipient_id, message_text)
+               VALUES ($1, $2, 'request'), ($3, $2, 'hello')"#,
+            alice,
+            bob,
+            carol
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            "INSERT INTO dm_requests (sender_id, recipient_id) VALUES ($1, $2)",
+            alice,
+            bob
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let counts = Postgre { pool }.find_unread_counts(bob).await.unwrap();
+
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].recipient_id, Some(carol));
+        assert_eq!(counts[0].unread, 1);
+    }
+}
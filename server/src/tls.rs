@@ -0,0 +1,80 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// IMPORTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use axum_server::tls_rustls::RustlsConfig;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::time::interval;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// RELOADER
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Reloads the certificate and key into `config` on SIGHUP and, when
+/// `poll_interval` is set, whenever either file's modification time changes.
+/// Existing connections keep the certificate they negotiated with.
+pub struct CertificateReloader {
+    config: RustlsConfig,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    poll_interval: Option<Duration>,
+}
+
+impl CertificateReloader {
+    pub fn new(config: RustlsConfig, cert_path: PathBuf, key_path: PathBuf) -> Self {
+        Self {
+            config,
+            cert_path,
+            key_path,
+            poll_interval: None,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Option<Duration>) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    fn modified_times(&self) -> (Option<SystemTime>, Option<SystemTime>) {
+        let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        (modified(&self.cert_path), modified(&self.key_path))
+    }
+
+    async fn reload(&self) {
+        match self
+            .config
+            .reload_from_pem_file(&self.cert_path, &self.key_path)
+            .await
+        {
+            Ok(()) => println!("Reloaded TLS certificates"),
+            Err(e) => eprintln!("Failed to reload TLS certificates: {}", e),
+        }
+    }
+
+    pub async fn run(self) {
+        let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+            eprintln!("Failed to install SIGHUP handler, TLS reload on signal disabled");
+            return;
+        };
+        let mut poll = interval(self.poll_interval.unwrap_or(Duration::from_secs(3600)));
+        let mut last_modified = self.modified_times();
+
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => {
+                    self.reload().await;
+                    last_modified = self.modified_times();
+                }
+                _ = poll.tick(), if self.poll_interval.is_some() => {
+                    let modified = self.modified_times();
+                    if modified != last_modified {
+                        self.reload().await;
+                        last_modified = modified;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use crate::error::DatabaseError;
 use crate::model::EventPayload;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use std::collections::HashSet;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // ERROR
@@ -17,7 +21,9 @@ pub enum DomainError {
 // ROUTING
 // ═══════════════════════════════════════════════════════════════════════════════
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
 pub enum ControlRoutingPolicy {
     GroupRights {
         group_id: i64,
@@ -36,6 +42,9 @@ pub enum ControlRoutingPolicy {
     Role {
         role_id: i64,
     },
+    Presence {
+        user_id: i64,
+    },
     Broadcast,
 }
 
@@ -45,46 +54,215 @@ pub enum ControlRoutingPolicy {
 
 #[derive(Debug, Clone)]
 pub enum CommandPayload {
-    Connect(i64, i64, mpsc::Sender<SubscriberMessage>, String, String),
-    Timeout(i64, String),
-    Disconnect(i64, String),
-    DisconnectUser(i64),
+    Connect(
+        i64,
+        i64,
+        SubscriberSenders,
+        String,
+        String,
+        Option<String>,
+        String,
+    ),
+    Timeout(i64, String, bool),
+    Disconnect(i64, String, CloseReason),
+    DisconnectUser(i64, CloseReason),
+    SubscribePresence(String, Vec<i64>),
+    Backfill(String, u64),
+    Terminate(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RealtimeMetrics {
+    pub active_connections: usize,
+    pub online_users: usize,
+    pub resumable_sessions: usize,
+    pub voip_participants: usize,
+    pub speaking_users: usize,
+    pub events_routed: u64,
+    pub dropped_events: u64,
+    pub backfill_resyncs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveConnection {
+    pub user_id: i64,
+    pub identifier: String,
+    pub remote_address: String,
+    #[serde(with = "time::serde::iso8601")]
+    pub connected_at: OffsetDateTime,
+}
+
+#[derive(Debug)]
+pub enum QueryPayload {
+    SpeakingUsers(oneshot::Sender<Vec<i64>>),
+    Metrics(oneshot::Sender<RealtimeMetrics>),
+    Recipients(ControlRoutingPolicy, oneshot::Sender<Vec<i64>>),
+    Connections(oneshot::Sender<Vec<ActiveConnection>>),
 }
 
 pub enum ServerMessage {
     Command(CommandPayload),
     Control(EventPayload, ControlRoutingPolicy),
+    Query(QueryPayload),
     InvalidateVoip,
     InvalidateAcl,
     InvalidateUsers,
+    InvalidateMutes,
 }
 
 pub enum SubscriberMessage {
-    Event(EventPayload),
+    Event(EventPayload, u64),
+    BackfillComplete { cursor: u64, truncated: bool },
     Error(String),
-    Close,
+    Close(CloseReason),
+}
+
+/// Why the server closed a connection. Sent as the WebSocket close code so
+/// clients can tell a ban or deleted account from a drop worth reconnecting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CloseReason {
+    #[default]
+    Disconnected,
+    LoggedOut,
+    SessionRevoked,
+    PasswordChanged,
+    Banned,
+    UserDeleted,
+    Terminated,
+    SessionExpired,
+    PingTimeout,
+    Overloaded,
+}
+
+impl CloseReason {
+    pub fn code(self) -> u16 {
+        match self {
+            CloseReason::Disconnected => 4002,
+            CloseReason::LoggedOut => 4003,
+            CloseReason::SessionRevoked => 4004,
+            CloseReason::PasswordChanged => 4005,
+            CloseReason::Banned => 4006,
+            CloseReason::UserDeleted => 4007,
+            CloseReason::Terminated => 4008,
+            CloseReason::SessionExpired => 4009,
+            CloseReason::PingTimeout => 4010,
+            CloseReason::Overloaded => 4011,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            CloseReason::Disconnected => "Disconnected",
+            CloseReason::LoggedOut => "Logged out",
+            CloseReason::SessionRevoked => "Session revoked",
+            CloseReason::PasswordChanged => "Password changed",
+            CloseReason::Banned => "Banned",
+            CloseReason::UserDeleted => "User deleted",
+            CloseReason::Terminated => "Connection terminated by an admin",
+            CloseReason::SessionExpired => "Session expired",
+            CloseReason::PingTimeout => "Ping timeout",
+            CloseReason::Overloaded => "Too far behind on events",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "camelCase")]
 pub enum ConnectionMessage {
-    Answer { ok: bool },
-    Ping { timestamp: u64 },
-    Pong { timestamp: u64 },
-    Event { payload: EventPayload },
+    #[serde(rename_all = "camelCase")]
+    Answer {
+        ok: bool,
+        resume_token: Option<String>,
+    },
+    Ping {
+        timestamp: u64,
+    },
+    Pong {
+        timestamp: u64,
+    },
+    /// Connectivity diagnostics: the server answers with an `EchoReply` carrying
+    /// the same payload, its receive time and the address it sees the client at.
+    Echo {
+        timestamp: u64,
+        payload: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    EchoReply {
+        timestamp: u64,
+        payload: String,
+        received_at: u64,
+        remote_address: String,
+    },
+    Event {
+        payload: EventPayload,
+        #[serde(default)]
+        cursor: u64,
+    },
+    /// Sent when the smoothed ping round trip or missed pongs cross the
+    /// congestion thresholds, and again once they recover.
+    #[serde(rename_all = "camelCase")]
+    Congestion {
+        congested: bool,
+        rtt_ms: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    SubscribePresence {
+        user_ids: Vec<i64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Backfill {
+        since_cursor: u64,
+    },
+    BackfillComplete {
+        cursor: u64,
+        truncated: bool,
+    },
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SUBSCRIBER HANDLER
 // ═══════════════════════════════════════════════════════════════════════════════
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SendError {
+    #[error("Subscriber queue is full")]
+    Full,
+
+    #[error("Subscriber is closed")]
+    Closed,
+}
+
+/// What the router does when a subscriber's outbound queue stays full.
+/// Lossy VoIP events such as speaking indicators are always dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    Drop,
+    Disconnect,
+}
+
+/// Queues feeding one subscriber session. `control` carries `Close` and
+/// `Error`, which the session drains first so they never wait behind events.
+#[derive(Debug, Clone)]
+pub struct SubscriberSenders {
+    pub events: mpsc::Sender<SubscriberMessage>,
+    pub control: mpsc::Sender<SubscriberMessage>,
+}
+
 pub struct SubscriberHandler {
     pub user_id: i64,
     pub session_id: i64,
     pub sender: mpsc::Sender<SubscriberMessage>,
+    pub control: mpsc::Sender<SubscriberMessage>,
     pub session_token: String,
     pub identifier: String,
+    pub presence: Option<HashSet<i64>>,
+    pub remote_address: String,
+    pub connected_at: OffsetDateTime,
+    pub dropped_events: u64,
+    pub overflow_since: Option<Instant>,
 }
 
 impl SubscriberHandler {
@@ -96,7 +274,45 @@ impl SubscriberHandler {
         self.sender.send(msg).await.is_ok()
     }
 
+    pub fn try_send(&self, msg: SubscriberMessage) -> Result<(), SendError> {
+        self.sender.try_send(msg).map_err(|err| match err {
+            TrySendError::Full(_) => SendError::Full,
+            TrySendError::Closed(_) => SendError::Closed,
+        })
+    }
+
+    pub fn close(&self, reason: CloseReason) {
+        let _ = self.control.try_send(SubscriberMessage::Close(reason));
+    }
+
     pub async fn send_error(&self, reason: String) {
-        let _ = self.sender.send(SubscriberMessage::Error(reason)).await;
+        let _ = self.control.send(SubscriberMessage::Error(reason)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_codes_are_stable() {
+        let reasons = [
+            (CloseReason::Disconnected, 4002),
+            (CloseReason::LoggedOut, 4003),
+            (CloseReason::SessionRevoked, 4004),
+            (CloseReason::PasswordChanged, 4005),
+            (CloseReason::Banned, 4006),
+            (CloseReason::UserDeleted, 4007),
+            (CloseReason::Terminated, 4008),
+            (CloseReason::SessionExpired, 4009),
+            (CloseReason::PingTimeout, 4010),
+            (CloseReason::Overloaded, 4011),
+        ];
+
+        for (reason, code) in reasons {
+            assert_eq!(reason.code(), code, "{:?}", reason);
+            // A close frame's reason must fit in 123 bytes.
+            assert!(reason.description().len() <= 123);
+        }
     }
 }
@@ -6,27 +6,29 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use utoipa::ToSchema;
 
+use crate::acl::{ACKNOWLEDGE_RIGHTS, READ_RIGHTS};
 use crate::auth::Session;
 use crate::db::Postgre;
 use crate::error::{ApiError, DatabaseError};
 use crate::managers::{
     DefaultNotifierManager, FileError, FileManager, LocalFileManager, LogManager, NotifierManager,
-    TextLogManager,
+    TextLogManager, UsernameValidator,
 };
-use crate::message::{File, FileMetadata, Message};
+use crate::message::{File, FileMetadata, Message, Reaction};
 use crate::middleware::{AuthorizeService, authorize};
 use crate::model::EventPayload;
 use crate::role::{ADMIN_ROLE_ID, OWNER_ROLE_ID};
 use crate::voip::VoipParticipant;
-use crate::transport::{CommandPayload, ControlRoutingPolicy, ServerMessage};
+use crate::transport::{CloseReason, CommandPayload, ControlRoutingPolicy, ServerMessage};
 
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use axum::Json;
 use axum::extract::{Extension, Multipart, Path, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
 use axum::middleware::from_fn_with_state;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -38,6 +40,9 @@ use utoipa_axum::{router::OpenApiRouter, routes};
 pub struct User {
     pub user_id: i64,
     pub username: String,
+    pub display_name: Option<String>,
+    #[serde(with = "time::serde::iso8601::option")]
+    pub last_seen_at: Option<OffsetDateTime>,
     #[serde(with = "time::serde::iso8601")]
     pub created_at: OffsetDateTime,
     pub avatar_file_id: Option<i64>,
@@ -68,6 +73,15 @@ pub enum UserStatusType {
     Offline,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedUser {
+    pub blocker_id: i64,
+    pub blocked_id: i64,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserStatus {
@@ -75,6 +89,18 @@ pub struct UserStatus {
     pub status: UserStatusType,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDataExport {
+    #[serde(with = "time::serde::iso8601")]
+    pub exported_at: OffsetDateTime,
+    pub profile: User,
+    pub messages: Vec<Message>,
+    pub reactions: Vec<Reaction>,
+    pub files: Vec<File>,
+    pub blocked_users: Vec<BlockedUser>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // ERROR
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -87,6 +113,9 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 
@@ -99,7 +128,8 @@ impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 println!("Database error: {}", db_err);
@@ -139,6 +169,12 @@ pub trait UserTransaction: Send + Sync {
         username: &str,
     ) -> Result<Option<User>, DatabaseError>;
 
+    async fn update_display_name(
+        &mut self,
+        user_id: i64,
+        display_name: Option<&str>,
+    ) -> Result<Option<User>, DatabaseError>;
+
     async fn update_manual_user_status(
         &mut self,
         user_id: i64,
@@ -147,6 +183,8 @@ pub trait UserTransaction: Send + Sync {
 
     async fn delete_all_files_by_user(&mut self, user_id: i64) -> Result<Vec<File>, DatabaseError>;
 
+    async fn find_unreferenced_blobs(&mut self, files: &[File]) -> Result<Vec<i64>, DatabaseError>;
+
     async fn delete_all_messages_by_user(
         &mut self,
         user_id: i64,
@@ -158,6 +196,18 @@ pub trait UserTransaction: Send + Sync {
     ) -> Result<Option<VoipParticipant>, DatabaseError>;
 
     async fn delete_user(&mut self, user_id: i64) -> Result<Option<User>, DatabaseError>;
+
+    async fn create_block(
+        &mut self,
+        blocker_id: i64,
+        blocked_id: i64,
+    ) -> Result<BlockedUser, DatabaseError>;
+
+    async fn delete_block(
+        &mut self,
+        blocker_id: i64,
+        blocked_id: i64,
+    ) -> Result<Option<BlockedUser>, DatabaseError>;
 }
 
 pub trait UserRepository: Send + Sync + Clone {
@@ -174,6 +224,16 @@ pub trait UserRepository: Send + Sync + Clone {
     async fn find_avatar_file(&self, avatar_id: i64) -> Result<Option<AvatarFile>, DatabaseError>;
 
     async fn find_user_role(&mut self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_blocked_users(&self, blocker_id: i64) -> Result<Vec<BlockedUser>, DatabaseError>;
+
+    async fn find_user(&self, user_id: i64) -> Result<Option<User>, DatabaseError>;
+
+    async fn find_user_messages(&self, user_id: i64) -> Result<Vec<Message>, DatabaseError>;
+
+    async fn find_user_reactions(&self, user_id: i64) -> Result<Vec<Reaction>, DatabaseError>;
+
+    async fn find_user_files(&self, user_id: i64) -> Result<Vec<File>, DatabaseError>;
 }
 
 pub struct PgUserTransaction {
@@ -231,6 +291,8 @@ impl UserTransaction for PgUserTransaction {
                RETURNING
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -259,6 +321,8 @@ impl UserTransaction for PgUserTransaction {
                RETURNING
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -273,6 +337,35 @@ impl UserTransaction for PgUserTransaction {
         Ok(result)
     }
 
+    async fn update_display_name(
+        &mut self,
+        user_id: i64,
+        display_name: Option<&str>,
+    ) -> Result<Option<User>, DatabaseError> {
+        let result = sqlx::query_as!(
+            User,
+            r#"UPDATE users
+               SET display_name = $2
+               WHERE user_id = $1
+               RETURNING
+                   user_id,
+                   username,
+                   display_name,
+                   last_seen_at,
+                   created_at,
+                   avatar_file_id,
+                   role_id,
+                   server_deafen,
+                   server_mute,
+                   CASE WHEN status = 'Offline' THEN status ELSE COALESCE(manual_status, status) END as "status!: UserStatusType""#,
+            user_id,
+            display_name
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+        Ok(result)
+    }
+
     async fn update_manual_user_status(
         &mut self,
         user_id: i64,
@@ -286,6 +379,8 @@ impl UserTransaction for PgUserTransaction {
                RETURNING
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -307,7 +402,7 @@ impl UserTransaction for PgUserTransaction {
                USING messages m
                WHERE files.message_id = m.id
                AND m.sender_id = $1
-               RETURNING files.file_id, files.file_uuid, files.message_id, files.file_name, files.metadata as "metadata: sqlx::types::Json<FileMetadata>", files.file_size, files.file_hash, files.created_at"#,
+               RETURNING files.file_id, files.file_uuid, files.message_id, files.file_name, files.metadata as "metadata: sqlx::types::Json<FileMetadata>", files.file_size, files.file_hash, files.blob_id, files.created_at"#,
             user_id
         )
         .fetch_all(&mut *self.transaction)
@@ -315,6 +410,19 @@ impl UserTransaction for PgUserTransaction {
         Ok(files)
     }
 
+    async fn find_unreferenced_blobs(&mut self, files: &[File]) -> Result<Vec<i64>, DatabaseError> {
+        let blob_ids: Vec<i64> = files.iter().map(|file| file.blob_id).collect();
+        let blobs = sqlx::query_scalar!(
+            r#"SELECT DISTINCT blob AS "blob!"
+               FROM UNNEST($1::bigint[]) AS blob
+               WHERE NOT EXISTS (SELECT 1 FROM files WHERE files.blob_id = blob)"#,
+            &blob_ids
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+        Ok(blobs)
+    }
+
     async fn delete_all_messages_by_user(
         &mut self,
         user_id: i64,
@@ -323,7 +431,7 @@ impl UserTransaction for PgUserTransaction {
             Message,
             r#"DELETE FROM messages
                WHERE sender_id = $1
-               RETURNING id, sender_id, channel_id, recipient_id, message_text, created_at, modified_at, reply_to_message_id"#,
+               RETURNING id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at"#,
             user_id
         )
         .fetch_all(&mut *self.transaction)
@@ -355,6 +463,8 @@ impl UserTransaction for PgUserTransaction {
                RETURNING
                    user_id,
                    username,
+                   display_name,
+                   last_seen_at,
                    created_at,
                    avatar_file_id,
                    role_id,
@@ -367,6 +477,43 @@ impl UserTransaction for PgUserTransaction {
         .await?;
         Ok(user)
     }
+
+    async fn create_block(
+        &mut self,
+        blocker_id: i64,
+        blocked_id: i64,
+    ) -> Result<BlockedUser, DatabaseError> {
+        let block = sqlx::query_as!(
+            BlockedUser,
+            r#"INSERT INTO blocked_users (blocker_id, blocked_id)
+               VALUES ($1, $2)
+               ON CONFLICT (blocker_id, blocked_id) DO UPDATE SET created_at = blocked_users.created_at
+               RETURNING blocker_id, blocked_id, created_at"#,
+            blocker_id,
+            blocked_id
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+        Ok(block)
+    }
+
+    async fn delete_block(
+        &mut self,
+        blocker_id: i64,
+        blocked_id: i64,
+    ) -> Result<Option<BlockedUser>, DatabaseError> {
+        let block = sqlx::query_as!(
+            BlockedUser,
+            r#"DELETE FROM blocked_users
+               WHERE blocker_id = $1 AND blocked_id = $2
+               RETURNING blocker_id, blocked_id, created_at"#,
+            blocker_id,
+            blocked_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+        Ok(block)
+    }
 }
 
 impl UserRepository for Postgre {
@@ -393,6 +540,8 @@ impl UserRepository for Postgre {
             r#"SELECT
                    u.user_id,
                    u.username,
+                   u.display_name,
+                   u.last_seen_at,
                    u.created_at,
                    u.avatar_file_id,
                    u.role_id,
@@ -446,12 +595,106 @@ impl UserRepository for Postgre {
         .await?;
         Ok(result)
     }
+
+    async fn find_blocked_users(&self, blocker_id: i64) -> Result<Vec<BlockedUser>, DatabaseError> {
+        let result = sqlx::query_as!(
+            BlockedUser,
+            r#"SELECT blocker_id, blocked_id, created_at
+               FROM blocked_users
+               WHERE blocker_id = $1
+               ORDER BY created_at"#,
+            blocker_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_user(&self, user_id: i64) -> Result<Option<User>, DatabaseError> {
+        let result = sqlx::query_as!(
+            User,
+            r#"SELECT
+                   user_id,
+                   username,
+                   display_name,
+                   last_seen_at,
+                   created_at,
+                   avatar_file_id,
+                   role_id,
+                   CASE WHEN status = 'Offline' THEN status ELSE COALESCE(manual_status, status) END as "status!: UserStatusType",
+                   server_mute,
+                   server_deafen
+               FROM users
+               WHERE user_id = $1"#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_user_messages(&self, user_id: i64) -> Result<Vec<Message>, DatabaseError> {
+        let result = sqlx::query_as!(
+            Message,
+            r#"SELECT id, sender_id, channel_id, recipient_id, conversation_id, message_text, created_at, modified_at, reply_to_message_id, forwarded_from_message_id, metadata, expires_at
+               FROM messages
+               WHERE (
+                   sender_id = $1
+                   OR (recipient_id = $1 AND NOT EXISTS (
+                       SELECT 1 FROM dm_requests req
+                       WHERE req.sender_id = messages.sender_id
+                       AND req.recipient_id = messages.recipient_id
+                       AND req.status <> 'Accepted'
+                   ))
+                   OR conversation_id IN (
+                       SELECT conversation_id FROM group_conversation_members WHERE user_id = $1
+                   )
+               )
+               AND (expires_at IS NULL OR expires_at > NOW())
+               ORDER BY id"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_user_reactions(&self, user_id: i64) -> Result<Vec<Reaction>, DatabaseError> {
+        let result = sqlx::query_as!(
+            Reaction,
+            r#"SELECT reaction_id, message_id, user_id, emoji, created_at
+               FROM reactions
+               WHERE user_id = $1
+               ORDER BY reaction_id"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_user_files(&self, user_id: i64) -> Result<Vec<File>, DatabaseError> {
+        let result = sqlx::query_as!(
+            File,
+            r#"SELECT f.file_id, f.file_uuid, f.message_id, f.file_name, f.metadata as "metadata: sqlx::types::Json<FileMetadata>", f.file_size, f.file_hash, f.blob_id, f.created_at
+               FROM files f
+               INNER JOIN messages m ON m.id = f.message_id
+               WHERE m.sender_id = $1
+               ORDER BY f.file_id"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // SERVICE
 // ═══════════════════════════════════════════════════════════════════════════════
 
+const MAX_DISPLAY_NAME_LENGTH: usize = 64;
+
 #[derive(Clone)]
 pub struct UserService<
     R: UserRepository,
@@ -463,6 +706,7 @@ pub struct UserService<
     file_manager: F,
     notifier: N,
     logger: G,
+    username_validator: UsernameValidator,
 }
 
 impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: LogManager>
@@ -474,9 +718,15 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
             file_manager,
             notifier,
             logger,
+            username_validator: UsernameValidator::default(),
         }
     }
 
+    pub fn with_username_validator(mut self, username_validator: UsernameValidator) -> Self {
+        self.username_validator = username_validator;
+        self
+    }
+
     pub async fn update_user_avatar(
         &self,
         user_id: i64,
@@ -520,7 +770,7 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
         let updated_user = tx
             .update_user_avatar(user_id, avatar_file.file_id)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -563,14 +813,14 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
             self.repository
                 .find_avatar_file(avatar_id)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "Avatar {} not found",
                     avatar_id
                 )))?;
 
         let file_data = self.file_manager.get_file(avatar_id).map_err(|e| match e {
             FileError::NotFound(_) => {
-                DomainError::BadRequest(format!("Avatar {} not found", avatar_id))
+                DomainError::NotFound(format!("Avatar {} not found", avatar_id))
             }
             _ => DomainError::FileManagerError(e),
         })?;
@@ -584,12 +834,23 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
         session_id: i64,
         username: String,
     ) -> Result<(), DomainError> {
+        let username = self
+            .username_validator
+            .normalize(&username)
+            .map_err(|e| DomainError::BadRequest(format!("Username validation failed: {}", e)))?;
+
         let mut tx = self.repository.begin().await?;
 
         let updated_user = tx
             .update_username(user_id, &username)
-            .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .await
+            .map_err(|e| match e {
+                DatabaseError::UniqueConstraintViolation { .. } => {
+                    DomainError::BadRequest(format!("Username {} already exists", username))
+                }
+                e => DomainError::InternalError(e),
+            })?
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -621,6 +882,64 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
         Ok(())
     }
 
+    pub async fn set_display_name(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        display_name: Option<String>,
+    ) -> Result<(), DomainError> {
+        let display_name = display_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+
+        if let Some(name) = &display_name {
+            if name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+                return Err(DomainError::BadRequest(format!(
+                    "Display name cannot exceed {} characters",
+                    MAX_DISPLAY_NAME_LENGTH
+                )));
+            }
+            if name.chars().any(char::is_control) {
+                return Err(DomainError::BadRequest(
+                    "Display name cannot contain control characters".to_string(),
+                ));
+            }
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let updated_user = tx
+            .update_display_name(user_id, display_name.as_deref())
+            .await?
+            .ok_or(DomainError::NotFound(format!("User {} not found", user_id)))?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::UserUpdated {
+            user: updated_user.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::Broadcast,
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "Display name updated: user_id={}, session_id={}, display_name={:?}",
+                    user_id, session_id, display_name
+                ),
+                "user".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+
     pub async fn update_manual_user_status(
         &self,
         user_id: i64,
@@ -639,7 +958,7 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
         let updated_user = tx
             .update_manual_user_status(user_id, manual_status.clone())
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "User {} not found",
                 user_id
             )))?;
@@ -671,6 +990,136 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
         Ok(())
     }
 
+    pub async fn get_blocked_users(&self, user_id: i64) -> Result<Vec<BlockedUser>, DomainError> {
+        let blocks = self.repository.find_blocked_users(user_id).await?;
+        Ok(blocks)
+    }
+
+    pub async fn block_user(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        target_user_id: i64,
+    ) -> Result<BlockedUser, DomainError> {
+        if user_id == target_user_id {
+            return Err(DomainError::BadRequest("Cannot block yourself".to_string()));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let block = tx
+            .create_block(user_id, target_user_id)
+            .await
+            .map_err(|e| match &e {
+                DatabaseError::ForeignKeyViolation { .. } => {
+                    DomainError::NotFound(format!("User {} not found", target_user_id))
+                }
+                _ => DomainError::InternalError(e),
+            })?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::UserBlocked {
+            block: block.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::User { user_id },
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "User blocked: user_id={}, session_id={}, blocked_id={}",
+                    user_id, session_id, target_user_id
+                ),
+                "user".to_string(),
+            )
+            .await;
+
+        Ok(block)
+    }
+
+    pub async fn unblock_user(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        target_user_id: i64,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        tx.delete_block(user_id, target_user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "User {} is not blocked",
+                target_user_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let event = EventPayload::UserUnblocked {
+            blocked_id: target_user_id,
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(
+                event,
+                ControlRoutingPolicy::User { user_id },
+            ))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "User unblocked: user_id={}, session_id={}, blocked_id={}",
+                    user_id, session_id, target_user_id
+                ),
+                "user".to_string(),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    pub async fn export_user_data(
+        &self,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<UserDataExport, DomainError> {
+        let profile = self
+            .repository
+            .find_user(user_id)
+            .await?
+            .ok_or(DomainError::NotFound("User not found".to_string()))?;
+
+        let export = UserDataExport {
+            exported_at: OffsetDateTime::now_utc(),
+            profile,
+            messages: self.repository.find_user_messages(user_id).await?,
+            reactions: self.repository.find_user_reactions(user_id).await?,
+            files: self.repository.find_user_files(user_id).await?,
+            blocked_users: self.repository.find_blocked_users(user_id).await?,
+        };
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "User data exported: user_id={}, session_id={}",
+                    user_id, session_id
+                ),
+                "user".to_string(),
+            )
+            .await;
+
+        Ok(export)
+    }
+
     pub async fn get_all_users(&self, requester_id: i64) -> Result<Vec<User>, DomainError> {
         let users = self.repository.find_all_users(requester_id).await?;
         Ok(users)
@@ -692,7 +1141,7 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
         let target_role =
             repo.find_user_role(target_user_id)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "User {} not found",
                     target_user_id
                 )))?;
@@ -718,20 +1167,21 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
         let mut tx = self.repository.begin().await?;
 
         let deleted_files = tx.delete_all_files_by_user(target_user_id).await?;
+        let deleted_blobs = tx.find_unreferenced_blobs(&deleted_files).await?;
         let deleted_messages = tx.delete_all_messages_by_user(target_user_id).await?;
         let deleted_voip = tx.delete_voip_participant_by_user(target_user_id).await?;
         let _deleted_user =
             tx.delete_user(target_user_id)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "User {} not found",
                     target_user_id
                 )))?;
 
         self.repository.commit(tx).await?;
 
-        for file in &deleted_files {
-            let _ = self.file_manager.delete_file(file.file_id);
+        for blob_id in deleted_blobs {
+            let _ = self.file_manager.delete_file(blob_id);
         }
 
         for message in &deleted_messages {
@@ -746,7 +1196,7 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
                         event,
                         ControlRoutingPolicy::ChannelRights {
                             channel_id,
-                            minimun_rights: 2,
+                            minimun_rights: READ_RIGHTS,
                         },
                     ))
                     .await;
@@ -775,7 +1225,7 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
                         event,
                         ControlRoutingPolicy::ChannelRights {
                             channel_id,
-                            minimun_rights: 1,
+                            minimun_rights: ACKNOWLEDGE_RIGHTS,
                         },
                     ))
                     .await;
@@ -800,6 +1250,7 @@ impl<R: UserRepository, F: FileManager + Clone + Send, N: NotifierManager, G: Lo
             .notifier
             .notify(ServerMessage::Command(CommandPayload::DisconnectUser(
                 target_user_id,
+                CloseReason::UserDeleted,
             )))
             .await;
 
@@ -836,6 +1287,12 @@ pub struct UpdateUsernameRequest {
     pub username: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDisplayNameRequest {
+    pub display_name: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateManualUserStatusRequest {
@@ -858,11 +1315,15 @@ pub fn user_routes(
 ) -> OpenApiRouter<Postgre> {
     OpenApiRouter::new()
         .routes(routes!(update_username_handler))
+        .routes(routes!(update_display_name_handler))
         .routes(routes!(update_user_avatar_handler))
         .routes(routes!(get_user_avatar_handler))
         .routes(routes!(update_manual_user_status_handler))
         .routes(routes!(get_all_users_handler))
         .routes(routes!(delete_user_handler))
+        .routes(routes!(get_blocked_users_handler))
+        .routes(routes!(export_user_data_handler))
+        .routes(routes!(block_user_handler, unblock_user_handler))
         .layer(from_fn_with_state(authorize_service, authorize))
         .with_state(user_service)
 }
@@ -871,6 +1332,118 @@ pub fn user_routes(
 // HANDLERS
 // ═══════════════════════════════════════════════════════════════════════════════
 
+pub fn avatar_response(
+    avatar_file: AvatarFile,
+    file_data: Vec<u8>,
+    request_headers: &HeaderMap,
+) -> Response {
+    let etag = format!("\"{}\"", avatar_file.file_hash);
+    let header_value = |name: header::HeaderName| {
+        request_headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, value);
+    }
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=3600"),
+    );
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    let not_modified = header_value(header::IF_NONE_MATCH).is_some_and(|value| {
+        value
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    });
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&avatar_file.file_type)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("inline; filename=\"{}\"", avatar_file.file_name))
+            .unwrap_or_else(|_| HeaderValue::from_static("inline")),
+    );
+
+    let range_applies = header_value(header::IF_RANGE).is_none_or(|value| value == etag);
+    let range = header_value(header::RANGE).filter(|_| range_applies);
+    let Some(range) = range else {
+        return (StatusCode::OK, headers, file_data).into_response();
+    };
+
+    let total = file_data.len();
+    match parse_byte_range(range, total) {
+        Some(ByteRange::Satisfiable(start, end)) => {
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, total))
+            {
+                headers.insert(header::CONTENT_RANGE, value);
+            }
+            (
+                StatusCode::PARTIAL_CONTENT,
+                headers,
+                file_data[start..=end].to_vec(),
+            )
+                .into_response()
+        }
+        Some(ByteRange::Unsatisfiable) => {
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{}", total)) {
+                headers.insert(header::CONTENT_RANGE, value);
+            }
+            (StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response()
+        }
+        None => (StatusCode::OK, headers, file_data).into_response(),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    Satisfiable(usize, usize),
+    Unsatisfiable,
+}
+
+fn parse_byte_range(value: &str, total: usize) -> Option<ByteRange> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let (start, end) = if start.is_empty() {
+        let suffix: usize = end.parse().ok()?;
+        if suffix == 0 {
+            return Some(ByteRange::Unsatisfiable);
+        }
+        (total.saturating_sub(suffix), total.checked_sub(1)?)
+    } else {
+        let start: usize = start.parse().ok()?;
+        let end = match end {
+            "" => total.saturating_sub(1),
+            end => end
+                .parse::<usize>()
+                .ok()
+                .filter(|end| *end >= start)?
+                .min(total.saturating_sub(1)),
+        };
+        (start, end)
+    };
+
+    if total == 0 || start >= total {
+        return Some(ByteRange::Unsatisfiable);
+    }
+    Some(ByteRange::Satisfiable(start, end))
+}
+
 #[utoipa::path(
     put,
     tag = "user",
@@ -897,6 +1470,32 @@ async fn update_username_handler(
     Ok(())
 }
 
+#[utoipa::path(
+    put,
+    tag = "user",
+    path = "/me/display-name",
+    request_body = UpdateDisplayNameRequest,
+    responses(
+        (status = 200, description = "Display name updated successfully"),
+        (status = 400, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn update_display_name_handler(
+    State(service): State<
+        UserService<Postgre, LocalFileManager, DefaultNotifierManager, TextLogManager>,
+    >,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<UpdateDisplayNameRequest>,
+) -> Result<(), ApiError> {
+    service
+        .set_display_name(session.user_id, session.session_id, payload.display_name)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
 #[utoipa::path(
     post,
     tag = "user",
@@ -951,7 +1550,10 @@ async fn update_user_avatar_handler(
     ),
     responses(
         (status = 200, description = "Avatar retrieved successfully"),
+        (status = 206, description = "Partial avatar content"),
+        (status = 304, description = "Avatar not modified"),
         (status = 404, description = "Avatar not found", body = ApiError),
+        (status = 416, description = "Range not satisfiable"),
         (status = 500, description = "Internal Server Error", body = ApiError),
     ),
     security(("api_key" = []))
@@ -962,21 +1564,14 @@ async fn get_user_avatar_handler(
     >,
     Extension(_session): Extension<Session>,
     Path(avatar_id): Path<i64>,
+    request_headers: HeaderMap,
 ) -> Result<impl IntoResponse, ApiError> {
     let (avatar_file, file_data) = service
         .get_avatar_by_id(avatar_id)
         .await
         .map_err(ApiError::from)?;
-    let headers = [
-        ("Content-Type", avatar_file.file_type),
-        (
-            "Content-Disposition",
-            format!("inline; filename=\"{}\"", avatar_file.file_name),
-        ),
-        ("Cache-Control", "public, max-age=3600".to_string()),
-    ];
-
-    Ok((headers, file_data))
+
+    Ok(avatar_response(avatar_file, file_data, &request_headers))
 }
 
 #[utoipa::path(
@@ -1064,3 +1659,326 @@ async fn delete_user_handler(
         .map_err(ApiError::from)?;
     Ok(())
 }
+
+#[utoipa::path(
+    get,
+    tag = "user",
+    path = "/me/export",
+    description = "Download the current user's profile, messages, reactions and file metadata. File contents are referenced by id.",
+    responses(
+        (status = 200, description = "User data exported successfully", body = UserDataExport),
+        (status = 404, description = "User not found", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn export_user_data_handler(
+    State(service): State<
+        UserService<Postgre, LocalFileManager, DefaultNotifierManager, TextLogManager>,
+    >,
+    Extension(session): Extension<Session>,
+) -> Result<impl IntoResponse, ApiError> {
+    let export = service
+        .export_user_data(session.user_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+    let headers = [(
+        "Content-Disposition",
+        format!("attachment; filename=\"opencord-export-{}.json\"", session.user_id),
+    )];
+
+    Ok((headers, Json(export)))
+}
+
+#[utoipa::path(
+    get,
+    tag = "user",
+    path = "/blocks",
+    responses(
+        (status = 200, description = "Blocked users retrieved successfully", body = Vec<BlockedUser>),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_blocked_users_handler(
+    State(service): State<
+        UserService<Postgre, LocalFileManager, DefaultNotifierManager, TextLogManager>,
+    >,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<BlockedUser>>, ApiError> {
+    let blocks = service
+        .get_blocked_users(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(blocks))
+}
+
+#[utoipa::path(
+    post,
+    tag = "user",
+    path = "/{user_id}/block",
+    params(
+        ("user_id", Path, description = "The ID of the user to block"),
+    ),
+    responses(
+        (status = 200, description = "User blocked successfully", body = BlockedUser),
+        (status = 422, description = "User not found", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn block_user_handler(
+    State(service): State<
+        UserService<Postgre, LocalFileManager, DefaultNotifierManager, TextLogManager>,
+    >,
+    Extension(session): Extension<Session>,
+    Path(target_user_id): Path<i64>,
+) -> Result<Json<BlockedUser>, ApiError> {
+    let block = service
+        .block_user(session.user_id, session.session_id, target_user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(block))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "user",
+    path = "/{user_id}/block",
+    params(
+        ("user_id", Path, description = "The ID of the user to unblock"),
+    ),
+    responses(
+        (status = 200, description = "User unblocked successfully"),
+        (status = 422, description = "User is not blocked", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn unblock_user_handler(
+    State(service): State<
+        UserService<Postgre, LocalFileManager, DefaultNotifierManager, TextLogManager>,
+    >,
+    Extension(session): Extension<Session>,
+    Path(target_user_id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .unblock_user(session.user_id, session.session_id, target_user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    async fn create_user(pool: &PgPool, username: &str) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO users (username) VALUES ($1) RETURNING user_id",
+            username
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn send_dm(pool: &PgPool, sender_id: i64, recipient_id: i64) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO messages (sender_id, recipient_id, message_text) VALUES ($1, $2, 'hi') RETURNING id",
+            sender_id,
+            recipient_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn create_conversation(pool: &PgPool, member_ids: &[i64]) -> i64 {
+        sqlx::query_scalar!(
+            r#"WITH conversation AS (
+                   INSERT INTO group_conversations DEFAULT VALUES RETURNING conversation_id
+               ), members AS (
+                   INSERT INTO group_conversation_members (conversation_id, user_id)
+                   SELECT conversation_id, UNNEST($1::bigint[]) FROM conversation
+               )
+               SELECT conversation_id AS "conversation_id!" FROM conversation"#,
+            member_ids
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn send_group(pool: &PgPool, sender_id: i64, conversation_id: i64) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO messages (sender_id, conversation_id, message_text) VALUES ($1, $2, 'hi') RETURNING id",
+            sender_id,
+            conversation_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn export_excludes_other_users_private_messages(pool: PgPool) {
+        let repository = Postgre { pool: pool.clone() };
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let shared = create_conversation(&pool, &[alice, bob]).await;
+        let private = create_conversation(&pool, &[bob, carol]).await;
+
+        let sent = send_dm(&pool, alice, bob).await;
+        let received = send_dm(&pool, bob, alice).await;
+        let _between_others = send_dm(&pool, bob, carol).await;
+        let _pending = send_dm(&pool, carol, alice).await;
+        sqlx::query!(
+            "INSERT INTO dm_requests (sender_id, recipient_id) VALUES ($1, $2)",
+            carol,
+            alice
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let group_reply = send_group(&pool, bob, shared).await;
+        let _other_group = send_group(&pool, bob, private).await;
+
+        let exported: Vec<i64> = repository
+            .find_user_messages(alice)
+            .await
+            .unwrap()
+            .iter()
+            .map(|message| message.id)
+            .collect();
+
+        assert_eq!(exported, vec![sent, received, group_reply]);
+    }
+
+    fn avatar() -> AvatarFile {
+        AvatarFile {
+            file_id: 1,
+            file_uuid: "uuid".to_string(),
+            file_name: "avatar.png".to_string(),
+            file_type: "image/png".to_string(),
+            file_size: 10,
+            file_hash: "abc".to_string(),
+        }
+    }
+
+    async fn respond(
+        request_headers: &[(header::HeaderName, &str)],
+    ) -> (StatusCode, HeaderMap, Vec<u8>) {
+        let mut headers = HeaderMap::new();
+        for (name, value) in request_headers {
+            headers.insert(name, HeaderValue::from_str(value).unwrap());
+        }
+        let response = avatar_response(avatar(), b"0123456789".to_vec(), &headers);
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, headers, body.to_vec())
+    }
+
+    #[test]
+    fn byte_ranges_are_parsed_and_clamped() {
+        assert_eq!(
+            parse_byte_range("bytes=0-3", 10),
+            Some(ByteRange::Satisfiable(0, 3))
+        );
+        assert_eq!(
+            parse_byte_range("bytes=5-", 10),
+            Some(ByteRange::Satisfiable(5, 9))
+        );
+        assert_eq!(
+            parse_byte_range("bytes=-4", 10),
+            Some(ByteRange::Satisfiable(6, 9))
+        );
+        assert_eq!(
+            parse_byte_range("bytes=8-100", 10),
+            Some(ByteRange::Satisfiable(8, 9))
+        );
+        assert_eq!(
+            parse_byte_range("bytes=10-", 10),
+            Some(ByteRange::Unsatisfiable)
+        );
+        assert_eq!(
+            parse_byte_range("bytes=-0", 10),
+            Some(ByteRange::Unsatisfiable)
+        );
+        assert_eq!(parse_byte_range("bytes=4-2", 10), None);
+        assert_eq!(parse_byte_range("bytes=0-1,4-5", 10), None);
+        assert_eq!(parse_byte_range("items=0-1", 10), None);
+    }
+
+    #[tokio::test]
+    async fn matching_etag_is_not_modified() {
+        let (status, headers, body) =
+            respond(&[(header::IF_NONE_MATCH, "\"other\", W/\"abc\"")]).await;
+
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+        assert_eq!(headers[header::ETAG], "\"abc\"");
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn range_request_returns_the_requested_slice() {
+        let (status, headers, body) = respond(&[(header::RANGE, "bytes=2-4")]).await;
+
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(headers[header::CONTENT_RANGE], "bytes 2-4/10");
+        assert_eq!(body, b"234");
+    }
+
+    #[tokio::test]
+    async fn stale_if_range_returns_the_whole_file() {
+        let (status, _, body) =
+            respond(&[(header::RANGE, "bytes=2-4"), (header::IF_RANGE, "\"old\"")]).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, b"0123456789");
+    }
+
+    #[tokio::test]
+    async fn range_past_the_end_is_not_satisfiable() {
+        let (status, headers, _) = respond(&[(header::RANGE, "bytes=20-")]).await;
+
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(headers[header::CONTENT_RANGE], "bytes */10");
+    }
+
+    fn service(
+        pool: PgPool,
+    ) -> UserService<Postgre, LocalFileManager, DefaultNotifierManager, TextLogManager> {
+        let (sender, _) = tokio::sync::mpsc::channel(16);
+        UserService::new(
+            Postgre { pool },
+            LocalFileManager::new(std::env::temp_dir().join("opencord-user-test-files")),
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-user-test.log")),
+        )
+    }
+
+    #[sqlx::test]
+    async fn unblocking_a_user_that_is_not_blocked_is_not_found(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let service = service(pool);
+
+        let block = service.block_user(alice, 1, bob).await.unwrap();
+        let unblocked = service.unblock_user(alice, 1, bob).await;
+        let unblocked_again = service.unblock_user(alice, 1, bob).await;
+
+        assert_eq!((block.blocker_id, block.blocked_id), (alice, bob));
+        assert!(unblocked.is_ok());
+        assert!(matches!(unblocked_again, Err(DomainError::NotFound(_))));
+        assert!(matches!(
+            service.block_user(alice, 1, alice).await,
+            Err(DomainError::BadRequest(_))
+        ));
+    }
+}
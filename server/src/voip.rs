@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use utoipa::ToSchema;
 
+use crate::acl::{ACKNOWLEDGE_RIGHTS, ACL_RIGHTS, READ_RIGHTS};
 use crate::auth::Session;
 use crate::db::Postgre;
 use crate::error::{ApiError, DatabaseError};
@@ -14,13 +15,22 @@ use crate::managers::{DefaultNotifierManager, LogManager, NotifierManager, TextL
 use crate::middleware::{AuthorizeService, authorize};
 use crate::model::EventPayload;
 use crate::role::{ADMIN_ROLE_ID, OWNER_ROLE_ID};
-use crate::transport::{ControlRoutingPolicy, ServerMessage};
+use crate::transport::{ControlRoutingPolicy, QueryPayload, ServerMessage};
 
 use axum::Json;
 use axum::extract::{Extension, Path, State};
 use axum::middleware::from_fn_with_state;
+use tokio::sync::oneshot;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONSTANTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+const MAX_CODEC_NAME_LENGTH: usize = 32;
+const MIN_CODEC_SAMPLE_RATE: i32 = 8_000;
+const MAX_CODEC_SAMPLE_RATE: i32 = 48_000;
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // MODELS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -39,6 +49,23 @@ pub struct VoipParticipant {
     pub created_at: OffsetDateTime,
 }
 
+/// Codec parameters a publisher advertises to its subscribers. The server does
+/// not transcode; it only relays what the publisher announced.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VoipCodecParams {
+    pub user_id: i64,
+    pub codec: String,
+    pub sample_rate: i32,
+    pub channels: i16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VoipSpeakingState {
+    pub user_id: i64,
+    pub speaking: bool,
+}
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // ERROR
@@ -52,18 +79,25 @@ pub enum DomainError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error")]
     InternalError(#[from] DatabaseError),
 
     #[error("LiveKit error: {0}")]
     LiveKitError(String),
+
+    #[error("Realtime server unavailable")]
+    RealtimeUnavailable,
 }
 
 impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
         match err {
             DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
-            DomainError::PermissionDenied(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
             DomainError::InternalError(db_err) => {
                 tracing::error!("Database error: {}", db_err);
                 ApiError::InternalServerError("Internal server error".to_string())
@@ -72,6 +106,10 @@ impl From<DomainError> for ApiError {
                 tracing::error!("LiveKit error: {}", msg);
                 ApiError::InternalServerError("Internal server error".to_string())
             }
+            DomainError::RealtimeUnavailable => {
+                tracing::error!("Realtime server unavailable");
+                ApiError::InternalServerError("Internal server error".to_string())
+            }
         }
     }
 }
@@ -81,6 +119,11 @@ impl From<DomainError> for ApiError {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 pub trait VoipTransaction: Send + Sync {
+    async fn count_channel_voip_participants(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<i64, DatabaseError>;
+
     async fn create_channel_voip_participant(
         &mut self,
         user_id: i64,
@@ -114,6 +157,11 @@ pub trait VoipTransaction: Send + Sync {
         user_id: i64,
     ) -> Result<Option<VoipParticipant>, DatabaseError>;
 
+    async fn remove_channel_participants(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<Vec<VoipParticipant>, DatabaseError>;
+
     async fn set_publish_screen(
         &mut self,
         user_id: i64,
@@ -125,6 +173,19 @@ pub trait VoipTransaction: Send + Sync {
         user_id: i64,
         publish: bool,
     ) -> Result<Option<VoipParticipant>, DatabaseError>;
+
+    async fn find_participant(
+        &mut self,
+        user_id: i64,
+    ) -> Result<Option<VoipParticipant>, DatabaseError>;
+
+    async fn set_codec_params(
+        &mut self,
+        user_id: i64,
+        codec: &str,
+        sample_rate: i32,
+        channels: i16,
+    ) -> Result<Option<VoipCodecParams>, DatabaseError>;
 }
 
 pub trait VoipRepository: Send + Sync + Clone {
@@ -141,6 +202,11 @@ pub trait VoipRepository: Send + Sync + Clone {
         requesting_user_id: i64,
     ) -> Result<Vec<VoipParticipant>, DatabaseError>;
 
+    async fn find_codec_params(
+        &self,
+        requesting_user_id: i64,
+    ) -> Result<Vec<VoipCodecParams>, DatabaseError>;
+
     async fn find_user_channel_rights(
         &self,
         channel_id: i64,
@@ -148,6 +214,14 @@ pub trait VoipRepository: Send + Sync + Clone {
     ) -> Result<Option<i64>, DatabaseError>;
 
     async fn find_user_role(&self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
+
+    async fn find_max_voip_participants(&self) -> Result<i32, DatabaseError>;
+
+    async fn is_blocked_between(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+    ) -> Result<bool, DatabaseError>;
 }
 
 pub struct PgVoipTransaction {
@@ -155,6 +229,27 @@ pub struct PgVoipTransaction {
 }
 
 impl VoipTransaction for PgVoipTransaction {
+    async fn count_channel_voip_participants(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<i64, DatabaseError> {
+        sqlx::query!(
+            "SELECT channel_id FROM channels WHERE channel_id = $1 FOR UPDATE",
+            channel_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM voip_participants WHERE channel_id = $1"#,
+            channel_id
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(count)
+    }
+
     async fn create_channel_voip_participant(
         &mut self,
         user_id: i64,
@@ -256,6 +351,23 @@ impl VoipTransaction for PgVoipTransaction {
         Ok(participant)
     }
 
+    async fn remove_channel_participants(
+        &mut self,
+        channel_id: i64,
+    ) -> Result<Vec<VoipParticipant>, DatabaseError> {
+        let participants = sqlx::query_as!(
+            VoipParticipant,
+            r#"DELETE FROM voip_participants
+               WHERE channel_id = $1
+               RETURNING user_id, channel_id, recipient_id, local_deafen, local_mute, publish_screen, publish_camera, created_at"#,
+            channel_id
+        )
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        Ok(participants)
+    }
+
     async fn set_publish_screen(
         &mut self,
         user_id: i64,
@@ -295,6 +407,49 @@ impl VoipTransaction for PgVoipTransaction {
 
         Ok(participant)
     }
+
+    async fn find_participant(
+        &mut self,
+        user_id: i64,
+    ) -> Result<Option<VoipParticipant>, DatabaseError> {
+        let participant = sqlx::query_as!(
+            VoipParticipant,
+            r#"SELECT user_id, channel_id, recipient_id, local_deafen, local_mute, publish_screen, publish_camera, created_at
+               FROM voip_participants
+               WHERE user_id = $1
+               FOR SHARE"#,
+            user_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(participant)
+    }
+
+    async fn set_codec_params(
+        &mut self,
+        user_id: i64,
+        codec: &str,
+        sample_rate: i32,
+        channels: i16,
+    ) -> Result<Option<VoipCodecParams>, DatabaseError> {
+        let params = sqlx::query_as!(
+            VoipCodecParams,
+            r#"INSERT INTO voip_codec_params (user_id, codec, sample_rate, channels)
+               SELECT user_id, $2, $3, $4 FROM voip_participants WHERE user_id = $1
+               ON CONFLICT (user_id) DO UPDATE
+               SET codec = EXCLUDED.codec, sample_rate = EXCLUDED.sample_rate, channels = EXCLUDED.channels
+               RETURNING user_id, codec, sample_rate, channels"#,
+            user_id,
+            codec,
+            sample_rate,
+            channels
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(params)
+    }
 }
 
 impl VoipRepository for Postgre {
@@ -327,10 +482,39 @@ impl VoipRepository for Postgre {
                LEFT JOIN group_role_rights grr ON c.group_id = grr.group_id
                LEFT JOIN users u ON u.role_id = grr.role_id AND u.user_id = $1
                WHERE
-                   (vp.channel_id IS NOT NULL AND grr.rights >= 1 AND u.user_id IS NOT NULL)
+                   (vp.channel_id IS NOT NULL AND grr.rights >= $2 AND u.user_id IS NOT NULL)
                    OR
                    (vp.recipient_id IS NOT NULL AND (vp.user_id = $1 OR vp.recipient_id = $1))"#,
-            requesting_user_id
+            requesting_user_id,
+            ACKNOWLEDGE_RIGHTS
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_codec_params(
+        &self,
+        requesting_user_id: i64,
+    ) -> Result<Vec<VoipCodecParams>, DatabaseError> {
+        let results = sqlx::query_as!(
+            VoipCodecParams,
+            r#"SELECT vc.user_id, vc.codec, vc.sample_rate, vc.channels
+               FROM voip_codec_params vc
+               WHERE vc.user_id IN (
+                   SELECT vp.user_id
+                   FROM voip_participants vp
+                   LEFT JOIN channels c ON vp.channel_id = c.channel_id
+                   LEFT JOIN group_role_rights grr ON c.group_id = grr.group_id
+                   LEFT JOIN users u ON u.role_id = grr.role_id AND u.user_id = $1
+                   WHERE
+                       (vp.channel_id IS NOT NULL AND grr.rights >= $2 AND u.user_id IS NOT NULL)
+                       OR
+                       (vp.recipient_id IS NOT NULL AND (vp.user_id = $1 OR vp.recipient_id = $1))
+               )"#,
+            requesting_user_id,
+            ACKNOWLEDGE_RIGHTS
         )
         .fetch_all(&self.pool)
         .await?;
@@ -364,6 +548,33 @@ impl VoipRepository for Postgre {
             .await?;
         Ok(result)
     }
+
+    async fn find_max_voip_participants(&self) -> Result<i32, DatabaseError> {
+        let result =
+            sqlx::query_scalar!("SELECT max_voip_participants FROM server_config WHERE id = 1")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(result)
+    }
+
+    async fn is_blocked_between(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM blocked_users
+                   WHERE (blocker_id = $1 AND blocked_id = $2)
+                      OR (blocker_id = $2 AND blocked_id = $1)
+               ) as "exists!""#,
+            user_id,
+            other_user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -430,6 +641,45 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         Ok(participants)
     }
 
+    pub async fn get_speaking_states(
+        &self,
+        requesting_user_id: i64,
+    ) -> Result<Vec<VoipSpeakingState>, DomainError> {
+        let participants = self
+            .repository
+            .find_voip_participants(requesting_user_id)
+            .await?;
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.notifier
+            .notify(ServerMessage::Query(QueryPayload::SpeakingUsers(reply_tx)))
+            .await
+            .map_err(|_| DomainError::RealtimeUnavailable)?;
+        let speaking_users = reply_rx
+            .await
+            .map_err(|_| DomainError::RealtimeUnavailable)?;
+
+        Ok(participants
+            .into_iter()
+            .map(|p| VoipSpeakingState {
+                user_id: p.user_id,
+                speaking: speaking_users.contains(&p.user_id),
+            })
+            .collect())
+    }
+
+    pub async fn get_codec_params(
+        &self,
+        requesting_user_id: i64,
+    ) -> Result<Vec<VoipCodecParams>, DomainError> {
+        let params = self
+            .repository
+            .find_codec_params(requesting_user_id)
+            .await?;
+
+        Ok(params)
+    }
+
     pub async fn join_channel_voip(
         &self,
         user_id: i64,
@@ -446,16 +696,24 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
                 "No access to channel".to_string(),
             ))?;
 
-        if rights < 2 {
+        if rights < READ_RIGHTS {
             return Err(DomainError::PermissionDenied(
                 "Insufficient permissions to join voice channel".to_string(),
             ));
         }
 
-        let can_publish = rights > 2;
+        let can_publish = rights > READ_RIGHTS;
+
+        let max_participants = self.repository.find_max_voip_participants().await?;
 
         let mut tx = self.repository.begin().await?;
 
+        let participants = tx.count_channel_voip_participants(channel_id).await?;
+        if participants >= max_participants as i64 {
+            self.repository.rollback(tx).await?;
+            return Err(DomainError::BadRequest("channel voice full".to_string()));
+        }
+
         let participant = tx
             .create_channel_voip_participant(user_id, channel_id, local_mute, local_deafen)
             .await
@@ -465,7 +723,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
                 }
                 DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
                     "channel_id" => {
-                        DomainError::BadRequest(format!("Channel {} not found", channel_id))
+                        DomainError::NotFound(format!("Channel {} not found", channel_id))
                     }
                     _ => DomainError::InternalError(e),
                 },
@@ -482,7 +740,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
                 event,
                 ControlRoutingPolicy::ChannelRights {
                     channel_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 },
             ))
             .await;
@@ -514,12 +772,22 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         let recipient_role = self.repository.find_user_role(recipient_user_id).await?;
 
         if recipient_role.is_none() {
-            return Err(DomainError::BadRequest(format!(
+            return Err(DomainError::NotFound(format!(
                 "User {} not found",
                 recipient_user_id
             )));
         }
 
+        if self
+            .repository
+            .is_blocked_between(user_id, recipient_user_id)
+            .await?
+        {
+            return Err(DomainError::PermissionDenied(
+                "Cannot call this user".to_string(),
+            ));
+        }
+
         let mut tx = self.repository.begin().await?;
 
         let participant = tx
@@ -531,7 +799,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
                 }
                 DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
                     "recipient_id" => {
-                        DomainError::BadRequest(format!("User {} not found", recipient_user_id))
+                        DomainError::NotFound(format!("User {} not found", recipient_user_id))
                     }
                     _ => DomainError::InternalError(e),
                 },
@@ -612,7 +880,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         let participant =
             tx.remove_participant(target_user_id)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "Participant {} not found",
                     target_user_id
                 )))?;
@@ -627,7 +895,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
             .await?
             .unwrap_or(0);
 
-        if rights < 8 {
+        if rights < ACL_RIGHTS {
             self.repository.rollback(tx).await?;
             return Err(DomainError::PermissionDenied(
                 "Insufficient permissions to kick".to_string(),
@@ -644,7 +912,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
             .repository
             .find_user_role(target_user_id)
             .await?
-            .ok_or(DomainError::BadRequest("Target user not found".to_string()))?;
+            .ok_or(DomainError::NotFound("Target user not found".to_string()))?;
 
         if target_role == OWNER_ROLE_ID && requester_role > OWNER_ROLE_ID {
             self.repository.rollback(tx).await?;
@@ -671,7 +939,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
                 event,
                 ControlRoutingPolicy::ChannelRights {
                     channel_id,
-                    minimun_rights: 1,
+                    minimun_rights: ACKNOWLEDGE_RIGHTS,
                 },
             ))
             .await;
@@ -692,6 +960,70 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         Ok(participant)
     }
 
+    pub async fn clear_channel_voip(
+        &self,
+        requester_user_id: i64,
+        session_id: i64,
+        channel_id: i64,
+    ) -> Result<Vec<VoipParticipant>, DomainError> {
+        let rights = self
+            .repository
+            .find_user_channel_rights(channel_id, requester_user_id)
+            .await?
+            .unwrap_or(0);
+
+        if rights < ACL_RIGHTS {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to end call".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let participants = tx.remove_channel_participants(channel_id).await?;
+
+        self.repository.commit(tx).await?;
+
+        if participants.is_empty() {
+            return Ok(participants);
+        }
+
+        for participant in &participants {
+            let event = EventPayload::VoipParticipantDeleted {
+                user_id: participant.user_id,
+            };
+
+            let _ = self
+                .notifier
+                .notify(ServerMessage::Control(
+                    event,
+                    ControlRoutingPolicy::ChannelRights {
+                        channel_id,
+                        minimun_rights: ACKNOWLEDGE_RIGHTS,
+                    },
+                ))
+                .await;
+        }
+
+        let _ = self.notifier.notify(ServerMessage::InvalidateVoip).await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "VoIP channel cleared: requester_user_id={}, session_id={}, channel_id={}, removed={}",
+                    requester_user_id,
+                    session_id,
+                    channel_id,
+                    participants.len()
+                ),
+                "voip".to_string(),
+            )
+            .await;
+
+        Ok(participants)
+    }
+
     pub async fn set_local_mute(
         &self,
         user_id: i64,
@@ -703,7 +1035,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         let participant = tx
             .local_mute(user_id, mute)
             .await?
-            .ok_or(DomainError::BadRequest(format!(
+            .ok_or(DomainError::NotFound(format!(
                 "Participant {} not found",
                 user_id
             )))?;
@@ -746,7 +1078,7 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         let participant =
             tx.local_deafen(user_id, deafen)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "Participant {} not found",
                     user_id
                 )))?;
@@ -784,12 +1116,18 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         session_id: i64,
         publish: bool,
     ) -> Result<(), DomainError> {
+        if publish && !self.livekit.screen_share_enabled() {
+            return Err(DomainError::PermissionDenied(
+                "Screen sharing is disabled on this server".to_string(),
+            ));
+        }
+
         let mut tx = self.repository.begin().await?;
 
         let participant =
             tx.set_publish_screen(user_id, publish)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "Participant {} not found",
                     user_id
                 )))?;
@@ -827,12 +1165,18 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
         session_id: i64,
         publish: bool,
     ) -> Result<(), DomainError> {
+        if publish && !self.livekit.camera_enabled() {
+            return Err(DomainError::PermissionDenied(
+                "Camera is disabled on this server".to_string(),
+            ));
+        }
+
         let mut tx = self.repository.begin().await?;
 
         let participant =
             tx.set_publish_camera(user_id, publish)
                 .await?
-                .ok_or(DomainError::BadRequest(format!(
+                .ok_or(DomainError::NotFound(format!(
                     "Participant {} not found",
                     user_id
                 )))?;
@@ -863,6 +1207,86 @@ impl<R: VoipRepository, N: NotifierManager, G: LogManager> VoipService<R, N, G>
 
         Ok(())
     }
+
+    pub async fn set_codec_params(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        codec: String,
+        sample_rate: i32,
+        channels: i16,
+    ) -> Result<VoipCodecParams, DomainError> {
+        let codec = codec.trim().to_lowercase();
+        if codec.is_empty() || codec.len() > MAX_CODEC_NAME_LENGTH {
+            return Err(DomainError::BadRequest(format!(
+                "Codec name must be 1-{} characters",
+                MAX_CODEC_NAME_LENGTH
+            )));
+        }
+        if !(MIN_CODEC_SAMPLE_RATE..=MAX_CODEC_SAMPLE_RATE).contains(&sample_rate) {
+            return Err(DomainError::BadRequest(format!(
+                "Sample rate must be between {} and {} Hz",
+                MIN_CODEC_SAMPLE_RATE, MAX_CODEC_SAMPLE_RATE
+            )));
+        }
+        if !(1..=2).contains(&channels) {
+            return Err(DomainError::BadRequest(
+                "Channels must be 1 (mono) or 2 (stereo)".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let participant = tx
+            .find_participant(user_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Participant {} not found",
+                user_id
+            )))?;
+
+        let params = tx
+            .set_codec_params(user_id, &codec, sample_rate, channels)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Participant {} not found",
+                user_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let policy = if let Some(channel_id) = participant.channel_id {
+            ControlRoutingPolicy::ChannelRights {
+                channel_id,
+                minimun_rights: ACKNOWLEDGE_RIGHTS,
+            }
+        } else {
+            ControlRoutingPolicy::Users {
+                user_ids: vec![participant.user_id, participant.recipient_id.unwrap()],
+            }
+        };
+
+        let event = EventPayload::VoipCodecUpdated {
+            codec: params.clone(),
+        };
+        let _ = self
+            .notifier
+            .notify(ServerMessage::Control(event, policy))
+            .await;
+
+        let _ = self
+            .logger
+            .log_entry(
+                format!(
+                    "VoIP codec advertised: user_id={}, session_id={}, codec={}, sample_rate={}, channels={}",
+                    user_id, session_id, params.codec, params.sample_rate, params.channels
+                ),
+                "voip".to_string(),
+            )
+            .await;
+
+        Ok(params)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -893,6 +1317,14 @@ pub struct SetPublishCameraRequest {
     pub publish: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCodecParamsRequest {
+    pub codec: String,
+    pub sample_rate: i32,
+    pub channels: i16,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct JoinVoipResponse {
@@ -910,6 +1342,7 @@ pub fn voip_routes(
 ) -> OpenApiRouter<Postgre> {
     OpenApiRouter::new()
         .routes(routes!(get_voip_participants_handler))
+        .routes(routes!(get_speaking_states_handler))
         .routes(routes!(join_channel_voip_handler))
         .routes(routes!(join_private_voip_handler))
         .routes(routes!(leave_voip_handler))
@@ -917,7 +1350,10 @@ pub fn voip_routes(
         .routes(routes!(set_local_deafen_handler))
         .routes(routes!(set_publish_screen_handler))
         .routes(routes!(set_publish_camera_handler))
+        .routes(routes!(get_codec_params_handler))
+        .routes(routes!(set_codec_params_handler))
         .routes(routes!(kick_participant_handler))
+        .routes(routes!(clear_channel_voip_handler))
         .layer(from_fn_with_state(authorize_service, authorize))
         .with_state(voip_service)
 }
@@ -947,6 +1383,27 @@ async fn get_voip_participants_handler(
     Ok(Json(participants))
 }
 
+#[utoipa::path(
+    get,
+    tag = "voip",
+    path = "/speaking",
+    responses(
+        (status = 200, description = "Successfully retrieved speaking state of VoIP participants", body = Vec<VoipSpeakingState>),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_speaking_states_handler(
+    State(service): State<VoipService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<VoipSpeakingState>>, ApiError> {
+    let states = service
+        .get_speaking_states(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(states))
+}
+
 #[utoipa::path(
     post,
     tag = "voip",
@@ -1143,6 +1600,58 @@ async fn set_publish_camera_handler(
     Ok(())
 }
 
+#[utoipa::path(
+    get,
+    tag = "voip",
+    path = "/codecs",
+    responses(
+        (status = 200, description = "Codec parameters advertised by visible VoIP participants", body = Vec<VoipCodecParams>),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn get_codec_params_handler(
+    State(service): State<VoipService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<VoipCodecParams>>, ApiError> {
+    let params = service
+        .get_codec_params(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(params))
+}
+
+#[utoipa::path(
+    put,
+    tag = "voip",
+    path = "/codec",
+    request_body = SetCodecParamsRequest,
+    responses(
+        (status = 200, description = "Successfully advertised codec parameters", body = VoipCodecParams),
+        (status = 404, description = "Participant not found", body = ApiError),
+        (status = 422, description = "Invalid codec parameters", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn set_codec_params_handler(
+    State(service): State<VoipService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<SetCodecParamsRequest>,
+) -> Result<Json<VoipCodecParams>, ApiError> {
+    let params = service
+        .set_codec_params(
+            session.user_id,
+            session.session_id,
+            payload.codec,
+            payload.sample_rate,
+            payload.channels,
+        )
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(params))
+}
+
 #[utoipa::path(
     post,
     tag = "voip",
@@ -1174,3 +1683,256 @@ async fn kick_participant_handler(
 
     Ok(Json(participant))
 }
+
+#[utoipa::path(
+    post,
+    tag = "voip",
+    path = "/channel/{channel_id}/clear",
+    params(
+        ("channel_id", Path, description = "The ID of the channel whose call should end"),
+    ),
+    responses(
+        (status = 200, description = "Successfully removed all participants", body = Vec<VoipParticipant>),
+        (status = 403, description = "Permission denied", body = ApiError),
+        (status = 500, description = "Internal Server Error", body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn clear_channel_voip_handler(
+    State(service): State<VoipService<Postgre, DefaultNotifierManager, TextLogManager>>,
+    Extension(session): Extension<Session>,
+    Path(channel_id): Path<i64>,
+) -> Result<Json<Vec<VoipParticipant>>, ApiError> {
+    let participants = service
+        .clear_channel_voip(session.user_id, session.session_id, channel_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    for participant in &participants {
+        let _ = service
+            .remove_from_room(participant.user_id, participant.channel_id, None)
+            .await;
+    }
+
+    Ok(Json(participants))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acl::WRITE_RIGHTS;
+    use crate::role::DEFAULT_ROLE_ID;
+    use sqlx::PgPool;
+    use tokio::sync::mpsc;
+
+    type TestVoipService = VoipService<Postgre, DefaultNotifierManager, TextLogManager>;
+
+    async fn create_user(pool: &PgPool, username: &str) -> i64 {
+        sqlx::query_scalar!(
+            "INSERT INTO users (username) VALUES ($1) RETURNING user_id",
+            username
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn blocked_pairs_cannot_call_each_other(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        sqlx::query!(
+            "INSERT INTO blocked_users (blocker_id, blocked_id) VALUES ($1, $2)",
+            alice,
+            bob
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (sender, _) = tokio::sync::mpsc::channel(16);
+        let service = VoipService::new(
+            Postgre { pool },
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-voip-test.log")),
+            LiveKitService::new("localhost:7880", "key", "secret"),
+        );
+
+        assert!(matches!(
+            service.join_private_voip(bob, 1, alice, false, false).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            service.join_private_voip(alice, 1, bob, false, false).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+    }
+
+    async fn create_voice_channel(pool: &PgPool) -> i64 {
+        let channel_id = sqlx::query_scalar!(
+            r#"WITH new_group AS (
+                   INSERT INTO groups (group_name) VALUES ('voice') RETURNING group_id
+               )
+               INSERT INTO channels (channel_name, group_id, channel_type)
+               SELECT 'voice', group_id, 'VoIP' FROM new_group
+               RETURNING channel_id"#
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            r#"INSERT INTO group_role_rights (group_id, role_id, rights)
+               SELECT group_id, $2, $3 FROM channels WHERE channel_id = $1"#,
+            channel_id,
+            DEFAULT_ROLE_ID,
+            WRITE_RIGHTS
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        channel_id
+    }
+
+    fn service(pool: PgPool) -> (TestVoipService, mpsc::Receiver<ServerMessage>) {
+        let (sender, receiver) = mpsc::channel(16);
+        let service = VoipService::new(
+            Postgre { pool },
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-voip-test.log")),
+            LiveKitService::new("localhost:7880", "key", "secret"),
+        );
+        (service, receiver)
+    }
+
+    async fn codec_policy(
+        service: &TestVoipService,
+        receiver: &mut mpsc::Receiver<ServerMessage>,
+        user_id: i64,
+    ) -> ControlRoutingPolicy {
+        service
+            .set_codec_params(user_id, 1, "opus".to_string(), 48000, 2)
+            .await
+            .unwrap();
+        match receiver.try_recv() {
+            Ok(ServerMessage::Control(EventPayload::VoipCodecUpdated { .. }, policy)) => policy,
+            _ => panic!("expected a codec update"),
+        }
+    }
+
+    #[sqlx::test]
+    async fn codec_updates_reach_only_those_who_can_see_the_call(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let channel_id = create_voice_channel(&pool).await;
+        sqlx::query!(
+            r#"INSERT INTO voip_participants (user_id, channel_id, recipient_id)
+               VALUES ($1, $2, NULL), ($3, NULL, $4)"#,
+            alice,
+            channel_id,
+            bob,
+            carol
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (service, mut receiver) = service(pool);
+
+        assert!(matches!(
+            codec_policy(&service, &mut receiver, alice).await,
+            ControlRoutingPolicy::ChannelRights {
+                channel_id: routed,
+                minimun_rights: ACKNOWLEDGE_RIGHTS,
+            } if routed == channel_id
+        ));
+        assert!(matches!(
+            codec_policy(&service, &mut receiver, bob).await,
+            ControlRoutingPolicy::Users { user_ids } if user_ids == vec![bob, carol]
+        ));
+    }
+
+    async fn join(
+        service: &TestVoipService,
+        user_id: i64,
+        channel_id: i64,
+    ) -> Result<bool, DomainError> {
+        service
+            .join_channel_voip(user_id, 1, channel_id, false, false)
+            .await
+    }
+
+    #[sqlx::test]
+    async fn full_voice_channel_rejects_joiners_until_a_slot_frees(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let channel_id = create_voice_channel(&pool).await;
+        sqlx::query!("UPDATE server_config SET max_voip_participants = 2 WHERE id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (service, _receiver) = service(pool);
+
+        join(&service, alice, channel_id).await.unwrap();
+        join(&service, bob, channel_id).await.unwrap();
+        assert!(matches!(
+            join(&service, carol, channel_id).await,
+            Err(DomainError::BadRequest(_))
+        ));
+
+        service.leave_voip(alice, 1).await.unwrap();
+        assert!(join(&service, carol, channel_id).await.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn concurrent_joins_cannot_overfill_a_channel(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let bob = create_user(&pool, "bob").await;
+        let carol = create_user(&pool, "carol").await;
+        let channel_id = create_voice_channel(&pool).await;
+        sqlx::query!("UPDATE server_config SET max_voip_participants = 2 WHERE id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (service, _receiver) = service(pool.clone());
+        join(&service, alice, channel_id).await.unwrap();
+
+        let (bob_joined, carol_joined) = tokio::join!(
+            join(&service, bob, channel_id),
+            join(&service, carol, channel_id)
+        );
+
+        assert!(bob_joined.is_ok() != carol_joined.is_ok());
+        let participants = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM voip_participants WHERE channel_id = $1"#,
+            channel_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(participants, 2);
+    }
+
+    #[sqlx::test]
+    async fn disabled_camera_is_rejected_while_voice_keeps_working(pool: PgPool) {
+        let alice = create_user(&pool, "alice").await;
+        let channel_id = create_voice_channel(&pool).await;
+        let (sender, _receiver) = mpsc::channel(16);
+        let service = VoipService::new(
+            Postgre { pool },
+            DefaultNotifierManager::new(sender),
+            TextLogManager::new(std::env::temp_dir().join("opencord-voip-test.log")),
+            LiveKitService::new("localhost:7880", "key", "secret").with_media_sources(false, true),
+        );
+
+        join(&service, alice, channel_id).await.unwrap();
+        service.set_local_mute(alice, 1, true).await.unwrap();
+        service.set_local_mute(alice, 1, false).await.unwrap();
+
+        assert!(matches!(
+            service.set_publish_camera(alice, 1, true).await,
+            Err(DomainError::PermissionDenied(_))
+        ));
+        service.set_publish_camera(alice, 1, false).await.unwrap();
+        service.set_publish_screen(alice, 1, true).await.unwrap();
+    }
+}
@@ -0,0 +1,874 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// IMPORTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::auth::Session;
+use crate::db::Postgre;
+use crate::error::{ApiError, DatabaseError};
+use crate::managers::{LogManager, TextLogManager};
+use crate::middleware::{AuthorizeService, authorize};
+use crate::model::EventPayload;
+use crate::role::{ADMIN_ROLE_ID, OWNER_ROLE_ID};
+
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::middleware::from_fn_with_state;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MODELS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub webhook_id: i64,
+    pub channel_id: i64,
+    pub target_url: String,
+    #[serde(skip)]
+    pub secret: String,
+    pub created_by: Option<i64>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookToken {
+    pub token: String,
+    pub channel_id: i64,
+    pub bot_user_id: i64,
+    pub created_by: Option<i64>,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub channel_id: i64,
+    pub payload: EventPayload,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookBody<'a> {
+    webhook_id: i64,
+    channel_id: i64,
+    #[serde(flatten)]
+    event: &'a EventPayload,
+}
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Opencord-Signature";
+const WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+const WEBHOOK_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const WEBHOOK_MAX_BACKOFF: Duration = Duration::from_secs(60);
+const WEBHOOK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ERROR
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, thiserror::Error)]
+pub enum DomainError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Internal error")]
+    InternalError(#[from] DatabaseError),
+}
+
+impl From<DomainError> for ApiError {
+    fn from(err: DomainError) -> Self {
+        match err {
+            DomainError::BadRequest(msg) => ApiError::UnprocessableEntity(msg),
+            DomainError::PermissionDenied(msg) => ApiError::PermissionDenied(msg),
+            DomainError::NotFound(msg) => ApiError::NotFound(msg),
+            DomainError::InternalError(db_err) => {
+                tracing::error!("Database error: {}", db_err);
+                ApiError::InternalServerError("Internal server error".to_string())
+            }
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// REPOSITORY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub trait WebhookTransaction: Send + Sync {
+    async fn create_webhook(
+        &mut self,
+        channel_id: i64,
+        target_url: &str,
+        secret: &str,
+        created_by: i64,
+    ) -> Result<Webhook, DatabaseError>;
+
+    async fn delete_webhook(&mut self, webhook_id: i64) -> Result<Option<Webhook>, DatabaseError>;
+
+    async fn create_webhook_token(
+        &mut self,
+        token: &str,
+        channel_id: i64,
+        bot_user_id: i64,
+        created_by: i64,
+    ) -> Result<WebhookToken, DatabaseError>;
+
+    async fn delete_webhook_token(
+        &mut self,
+        token: &str,
+    ) -> Result<Option<WebhookToken>, DatabaseError>;
+
+    async fn find_user_role(&mut self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
+}
+
+pub trait WebhookRepository: Send + Sync + Clone {
+    type Transaction: WebhookTransaction;
+
+    async fn begin(&self) -> Result<Self::Transaction, DatabaseError>;
+
+    async fn commit(&self, transaction: Self::Transaction) -> Result<(), DatabaseError>;
+
+    async fn rollback(&self, transaction: Self::Transaction) -> Result<(), DatabaseError>;
+
+    async fn find_webhooks(&self) -> Result<Vec<Webhook>, DatabaseError>;
+
+    async fn find_channel_webhooks(&self, channel_id: i64) -> Result<Vec<Webhook>, DatabaseError>;
+
+    async fn find_webhook_tokens(&self) -> Result<Vec<WebhookToken>, DatabaseError>;
+
+    async fn find_user_role(&self, user_id: i64) -> Result<Option<i64>, DatabaseError>;
+}
+
+pub struct PgWebhookTransaction {
+    transaction: sqlx::Transaction<'static, sqlx::Postgres>,
+}
+
+impl WebhookTransaction for PgWebhookTransaction {
+    async fn create_webhook(
+        &mut self,
+        channel_id: i64,
+        target_url: &str,
+        secret: &str,
+        created_by: i64,
+    ) -> Result<Webhook, DatabaseError> {
+        let webhook = sqlx::query_as!(
+            Webhook,
+            r#"INSERT INTO webhooks (channel_id, target_url, secret, created_by)
+               VALUES ($1, $2, $3, $4)
+               RETURNING webhook_id, channel_id, target_url, secret, created_by, created_at"#,
+            channel_id,
+            target_url,
+            secret,
+            created_by
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(webhook)
+    }
+
+    async fn delete_webhook(&mut self, webhook_id: i64) -> Result<Option<Webhook>, DatabaseError> {
+        let webhook = sqlx::query_as!(
+            Webhook,
+            r#"DELETE FROM webhooks WHERE webhook_id = $1
+               RETURNING webhook_id, channel_id, target_url, secret, created_by, created_at"#,
+            webhook_id
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(webhook)
+    }
+
+    async fn create_webhook_token(
+        &mut self,
+        token: &str,
+        channel_id: i64,
+        bot_user_id: i64,
+        created_by: i64,
+    ) -> Result<WebhookToken, DatabaseError> {
+        let webhook_token = sqlx::query_as!(
+            WebhookToken,
+            r#"INSERT INTO webhook_tokens (token, channel_id, bot_user_id, created_by)
+               VALUES ($1, $2, $3, $4)
+               RETURNING token, channel_id, bot_user_id, created_by, created_at"#,
+            token,
+            channel_id,
+            bot_user_id,
+            created_by
+        )
+        .fetch_one(&mut *self.transaction)
+        .await?;
+
+        Ok(webhook_token)
+    }
+
+    async fn delete_webhook_token(
+        &mut self,
+        token: &str,
+    ) -> Result<Option<WebhookToken>, DatabaseError> {
+        let webhook_token = sqlx::query_as!(
+            WebhookToken,
+            r#"DELETE FROM webhook_tokens WHERE token = $1
+               RETURNING token, channel_id, bot_user_id, created_by, created_at"#,
+            token
+        )
+        .fetch_optional(&mut *self.transaction)
+        .await?;
+
+        Ok(webhook_token)
+    }
+
+    async fn find_user_role(&mut self, user_id: i64) -> Result<Option<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!("SELECT role_id FROM users WHERE user_id = $1", user_id)
+            .fetch_optional(&mut *self.transaction)
+            .await?;
+        Ok(result)
+    }
+}
+
+impl WebhookRepository for Postgre {
+    type Transaction = PgWebhookTransaction;
+
+    async fn begin(&self) -> Result<Self::Transaction, DatabaseError> {
+        let tx = self.pool.begin().await?;
+        Ok(PgWebhookTransaction { transaction: tx })
+    }
+
+    async fn commit(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
+        transaction.transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(&self, transaction: Self::Transaction) -> Result<(), DatabaseError> {
+        transaction.transaction.rollback().await?;
+        Ok(())
+    }
+
+    async fn find_webhooks(&self) -> Result<Vec<Webhook>, DatabaseError> {
+        let results = sqlx::query_as!(
+            Webhook,
+            r#"SELECT webhook_id, channel_id, target_url, secret, created_by, created_at
+               FROM webhooks
+               ORDER BY webhook_id"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_channel_webhooks(&self, channel_id: i64) -> Result<Vec<Webhook>, DatabaseError> {
+        let results = sqlx::query_as!(
+            Webhook,
+            r#"SELECT webhook_id, channel_id, target_url, secret, created_by, created_at
+               FROM webhooks
+               WHERE channel_id = $1"#,
+            channel_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_webhook_tokens(&self) -> Result<Vec<WebhookToken>, DatabaseError> {
+        let results = sqlx::query_as!(
+            WebhookToken,
+            r#"SELECT token, channel_id, bot_user_id, created_by, created_at
+               FROM webhook_tokens
+               ORDER BY created_at"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_user_role(&self, user_id: i64) -> Result<Option<i64>, DatabaseError> {
+        let result = sqlx::query_scalar!("SELECT role_id FROM users WHERE user_id = $1", user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(result)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SERVICE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Clone)]
+pub struct WebhookService<R: WebhookRepository, G: LogManager> {
+    repository: R,
+    logger: G,
+}
+
+impl<R: WebhookRepository, G: LogManager> WebhookService<R, G> {
+    pub fn new(repository: R, logger: G) -> Self {
+        Self { repository, logger }
+    }
+
+    pub async fn list_webhooks(&self, user_id: i64) -> Result<Vec<Webhook>, DomainError> {
+        let role_id = self
+            .repository
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to list webhooks".to_string(),
+            ));
+        }
+
+        let webhooks = self.repository.find_webhooks().await?;
+
+        Ok(webhooks)
+    }
+
+    pub async fn create_webhook(
+        &self,
+        channel_id: i64,
+        target_url: String,
+        secret: String,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<Webhook, DomainError> {
+        let target_url = target_url.trim();
+        let url = reqwest::Url::parse(target_url)
+            .map_err(|_| DomainError::BadRequest("Invalid webhook URL".to_string()))?;
+        if url.scheme() != "https" && url.scheme() != "http" {
+            return Err(DomainError::BadRequest(
+                "Webhook URL must use http or https".to_string(),
+            ));
+        }
+
+        if !(16..=256).contains(&secret.len()) {
+            return Err(DomainError::BadRequest(
+                "Webhook secret must be between 16 and 256 bytes".to_string(),
+            ));
+        }
+
+        let mut tx = self.repository.begin().await?;
+
+        let role_id = tx
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to create webhook".to_string(),
+            ));
+        }
+
+        let webhook = tx
+            .create_webhook(channel_id, target_url, &secret, user_id)
+            .await
+            .map_err(|e| match &e {
+                DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
+                    "webhooks_channel_id" => {
+                        DomainError::NotFound(format!("Channel {} not found", channel_id))
+                    }
+                    _ => DomainError::InternalError(e),
+                },
+                _ => DomainError::InternalError(e),
+            })?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self.logger.log_entry(
+            format!("Webhook created: user_id={}, session_id={}, webhook_id={}, channel_id={}", user_id, session_id, webhook.webhook_id, channel_id),
+            "webhook".to_string(),
+        ).await;
+
+        Ok(webhook)
+    }
+
+    pub async fn delete_webhook(
+        &self,
+        webhook_id: i64,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let role_id = tx
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to delete webhook".to_string(),
+            ));
+        }
+
+        let webhook = tx
+            .delete_webhook(webhook_id)
+            .await?
+            .ok_or(DomainError::NotFound(format!(
+                "Webhook {} not found",
+                webhook_id
+            )))?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self.logger.log_entry(
+            format!("Webhook deleted: user_id={}, session_id={}, webhook_id={}, channel_id={}", user_id, session_id, webhook_id, webhook.channel_id),
+            "webhook".to_string(),
+        ).await;
+
+        Ok(())
+    }
+
+    pub async fn list_webhook_tokens(&self, user_id: i64) -> Result<Vec<WebhookToken>, DomainError> {
+        let role_id = self
+            .repository
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to list webhook tokens".to_string(),
+            ));
+        }
+
+        let tokens = self.repository.find_webhook_tokens().await?;
+
+        Ok(tokens)
+    }
+
+    pub async fn create_webhook_token(
+        &self,
+        channel_id: i64,
+        bot_user_id: i64,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<WebhookToken, DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let role_id = tx
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to create webhook token".to_string(),
+            ));
+        }
+
+        let token = Uuid::new_v4().simple().to_string();
+
+        let webhook_token = tx
+            .create_webhook_token(&token, channel_id, bot_user_id, user_id)
+            .await
+            .map_err(|e| match &e {
+                DatabaseError::ForeignKeyViolation { column } => match column.as_str() {
+                    "webhook_tokens_channel_id" => {
+                        DomainError::NotFound(format!("Channel {} not found", channel_id))
+                    }
+                    "webhook_tokens_bot_user_id" => {
+                        DomainError::NotFound(format!("User {} not found", bot_user_id))
+                    }
+                    _ => DomainError::InternalError(e),
+                },
+                _ => DomainError::InternalError(e),
+            })?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self.logger.log_entry(
+            format!("Webhook token created: user_id={}, session_id={}, channel_id={}, bot_user_id={}", user_id, session_id, channel_id, bot_user_id),
+            "webhook".to_string(),
+        ).await;
+
+        Ok(webhook_token)
+    }
+
+    pub async fn delete_webhook_token(
+        &self,
+        token: &str,
+        user_id: i64,
+        session_id: i64,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.repository.begin().await?;
+
+        let role_id = tx
+            .find_user_role(user_id)
+            .await?
+            .ok_or(DomainError::PermissionDenied("User not found".to_string()))?;
+
+        if role_id != OWNER_ROLE_ID && role_id != ADMIN_ROLE_ID {
+            return Err(DomainError::PermissionDenied(
+                "Insufficient permissions to delete webhook token".to_string(),
+            ));
+        }
+
+        let webhook_token = tx
+            .delete_webhook_token(token)
+            .await?
+            .ok_or(DomainError::NotFound("Webhook token not found".to_string()))?;
+
+        self.repository.commit(tx).await?;
+
+        let _ = self.logger.log_entry(
+            format!("Webhook token deleted: user_id={}, session_id={}, channel_id={}, bot_user_id={}", user_id, session_id, webhook_token.channel_id, webhook_token.bot_user_id),
+            "webhook".to_string(),
+        ).await;
+
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DISPATCHER
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub struct WebhookDispatcher<R: WebhookRepository> {
+    repository: R,
+    client: reqwest::Client,
+}
+
+impl<R: WebhookRepository> WebhookDispatcher<R> {
+    pub fn new(repository: R) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self { repository, client }
+    }
+
+    pub async fn run(self, mut receiver: mpsc::Receiver<WebhookEvent>) {
+        while let Some(event) = receiver.recv().await {
+            let webhooks = match self.repository.find_channel_webhooks(event.channel_id).await {
+                Ok(webhooks) => webhooks,
+                Err(e) => {
+                    tracing::error!("Failed to load webhooks for channel {}: {}", event.channel_id, e);
+                    continue;
+                }
+            };
+
+            for webhook in webhooks {
+                let body = match serde_json::to_vec(&WebhookBody {
+                    webhook_id: webhook.webhook_id,
+                    channel_id: event.channel_id,
+                    event: &event.payload,
+                }) {
+                    Ok(body) => body,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize webhook payload: {}", e);
+                        continue;
+                    }
+                };
+
+                tokio::spawn(deliver(self.client.clone(), webhook, body));
+            }
+        }
+    }
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Delay before retrying after the given failed attempt, doubling up to
+/// `WEBHOOK_MAX_BACKOFF`.
+fn retry_delay(attempt: u32) -> Duration {
+    WEBHOOK_INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(WEBHOOK_MAX_BACKOFF)
+}
+
+async fn deliver(client: reqwest::Client, webhook: Webhook, body: Vec<u8>) {
+    let signature = format!("sha256={}", sign(&webhook.secret, &body));
+
+    for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+        let result = client
+            .post(&webhook.target_url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => tracing::warn!(
+                "Webhook {} attempt {} failed with status {}",
+                webhook.webhook_id,
+                attempt,
+                response.status()
+            ),
+            Err(e) => tracing::warn!(
+                "Webhook {} attempt {} failed: {}",
+                webhook.webhook_id,
+                attempt,
+                e
+            ),
+        }
+
+        if attempt < WEBHOOK_MAX_ATTEMPTS {
+            tokio::time::sleep(retry_delay(attempt)).await;
+        }
+    }
+
+    tracing::error!(
+        "Webhook {} delivery dropped after {} attempts",
+        webhook.webhook_id,
+        WEBHOOK_MAX_ATTEMPTS
+    );
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// REQUEST/RESPONSE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhookRequest {
+    pub channel_id: i64,
+    pub target_url: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhookTokenRequest {
+    pub channel_id: i64,
+    pub bot_user_id: i64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ROUTES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+type AppWebhookService = WebhookService<Postgre, TextLogManager>;
+
+pub fn webhook_routes(
+    webhook_service: AppWebhookService,
+    authorize_service: AuthorizeService<Postgre>,
+) -> OpenApiRouter<Postgre> {
+    OpenApiRouter::new()
+        .routes(routes!(list_webhooks_handler, create_webhook_handler))
+        .routes(routes!(delete_webhook_handler))
+        .routes(routes!(list_webhook_tokens_handler, create_webhook_token_handler))
+        .routes(routes!(delete_webhook_token_handler))
+        .layer(from_fn_with_state(authorize_service, authorize))
+        .with_state(webhook_service)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HANDLERS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[utoipa::path(
+    get,
+    tag = "webhook",
+    path = "/",
+    description = "List registered outbound webhooks",
+    responses(
+        (status = 200, body = Vec<Webhook>),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn list_webhooks_handler(
+    State(service): State<AppWebhookService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<Webhook>>, ApiError> {
+    let webhooks = service
+        .list_webhooks(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(webhooks))
+}
+
+#[utoipa::path(
+    post,
+    tag = "webhook",
+    path = "/",
+    description = "Register a webhook that receives new messages of a channel",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, body = Webhook),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn create_webhook_handler(
+    State(service): State<AppWebhookService>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<Webhook>), ApiError> {
+    let webhook = service
+        .create_webhook(
+            payload.channel_id,
+            payload.target_url,
+            payload.secret,
+            session.user_id,
+            session.session_id,
+        )
+        .await
+        .map_err(ApiError::from)?;
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "webhook",
+    path = "/{webhook_id}",
+    description = "Delete a webhook",
+    params(("webhook_id" = i64, Path, description = "Webhook ID")),
+    responses(
+        (status = 200),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn delete_webhook_handler(
+    State(service): State<AppWebhookService>,
+    Extension(session): Extension<Session>,
+    Path(webhook_id): Path<i64>,
+) -> Result<(), ApiError> {
+    service
+        .delete_webhook(webhook_id, session.user_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "webhook",
+    path = "/tokens",
+    description = "List inbound webhook tokens",
+    responses(
+        (status = 200, body = Vec<WebhookToken>),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn list_webhook_tokens_handler(
+    State(service): State<AppWebhookService>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<WebhookToken>>, ApiError> {
+    let tokens = service
+        .list_webhook_tokens(session.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(tokens))
+}
+
+#[utoipa::path(
+    post,
+    tag = "webhook",
+    path = "/tokens",
+    description = "Create an inbound webhook token that posts to a channel as a bot user",
+    request_body = CreateWebhookTokenRequest,
+    responses(
+        (status = 201, body = WebhookToken),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn create_webhook_token_handler(
+    State(service): State<AppWebhookService>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<CreateWebhookTokenRequest>,
+) -> Result<(StatusCode, Json<WebhookToken>), ApiError> {
+    let token = service
+        .create_webhook_token(
+            payload.channel_id,
+            payload.bot_user_id,
+            session.user_id,
+            session.session_id,
+        )
+        .await
+        .map_err(ApiError::from)?;
+    Ok((StatusCode::CREATED, Json(token)))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "webhook",
+    path = "/tokens/{token}",
+    description = "Revoke an inbound webhook token",
+    params(("token" = String, Path, description = "Webhook token")),
+    responses(
+        (status = 200),
+        (status = 422, body = ApiError),
+        (status = 500, body = ApiError),
+    ),
+    security(("api_key" = []))
+)]
+async fn delete_webhook_token_handler(
+    State(service): State<AppWebhookService>,
+    Extension(session): Extension<Session>,
+    Path(token): Path<String>,
+) -> Result<(), ApiError> {
+    service
+        .delete_webhook_token(&token, session.user_id, session.session_id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_a_hex_hmac_sha256_of_the_body() {
+        // RFC 4231, test case 2.
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        let delays: Vec<u64> = (1..=4)
+            .map(|attempt| retry_delay(attempt).as_secs())
+            .collect();
+
+        assert_eq!(delays, vec![1, 2, 4, 8]);
+        assert_eq!(retry_delay(7), WEBHOOK_MAX_BACKOFF);
+        assert_eq!(retry_delay(u32::MAX), WEBHOOK_MAX_BACKOFF);
+    }
+}